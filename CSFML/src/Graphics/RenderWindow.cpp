@@ -20,6 +20,7 @@
 #include <SFML/Window/Touch.hpp>
 #include <chrono>
 #include <cstddef>
+#include <cstring>
 
 extern "C" sf::RenderWindow *sfRenderWindow_new_mtsss(sfVideoMode mode, const uint32_t *title, uint32_t style, sfState state, const sf::ContextSettings *settings) {
     // Convert video mode
@@ -133,6 +134,15 @@ extern "C" void sfRenderWindow_requestFocus(sf::RenderWindow *renderWindow) {
     renderWindow->requestFocus();
 }
 
+extern "C" bool sfRenderWindow_createVulkanSurface(sf::RenderWindow *renderWindow, VkInstance instance, uint64_t *surface, const VkAllocationCallbacks *allocator) {
+    // `VkSurfaceKHR` is a pointer on 64 bit platforms, and a 64 bit integer otherwise
+    VkSurfaceKHR vkSurface{};
+    const bool result = renderWindow->createVulkanSurface(instance, vkSurface, allocator);
+    *surface = 0;
+    std::memcpy(surface, &vkSurface, sizeof(vkSurface));
+    return result;
+}
+
 extern "C" bool sfRenderWindow_hasFocus(const sf::RenderWindow *renderWindow) {
     return renderWindow->hasFocus();
 }
@@ -228,6 +238,9 @@ extern "C" void sfRenderWindow_drawRectangleShape(sf::RenderWindow *renderWindow
 extern "C" void sfRenderWindow_drawVertexBuffer(sf::RenderWindow *renderWindow, const sf::VertexBuffer *object, const sf::RenderStates *states) {
     renderWindow->draw(*object, *states);
 }
+extern "C" void sfRenderWindow_drawVertexBufferRange(sf::RenderWindow *renderWindow, const sf::VertexBuffer *object, size_t firstVertex, size_t vertexCount, const sf::RenderStates *states) {
+    renderWindow->draw(*object, firstVertex, vertexCount, *states);
+}
 
 extern "C" void sfRenderWindow_drawPrimitives(sf::RenderWindow *renderWindow,
                                               const sf::Vertex *vertices, size_t vertexCount,
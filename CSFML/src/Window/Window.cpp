@@ -9,6 +9,7 @@
 #include <SFML/Window/Touch.hpp>
 #include <chrono>
 #include <cstdint>
+#include <cstring>
 #include <optional>
 #include <stdexcept>
 
@@ -133,6 +134,15 @@ extern "C" void sfWindow_requestFocus(sf::Window *window) {
     window->requestFocus();
 }
 
+extern "C" bool sfWindow_createVulkanSurface(sf::Window *window, VkInstance instance, uint64_t *surface, const VkAllocationCallbacks *allocator) {
+    // `VkSurfaceKHR` is a pointer on 64 bit platforms, and a 64 bit integer otherwise
+    VkSurfaceKHR vkSurface{};
+    const bool result = window->createVulkanSurface(instance, vkSurface, allocator);
+    *surface = 0;
+    std::memcpy(surface, &vkSurface, sizeof(vkSurface));
+    return result;
+}
+
 extern "C" bool sfWindow_hasFocus(const sf::Window *window) {
     return window->hasFocus();
 }
@@ -290,6 +290,7 @@ fn main() {
                 "CSFML/src/Window/Sensor.cpp",
                 "CSFML/src/Window/Touch.cpp",
                 "CSFML/src/Window/VideoMode.cpp",
+                "CSFML/src/Window/Vulkan.cpp",
                 "CSFML/src/Window/Window.cpp",
                 "CSFML/src/Window/Context.cpp",
             ]
@@ -0,0 +1,491 @@
+use {
+    super::{Music, SoundStream, SoundStreamPlayer, TimeSpan},
+    crate::system::Time,
+    std::collections::VecDeque,
+};
+
+/// The time signature of a [`TempoMap`], like 3/4 or 6/8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeSignature {
+    /// Number of beats in a bar (the upper number)
+    pub beats_per_bar: u32,
+    /// Note value of one beat (the lower number)
+    pub beat_unit: u32,
+}
+
+impl Default for TimeSignature {
+    /// 4/4
+    fn default() -> Self {
+        Self {
+            beats_per_bar: 4,
+            beat_unit: 4,
+        }
+    }
+}
+
+/// A tempo change, starting at a given beat.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TempoSegment {
+    beat: u64,
+    /// Start of the segment, in microseconds from the start of the music
+    start: f64,
+    /// Length of a beat, in microseconds
+    beat_length: f64,
+}
+
+/// Describes where beats fall in a piece of music.
+///
+/// Beat `0` falls on the offset, the first beat of the first bar. Tempo changes take effect on a
+/// given beat, so that beats always stay whole.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TempoMap {
+    signature: TimeSignature,
+    segments: Vec<TempoSegment>,
+}
+
+fn beat_length(bpm: f64) -> f64 {
+    60_000_000.0 / bpm.max(f64::MIN_POSITIVE)
+}
+
+impl TempoMap {
+    /// Create a tempo map with a constant tempo of `bpm` beats per minute, in 4/4 time, with the
+    /// first beat at the start of the music.
+    #[must_use]
+    pub fn new(bpm: f64) -> Self {
+        Self {
+            signature: TimeSignature::default(),
+            segments: vec![TempoSegment {
+                beat: 0,
+                start: 0.0,
+                beat_length: beat_length(bpm),
+            }],
+        }
+    }
+    /// Set the time signature.
+    #[must_use]
+    pub fn with_signature(mut self, beats_per_bar: u32, beat_unit: u32) -> Self {
+        self.signature = TimeSignature {
+            beats_per_bar: beats_per_bar.max(1),
+            beat_unit: beat_unit.max(1),
+        };
+        self
+    }
+    /// Set the time of the first beat, for music that doesn't start right on it.
+    #[must_use]
+    pub fn with_offset(mut self, offset: Time) -> Self {
+        let shift = micros(offset) - self.segments[0].start;
+        for segment in &mut self.segments {
+            segment.start += shift;
+        }
+        self
+    }
+    /// Change the tempo to `bpm` beats per minute, starting at beat `beat`.
+    ///
+    /// Later tempo changes are discarded.
+    #[must_use]
+    pub fn with_tempo_change(mut self, beat: u64, bpm: f64) -> Self {
+        self.segments.retain(|s| s.beat < beat || s.beat == 0);
+        if beat == 0 {
+            self.segments[0].beat_length = beat_length(bpm);
+            self.segments.truncate(1);
+            return self;
+        }
+        let last = self.segments[self.segments.len() - 1];
+        let start = last.start + (beat - last.beat) as f64 * last.beat_length;
+        self.segments.push(TempoSegment {
+            beat,
+            start,
+            beat_length: beat_length(bpm),
+        });
+        self
+    }
+    /// The time signature.
+    #[must_use]
+    pub fn signature(&self) -> TimeSignature {
+        self.signature
+    }
+    /// The tempo at `time`, in beats per minute.
+    #[must_use]
+    pub fn bpm_at(&self, time: Time) -> f64 {
+        60_000_000.0 / self.segment_at(micros(time)).beat_length
+    }
+    /// The (fractional) beat at `time`, negative before the first beat.
+    #[must_use]
+    pub fn beat_at(&self, time: Time) -> f64 {
+        let time = micros(time);
+        let segment = self.segment_at(time);
+        let beat = segment.beat as f64;
+        beat + (time - segment.start) / segment.beat_length
+    }
+    /// The time at which beat `beat` falls.
+    #[must_use]
+    pub fn time_of_beat(&self, beat: u64) -> Time {
+        let segment = self
+            .segments
+            .iter()
+            .rev()
+            .find(|s| s.beat <= beat)
+            .unwrap_or(&self.segments[0]);
+        let time = segment.start + (beat - segment.beat) as f64 * segment.beat_length;
+        #[expect(clippy::cast_possible_truncation)]
+        Time::microseconds(time.round() as i64)
+    }
+    /// The time at which the (fractional) beat `beat` falls.
+    ///
+    /// Negative beats fall before the first beat, at the initial tempo.
+    #[must_use]
+    pub fn time_at(&self, beat: f64) -> Time {
+        let segment = self
+            .segments
+            .iter()
+            .rev()
+            .find(|s| s.beat as f64 <= beat)
+            .unwrap_or(&self.segments[0]);
+        let time = segment.start + (beat - segment.beat as f64) * segment.beat_length;
+        #[expect(clippy::cast_possible_truncation)]
+        Time::microseconds(time.round() as i64)
+    }
+    fn segment_at(&self, time: f64) -> &TempoSegment {
+        self.segments
+            .iter()
+            .rev()
+            .find(|s| s.start <= time)
+            .unwrap_or(&self.segments[0])
+    }
+    /// Index of the first beat falling strictly after `time`.
+    fn first_beat_after(&self, time: Time) -> u64 {
+        let beat = self.beat_at(time).floor();
+        #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let mut beat = if beat < 0.0 { 0 } else { beat as u64 };
+        // Correct for rounding in either direction
+        while beat > 0 && self.time_of_beat(beat - 1) > time {
+            beat -= 1;
+        }
+        while self.time_of_beat(beat) <= time {
+            beat += 1;
+        }
+        beat
+    }
+}
+
+fn micros(time: Time) -> f64 {
+    time.as_microseconds() as f64
+}
+
+/// A named point in a piece of music.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CueMarker {
+    /// Name of the cue
+    pub name: String,
+    /// Where the cue falls, from the start of the music
+    pub time: Time,
+}
+
+/// Something that happened between two updates of a [`BeatClock`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BeatEvent {
+    /// A beat was crossed
+    Beat {
+        /// Index of the beat, from the first beat of the tempo map
+        index: u64,
+        /// Bar the beat is in, starting at `0`
+        bar: u64,
+        /// Position of the beat in its bar, `0` being the downbeat
+        beat_in_bar: u32,
+        /// When the beat falls
+        time: Time,
+    },
+    /// A cue marker was crossed
+    Cue {
+        /// Name of the cue
+        name: String,
+        /// When the cue falls
+        time: Time,
+    },
+}
+
+impl BeatEvent {
+    /// When the event falls, from the start of the music.
+    #[must_use]
+    pub fn time(&self) -> Time {
+        match self {
+            Self::Beat { time, .. } | Self::Cue { time, .. } => *time,
+        }
+    }
+}
+
+/// Reports the beats and cue markers crossed while a music plays.
+///
+/// Every frame, call [`update_music`] (or [`update_stream`], or [`update`]) with the music being
+/// played, then [`drain`] the events crossed since the last update. The clock follows the
+/// music's actual playing offset, so the events stay in sync with what's heard, including when
+/// the music wraps around its loop points.
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::audio::{BeatClock, BeatEvent, Music, TempoMap};
+/// # use sfml::system::Time;
+/// let mut music = Music::from_file("song.ogg").unwrap();
+/// let tempo = TempoMap::new(128.0).with_offset(Time::milliseconds(250));
+/// let mut clock = BeatClock::new(tempo);
+/// clock.add_cue("drop", Time::seconds(30.0));
+/// music.play();
+/// loop {
+///     clock.update_music(&music);
+///     for event in clock.drain() {
+///         match event {
+///             BeatEvent::Beat { beat_in_bar: 0, .. } => { /* flash on the downbeat */ }
+///             BeatEvent::Cue { name, .. } if name == "drop" => { /* shake the camera */ }
+///             _ => {}
+///         }
+///     }
+/// #   break;
+/// }
+/// ```
+///
+/// [`update_music`]: BeatClock::update_music
+/// [`update_stream`]: BeatClock::update_stream
+/// [`update`]: BeatClock::update
+/// [`drain`]: BeatClock::drain
+#[derive(Debug, Clone)]
+pub struct BeatClock {
+    tempo: TempoMap,
+    /// Sorted by time
+    cues: Vec<CueMarker>,
+    /// Offset of the last update, `None` before the first one
+    last: Option<Time>,
+    events: VecDeque<BeatEvent>,
+}
+
+impl BeatClock {
+    /// Create a clock following `tempo`, without any cue.
+    #[must_use]
+    pub fn new(tempo: TempoMap) -> Self {
+        Self {
+            tempo,
+            cues: Vec::new(),
+            last: None,
+            events: VecDeque::new(),
+        }
+    }
+    /// The tempo map followed by this clock.
+    #[must_use]
+    pub fn tempo_map(&self) -> &TempoMap {
+        &self.tempo
+    }
+    /// Replace the tempo map.
+    pub fn set_tempo_map(&mut self, tempo: TempoMap) {
+        self.tempo = tempo;
+    }
+    /// Add a cue marker named `name` at `time`.
+    pub fn add_cue(&mut self, name: impl Into<String>, time: Time) {
+        let index = self.cues.partition_point(|c| c.time <= time);
+        self.cues.insert(
+            index,
+            CueMarker {
+                name: name.into(),
+                time,
+            },
+        );
+    }
+    /// Remove all the cue markers named `name`.
+    pub fn remove_cue(&mut self, name: &str) {
+        self.cues.retain(|c| c.name != name);
+    }
+    /// The cue markers, sorted by time.
+    #[must_use]
+    pub fn cues(&self) -> &[CueMarker] {
+        &self.cues
+    }
+    /// The beat at the offset of the last update.
+    #[must_use]
+    pub fn beat(&self) -> f64 {
+        self.last.map_or(0.0, |t| self.tempo.beat_at(t))
+    }
+    /// Forget the last offset, like when the music was stopped or seeked.
+    ///
+    /// Events at `offset` or before aren't reported by the next update.
+    pub fn reset(&mut self, offset: Time) {
+        self.last = Some(offset);
+        self.events.clear();
+    }
+
+    /// Update the clock with the playing offset of `music`.
+    ///
+    /// When the music loops, events up to the end of its loop points (or of the music, if it was
+    /// playing past them) are reported before the ones from the loop start.
+    pub fn update_music(&mut self, music: &Music) {
+        let offset = music.playing_offset();
+        let span = music.is_looping().then(|| {
+            let points = music.loop_points();
+            match self.last {
+                // SFML still loops at the end of the music when playing past the loop points
+                Some(last) if last > points.offset + points.length => TimeSpan {
+                    offset: Time::ZERO,
+                    length: music.duration(),
+                },
+                _ => points,
+            }
+        });
+        self.update(offset, span);
+    }
+    /// Update the clock with the playing offset of `player`.
+    ///
+    /// A looping stream is assumed to loop back to its start. Since the length of a stream isn't
+    /// known, events between the last update and the end of the stream are lost when it loops.
+    pub fn update_stream<S: SoundStream>(&mut self, player: &SoundStreamPlayer<S>) {
+        let span = player.is_looping().then_some(TimeSpan {
+            offset: Time::ZERO,
+            length: Time::ZERO,
+        });
+        self.update(player.playing_offset(), span);
+    }
+    /// Update the clock with a playing offset.
+    ///
+    /// `loop_span` is the range the playback wraps around in, if it loops. When `offset` is
+    /// before the last offset, the playback is assumed to have wrapped around `loop_span`, or
+    /// to have been seeked if it's `None`.
+    pub fn update(&mut self, offset: Time, loop_span: Option<TimeSpan>) {
+        let Some(last) = self.last else {
+            // Events right at the start are reported by the first update
+            self.collect(Time::microseconds(-1), offset);
+            self.last = Some(offset);
+            return;
+        };
+        if offset >= last {
+            self.collect(last, offset);
+        } else if let Some(span) = loop_span {
+            let end = span.offset + span.length;
+            if end > last {
+                self.collect(last, end);
+            }
+            // The loop start itself is crossed when wrapping
+            self.collect(span.offset - Time::microseconds(1), offset);
+        }
+        self.last = Some(offset);
+    }
+    /// Take the next event, in the order they were crossed.
+    pub fn poll(&mut self) -> Option<BeatEvent> {
+        self.events.pop_front()
+    }
+    /// Take all the pending events, in the order they were crossed.
+    pub fn drain(&mut self) -> impl Iterator<Item = BeatEvent> + '_ {
+        self.events.drain(..)
+    }
+
+    /// Queue the events in `(from, to]`.
+    fn collect(&mut self, from: Time, to: Time) {
+        let mut crossed = Vec::new();
+        let signature = self.tempo.signature;
+        let mut beat = self.tempo.first_beat_after(from);
+        loop {
+            let time = self.tempo.time_of_beat(beat);
+            if time > to {
+                break;
+            }
+            let per_bar = u64::from(signature.beats_per_bar);
+            crossed.push(BeatEvent::Beat {
+                index: beat,
+                bar: beat / per_bar,
+                beat_in_bar: u32::try_from(beat % per_bar).unwrap_or(0),
+                time,
+            });
+            beat += 1;
+        }
+        let start = self.cues.partition_point(|c| c.time <= from);
+        crossed.extend(
+            self.cues[start..]
+                .iter()
+                .take_while(|c| c.time <= to)
+                .map(|c| BeatEvent::Cue {
+                    name: c.name.clone(),
+                    time: c.time,
+                }),
+        );
+        // Stable, so beats stay before cues falling at the same time
+        crossed.sort_by_key(BeatEvent::time);
+        self.events.extend(crossed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn beats(clock: &mut BeatClock) -> Vec<u64> {
+        clock
+            .drain()
+            .filter_map(|e| match e {
+                BeatEvent::Beat { index, .. } => Some(index),
+                BeatEvent::Cue { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tempo_map() {
+        let map = TempoMap::new(120.0)
+            .with_offset(Time::milliseconds(100))
+            .with_tempo_change(8, 60.0);
+        assert_eq!(map.time_of_beat(0), Time::milliseconds(100));
+        assert_eq!(map.time_of_beat(1), Time::milliseconds(600));
+        assert_eq!(map.time_of_beat(8), Time::milliseconds(4100));
+        assert_eq!(map.time_of_beat(9), Time::milliseconds(5100));
+        assert!((map.beat_at(Time::milliseconds(4600)) - 8.5).abs() < 1e-9);
+        assert_eq!(map.time_at(8.5), Time::milliseconds(4600));
+        assert!((map.bpm_at(Time::seconds(10.0)) - 60.0).abs() < 1e-9);
+        assert!(map.beat_at(Time::ZERO) < 0.0);
+    }
+
+    #[test]
+    fn beats_and_cues() {
+        let tempo = TempoMap::new(120.0).with_signature(3, 4);
+        let mut clock = BeatClock::new(tempo);
+        clock.add_cue("chorus", Time::milliseconds(1250));
+        clock.update(Time::ZERO, None);
+        assert_eq!(beats(&mut clock), [0]);
+        clock.update(Time::milliseconds(400), None);
+        assert!(clock.poll().is_none());
+        clock.update(Time::milliseconds(1500), None);
+        let events: Vec<_> = clock.drain().collect();
+        assert_eq!(events.len(), 4);
+        assert!(matches!(
+            events[0],
+            BeatEvent::Beat {
+                index: 1,
+                bar: 0,
+                beat_in_bar: 1,
+                ..
+            }
+        ));
+        assert!(matches!(events[2], BeatEvent::Cue { ref name, .. } if name == "chorus"));
+        assert!(matches!(
+            events[3],
+            BeatEvent::Beat {
+                index: 3,
+                bar: 1,
+                beat_in_bar: 0,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn loop_wrap_and_seek() {
+        let mut clock = BeatClock::new(TempoMap::new(60.0));
+        let span = TimeSpan {
+            offset: Time::seconds(2.0),
+            length: Time::seconds(2.0),
+        };
+        clock.reset(Time::milliseconds(3500));
+        // Wrapping from 3.5s to 2.5s crosses the beat at 4s, then the one at 2s
+        clock.update(Time::milliseconds(2500), Some(span));
+        assert_eq!(beats(&mut clock), [4, 2]);
+        // Seeking backwards without looping reports nothing
+        clock.update(Time::milliseconds(500), None);
+        assert!(beats(&mut clock).is_empty());
+        clock.update(Time::milliseconds(1000), None);
+        assert_eq!(beats(&mut clock), [1]);
+    }
+}
@@ -0,0 +1,95 @@
+use std::{
+    any::Any,
+    error::Error,
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// Error a [`SoundStream`] can return from [`SoundStream::try_get_data`].
+///
+/// [`SoundStream`]: crate::audio::SoundStream
+/// [`SoundStream::try_get_data`]: crate::audio::SoundStream::try_get_data
+pub type StreamError = Box<dyn Error + Send + Sync>;
+
+/// A failure in a callback called from SFML's audio threads.
+///
+/// Audio threads can't report errors to the caller, so these are recorded by the
+/// [`SoundStreamPlayer`] or [`SoundRecorderDriver`] running the callback, and can be queried
+/// with their `last_error` method.
+///
+/// [`SoundStreamPlayer`]: crate::audio::SoundStreamPlayer
+/// [`SoundRecorderDriver`]: crate::audio::SoundRecorderDriver
+#[derive(Debug, Clone)]
+pub enum CallbackError {
+    /// A callback panicked, which stopped playback or capture
+    Panicked {
+        /// Name of the method that panicked, like `"get_data"`
+        callback: &'static str,
+        /// The panic message, if it was a string
+        message: String,
+    },
+    /// The stream returned an error from [`SoundStream::try_get_data`], which stopped playback
+    ///
+    /// [`SoundStream::try_get_data`]: crate::audio::SoundStream::try_get_data
+    Stream(Arc<dyn Error + Send + Sync>),
+}
+
+impl Error for CallbackError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Panicked { .. } => None,
+            Self::Stream(error) => Some(&**error),
+        }
+    }
+}
+
+impl fmt::Display for CallbackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Panicked { callback, message } => write!(f, "`{callback}` panicked: {message}"),
+            Self::Stream(error) => write!(f, "Stream failed: {error}"),
+        }
+    }
+}
+
+impl CallbackError {
+    pub(super) fn from_panic(callback: &'static str, payload: &(dyn Any + Send)) -> Self {
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            (*message).to_owned()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "Box<dyn Any>".to_owned()
+        };
+        Self::Panicked { callback, message }
+    }
+}
+
+/// What SFML's audio callbacks get as user data: the object they call into, and where to
+/// record their failures.
+#[derive(Debug)]
+pub(super) struct CallbackContext<T> {
+    pub(super) target: *mut T,
+    error: Mutex<Option<CallbackError>>,
+}
+
+impl<T> CallbackContext<T> {
+    pub(super) fn new(target: *mut T) -> Box<Self> {
+        Box::new(Self {
+            target,
+            error: Mutex::new(None),
+        })
+    }
+    pub(super) fn record(&self, error: CallbackError) {
+        *self.error.lock().unwrap_or_else(PoisonError::into_inner) = Some(error);
+    }
+    pub(super) fn last_error(&self) -> Option<CallbackError> {
+        self.error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+    pub(super) fn clear_error(&self) {
+        *self.error.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+}
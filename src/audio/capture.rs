@@ -1,11 +1,14 @@
 use {
     crate::{
         IntoSfResult, SfResult,
-        audio::SoundBuffer,
+        audio::{
+            SoundBuffer,
+            callback_error::{CallbackContext, CallbackError},
+        },
         cpp::{CppString, CppVector, FBox},
         ffi::audio as ffi,
     },
-    std::{ffi::CString, os::raw::c_void, ptr::NonNull},
+    std::{ffi::CString, os::raw::c_void, panic, ptr::NonNull},
 };
 
 /// Trait for processing captured sound data.
@@ -93,6 +96,8 @@ pub trait SoundRecorder: Send {
 pub struct SoundRecorderDriver<'a, R: 'a> {
     handle: NonNull<ffi::sfCustomSoundRecorder>,
     recorder: &'a mut R,
+    /// User data of the callbacks, which also records their failures.
+    context: Box<CallbackContext<R>>,
 }
 
 // SAFETY: An `sfCustomSoundRecorder` isn't tied to a particular thread, so it can be sent between
@@ -104,9 +109,26 @@ unsafe impl<R: Send> Send for SoundRecorderDriver<'_, R> {}
 // `&SoundRecorderDriver` between threads.
 unsafe impl<R: Sync> Sync for SoundRecorderDriver<'_, R> {}
 
+/// Call `f` on the recorder of the callback context `user_data`, recording a panic as a failure
+/// of `callback`.
+unsafe fn call_recorder<R, T>(
+    user_data: *mut c_void,
+    callback: &'static str,
+    f: impl FnOnce(&mut R) -> T,
+) -> Option<T> {
+    let context: &CallbackContext<R> = unsafe { &*user_data.cast() };
+    let recorder = context.target;
+    match panic::catch_unwind(panic::AssertUnwindSafe(|| f(unsafe { &mut *recorder }))) {
+        Ok(ret) => Some(ret),
+        Err(payload) => {
+            context.record(CallbackError::from_panic(callback, &*payload));
+            None
+        }
+    }
+}
+
 unsafe extern "C" fn on_start_callback<R: SoundRecorder>(user_data: *mut c_void) -> bool {
-    let recorder: *mut R = user_data.cast();
-    unsafe { (*recorder).on_start() }
+    unsafe { call_recorder(user_data, "on_start", R::on_start) }.unwrap_or(false)
 }
 
 unsafe extern "C" fn on_process_callback<R: SoundRecorder>(
@@ -114,13 +136,17 @@ unsafe extern "C" fn on_process_callback<R: SoundRecorder>(
     len: usize,
     user_data: *mut c_void,
 ) -> bool {
-    let recorder: *mut R = user_data.cast();
-    unsafe { (*recorder).on_process_samples(std::slice::from_raw_parts(data, len)) }
+    let samples = unsafe { std::slice::from_raw_parts(data, len) };
+    unsafe {
+        call_recorder(user_data, "on_process_samples", |r: &mut R| {
+            r.on_process_samples(samples)
+        })
+    }
+    .unwrap_or(false)
 }
 
 unsafe extern "C" fn on_stop_callback<R: SoundRecorder>(user_data: *mut c_void) {
-    let recorder: *mut R = user_data.cast();
-    unsafe { (*recorder).on_stop() }
+    let _ = unsafe { call_recorder(user_data, "on_stop", R::on_stop) };
 }
 
 impl<'a, R: SoundRecorder> SoundRecorderDriver<'a, R> {
@@ -130,18 +156,20 @@ impl<'a, R: SoundRecorder> SoundRecorderDriver<'a, R> {
     ///
     /// Panics if a `SoundRecorderDriver` can't be created for whatever reason
     pub fn new(sound_recorder: &'a mut R) -> Self {
+        let context = CallbackContext::new(std::ptr::from_mut(sound_recorder));
+        let user_data: *const CallbackContext<R> = &*context;
         Self {
             handle: unsafe {
-                let ptr: *mut R = sound_recorder;
                 NonNull::new(ffi::sfCustomSoundRecorder_new(
                     Some(on_start_callback::<R>),
                     Some(on_process_callback::<R>),
                     Some(on_stop_callback::<R>),
-                    ptr.cast(),
+                    user_data.cast_mut().cast(),
                 ))
                 .expect("Failed to create SoundRecorderDriver")
             },
             recorder: sound_recorder,
+            context,
         }
     }
     /// Start the capture.
@@ -162,6 +190,18 @@ impl<'a, R: SoundRecorder> SoundRecorderDriver<'a, R> {
         unsafe { ffi::sfCustomSoundRecorder_start(self.handle.as_ptr(), sample_rate) }
             .into_sf_result()
     }
+    /// The last failure of the recorder's callbacks, if any.
+    ///
+    /// Capture stops when [`SoundRecorder::on_start`] or [`SoundRecorder::on_process_samples`]
+    /// panics. A panic in [`SoundRecorder::on_stop`] is recorded too.
+    #[must_use]
+    pub fn last_error(&self) -> Option<CallbackError> {
+        self.context.last_error()
+    }
+    /// Forget the last failure of the recorder's callbacks.
+    pub fn clear_error(&mut self) {
+        self.context.clear_error();
+    }
     /// Stop the capture, lending out the underlying [`SoundRecorder`].
     pub fn stop(&mut self) -> &mut R {
         unsafe {
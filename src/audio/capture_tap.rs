@@ -0,0 +1,359 @@
+use {
+    super::capture::SoundRecorder,
+    crate::system::Time,
+    std::{
+        cell::UnsafeCell,
+        fmt,
+        sync::{
+            Arc,
+            atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        },
+        time::Instant,
+    },
+};
+
+/// A chunk of samples captured by a [`CaptureTap`].
+#[derive(Debug, Clone, Default)]
+pub struct CapturedChunk {
+    samples: Vec<i16>,
+    sample_offset: u64,
+    timestamp: Time,
+}
+
+impl CapturedChunk {
+    /// The captured samples, interleaved if recording with more than one channel.
+    #[must_use]
+    pub fn samples(&self) -> &[i16] {
+        &self.samples
+    }
+    /// Index of the first sample of this chunk, counted from the start of the capture.
+    ///
+    /// Samples dropped because of overruns are counted too, so gaps in the recording can be
+    /// detected by comparing the offset with the end of the previous chunk.
+    #[must_use]
+    pub fn sample_offset(&self) -> u64 {
+        self.sample_offset
+    }
+    /// Time elapsed between the start of the capture and the moment this chunk was delivered
+    /// by the capture thread.
+    #[must_use]
+    pub fn timestamp(&self) -> Time {
+        self.timestamp
+    }
+    /// Position of the first sample of this chunk in the recording, derived from
+    /// [`sample_offset`](Self::sample_offset).
+    ///
+    /// Unlike [`timestamp`](Self::timestamp), this doesn't suffer from the scheduling jitter
+    /// of the capture thread.
+    #[must_use]
+    pub fn position(&self, sample_rate: u32, channel_count: u32) -> Time {
+        let frames = self.sample_offset / u64::from(channel_count.max(1));
+        let micros = u128::from(frames) * 1_000_000 / u128::from(sample_rate.max(1));
+        Time::microseconds(i64::try_from(micros).unwrap_or(i64::MAX))
+    }
+    /// Peak absolute amplitude of the chunk, normalized to `[0, 1]`.
+    #[must_use]
+    pub fn peak(&self) -> f32 {
+        let peak = self
+            .samples
+            .iter()
+            .map(|s| s.unsigned_abs())
+            .max()
+            .unwrap_or(0);
+        f32::from(peak) / 32_768.0
+    }
+    /// Root mean square amplitude of the chunk, normalized to `[0, 1]`.
+    #[must_use]
+    pub fn rms(&self) -> f32 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        let sum: f64 = self
+            .samples
+            .iter()
+            .map(|&s| f64::from(s) * f64::from(s))
+            .sum();
+        #[expect(clippy::cast_possible_truncation)]
+        let rms = ((sum / self.samples.len() as f64).sqrt() / 32_768.0) as f32;
+        rms
+    }
+}
+
+/// State shared by the two halves of a capture tap.
+///
+/// This is a single-producer/single-consumer ring buffer of chunks.
+/// `head` and `tail` only ever increase, and each of them is only written by one side.
+/// A slot is owned by the producer while it is outside of `tail..head`, and by the
+/// consumer while it is inside.
+struct Shared {
+    slots: Box<[UnsafeCell<CapturedChunk>]>,
+    /// Number of chunks ever pushed. Only written by the producer.
+    head: AtomicUsize,
+    /// Number of chunks ever popped. Only written by the consumer.
+    tail: AtomicUsize,
+    overruns: AtomicU64,
+    dropped_samples: AtomicU64,
+    capturing: AtomicBool,
+}
+
+// SAFETY: Access to the slots is synchronized by `head` and `tail`, as described above.
+unsafe impl Sync for Shared {}
+
+impl Shared {
+    fn slot(&self, index: usize) -> *mut CapturedChunk {
+        self.slots[index % self.slots.len()].get()
+    }
+}
+
+/// A ready-made [`SoundRecorder`] that hands captured samples over to another thread.
+///
+/// [`SoundRecorder::on_process_samples`] is called from SFML's capture thread, so getting the
+/// samples back to the game loop needs some synchronization. A `CaptureTap` pushes every chunk
+/// into a bounded, lock-free ring buffer, which the matching [`CaptureTapReader`] drains
+/// without ever blocking. Neither side ever waits on the other, which makes it suitable for
+/// voice chat, level meters or speech recognition.
+///
+/// If the reader doesn't keep up and the ring buffer is full, new chunks are dropped.
+/// Such overruns are counted, see [`CaptureTapReader::overrun_count`].
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::audio::{capture::SoundRecorderDriver, CaptureTap};
+/// let (mut tap, mut reader) = CaptureTap::new(16);
+/// let mut driver = SoundRecorderDriver::new(&mut tap);
+/// driver.start(44_100).unwrap();
+/// loop {
+///     // Once per frame
+///     reader.drain(|chunk| println!("Input level: {:.2}", chunk.peak()));
+/// #   break;
+/// }
+/// ```
+pub struct CaptureTap {
+    shared: Arc<Shared>,
+    started: Instant,
+    next_offset: u64,
+}
+
+/// The receiving half of a [`CaptureTap`].
+pub struct CaptureTapReader {
+    shared: Arc<Shared>,
+}
+
+impl fmt::Debug for CaptureTap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CaptureTap")
+            .field("capacity", &self.shared.slots.len())
+            .field("next_offset", &self.next_offset)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for CaptureTapReader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CaptureTapReader")
+            .field("capacity", &self.capacity())
+            .field("len", &self.len())
+            .field("overruns", &self.overrun_count())
+            .finish_non_exhaustive()
+    }
+}
+
+impl CaptureTap {
+    /// Create a new capture tap holding up to `capacity` chunks, and its reader.
+    ///
+    /// The capacity should cover the longest time the reader may go without draining.
+    /// With the default processing interval of 100 ms, a capacity of 16 is 1.6 seconds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    #[must_use]
+    pub fn new(capacity: usize) -> (Self, CaptureTapReader) {
+        assert!(capacity > 0, "CaptureTap capacity must be non-zero");
+        let shared = Arc::new(Shared {
+            slots: (0..capacity)
+                .map(|_| UnsafeCell::new(CapturedChunk::default()))
+                .collect(),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            overruns: AtomicU64::new(0),
+            dropped_samples: AtomicU64::new(0),
+            capturing: AtomicBool::new(false),
+        });
+        (
+            Self {
+                shared: Arc::clone(&shared),
+                started: Instant::now(),
+                next_offset: 0,
+            },
+            CaptureTapReader { shared },
+        )
+    }
+    /// Push a chunk of samples, as if it had been captured.
+    ///
+    /// Returns `false` if the ring buffer was full and the chunk was dropped.
+    pub fn push(&mut self, samples: &[i16]) -> bool {
+        let shared = &*self.shared;
+        let offset = self.next_offset;
+        self.next_offset += samples.len() as u64;
+        let head = shared.head.load(Ordering::Relaxed);
+        let tail = shared.tail.load(Ordering::Acquire);
+        if head - tail == shared.slots.len() {
+            let _ = shared.overruns.fetch_add(1, Ordering::Relaxed);
+            let _ = shared
+                .dropped_samples
+                .fetch_add(samples.len() as u64, Ordering::Relaxed);
+            return false;
+        }
+        let elapsed = self.started.elapsed();
+        // SAFETY: The slot at `head` is outside of `tail..head`, so the consumer doesn't touch it
+        let slot = unsafe { &mut *shared.slot(head) };
+        slot.samples.clear();
+        slot.samples.extend_from_slice(samples);
+        slot.sample_offset = offset;
+        slot.timestamp = Time::microseconds(i64::try_from(elapsed.as_micros()).unwrap_or(i64::MAX));
+        shared.head.store(head + 1, Ordering::Release);
+        true
+    }
+}
+
+impl SoundRecorder for CaptureTap {
+    fn on_start(&mut self) -> bool {
+        self.started = Instant::now();
+        self.next_offset = 0;
+        self.shared.capturing.store(true, Ordering::Release);
+        true
+    }
+    fn on_process_samples(&mut self, samples: &[i16]) -> bool {
+        let _ = self.push(samples);
+        true
+    }
+    fn on_stop(&mut self) {
+        self.shared.capturing.store(false, Ordering::Release);
+    }
+}
+
+impl CaptureTapReader {
+    /// Pop the oldest chunk, swapping its contents into `chunk`.
+    ///
+    /// The previous buffer of `chunk` is handed back to the capture thread for reuse,
+    /// so reusing the same `chunk` avoids allocating on either side.
+    ///
+    /// Returns `false` without waiting if no chunk is available.
+    pub fn pop_into(&mut self, chunk: &mut CapturedChunk) -> bool {
+        let shared = &*self.shared;
+        let tail = shared.tail.load(Ordering::Relaxed);
+        if tail == shared.head.load(Ordering::Acquire) {
+            return false;
+        }
+        // SAFETY: The slot at `tail` is inside of `tail..head`, so the producer doesn't touch it
+        std::mem::swap(unsafe { &mut *shared.slot(tail) }, chunk);
+        shared.tail.store(tail + 1, Ordering::Release);
+        true
+    }
+    /// Pop the oldest chunk, if any.
+    pub fn pop(&mut self) -> Option<CapturedChunk> {
+        let mut chunk = CapturedChunk::default();
+        self.pop_into(&mut chunk).then_some(chunk)
+    }
+    /// Call `f` on every available chunk, oldest first, removing them from the buffer.
+    ///
+    /// Chunks captured while this runs are left for the next call.
+    /// Returns the number of chunks processed.
+    pub fn drain<F: FnMut(&CapturedChunk)>(&mut self, mut f: F) -> usize {
+        let shared = &*self.shared;
+        let tail = shared.tail.load(Ordering::Relaxed);
+        let head = shared.head.load(Ordering::Acquire);
+        for index in tail..head {
+            // SAFETY: The slot is inside of `tail..head`, so the producer doesn't touch it
+            f(unsafe { &*shared.slot(index) });
+            shared.tail.store(index + 1, Ordering::Release);
+        }
+        head - tail
+    }
+    /// Number of chunks waiting to be read.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        let tail = self.shared.tail.load(Ordering::Relaxed);
+        self.shared.head.load(Ordering::Acquire) - tail
+    }
+    /// Whether there are no chunks waiting to be read.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Maximum number of chunks the buffer can hold.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.shared.slots.len()
+    }
+    /// Number of chunks dropped because the buffer was full.
+    #[must_use]
+    pub fn overrun_count(&self) -> u64 {
+        self.shared.overruns.load(Ordering::Relaxed)
+    }
+    /// Number of samples dropped because the buffer was full.
+    #[must_use]
+    pub fn dropped_sample_count(&self) -> u64 {
+        self.shared.dropped_samples.load(Ordering::Relaxed)
+    }
+    /// Whether the tap is currently capturing, i.e. between `on_start` and `on_stop`.
+    #[must_use]
+    pub fn is_capturing(&self) -> bool {
+        self.shared.capturing.load(Ordering::Acquire)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{CaptureTap, CapturedChunk},
+        crate::system::Time,
+    };
+
+    #[test]
+    fn overruns_drop_new_chunks() {
+        let (mut tap, mut reader) = CaptureTap::new(2);
+        assert!(tap.push(&[1, 2]));
+        assert!(tap.push(&[3]));
+        assert!(!tap.push(&[4, 5, 6]));
+        assert_eq!(reader.overrun_count(), 1);
+        assert_eq!(reader.dropped_sample_count(), 3);
+        assert_eq!(reader.len(), 2);
+        let first = reader.pop().expect("chunk available");
+        assert_eq!((first.samples(), first.sample_offset()), (&[1, 2][..], 0));
+        assert!(tap.push(&[7]));
+        let mut offsets = Vec::new();
+        assert_eq!(reader.drain(|c| offsets.push(c.sample_offset())), 2);
+        // The dropped chunk leaves a gap in the offsets
+        assert_eq!(offsets, [2, 6]);
+        assert!(reader.is_empty());
+        let chunk = CapturedChunk {
+            sample_offset: 6,
+            ..CapturedChunk::default()
+        };
+        assert_eq!(chunk.position(4, 2), Time::milliseconds(750));
+    }
+
+    #[test]
+    fn chunks_cross_threads_in_order() {
+        let (mut tap, mut reader) = CaptureTap::new(4);
+        let producer = std::thread::spawn(move || {
+            for i in 0..1000i16 {
+                while !tap.push(&[i, i]) {
+                    std::thread::yield_now();
+                }
+            }
+        });
+        let mut chunk = CapturedChunk::default();
+        let mut expected = 0;
+        while expected < 1000 {
+            if reader.pop_into(&mut chunk) {
+                assert_eq!(chunk.samples(), [expected, expected]);
+                expected += 1;
+            }
+        }
+        producer.join().expect("producer panicked");
+    }
+}
@@ -0,0 +1,837 @@
+use {
+    libflac_sys as flac,
+    std::{
+        error::Error,
+        ffi::{c_int, c_void},
+        fmt, fs,
+        io::{self, Cursor, Read, Seek, SeekFrom},
+        path::Path,
+    },
+};
+
+/// Error that can happen when reading the tags of an audio file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataError {
+    /// The data isn't a FLAC, Ogg Vorbis or MP3 file
+    UnknownFormat,
+    /// The tags are malformed
+    Invalid(&'static str),
+    /// The data couldn't be read
+    Io(io::ErrorKind),
+}
+
+impl Error for MetadataError {}
+
+impl fmt::Display for MetadataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownFormat => write!(f, "Unknown audio file format"),
+            Self::Invalid(what) => write!(f, "Invalid audio tags: {what}"),
+            Self::Io(kind) => write!(f, "Failed to read audio tags: {kind}"),
+        }
+    }
+}
+
+impl From<io::Error> for MetadataError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error.kind())
+    }
+}
+
+/// Where the tags of an [`AudioTags`] were read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagFormat {
+    /// The Vorbis comment block of a FLAC or Ogg FLAC file
+    Flac,
+    /// The comment header of an Ogg Vorbis file
+    VorbisComment,
+    /// An `ID3v2` tag (versions 2.2 to 2.4), at the start of an MP3 file
+    Id3v2,
+    /// An `ID3v1` tag, at the end of an MP3 file
+    Id3v1,
+}
+
+/// The tags of an audio file, like its title, artist and album.
+///
+/// Tags are stored as key-value pairs, with keys in the upper case Vorbis comment style
+/// (`TITLE`, `ARTIST`, `TRACKNUMBER`...). ID3 frames are translated to these keys, and the ones
+/// that have no equivalent keep their frame identifier as key. A key can have several values.
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::audio::AudioTags;
+/// let tags = AudioTags::from_file("song.flac").unwrap();
+/// println!(
+///     "{} - {} ({})",
+///     tags.artist().unwrap_or("Unknown artist"),
+///     tags.title().unwrap_or("Untitled"),
+///     tags.album().unwrap_or("Unknown album"),
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioTags {
+    format: TagFormat,
+    vendor: Option<String>,
+    fields: Vec<(String, String)>,
+}
+
+impl AudioTags {
+    /// Read the tags of the audio file at `path`.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, MetadataError> {
+        Self::from_stream(&mut fs::File::open(path)?)
+    }
+    /// Read the tags of an audio file in memory.
+    pub fn from_memory(data: &[u8]) -> Result<Self, MetadataError> {
+        Self::from_stream(&mut Cursor::new(data))
+    }
+    /// Read the tags of an audio file from a stream, starting at its current position.
+    ///
+    /// The format is detected from the contents: FLAC and Ogg FLAC files are read with
+    /// libFLAC, Ogg Vorbis comments and ID3 tags are parsed directly.
+    pub fn from_stream<T: Read + Seek>(stream: &mut T) -> Result<Self, MetadataError> {
+        let start = stream.stream_position()?;
+        let mut magic = [0; 64];
+        let len = read_up_to(stream, &mut magic)?;
+        let magic = &magic[..len];
+        let _ = stream.seek(SeekFrom::Start(start))?;
+        if magic.starts_with(b"fLaC") {
+            read_flac(stream, false)
+        } else if magic.starts_with(b"OggS") {
+            // The first packet of the stream starts right after the segment table
+            let packet = magic
+                .get(26)
+                .and_then(|&segments| magic.get(27 + usize::from(segments)..))
+                .unwrap_or_default();
+            if packet.starts_with(b"\x7FFLAC") {
+                read_flac(stream, true)
+            } else if packet.starts_with(b"\x01vorbis") {
+                read_ogg_vorbis(stream)
+            } else {
+                Err(MetadataError::UnknownFormat)
+            }
+        } else if magic.starts_with(b"ID3") {
+            read_id3v2(stream)
+        } else if magic.len() >= 2 && magic[0] == 0xFF && magic[1] & 0xE0 == 0xE0 {
+            // MPEG frame sync, without an ID3v2 tag
+            read_id3v1(stream, start)?.ok_or(MetadataError::UnknownFormat)
+        } else {
+            Err(MetadataError::UnknownFormat)
+        }
+    }
+
+    /// Where the tags were read from.
+    #[must_use]
+    pub fn format(&self) -> TagFormat {
+        self.format
+    }
+    /// The vendor string of Vorbis comments, naming the encoder.
+    #[must_use]
+    pub fn vendor(&self) -> Option<&str> {
+        self.vendor.as_deref()
+    }
+    /// The first value of the tag `key`, ignoring case.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.get_all(key).next()
+    }
+    /// All the values of the tag `key`, ignoring case.
+    pub fn get_all<'a>(&'a self, key: &str) -> impl Iterator<Item = &'a str> {
+        self.fields
+            .iter()
+            .filter(move |(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+    /// All the tags, as `(key, value)` pairs, in file order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.fields.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+    /// The number of tags.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+    /// Whether there are no tags.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+    /// The title (`TITLE`).
+    #[must_use]
+    pub fn title(&self) -> Option<&str> {
+        self.get("TITLE")
+    }
+    /// The artist (`ARTIST`).
+    #[must_use]
+    pub fn artist(&self) -> Option<&str> {
+        self.get("ARTIST")
+    }
+    /// The album (`ALBUM`).
+    #[must_use]
+    pub fn album(&self) -> Option<&str> {
+        self.get("ALBUM")
+    }
+    /// The track number (`TRACKNUMBER`), ignoring a total in the `3/12` form.
+    #[must_use]
+    pub fn track_number(&self) -> Option<u32> {
+        let value = self.get("TRACKNUMBER")?;
+        value.split('/').next()?.trim().parse().ok()
+    }
+    /// The number of tracks of the album (`TRACKTOTAL`, or the total of `TRACKNUMBER`).
+    #[must_use]
+    pub fn track_total(&self) -> Option<u32> {
+        self.get("TRACKTOTAL")
+            .or_else(|| self.get("TOTALTRACKS"))
+            .or_else(|| self.get("TRACKNUMBER")?.split_once('/').map(|(_, t)| t))
+            .and_then(|total| total.trim().parse().ok())
+    }
+
+    fn new(format: TagFormat) -> Self {
+        Self {
+            format,
+            vendor: None,
+            fields: Vec::new(),
+        }
+    }
+    fn push(&mut self, key: &str, value: String) {
+        let value = value.trim_end_matches('\0').to_owned();
+        if !value.is_empty() {
+            self.fields.push((key.to_ascii_uppercase(), value));
+        }
+    }
+    /// Add a `KEY=value` Vorbis comment.
+    fn push_comment(&mut self, comment: &[u8]) {
+        let comment = String::from_utf8_lossy(comment);
+        if let Some((key, value)) = comment.split_once('=') {
+            self.push(key, value.to_owned());
+        }
+    }
+}
+
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut len = 0;
+    while len < buf.len() {
+        match reader.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(len)
+}
+
+fn u32_le(data: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(pos..pos + 4)?.try_into().ok()?))
+}
+
+fn to_usize(value: u32) -> usize {
+    usize::try_from(value).unwrap_or(usize::MAX)
+}
+
+/// Parse a Vorbis comment structure (without the packet type and framing bit).
+fn parse_vorbis_comment(data: &[u8], format: TagFormat) -> Option<AudioTags> {
+    let mut tags = AudioTags::new(format);
+    let vendor_len = to_usize(u32_le(data, 0)?);
+    let vendor = data.get(4..4usize.checked_add(vendor_len)?)?;
+    tags.vendor = Some(String::from_utf8_lossy(vendor).into_owned());
+    let mut pos = 4 + vendor_len;
+    let count = u32_le(data, pos)?;
+    pos += 4;
+    for _ in 0..count {
+        let len = to_usize(u32_le(data, pos)?);
+        pos += 4;
+        tags.push_comment(data.get(pos..pos.checked_add(len)?)?);
+        pos += len;
+    }
+    Some(tags)
+}
+
+// FLAC, through libFLAC's metadata interface reading from the stream
+
+unsafe extern "C" fn flac_read(
+    ptr: *mut c_void,
+    size: usize,
+    count: usize,
+    handle: flac::FLAC__IOHandle,
+) -> usize {
+    let reader = unsafe { &mut *handle.cast::<&mut dyn ReadSeekDyn>() };
+    let Some(len) = size.checked_mul(count).filter(|&len| len > 0) else {
+        return 0;
+    };
+    let buf = unsafe { std::slice::from_raw_parts_mut(ptr.cast::<u8>(), len) };
+    read_up_to(reader, buf).map_or(0, |read| read / size)
+}
+
+unsafe extern "C" fn flac_seek(
+    handle: flac::FLAC__IOHandle,
+    offset: flac::FLAC__int64,
+    whence: c_int,
+) -> c_int {
+    let reader = unsafe { &mut *handle.cast::<&mut dyn ReadSeekDyn>() };
+    let pos = match whence {
+        0 => u64::try_from(offset).map(SeekFrom::Start),
+        1 => Ok(SeekFrom::Current(offset)),
+        2 => Ok(SeekFrom::End(offset)),
+        _ => return -1,
+    };
+    match pos.map(|pos| reader.seek(pos)) {
+        Ok(Ok(_)) => 0,
+        _ => -1,
+    }
+}
+
+unsafe extern "C" fn flac_tell(handle: flac::FLAC__IOHandle) -> flac::FLAC__int64 {
+    let reader = unsafe { &mut *handle.cast::<&mut dyn ReadSeekDyn>() };
+    reader
+        .stream_position()
+        .ok()
+        .and_then(|pos| i64::try_from(pos).ok())
+        .unwrap_or(-1)
+}
+
+unsafe extern "C" fn flac_eof(handle: flac::FLAC__IOHandle) -> c_int {
+    let reader = unsafe { &mut *handle.cast::<&mut dyn ReadSeekDyn>() };
+    let at_end = (|| {
+        let pos = reader.stream_position()?;
+        let end = reader.seek(SeekFrom::End(0))?;
+        let _ = reader.seek(SeekFrom::Start(pos))?;
+        io::Result::Ok(pos >= end)
+    })();
+    c_int::from(at_end.unwrap_or(true))
+}
+
+trait ReadSeekDyn: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeekDyn for T {}
+
+/// Owns a libFLAC metadata chain and iterator.
+struct FlacChain {
+    chain: *mut flac::FLAC__Metadata_Chain,
+    iterator: *mut flac::FLAC__Metadata_Iterator,
+}
+
+impl Drop for FlacChain {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.iterator.is_null() {
+                flac::FLAC__metadata_iterator_delete(self.iterator);
+            }
+            if !self.chain.is_null() {
+                flac::FLAC__metadata_chain_delete(self.chain);
+            }
+        }
+    }
+}
+
+fn read_flac<T: Read + Seek>(stream: &mut T, ogg: bool) -> Result<AudioTags, MetadataError> {
+    let chain = FlacChain {
+        chain: unsafe { flac::FLAC__metadata_chain_new() },
+        iterator: unsafe { flac::FLAC__metadata_iterator_new() },
+    };
+    if chain.chain.is_null() || chain.iterator.is_null() {
+        return Err(MetadataError::Io(io::ErrorKind::OutOfMemory));
+    }
+    let mut reader: &mut dyn ReadSeekDyn = stream;
+    let handle: *mut &mut dyn ReadSeekDyn = &mut reader;
+    let callbacks = flac::FLAC__IOCallbacks {
+        read: Some(flac_read),
+        write: None,
+        seek: Some(flac_seek),
+        tell: Some(flac_tell),
+        eof: Some(flac_eof),
+        close: None,
+    };
+    let read = unsafe {
+        if ogg {
+            flac::FLAC__metadata_chain_read_ogg_with_callbacks(
+                chain.chain,
+                handle.cast(),
+                callbacks,
+            )
+        } else {
+            flac::FLAC__metadata_chain_read_with_callbacks(chain.chain, handle.cast(), callbacks)
+        }
+    };
+    if read == 0 {
+        return Err(MetadataError::Invalid("libFLAC couldn't read the metadata"));
+    }
+    let mut tags = AudioTags::new(TagFormat::Flac);
+    unsafe {
+        flac::FLAC__metadata_iterator_init(chain.iterator, chain.chain);
+        loop {
+            if flac::FLAC__metadata_iterator_get_block_type(chain.iterator)
+                == flac::FLAC__METADATA_TYPE_VORBIS_COMMENT
+            {
+                let block = &*flac::FLAC__metadata_iterator_get_block(chain.iterator);
+                let comment = &block.data.vorbis_comment;
+                let entry = |entry: &flac::FLAC__StreamMetadata_VorbisComment_Entry| {
+                    if entry.entry.is_null() {
+                        &[][..]
+                    } else {
+                        std::slice::from_raw_parts(entry.entry, to_usize(entry.length))
+                    }
+                };
+                tags.vendor =
+                    Some(String::from_utf8_lossy(entry(&comment.vendor_string)).into_owned());
+                if !comment.comments.is_null() {
+                    let comments = std::slice::from_raw_parts(
+                        comment.comments,
+                        to_usize(comment.num_comments),
+                    );
+                    for comment in comments {
+                        tags.push_comment(entry(comment));
+                    }
+                }
+            }
+            if flac::FLAC__metadata_iterator_next(chain.iterator) == 0 {
+                break;
+            }
+        }
+    }
+    Ok(tags)
+}
+
+// Ogg Vorbis
+
+/// Read the next Ogg page, returning its serial number, its segment table and its data.
+fn read_ogg_page<R: Read>(reader: &mut R) -> Result<(u32, Vec<u8>, Vec<u8>), MetadataError> {
+    let mut header = [0; 27];
+    reader.read_exact(&mut header)?;
+    if &header[..4] != b"OggS" {
+        return Err(MetadataError::Invalid("missing Ogg page"));
+    }
+    let serial = u32_le(&header, 14).unwrap_or_default();
+    let mut segments = vec![0; usize::from(header[26])];
+    reader.read_exact(&mut segments)?;
+    let mut data = vec![0; segments.iter().map(|&s| usize::from(s)).sum()];
+    reader.read_exact(&mut data)?;
+    Ok((serial, segments, data))
+}
+
+fn read_ogg_vorbis<R: Read>(reader: &mut R) -> Result<AudioTags, MetadataError> {
+    // Packets are split into segments, a segment shorter than 255 bytes ending a packet
+    let mut serial = None;
+    let mut packets = Vec::new();
+    let mut packet = Vec::new();
+    // The comment header is the second packet, it's usually within a couple pages but may
+    // hold cover art, so cap its size rather than the number of pages
+    while packets.len() < 2 {
+        let (page_serial, segments, data) = read_ogg_page(reader)?;
+        if *serial.get_or_insert(page_serial) != page_serial {
+            continue;
+        }
+        let mut pos = 0;
+        for &len in &segments {
+            let len = usize::from(len);
+            packet.extend_from_slice(&data[pos..pos + len]);
+            pos += len;
+            if len < 255 {
+                packets.push(std::mem::take(&mut packet));
+            }
+        }
+        if packet.len() > 16 << 20 {
+            return Err(MetadataError::Invalid("Vorbis comment header too large"));
+        }
+    }
+    packets[1]
+        .strip_prefix(b"\x03vorbis")
+        .and_then(|comment| parse_vorbis_comment(comment, TagFormat::VorbisComment))
+        .ok_or(MetadataError::Invalid("malformed Vorbis comment header"))
+}
+
+// ID3
+
+/// Decode an ID3 text in encoding `encoding`.
+fn decode_id3_text(encoding: u8, data: &[u8]) -> String {
+    match encoding {
+        // UTF-16 with a byte order mark, or big endian
+        1 | 2 => {
+            let little_endian = encoding == 1 && data.starts_with(&[0xFF, 0xFE]);
+            let data = if encoding == 1 && data.len() >= 2 && matches!(data[0], 0xFE | 0xFF) {
+                &data[2..]
+            } else {
+                data
+            };
+            let units = data.chunks_exact(2).map(|c| {
+                if little_endian {
+                    u16::from_le_bytes([c[0], c[1]])
+                } else {
+                    u16::from_be_bytes([c[0], c[1]])
+                }
+            });
+            char::decode_utf16(units)
+                .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect()
+        }
+        3 => String::from_utf8_lossy(data).into_owned(),
+        // ISO-8859-1 maps directly to the first 256 code points
+        _ => data.iter().map(|&b| char::from(b)).collect(),
+    }
+}
+
+/// Split ID3 text at its first terminator, returning the text and what follows.
+fn split_id3_text(encoding: u8, data: &[u8]) -> (&[u8], &[u8]) {
+    let position = if matches!(encoding, 1 | 2) {
+        data.chunks_exact(2)
+            .position(|c| c == [0, 0])
+            .map(|i| (i * 2, i * 2 + 2))
+    } else {
+        data.iter().position(|&b| b == 0).map(|i| (i, i + 1))
+    };
+    match position {
+        Some((end, next)) => (&data[..end], &data[next..]),
+        None => (data, &[]),
+    }
+}
+
+/// Remove the unsynchronisation scheme, which turns `FF 00` into `FF`.
+fn resynchronize(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut last = 0;
+    for &byte in data {
+        if !(last == 0xFF && byte == 0) {
+            out.push(byte);
+        }
+        last = byte;
+    }
+    out
+}
+
+fn syncsafe(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .fold(0, |acc, &b| (acc << 7) | usize::from(b & 0x7F))
+}
+
+/// The Vorbis comment key matching an ID3 frame identifier.
+fn id3_key(id: &str) -> &str {
+    match id {
+        "TIT2" | "TT2" => "TITLE",
+        "TPE1" | "TP1" => "ARTIST",
+        "TALB" | "TAL" => "ALBUM",
+        "TPE2" | "TP2" => "ALBUMARTIST",
+        "TRCK" | "TRK" => "TRACKNUMBER",
+        "TPOS" | "TPA" => "DISCNUMBER",
+        "TYER" | "TDRC" | "TYE" => "DATE",
+        "TCON" | "TCO" => "GENRE",
+        "TCOM" | "TCM" => "COMPOSER",
+        "COMM" | "COM" => "COMMENT",
+        _ => id,
+    }
+}
+
+fn read_id3v2<R: Read + Seek>(reader: &mut R) -> Result<AudioTags, MetadataError> {
+    let start = reader.stream_position()?;
+    let mut header = [0; 10];
+    reader.read_exact(&mut header)?;
+    let version = header[3];
+    if !(2..=4).contains(&version) {
+        return Err(MetadataError::Invalid("unsupported ID3v2 version"));
+    }
+    let flags = header[5];
+    // The size isn't trusted with an allocation before the data is there
+    let size = syncsafe(&header[6..10]);
+    let mut data = Vec::new();
+    let _ = reader.take(size as u64).read_to_end(&mut data)?;
+    if data.len() < size {
+        return Err(MetadataError::Io(io::ErrorKind::UnexpectedEof));
+    }
+    if flags & 0x80 != 0 && version < 4 {
+        data = resynchronize(&data);
+    }
+    let mut pos = 0;
+    if flags & 0x40 != 0 && version > 2 {
+        // Extended header, whose size includes itself in 2.4 only
+        let size = data
+            .get(0..4)
+            .ok_or(MetadataError::Invalid("truncated ID3v2 tag"))?;
+        pos = if version == 4 {
+            syncsafe(size)
+        } else {
+            to_usize(u32::from_be_bytes([size[0], size[1], size[2], size[3]])) + 4
+        };
+    }
+    let (id_len, header_len) = if version == 2 { (3, 6) } else { (4, 10) };
+    let mut tags = AudioTags::new(TagFormat::Id3v2);
+    while let Some(frame_header) = data.get(pos..pos + header_len) {
+        if frame_header[0] == 0 {
+            // Padding
+            break;
+        }
+        let id = String::from_utf8_lossy(&frame_header[..id_len]).into_owned();
+        let size = match version {
+            2 => to_usize(u32::from_be_bytes([
+                0,
+                frame_header[3],
+                frame_header[4],
+                frame_header[5],
+            ])),
+            3 => to_usize(u32::from_be_bytes([
+                frame_header[4],
+                frame_header[5],
+                frame_header[6],
+                frame_header[7],
+            ])),
+            _ => syncsafe(&frame_header[4..8]),
+        };
+        let format_flags = if version == 2 { 0 } else { frame_header[9] };
+        pos += header_len;
+        let Some(body) = data.get(pos..pos + size) else {
+            break;
+        };
+        pos += size;
+        // Compressed or encrypted frames are skipped
+        let skip = if version == 4 { 0x0C } else { 0xC0 };
+        if format_flags & skip != 0 {
+            continue;
+        }
+        let mut body = if version == 4 && format_flags & 0x02 != 0 {
+            resynchronize(body)
+        } else {
+            body.to_vec()
+        };
+        if version == 4 && format_flags & 0x01 != 0 && body.len() >= 4 {
+            // Data length indicator
+            let _ = body.drain(..4);
+        }
+        add_id3_frame(&mut tags, &id, &body);
+    }
+    // Fall back to an ID3v1 tag when the ID3v2 one is empty
+    if tags.is_empty() {
+        if let Some(v1) = read_id3v1(reader, start)? {
+            return Ok(v1);
+        }
+    }
+    Ok(tags)
+}
+
+fn add_id3_frame(tags: &mut AudioTags, id: &str, body: &[u8]) {
+    let Some((&encoding, text)) = body.split_first() else {
+        return;
+    };
+    match id {
+        "TXXX" | "TXX" => {
+            let (description, value) = split_id3_text(encoding, text);
+            let key = decode_id3_text(encoding, description);
+            tags.push(&key, decode_id3_text(encoding, value));
+        }
+        "COMM" | "COM" if text.len() >= 3 => {
+            // Language, then a description
+            let (_, value) = split_id3_text(encoding, &text[3..]);
+            tags.push("COMMENT", decode_id3_text(encoding, value));
+        }
+        _ if id.starts_with('T') => {
+            let key = id3_key(id);
+            // Version 2.4 separates multiple values with terminators
+            let mut rest = text;
+            while !rest.is_empty() {
+                let (value, next) = split_id3_text(encoding, rest);
+                tags.push(key, decode_id3_text(encoding, value));
+                rest = next;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Read the `ID3v1` tag at the end of the stream, if there's one.
+fn read_id3v1<R: Read + Seek>(
+    reader: &mut R,
+    start: u64,
+) -> Result<Option<AudioTags>, MetadataError> {
+    let end = reader.seek(SeekFrom::End(0))?;
+    if end < start + 128 {
+        return Ok(None);
+    }
+    let _ = reader.seek(SeekFrom::End(-128))?;
+    let mut tag = [0; 128];
+    reader.read_exact(&mut tag)?;
+    if &tag[..3] != b"TAG" {
+        return Ok(None);
+    }
+    let text = |range: std::ops::Range<usize>| {
+        let (text, _) = split_id3_text(0, &tag[range]);
+        decode_id3_text(0, text).trim_end().to_owned()
+    };
+    let mut tags = AudioTags::new(TagFormat::Id3v1);
+    tags.push("TITLE", text(3..33));
+    tags.push("ARTIST", text(33..63));
+    tags.push("ALBUM", text(63..93));
+    tags.push("DATE", text(93..97));
+    // Version 1.1 stores the track number in the last byte of the comment
+    if tag[125] == 0 && tag[126] != 0 {
+        tags.push("COMMENT", text(97..125));
+        tags.push("TRACKNUMBER", tag[126].to_string());
+    } else {
+        tags.push("COMMENT", text(97..127));
+    }
+    Ok(Some(tags))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vorbis_comment(vendor: &str, comments: &[&str]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let string = |data: &mut Vec<u8>, s: &str| {
+            data.extend_from_slice(&u32::try_from(s.len()).expect("short").to_le_bytes());
+            data.extend_from_slice(s.as_bytes());
+        };
+        string(&mut data, vendor);
+        data.extend_from_slice(&u32::try_from(comments.len()).expect("few").to_le_bytes());
+        for comment in comments {
+            string(&mut data, comment);
+        }
+        data
+    }
+
+    fn ogg_page(serial: u32, sequence: u32, packets: &[&[u8]]) -> Vec<u8> {
+        let mut segments = Vec::new();
+        for packet in packets {
+            segments.extend(std::iter::repeat_n(255, packet.len() / 255));
+            segments.push(u8::try_from(packet.len() % 255).expect("remainder"));
+        }
+        let mut page = b"OggS\0\0".to_vec();
+        page.extend_from_slice(&[0; 8]);
+        page.extend_from_slice(&serial.to_le_bytes());
+        page.extend_from_slice(&sequence.to_le_bytes());
+        // The checksum isn't verified
+        page.extend_from_slice(&[0; 4]);
+        page.push(u8::try_from(segments.len()).expect("few segments"));
+        page.extend_from_slice(&segments);
+        for packet in packets {
+            page.extend_from_slice(packet);
+        }
+        page
+    }
+
+    #[test]
+    fn ogg_vorbis_comments() {
+        let mut ident = b"\x01vorbis".to_vec();
+        ident.resize(30, 0);
+        let mut comment = b"\x03vorbis".to_vec();
+        let long_description = format!("DESCRIPTION={}", "x".repeat(600));
+        comment.extend(vorbis_comment(
+            "Xiph.Org libVorbis",
+            &[
+                "TITLE=Song",
+                "artist=Band",
+                "ARTIST=Guest",
+                "ALBUM=Album",
+                "TRACKNUMBER=3/12",
+                &long_description,
+            ],
+        ));
+        comment.push(1);
+        let mut file = ogg_page(7, 0, &[&ident]);
+        // Another logical stream is ignored
+        file.extend(ogg_page(8, 0, &[b"other"]));
+        file.extend(ogg_page(7, 1, &[&comment]));
+        let tags = AudioTags::from_memory(&file).expect("valid tags");
+        assert_eq!(tags.format(), TagFormat::VorbisComment);
+        assert_eq!(tags.vendor(), Some("Xiph.Org libVorbis"));
+        assert_eq!(tags.title(), Some("Song"));
+        assert_eq!(
+            tags.get_all("Artist").collect::<Vec<_>>(),
+            ["Band", "Guest"]
+        );
+        assert_eq!(tags.album(), Some("Album"));
+        assert_eq!(tags.track_number(), Some(3));
+        assert_eq!(tags.track_total(), Some(12));
+        assert_eq!(tags.get("description").map(str::len), Some(600));
+        assert_eq!(tags.len(), 6);
+        // A tag larger than the file
+        assert_eq!(
+            AudioTags::from_memory(b"ID3\x03\x00\x00\x7F\x7F\x7F\x7Fshort").err(),
+            Some(MetadataError::Io(io::ErrorKind::UnexpectedEof))
+        );
+    }
+
+    fn id3v2_frame(id: &[u8], body: &[u8]) -> Vec<u8> {
+        let mut frame = id.to_vec();
+        frame.extend_from_slice(&u32::try_from(body.len()).expect("short").to_be_bytes());
+        frame.extend_from_slice(&[0, 0]);
+        frame.extend_from_slice(body);
+        frame
+    }
+
+    #[test]
+    fn id3v2_tags() {
+        let mut frames = id3v2_frame(b"TIT2", b"\x00Caf\xE9");
+        // UTF-16 with a byte order mark
+        let mut artist = vec![1, 0xFF, 0xFE];
+        artist.extend("Bändi".encode_utf16().flat_map(u16::to_le_bytes));
+        frames.extend(id3v2_frame(b"TPE1", &artist));
+        frames.extend(id3v2_frame(b"TALB", b"\x03\xC3\x89t\xC3\xA9\x00"));
+        frames.extend(id3v2_frame(b"TRCK", b"\x007/9"));
+        frames.extend(id3v2_frame(b"COMM", b"\x00engdesc\x00Nice"));
+        frames.extend(id3v2_frame(b"TXXX", b"\x00MOOD\x00Happy"));
+        frames.extend(id3v2_frame(b"APIC", b"\x00image/png\x00\x03\x00data"));
+        frames.extend([0; 32]);
+        let size = frames.len();
+        let mut file = b"ID3\x03\x00\x00".to_vec();
+        file.extend(
+            (0..4)
+                .rev()
+                .map(|i| u8::try_from(size >> (7 * i) & 0x7F).expect("7 bits")),
+        );
+        file.extend(frames);
+        file.extend([0xFF, 0xFB, 0x90, 0x00]);
+        let tags = AudioTags::from_memory(&file).expect("valid tags");
+        assert_eq!(tags.format(), TagFormat::Id3v2);
+        assert_eq!(tags.title(), Some("Café"));
+        assert_eq!(tags.artist(), Some("Bändi"));
+        assert_eq!(tags.album(), Some("Été"));
+        assert_eq!(tags.track_number(), Some(7));
+        assert_eq!(tags.track_total(), Some(9));
+        assert_eq!(tags.get("COMMENT"), Some("Nice"));
+        assert_eq!(tags.get("mood"), Some("Happy"));
+        assert_eq!(tags.len(), 6);
+    }
+
+    #[test]
+    fn id3v1_tags() {
+        let mut file = vec![0xFF, 0xFB, 0x90, 0x00];
+        file.resize(1000, 0);
+        let mut tag = b"TAG".to_vec();
+        for (text, len) in [("Title", 30), ("Artist", 30), ("Album", 30), ("1999", 4)] {
+            tag.extend_from_slice(text.as_bytes());
+            tag.resize(tag.len() + len - text.len(), b' ');
+        }
+        tag.resize(3 + 30 * 3 + 4 + 28, 0);
+        tag.extend_from_slice(&[0, 5, 17]);
+        file.extend(tag);
+        let tags = AudioTags::from_memory(&file).expect("valid tags");
+        assert_eq!(tags.format(), TagFormat::Id3v1);
+        assert_eq!(tags.title(), Some("Title"));
+        assert_eq!(tags.artist(), Some("Artist"));
+        assert_eq!(tags.album(), Some("Album"));
+        assert_eq!(tags.get("DATE"), Some("1999"));
+        assert_eq!(tags.track_number(), Some(5));
+        assert_eq!(
+            AudioTags::from_memory(b"RIFF....WAVE").err(),
+            Some(MetadataError::UnknownFormat)
+        );
+    }
+
+    #[test]
+    fn flac_vorbis_comment_block() {
+        let mut file = b"fLaC".to_vec();
+        // STREAMINFO: 44.1 kHz stereo 16 bits, no frames
+        file.extend_from_slice(&[0, 0, 0, 34]);
+        file.extend_from_slice(&[0x10, 0x00, 0x10, 0x00, 0, 0, 0, 0, 0, 0]);
+        file.extend_from_slice(&[0x0A, 0xC4, 0x42, 0xF0, 0, 0, 0, 0]);
+        file.extend_from_slice(&[0; 16]);
+        let comment = vorbis_comment("reference libFLAC", &["TITLE=Lossless", "ALBUM=Flac"]);
+        file.push(0x80 | 4);
+        file.extend_from_slice(&u32::try_from(comment.len()).expect("short").to_be_bytes()[1..]);
+        file.extend(comment);
+        let tags = AudioTags::from_memory(&file).expect("valid tags");
+        assert_eq!(tags.format(), TagFormat::Flac);
+        assert_eq!(tags.vendor(), Some("reference libFLAC"));
+        assert_eq!(tags.title(), Some("Lossless"));
+        assert_eq!(tags.album(), Some("Flac"));
+    }
+}
@@ -4,18 +4,40 @@
 #[doc(inline)]
 pub use self::capture::{SoundBufferRecorder, SoundRecorder, SoundRecorderDriver};
 pub use self::{
+    beat_clock::{BeatClock, BeatEvent, CueMarker, TempoMap, TimeSignature},
+    callback_error::{CallbackError, StreamError},
+    capture_tap::{CaptureTap, CaptureTapReader, CapturedChunk},
+    metadata::{AudioTags, MetadataError, TagFormat},
+    monitor::{
+        LiveMonitor, MonitorConfig, MonitorEffect, MonitorInput, MonitorOutput, MonitorStats,
+    },
     music::Music,
+    resampler::{ResampledStream, Resampler, ResamplerQuality},
+    sample_conversion::{Ditherer, sample_to_f32, samples_to_f32},
+    sequencer::{Quantize, ScheduleId, Sequencer, SequencerClip, SequencerEvent, SequencerHandle},
     sound::Sound,
     sound_buffer::SoundBuffer,
     sound_channel::SoundChannel,
-    sound_stream::{SoundStream, SoundStreamPlayer},
+    sound_stream::{F32StreamAdapter, SoundStream, SoundStreamF32, SoundStreamPlayer},
     time_span::TimeSpan,
+    time_stretch::{TimeStretchControl, TimeStretchedStream, TimeStretcher},
+    tracker::{
+        ModuleFormat, TrackerControl, TrackerError, TrackerModule, TrackerPosition, TrackerStream,
+    },
 };
 
 /// Types and helper functions dealing with audio capture.
+mod beat_clock;
+mod callback_error;
 pub mod capture;
+mod capture_tap;
 pub mod listener;
+mod metadata;
+mod monitor;
 mod music;
+mod resampler;
+mod sample_conversion;
+mod sequencer;
 mod sound;
 mod sound_buffer;
 mod sound_channel;
@@ -23,3 +45,5 @@ mod sound_channel;
 pub mod sound_source;
 mod sound_stream;
 mod time_span;
+mod time_stretch;
+pub mod tracker;
@@ -0,0 +1,429 @@
+use {
+    super::{
+        SoundChannel, SoundStream,
+        capture::SoundRecorder,
+        sample_conversion::{Ditherer, sample_to_f32},
+    },
+    crate::system::Time,
+    std::{
+        fmt,
+        sync::{
+            Arc,
+            atomic::{AtomicBool, AtomicI16, AtomicU64, AtomicUsize, Ordering},
+        },
+    },
+};
+
+/// Settings of a [`LiveMonitor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonitorConfig {
+    /// Amount of audio kept in the jitter buffer before playback starts.
+    ///
+    /// Lower values reduce the delay between the microphone and the speakers,
+    /// higher values make underruns less likely.
+    pub target_latency: Time,
+    /// Maximum amount of audio the jitter buffer can hold.
+    ///
+    /// Whenever the buffer drifts past the target latency by more than two chunks,
+    /// it is brought back to the target latency by skipping the oldest samples.
+    pub max_latency: Time,
+    /// Duration of the chunks handed to the playback stream.
+    pub chunk_duration: Time,
+}
+
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        Self {
+            target_latency: Time::milliseconds(40),
+            max_latency: Time::milliseconds(200),
+            chunk_duration: Time::milliseconds(10),
+        }
+    }
+}
+
+/// Statistics about a [`LiveMonitor`], see [`LiveMonitor::stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MonitorStats {
+    /// Number of times playback ran out of captured samples and had to re-buffer.
+    pub underruns: u64,
+    /// Number of captured chunks which didn't fully fit in the jitter buffer.
+    pub overruns: u64,
+    /// Number of times playback skipped samples to get back to the target latency.
+    pub resyncs: u64,
+    /// Amount of audio currently waiting in the jitter buffer.
+    pub buffered: Time,
+}
+
+/// An effect applied to monitored audio, before it is played back.
+///
+/// Effects process interleaved, normalized `f32` samples in place.
+/// They run on the audio thread, so they should not block.
+///
+/// Any `FnMut(&mut [f32]) + Send` closure is an effect.
+pub trait MonitorEffect: Send {
+    /// Process a chunk of interleaved samples in place.
+    fn process(&mut self, samples: &mut [f32], channel_count: u32, sample_rate: u32);
+}
+
+impl<F: FnMut(&mut [f32]) + Send> MonitorEffect for F {
+    fn process(&mut self, samples: &mut [f32], _channel_count: u32, _sample_rate: u32) {
+        self(samples);
+    }
+}
+
+/// Lock-free single-producer/single-consumer jitter buffer of samples.
+///
+/// `head` is only written by the recorder, and `tail` only by the stream.
+struct Shared {
+    samples: Box<[AtomicI16]>,
+    head: AtomicUsize,
+    tail: AtomicUsize,
+    underruns: AtomicU64,
+    overruns: AtomicU64,
+    resyncs: AtomicU64,
+    bypass: AtomicBool,
+}
+
+/// Plays captured audio back in real time.
+///
+/// A live monitor connects a [`SoundRecorder`] to a [`SoundStream`], for things like karaoke
+/// or voice effects. It is made of three parts:
+///
+/// - a [`MonitorInput`], which is the recorder, to drive with a [`SoundRecorderDriver`]
+/// - a [`MonitorOutput`], which is the stream, to play with a [`SoundStreamPlayer`]
+/// - the `LiveMonitor` itself, which reports statistics while the other two are in use
+///
+/// Captured samples go through a lock-free jitter buffer, which absorbs the scheduling
+/// differences between the capture and playback threads. Playback only starts once
+/// [`MonitorConfig::target_latency`] worth of audio is buffered, and starts over
+/// after an underrun. Since the capture and playback devices don't run on exactly the same
+/// clock, the buffer is also kept from slowly drifting away from the target latency.
+///
+/// The recorder must be started with the same sample rate and channel count as the ones
+/// passed to [`LiveMonitor::new`].
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::audio::{
+/// #     capture::SoundRecorderDriver, LiveMonitor, MonitorConfig, SoundStreamPlayer,
+/// # };
+/// let (monitor, mut input, mut output) = LiveMonitor::new(1, 44_100, MonitorConfig::default());
+/// // Simple distortion
+/// output.add_effect(|samples: &mut [f32]| {
+///     for sample in samples {
+///         *sample = (*sample * 4.0).clamp(-0.5, 0.5);
+///     }
+/// });
+/// let mut driver = SoundRecorderDriver::new(&mut input);
+/// let mut player = SoundStreamPlayer::new(&mut output);
+/// driver.start(44_100).unwrap();
+/// player.play();
+/// loop {
+///     println!("{:?}", monitor.stats());
+/// #   break;
+/// }
+/// ```
+///
+/// [`SoundRecorderDriver`]: crate::audio::SoundRecorderDriver
+/// [`SoundStreamPlayer`]: crate::audio::SoundStreamPlayer
+pub struct LiveMonitor {
+    shared: Arc<Shared>,
+    frame_duration: f64,
+    channel_count: usize,
+}
+
+/// The recording half of a [`LiveMonitor`].
+pub struct MonitorInput {
+    shared: Arc<Shared>,
+}
+
+/// The playback half of a [`LiveMonitor`].
+pub struct MonitorOutput {
+    shared: Arc<Shared>,
+    channel_count: u32,
+    sample_rate: u32,
+    target_samples: usize,
+    chunk_samples: usize,
+    buffering: bool,
+    effects: Vec<Box<dyn MonitorEffect>>,
+    ditherer: Ditherer,
+    scratch: Vec<f32>,
+    chunk: Vec<i16>,
+}
+
+impl fmt::Debug for LiveMonitor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LiveMonitor")
+            .field("stats", &self.stats())
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for MonitorInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MonitorInput").finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for MonitorOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MonitorOutput")
+            .field("channel_count", &self.channel_count)
+            .field("sample_rate", &self.sample_rate)
+            .field("effects", &self.effects.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Number of interleaved samples covering `duration`, rounded down to whole frames.
+fn samples_for(duration: Time, sample_rate: u32, channel_count: usize) -> usize {
+    let micros = u64::try_from(duration.as_microseconds()).unwrap_or(0);
+    let frames = micros * u64::from(sample_rate) / 1_000_000;
+    usize::try_from(frames).unwrap_or(usize::MAX) * channel_count
+}
+
+impl LiveMonitor {
+    /// Create a new live monitor, along with its recording and playback halves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel_count` or `sample_rate` is zero.
+    #[must_use]
+    pub fn new(
+        channel_count: u32,
+        sample_rate: u32,
+        config: MonitorConfig,
+    ) -> (Self, MonitorInput, MonitorOutput) {
+        assert!(
+            channel_count > 0 && sample_rate > 0,
+            "LiveMonitor channel count and sample rate must be non-zero"
+        );
+        let channels = channel_count as usize;
+        let chunk_samples = samples_for(config.chunk_duration, sample_rate, channels).max(channels);
+        let target_samples = samples_for(config.target_latency, sample_rate, channels);
+        let capacity = samples_for(config.max_latency, sample_rate, channels)
+            .max(target_samples + 2 * chunk_samples);
+        let shared = Arc::new(Shared {
+            samples: (0..capacity).map(|_| AtomicI16::new(0)).collect(),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            underruns: AtomicU64::new(0),
+            overruns: AtomicU64::new(0),
+            resyncs: AtomicU64::new(0),
+            bypass: AtomicBool::new(false),
+        });
+        (
+            Self {
+                shared: Arc::clone(&shared),
+                frame_duration: 1.0 / f64::from(sample_rate),
+                channel_count: channels,
+            },
+            MonitorInput {
+                shared: Arc::clone(&shared),
+            },
+            MonitorOutput {
+                shared,
+                channel_count,
+                sample_rate,
+                target_samples,
+                chunk_samples,
+                buffering: true,
+                effects: Vec::new(),
+                ditherer: Ditherer::new(),
+                scratch: Vec::new(),
+                chunk: Vec::new(),
+            },
+        )
+    }
+    /// Get the current statistics of the monitor.
+    #[must_use]
+    pub fn stats(&self) -> MonitorStats {
+        let shared = &*self.shared;
+        let tail = shared.tail.load(Ordering::Acquire);
+        let buffered = shared.head.load(Ordering::Acquire).saturating_sub(tail);
+        #[expect(clippy::cast_possible_truncation)]
+        let micros = ((buffered / self.channel_count) as f64 * self.frame_duration * 1e6) as i64;
+        MonitorStats {
+            underruns: shared.underruns.load(Ordering::Relaxed),
+            overruns: shared.overruns.load(Ordering::Relaxed),
+            resyncs: shared.resyncs.load(Ordering::Relaxed),
+            buffered: Time::microseconds(micros),
+        }
+    }
+    /// Enable or disable the effect chain of the [`MonitorOutput`].
+    ///
+    /// When bypassed, the captured audio is played back unprocessed.
+    pub fn set_bypass(&self, bypass: bool) {
+        self.shared.bypass.store(bypass, Ordering::Relaxed);
+    }
+    /// Whether the effect chain is currently bypassed.
+    #[must_use]
+    pub fn is_bypassed(&self) -> bool {
+        self.shared.bypass.load(Ordering::Relaxed)
+    }
+}
+
+impl SoundRecorder for MonitorInput {
+    fn on_process_samples(&mut self, samples: &[i16]) -> bool {
+        let shared = &*self.shared;
+        let capacity = shared.samples.len();
+        let head = shared.head.load(Ordering::Relaxed);
+        let tail = shared.tail.load(Ordering::Acquire);
+        let free = capacity - (head - tail);
+        if samples.len() > free {
+            let _ = shared.overruns.fetch_add(1, Ordering::Relaxed);
+        }
+        let len = samples.len().min(free);
+        for (i, &sample) in samples[..len].iter().enumerate() {
+            shared.samples[(head + i) % capacity].store(sample, Ordering::Relaxed);
+        }
+        shared.head.store(head + len, Ordering::Release);
+        true
+    }
+}
+
+impl MonitorOutput {
+    /// Append an effect to the effect chain.
+    ///
+    /// Effects are applied in the order they were added.
+    pub fn add_effect<E: MonitorEffect + 'static>(&mut self, effect: E) {
+        self.effects.push(Box::new(effect));
+    }
+    /// Remove all effects from the effect chain.
+    pub fn clear_effects(&mut self) {
+        self.effects.clear();
+    }
+    fn read_chunk(&mut self) {
+        let shared = &*self.shared;
+        let capacity = shared.samples.len();
+        let channels = self.channel_count as usize;
+        let head = shared.head.load(Ordering::Acquire);
+        let mut tail = shared.tail.load(Ordering::Relaxed);
+        let available = head - tail;
+        self.chunk.clear();
+        if self.buffering {
+            if available < self.target_samples.max(self.chunk_samples) {
+                self.chunk.resize(self.chunk_samples, 0);
+                return;
+            }
+            self.buffering = false;
+        }
+        // Skip the oldest samples if capture got too far ahead of playback
+        if available > self.target_samples + 2 * self.chunk_samples {
+            let skip = (available - self.target_samples) / channels * channels;
+            tail += skip;
+            let _ = shared.resyncs.fetch_add(1, Ordering::Relaxed);
+        }
+        let len = (head - tail).min(self.chunk_samples) / channels * channels;
+        self.chunk.extend(
+            (tail..tail + len).map(|i| shared.samples[i % capacity].load(Ordering::Relaxed)),
+        );
+        shared.tail.store(tail + len, Ordering::Release);
+        if len < self.chunk_samples {
+            let _ = shared.underruns.fetch_add(1, Ordering::Relaxed);
+            self.chunk.resize(self.chunk_samples, 0);
+            self.buffering = true;
+        }
+    }
+}
+
+impl SoundStream for MonitorOutput {
+    fn get_data(&mut self) -> (&[i16], bool) {
+        self.read_chunk();
+        if !self.effects.is_empty() && !self.shared.bypass.load(Ordering::Relaxed) {
+            self.scratch.clear();
+            self.scratch
+                .extend(self.chunk.iter().copied().map(sample_to_f32));
+            for effect in &mut self.effects {
+                effect.process(&mut self.scratch, self.channel_count, self.sample_rate);
+            }
+            self.chunk.clear();
+            self.ditherer.convert_into(&self.scratch, &mut self.chunk);
+        }
+        (&self.chunk, true)
+    }
+    fn seek(&mut self, _offset: Time) {
+        // Live input can't seek, but a restarted stream should buffer up again
+        self.buffering = true;
+    }
+    fn channel_count(&self) -> u32 {
+        self.channel_count
+    }
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+    fn get_channel_map(&self) -> Vec<SoundChannel> {
+        match self.channel_count {
+            1 => vec![SoundChannel::Mono],
+            2 => vec![SoundChannel::FrontLeft, SoundChannel::FrontRight],
+            n => vec![SoundChannel::Unspecified; n as usize],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{LiveMonitor, MonitorConfig},
+        crate::{
+            audio::{SoundStream, capture::SoundRecorder},
+            system::Time,
+        },
+    };
+
+    fn config() -> MonitorConfig {
+        // 1 kHz mono: 1 sample per millisecond
+        MonitorConfig {
+            target_latency: Time::milliseconds(30),
+            max_latency: Time::milliseconds(100),
+            chunk_duration: Time::milliseconds(10),
+        }
+    }
+
+    #[test]
+    fn buffers_up_to_target_latency_before_playing() {
+        let (monitor, mut input, mut output) = LiveMonitor::new(1, 1000, config());
+        let ramp: Vec<i16> = (1..=20).collect();
+        assert!(input.on_process_samples(&ramp));
+        assert_eq!(output.get_data().0, [0; 10]);
+        assert!(input.on_process_samples(&(21..=40).collect::<Vec<_>>()));
+        assert_eq!(monitor.stats().buffered, Time::milliseconds(40));
+        assert_eq!(output.get_data().0, (1..=10).collect::<Vec<_>>());
+        assert_eq!(output.get_data().0, (11..=20).collect::<Vec<_>>());
+        assert_eq!(monitor.stats().underruns, 0);
+    }
+
+    #[test]
+    fn underruns_rebuffer_and_overruns_are_counted() {
+        let (monitor, mut input, mut output) = LiveMonitor::new(1, 1000, config());
+        assert!(input.on_process_samples(&[1; 35]));
+        for _ in 0..3 {
+            assert_eq!(output.get_data().0, [1; 10]);
+        }
+        // Only 5 samples left
+        assert_eq!(output.get_data().0, [1, 1, 1, 1, 1, 0, 0, 0, 0, 0]);
+        assert_eq!(monitor.stats().underruns, 1);
+        assert!(input.on_process_samples(&[2; 20]));
+        assert_eq!(output.get_data().0, [0; 10], "buffering again");
+        assert!(input.on_process_samples(&[3; 150]));
+        assert_eq!(monitor.stats().overruns, 1);
+        // Way above the target latency, so playback skips ahead
+        let _ = output.get_data();
+        let stats = monitor.stats();
+        assert_eq!(stats.resyncs, 1);
+        assert_eq!(stats.buffered, Time::milliseconds(20));
+    }
+
+    #[test]
+    fn effects_process_the_chunk_unless_bypassed() {
+        let (monitor, mut input, mut output) = LiveMonitor::new(2, 1000, config());
+        output.add_effect(|samples: &mut [f32]| samples.iter_mut().for_each(|s| *s = -*s));
+        assert!(input.on_process_samples(&[16_384; 120]));
+        let inverted = output.get_data().0.to_vec();
+        assert_eq!(inverted.len(), 20);
+        assert!(inverted.iter().all(|&s| (s + 16_384).abs() <= 1));
+        monitor.set_bypass(true);
+        assert_eq!(output.get_data().0, [16_384; 20]);
+    }
+}
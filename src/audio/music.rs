@@ -2,18 +2,68 @@ use {
     super::{sound_channel::SoundChannel, sound_source::SoundSource},
     crate::{
         IntoSfResult, SfResult,
-        audio::TimeSpan,
+        audio::{AudioTags, TimeSpan},
         cpp::CppVector,
         ffi,
         system::{InputStream, Time, Vector3f},
     },
     std::{
         ffi::{CString, c_uint, c_void},
+        fmt,
         io::{Read, Seek},
         marker::PhantomData,
+        mem::ManuallyDrop,
     },
 };
 
+/// A `Read + Seek` source that can be sent to SFML's streaming thread.
+trait ReadSeek: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+/// Source data owned by a [`Music`], kept alive for as long as SFML streams from it.
+enum OwnedSource {
+    Memory(Box<dyn AsRef<[u8]> + Send + Sync>),
+    Stream(OwnedStream),
+}
+
+impl fmt::Debug for OwnedSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Memory(data) => write!(f, "Memory({} bytes)", (**data).as_ref().len()),
+            Self::Stream(stream) => write!(f, "Stream({:p})", stream.source),
+        }
+    }
+}
+
+/// An owned reader, along with the SFML input stream reading from it.
+struct OwnedStream {
+    input: ManuallyDrop<InputStream<'static, Box<dyn ReadSeek>>>,
+    /// Allocated with `Box::into_raw`, so its address stays valid while `input` uses it
+    source: *mut Box<dyn ReadSeek>,
+}
+
+impl OwnedStream {
+    fn new<R: Read + Seek + Send + 'static>(reader: R) -> Self {
+        let boxed: Box<dyn ReadSeek> = Box::new(reader);
+        let source = Box::into_raw(Box::new(boxed));
+        Self {
+            // SAFETY: `source` is only freed in `drop`, after `input` is gone
+            input: ManuallyDrop::new(InputStream::new(unsafe { &mut *source })),
+            source,
+        }
+    }
+}
+
+impl Drop for OwnedStream {
+    fn drop(&mut self) {
+        unsafe {
+            ManuallyDrop::drop(&mut self.input);
+            drop(Box::from_raw(self.source));
+        }
+    }
+}
+
 /// Streamed music played from an audio file.
 ///
 /// `Music`s are sounds that are streamed rather than completely loaded in memory.
@@ -22,6 +72,8 @@ use {
 /// uncompressed: by streaming it instead of loading it entirely, you avoid saturating the memory
 /// and have almost no loading delay. This implies that the underlying resource
 /// (file, stream or memory buffer) must remain valid for the lifetime of the `Music` object.
+/// If that's inconvenient, [`from_bytes`] and [`from_reader`] take ownership of the data instead,
+/// and return a `Music<'static>`.
 ///
 /// Apart from that, a `Music` has almost the same features as the
 /// [`SoundBuffer`] / [`Sound`] pair: you can play/pause/stop it, request its parameters
@@ -32,11 +84,15 @@ use {
 /// it will manage itself very well.
 ///
 /// [`play`]: Music::play
+/// [`from_bytes`]: Music::from_bytes
+/// [`from_reader`]: Music::from_reader
 /// [`SoundBuffer`]: crate::audio::SoundBuffer
 /// [`Sound`]: crate::audio::Sound
 #[derive(Debug)]
 pub struct Music<'src> {
     music: *mut ffi::audio::sfMusic,
+    /// Data opened with one of the owning constructors, like [`Music::from_bytes`]
+    owned: Option<OwnedSource>,
     _stream: PhantomData<&'src mut ()>,
 }
 
@@ -53,6 +109,7 @@ impl<'src> Music<'src> {
     pub fn new() -> SfResult<Self> {
         Ok(Self {
             music: unsafe { ffi::audio::sfMusic_new() },
+            owned: None,
             _stream: PhantomData,
         })
     }
@@ -80,6 +137,21 @@ impl<'src> Music<'src> {
         new.open_from_memory(data)?;
         Ok(new)
     }
+    /// Create a new `Music` from a file, along with the tags of the file if it has any.
+    ///
+    /// The tags are `None` if the file has none, or if they couldn't be read.
+    /// See [`AudioTags`] for the supported tag formats.
+    pub fn from_file_with_tags(filename: &str) -> SfResult<(Self, Option<AudioTags>)> {
+        let new = Self::from_file(filename)?;
+        Ok((new, AudioTags::from_file(filename).ok()))
+    }
+    /// Create a new `Music` from music data in memory, along with its tags if it has any.
+    ///
+    /// See [`Self::from_file_with_tags`].
+    pub fn from_memory_with_tags(data: &'src [u8]) -> SfResult<(Self, Option<AudioTags>)> {
+        let new = Self::from_memory(data)?;
+        Ok((new, AudioTags::from_memory(data).ok()))
+    }
     /// Open a new file for playback
     ///
     /// This function doesn't start playing the music (call [`play`] to do so).
@@ -132,6 +204,74 @@ impl<'src> Music<'src> {
     }
 }
 
+/// Opening from owned data
+///
+/// The `Music` returned by these constructors is not tied to any lifetime, since it keeps its
+/// source data alive internally for as long as SFML streams from it.
+/// This makes it easy to store in a struct, even if the data was read from an archive
+/// or downloaded.
+impl Music<'static> {
+    /// Create a new `Music` from music data in memory, taking ownership of it.
+    ///
+    /// Any byte container works, like a `Vec<u8>`, `Box<[u8]>` or `Arc<[u8]>`.
+    /// The data is not copied.
+    /// See [`Self::open_from_memory`] for the list of supported formats.
+    ///
+    /// # Usage example
+    ///
+    /// ```no_run
+    /// # use sfml::audio::Music;
+    /// struct Jukebox {
+    ///     current: Music<'static>,
+    /// }
+    ///
+    /// let data = std::fs::read("orchestral.ogg").unwrap();
+    /// let jukebox = Jukebox {
+    ///     current: Music::from_bytes(data).unwrap(),
+    /// };
+    /// ```
+    pub fn from_bytes<T: AsRef<[u8]> + Send + Sync + 'static>(data: T) -> SfResult<Self> {
+        let mut new = Self::new()?;
+        new.open_from_bytes(data)?;
+        Ok(new)
+    }
+    /// Create a new `Music` streaming from a `Read + Seek` source, taking ownership of it.
+    ///
+    /// This also accepts boxed readers, like `Box<File>`.
+    /// See [`Self::open_from_stream`] for the list of supported formats.
+    pub fn from_reader<R: Read + Seek + Send + 'static>(reader: R) -> SfResult<Self> {
+        let mut new = Self::new()?;
+        new.open_from_reader(reader)?;
+        Ok(new)
+    }
+    /// Open music from data in memory, taking ownership of it.
+    ///
+    /// See [`Self::from_bytes`].
+    pub fn open_from_bytes<T: AsRef<[u8]> + Send + Sync + 'static>(
+        &mut self,
+        data: T,
+    ) -> SfResult<()> {
+        let data: Box<dyn AsRef<[u8]> + Send + Sync> = Box::new(data);
+        let bytes = (*data).as_ref();
+        let result =
+            unsafe { ffi::audio::sfMusic_openFromMemory(self.music, bytes.as_ptr(), bytes.len()) };
+        // The previous source is only released once SFML is done with it
+        self.owned = Some(OwnedSource::Memory(data));
+        result.into_sf_result()
+    }
+    /// Open music from a `Read + Seek` source, taking ownership of it.
+    ///
+    /// See [`Self::from_reader`].
+    pub fn open_from_reader<R: Read + Seek + Send + 'static>(&mut self, reader: R) -> SfResult<()> {
+        let mut stream = OwnedStream::new(reader);
+        let result = unsafe {
+            ffi::audio::sfMusic_openFromStream(self.music, &raw mut *stream.input.stream)
+        };
+        self.owned = Some(OwnedSource::Stream(stream));
+        result.into_sf_result()
+    }
+}
+
 /// Playback
 impl Music<'_> {
     /// Start or resume playing a music
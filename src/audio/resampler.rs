@@ -0,0 +1,559 @@
+use {
+    super::{
+        SoundChannel, SoundStream, StreamError,
+        sample_conversion::{Ditherer, sample_to_f32},
+    },
+    crate::system::Time,
+    std::f64::consts::PI,
+};
+
+/// Number of filter table entries per zero crossing of the sinc kernel.
+const TABLE_RESOLUTION: usize = 512;
+/// Shape parameter of the Kaiser window applied to the sinc kernel (roughly 90 dB stopband).
+const KAISER_BETA: f64 = 8.6;
+
+/// Quality presets for a [`Resampler`].
+///
+/// Higher qualities use longer filters, which have a narrower transition band
+/// and a flatter passband, at the cost of more CPU time per sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResamplerQuality {
+    /// Short filter. Suitable for real-time use with many streams.
+    Fast,
+    /// Good trade-off between quality and speed.
+    #[default]
+    Medium,
+    /// Long filter with a narrow transition band, for offline conversion.
+    Best,
+}
+
+impl ResamplerQuality {
+    /// Number of zero crossings of the sinc kernel on each side of its center.
+    fn zero_crossings(self) -> usize {
+        match self {
+            Self::Fast => 8,
+            Self::Medium => 16,
+            Self::Best => 32,
+        }
+    }
+    /// Cutoff frequency, as a fraction of the lower of the two Nyquist frequencies.
+    fn cutoff(self) -> f64 {
+        match self {
+            Self::Fast => 0.85,
+            Self::Medium => 0.91,
+            Self::Best => 0.95,
+        }
+    }
+}
+
+/// Band-limited sample rate converter for interleaved `f32` samples.
+///
+/// The conversion is done with a Kaiser-windowed sinc filter, evaluated from a precomputed
+/// polyphase table. When downsampling, the cutoff of the filter is lowered to the new Nyquist
+/// frequency, so content which can't be represented at the target rate is removed instead of
+/// aliasing.
+///
+/// A `Resampler` is a streaming processor: input can be fed in chunks of any size through
+/// [`process`], and the output is the same as if all of it had been passed at once.
+/// Once the input ends, [`flush`] emits the remaining tail of the signal.
+/// The output is aligned with the input, i.e. there is no leading delay to compensate for.
+///
+/// To convert a whole [`SoundBuffer`] at once, see [`SoundBuffer::resampled`].
+/// To convert a [`SoundStream`] on the fly, see [`ResampledStream`].
+///
+/// [`process`]: Resampler::process
+/// [`flush`]: Resampler::flush
+/// [`SoundBuffer`]: crate::audio::SoundBuffer
+/// [`SoundBuffer::resampled`]: crate::audio::SoundBuffer::resampled
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    channel_count: usize,
+    source_rate: u32,
+    target_rate: u32,
+    /// Source rate divided by the greatest common divisor of both rates
+    step_num: usize,
+    /// Target rate divided by the greatest common divisor of both rates
+    step_den: usize,
+    /// Kernel values for `x` in `[0, zero_crossings]` zero crossings, `TABLE_RESOLUTION` per unit
+    table: Vec<f32>,
+    /// Cutoff frequency relative to the source Nyquist frequency
+    cutoff: f64,
+    /// Half width of the kernel, in source frames
+    half_width: f64,
+    /// Number of silent frames inserted before the signal, so the first output frame has a
+    /// full history. Frame indices below are relative to the start of that padding.
+    padding: usize,
+    /// Pending interleaved input frames
+    buffer: Vec<f32>,
+    /// Index of the first frame in `buffer`
+    buffer_start: usize,
+    /// Integer part of the position of the next output frame, in source frames
+    pos_int: usize,
+    /// Fractional part of the position of the next output frame, in units of `1 / step_den`
+    pos_frac: usize,
+    frames_in: usize,
+    frames_out: usize,
+}
+
+impl Resampler {
+    /// Create a new `Resampler` converting from `source_rate` to `target_rate` with the
+    /// default [`ResamplerQuality`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel_count`, `source_rate` or `target_rate` is zero.
+    #[must_use]
+    pub fn new(channel_count: u32, source_rate: u32, target_rate: u32) -> Self {
+        Self::with_quality(
+            channel_count,
+            source_rate,
+            target_rate,
+            ResamplerQuality::default(),
+        )
+    }
+    /// Create a new `Resampler` converting from `source_rate` to `target_rate`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel_count`, `source_rate` or `target_rate` is zero.
+    #[must_use]
+    pub fn with_quality(
+        channel_count: u32,
+        source_rate: u32,
+        target_rate: u32,
+        quality: ResamplerQuality,
+    ) -> Self {
+        assert!(channel_count > 0, "Resampler needs at least one channel");
+        assert!(
+            source_rate > 0 && target_rate > 0,
+            "Resampler sample rates must be non-zero"
+        );
+        let divisor = gcd(source_rate, target_rate);
+        let scale = (f64::from(target_rate) / f64::from(source_rate)).min(1.0);
+        let cutoff = quality.cutoff() * scale;
+        let zero_crossings = quality.zero_crossings();
+        let half_width = zero_crossings as f64 / cutoff;
+        let mut new = Self {
+            channel_count: channel_count as usize,
+            source_rate,
+            target_rate,
+            step_num: (source_rate / divisor) as usize,
+            step_den: (target_rate / divisor) as usize,
+            table: kernel_table(zero_crossings, cutoff),
+            cutoff,
+            half_width,
+            padding: to_index(half_width.ceil()) + 1,
+            buffer: Vec::new(),
+            buffer_start: 0,
+            pos_int: 0,
+            pos_frac: 0,
+            frames_in: 0,
+            frames_out: 0,
+        };
+        new.reset();
+        new
+    }
+    /// Return the number of interleaved channels this resampler processes.
+    #[must_use]
+    pub fn channel_count(&self) -> u32 {
+        #[expect(clippy::cast_possible_truncation)]
+        let count = self.channel_count as u32;
+        count
+    }
+    /// Return the sample rate of the input.
+    #[must_use]
+    pub fn source_rate(&self) -> u32 {
+        self.source_rate
+    }
+    /// Return the sample rate of the output.
+    #[must_use]
+    pub fn target_rate(&self) -> u32 {
+        self.target_rate
+    }
+    /// Discard all buffered input, so the resampler can be used for an unrelated signal.
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.buffer.resize(self.padding * self.channel_count, 0.0);
+        self.buffer_start = 0;
+        self.pos_int = self.padding;
+        self.pos_frac = 0;
+        self.frames_in = 0;
+        self.frames_out = 0;
+    }
+    /// Feed interleaved `input` samples, appending every output sample that can be computed
+    /// so far to `output`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `input` is not a multiple of the channel count.
+    pub fn process(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        assert!(
+            input.len() % self.channel_count == 0,
+            "Resampler input must contain whole frames"
+        );
+        self.buffer.extend_from_slice(input);
+        self.frames_in += input.len() / self.channel_count;
+        self.render(output, usize::MAX);
+    }
+    /// Signal the end of the input, appending the remaining output samples to `output`.
+    ///
+    /// The total output length is the input length scaled by the rate ratio, rounded up.
+    /// After flushing, the resampler is [`reset`](Resampler::reset) and ready for a new signal.
+    pub fn flush(&mut self, output: &mut Vec<f32>) {
+        let total = (self.frames_in as u64 * self.step_den as u64).div_ceil(self.step_num as u64);
+        let remaining = usize::try_from(total)
+            .unwrap_or(usize::MAX)
+            .saturating_sub(self.frames_out);
+        self.buffer.resize(
+            self.buffer.len() + (self.padding + 1) * self.channel_count,
+            0.0,
+        );
+        self.render(output, remaining);
+        self.reset();
+    }
+    /// Compute up to `limit` output frames from the buffered input.
+    fn render(&mut self, output: &mut Vec<f32>, limit: usize) {
+        let end = self.buffer_start + self.buffer.len() / self.channel_count;
+        let mut rendered = 0;
+        while rendered < limit {
+            let pos = self.pos_int as f64 + self.pos_frac as f64 / self.step_den as f64;
+            let first = to_index((pos - self.half_width).ceil());
+            let last = to_index((pos + self.half_width).floor());
+            if last >= end {
+                break;
+            }
+            let start = output.len();
+            output.resize(start + self.channel_count, 0.0);
+            let frame_out = &mut output[start..];
+            for frame in first..=last {
+                let weight = self.kernel(pos - frame as f64);
+                let offset = (frame - self.buffer_start) * self.channel_count;
+                let frame_in = &self.buffer[offset..offset + self.channel_count];
+                for (out, &sample) in frame_out.iter_mut().zip(frame_in) {
+                    *out += weight * sample;
+                }
+            }
+            self.pos_frac += self.step_num;
+            self.pos_int += self.pos_frac / self.step_den;
+            self.pos_frac %= self.step_den;
+            self.frames_out += 1;
+            rendered += 1;
+        }
+        // Drop the frames that no future output frame can reach
+        let first_needed = to_index((self.pos_int as f64 - self.half_width).floor()).min(end);
+        if first_needed > self.buffer_start {
+            let drop_frames = first_needed - self.buffer_start;
+            drop(self.buffer.drain(..drop_frames * self.channel_count));
+            self.buffer_start = first_needed;
+        }
+    }
+    /// Evaluate the windowed sinc kernel at `x` source frames from its center.
+    fn kernel(&self, x: f64) -> f32 {
+        let index = x.abs() * self.cutoff * TABLE_RESOLUTION as f64;
+        let whole = to_index(index);
+        if whole + 1 >= self.table.len() {
+            return 0.0;
+        }
+        #[expect(clippy::cast_possible_truncation)]
+        let frac = (index - whole as f64) as f32;
+        self.table[whole] + (self.table[whole + 1] - self.table[whole]) * frac
+    }
+}
+
+/// Truncate a non-negative frame position to an index.
+#[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn to_index(x: f64) -> usize {
+    x as usize
+}
+
+fn gcd(mut a: u32, mut b: u32) -> u32 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Zeroth order modified Bessel function of the first kind, used by the Kaiser window.
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let half_sq = x * x / 4.0;
+    let mut k = 1.0;
+    while term > sum * 1e-12 {
+        term *= half_sq / (k * k);
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
+
+/// Tabulate the right half of a Kaiser-windowed sinc lowpass with the given relative `cutoff`.
+fn kernel_table(zero_crossings: usize, cutoff: f64) -> Vec<f32> {
+    let len = zero_crossings * TABLE_RESOLUTION;
+    let window_norm = bessel_i0(KAISER_BETA);
+    (0..=len + 1)
+        .map(|i| {
+            let t = i as f64 / TABLE_RESOLUTION as f64;
+            let ratio = t / zero_crossings as f64;
+            if ratio >= 1.0 {
+                return 0.0;
+            }
+            let sinc = if t == 0.0 {
+                1.0
+            } else {
+                (PI * t).sin() / (PI * t)
+            };
+            let window = bessel_i0(KAISER_BETA * (1.0 - ratio * ratio).sqrt()) / window_norm;
+            #[expect(clippy::cast_possible_truncation)]
+            let value = (cutoff * sinc * window) as f32;
+            value
+        })
+        .collect()
+}
+
+/// A [`SoundStream`] that converts another stream to a different sample rate on the fly.
+///
+/// Every chunk returned by the wrapped stream is resampled with a [`Resampler`] and
+/// converted back to 16 bits with dithering.
+/// Seeking is forwarded to the wrapped stream and discards the resampler state.
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::audio::{ResampledStream, SoundStream, SoundStreamPlayer};
+/// # fn play<S: SoundStream>(my_stream: S) {
+/// let mut stream = ResampledStream::new(my_stream, 48_000);
+/// let mut player = SoundStreamPlayer::new(&mut stream);
+/// player.play();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ResampledStream<S> {
+    stream: S,
+    resampler: Resampler,
+    ditherer: Ditherer,
+    input: Vec<f32>,
+    output: Vec<f32>,
+    chunk: Vec<i16>,
+}
+
+impl<S: SoundStream> ResampledStream<S> {
+    /// Wrap `stream`, converting it to `sample_rate` with the default [`ResamplerQuality`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sample_rate` is zero, or the stream reports a channel count or
+    /// sample rate of zero.
+    pub fn new(stream: S, sample_rate: u32) -> Self {
+        Self::with_quality(stream, sample_rate, ResamplerQuality::default())
+    }
+    /// Wrap `stream`, converting it to `sample_rate` with the given quality.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sample_rate` is zero, or the stream reports a channel count or
+    /// sample rate of zero.
+    pub fn with_quality(stream: S, sample_rate: u32, quality: ResamplerQuality) -> Self {
+        let resampler = Resampler::with_quality(
+            stream.channel_count(),
+            stream.sample_rate(),
+            sample_rate,
+            quality,
+        );
+        Self {
+            stream,
+            resampler,
+            ditherer: Ditherer::new(),
+            input: Vec::new(),
+            output: Vec::new(),
+            chunk: Vec::new(),
+        }
+    }
+    /// Get a reference to the wrapped stream.
+    pub fn inner(&self) -> &S {
+        &self.stream
+    }
+    /// Get a mutable reference to the wrapped stream.
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+    /// Unwrap the wrapped stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S: SoundStream> SoundStream for ResampledStream<S> {
+    fn get_data(&mut self) -> (&[i16], bool) {
+        self.try_get_data().unwrap_or((&[], false))
+    }
+    fn try_get_data(&mut self) -> Result<(&[i16], bool), StreamError> {
+        self.output.clear();
+        self.chunk.clear();
+        let mut keep_playing = true;
+        // The resampler may hold back a whole small chunk, so keep pulling until there is output,
+        // unless the stream has nothing to give right now
+        while self.output.is_empty() && keep_playing {
+            let (data, keep) = self.stream.try_get_data()?;
+            let starved = data.is_empty();
+            self.input.clear();
+            self.input.extend(data.iter().copied().map(sample_to_f32));
+            self.resampler.process(&self.input, &mut self.output);
+            if !keep {
+                self.resampler.flush(&mut self.output);
+                keep_playing = false;
+            }
+            if starved {
+                break;
+            }
+        }
+        self.ditherer.convert_into(&self.output, &mut self.chunk);
+        Ok((&self.chunk, keep_playing))
+    }
+    fn seek(&mut self, offset: Time) {
+        self.stream.seek(offset);
+        self.resampler.reset();
+    }
+    fn channel_count(&self) -> u32 {
+        self.stream.channel_count()
+    }
+    fn sample_rate(&self) -> u32 {
+        self.resampler.target_rate()
+    }
+    fn get_channel_map(&self) -> Vec<SoundChannel> {
+        self.stream.get_channel_map()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+mod tests {
+    use {
+        super::{ResampledStream, Resampler, ResamplerQuality},
+        crate::{
+            audio::{SoundChannel, SoundStream},
+            system::Time,
+        },
+        std::f64::consts::PI,
+    };
+
+    fn resample_all(resampler: &mut Resampler, input: &[f32]) -> Vec<f32> {
+        let mut output = Vec::new();
+        resampler.process(input, &mut output);
+        resampler.flush(&mut output);
+        output
+    }
+
+    /// Linear sine sweep from `f0` to `f1` Hz over `duration` seconds
+    fn sweep(rate: u32, f0: f64, f1: f64, duration: f64, amplitude: f64) -> Vec<f32> {
+        let len = (f64::from(rate) * duration) as usize;
+        (0..len)
+            .map(|i| sweep_at(i as f64 / f64::from(rate), f0, f1, duration, amplitude) as f32)
+            .collect()
+    }
+
+    fn sweep_at(t: f64, f0: f64, f1: f64, duration: f64, amplitude: f64) -> f64 {
+        let phase = 2.0 * PI * (f0 * t + (f1 - f0) * t * t / (2.0 * duration));
+        amplitude * phase.sin()
+    }
+
+    fn rms(samples: &[f32]) -> f64 {
+        let sum: f64 = samples.iter().map(|&s| f64::from(s) * f64::from(s)).sum();
+        (sum / samples.len() as f64).sqrt()
+    }
+
+    #[test]
+    fn output_length_follows_rate_ratio() {
+        for (from, to) in [
+            (44_100, 48_000),
+            (48_000, 44_100),
+            (22_050, 44_100),
+            (8_000, 44_100),
+        ] {
+            let mut resampler = Resampler::new(2, from, to);
+            let output = resample_all(&mut resampler, &vec![0.0; 2 * 10_000]);
+            let expected = (10_000 * u64::from(to)).div_ceil(u64::from(from));
+            assert_eq!(output.len() as u64, 2 * expected, "{from} -> {to}");
+        }
+    }
+
+    #[test]
+    fn sweep_matches_analytic_signal() {
+        let (from, to, duration) = (44_100, 48_000, 0.5);
+        let input = sweep(from, 20.0, 16_000.0, duration, 0.5);
+        let mut resampler = Resampler::with_quality(1, from, to, ResamplerQuality::Best);
+        let output = resample_all(&mut resampler, &input);
+        // Skip the edges, where the sweep starts and ends abruptly
+        let margin = 200;
+        let max_error = output[margin..output.len() - margin]
+            .iter()
+            .enumerate()
+            .map(|(i, &s)| {
+                let t = (i + margin) as f64 / f64::from(to);
+                (f64::from(s) - sweep_at(t, 20.0, 16_000.0, duration, 0.5)).abs()
+            })
+            .fold(0.0, f64::max);
+        assert!(max_error < 5e-3, "max error {max_error}");
+    }
+
+    #[test]
+    fn downsampling_removes_content_above_nyquist() {
+        // Sweep entirely above the 8 kHz Nyquist frequency of the target rate
+        let input = sweep(48_000, 9_000.0, 20_000.0, 0.25, 0.5);
+        let mut resampler = Resampler::new(1, 48_000, 16_000);
+        let output = resample_all(&mut resampler, &input);
+        let level = rms(&output[100..output.len() - 100]);
+        assert!(level < 0.005, "aliased level {level}");
+        // A sweep inside the passband survives
+        let input = sweep(48_000, 100.0, 6_000.0, 0.25, 0.5);
+        let output = resample_all(&mut resampler, &input);
+        let level = rms(&output[100..output.len() - 100]);
+        assert!(
+            (level - 0.5 / 2f64.sqrt()).abs() < 0.01,
+            "passband level {level}"
+        );
+    }
+
+    #[test]
+    fn chunked_processing_matches_single_pass() {
+        let input: Vec<f32> = sweep(44_100, 50.0, 10_000.0, 0.2, 0.8)
+            .into_iter()
+            .flat_map(|s| [s, -s])
+            .collect();
+        let mut resampler = Resampler::new(2, 44_100, 32_000);
+        let whole = resample_all(&mut resampler, &input);
+        let mut chunked = Vec::new();
+        for chunk in input.chunks(2 * 37) {
+            resampler.process(chunk, &mut chunked);
+        }
+        resampler.flush(&mut chunked);
+        assert_eq!(whole, chunked);
+        // Channels stay independent
+        assert!(whole.chunks(2).all(|f| (f[0] + f[1]).abs() < 1e-6));
+    }
+
+    /// A stream that never has data, but never ends.
+    struct Starved;
+
+    impl SoundStream for Starved {
+        fn get_data(&mut self) -> (&[i16], bool) {
+            (&[], true)
+        }
+        fn seek(&mut self, _offset: Time) {}
+        fn channel_count(&self) -> u32 {
+            1
+        }
+        fn sample_rate(&self) -> u32 {
+            44_100
+        }
+        fn get_channel_map(&self) -> Vec<SoundChannel> {
+            vec![SoundChannel::Mono]
+        }
+    }
+
+    #[test]
+    fn empty_chunks_return_right_away() {
+        let mut stream = ResampledStream::new(Starved, 48_000);
+        assert_eq!(stream.get_data(), (&[][..], true));
+    }
+}
@@ -0,0 +1,623 @@
+use {
+    super::{
+        SoundBuffer, SoundChannel, SoundStream, TempoMap,
+        sample_conversion::{Ditherer, sample_to_f32},
+    },
+    crate::system::Time,
+    std::{
+        f32::consts::FRAC_PI_4,
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicU64, Ordering},
+        },
+    },
+};
+
+/// Number of frames rendered per chunk handed to SFML.
+///
+/// Kept small, since events scheduled after a chunk was rendered can't land in it.
+const CHUNK_FRAMES: usize = 1024;
+
+/// Range of [`SequencerEvent::pitch`], so that every voice eventually ends.
+const PITCH_RANGE: (f32, f32) = (0.01, 100.0);
+
+/// Clamp `pitch` to [`PITCH_RANGE`], playing it unchanged if it isn't a number.
+fn clamp_pitch(pitch: f32) -> f32 {
+    if pitch.is_nan() {
+        1.0
+    } else {
+        pitch.clamp(PITCH_RANGE.0, PITCH_RANGE.1)
+    }
+}
+
+/// Audio data that can be scheduled on a [`Sequencer`].
+///
+/// The samples are copied once when the clip is created, cloning a `SequencerClip` is cheap.
+#[derive(Debug, Clone)]
+pub struct SequencerClip {
+    samples: Arc<[f32]>,
+    channel_count: u32,
+    sample_rate: u32,
+}
+
+impl SequencerClip {
+    /// Create a clip from the samples of `buffer`.
+    #[must_use]
+    pub fn from_buffer(buffer: &SoundBuffer) -> Self {
+        Self::from_samples(
+            buffer.samples(),
+            buffer.channel_count(),
+            buffer.sample_rate(),
+        )
+    }
+    /// Create a clip from interleaved 16-bit samples.
+    ///
+    /// Only mono and stereo clips are supported, other channel counts are downmixed to mono.
+    #[must_use]
+    pub fn from_samples(samples: &[i16], channel_count: u32, sample_rate: u32) -> Self {
+        let samples: Vec<f32> = samples.iter().copied().map(sample_to_f32).collect();
+        Self::from_samples_f32(&samples, channel_count, sample_rate)
+    }
+    /// Create a clip from interleaved normalized `f32` samples.
+    ///
+    /// Only mono and stereo clips are supported, other channel counts are downmixed to mono.
+    #[must_use]
+    pub fn from_samples_f32(samples: &[f32], channel_count: u32, sample_rate: u32) -> Self {
+        let channel_count = channel_count.max(1);
+        let samples = if channel_count <= 2 {
+            samples.into()
+        } else {
+            let scale = 1.0 / channel_count as f32;
+            samples
+                .chunks_exact(channel_count as usize)
+                .map(|frame| frame.iter().sum::<f32>() * scale)
+                .collect()
+        };
+        Self {
+            samples,
+            channel_count: if channel_count <= 2 { channel_count } else { 1 },
+            sample_rate: sample_rate.max(1),
+        }
+    }
+    /// The number of channels of the clip, `1` or `2`.
+    #[must_use]
+    pub fn channel_count(&self) -> u32 {
+        self.channel_count
+    }
+    /// The sample rate of the clip.
+    #[must_use]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+    /// The duration of the clip at its original pitch.
+    #[must_use]
+    pub fn duration(&self) -> Time {
+        let frames = u64::try_from(self.frame_count()).unwrap_or(u64::MAX);
+        let micros = frames * 1_000_000 / u64::from(self.sample_rate);
+        Time::microseconds(i64::try_from(micros).unwrap_or(i64::MAX))
+    }
+    fn frame_count(&self) -> usize {
+        self.samples.len() / self.channel_count as usize
+    }
+}
+
+/// A clip scheduled to play at a given time on a [`Sequencer`].
+#[derive(Debug, Clone)]
+pub struct SequencerEvent {
+    /// The clip to play
+    pub clip: SequencerClip,
+    /// When the clip starts, on the sequencer's timeline
+    pub start: Time,
+    /// Volume factor, `1.0` plays the clip unchanged
+    pub gain: f32,
+    /// Playback speed factor, which also changes the pitch, `1.0` plays the clip unchanged
+    ///
+    /// Clamped to `0.01..=100.0` when scheduled, values that aren't a number play at `1.0`.
+    pub pitch: f32,
+    /// Stereo position, from `-1.0` (left) to `1.0` (right)
+    pub pan: f32,
+}
+
+impl SequencerEvent {
+    /// Create an event playing `clip` unchanged at `start`.
+    #[must_use]
+    pub fn new(clip: SequencerClip, start: Time) -> Self {
+        Self {
+            clip,
+            start,
+            gain: 1.0,
+            pitch: 1.0,
+            pan: 0.0,
+        }
+    }
+    /// Set the volume factor.
+    #[must_use]
+    pub fn with_gain(mut self, gain: f32) -> Self {
+        self.gain = gain;
+        self
+    }
+    /// Set the playback speed factor, clamped to `0.01..=100.0`.
+    #[must_use]
+    pub fn with_pitch(mut self, pitch: f32) -> Self {
+        self.pitch = clamp_pitch(pitch);
+        self
+    }
+    /// Set the stereo position.
+    #[must_use]
+    pub fn with_pan(mut self, pan: f32) -> Self {
+        self.pan = pan;
+        self
+    }
+}
+
+/// How [`SequencerHandle::schedule_quantized`] snaps start times to the tempo grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Quantize {
+    /// Start times are used as is
+    #[default]
+    Off,
+    /// Start times are snapped to the next `1 / n`th of a beat, `Beats(1)` being whole beats
+    Beats(u32),
+    /// Start times are snapped to the next downbeat
+    Bars,
+}
+
+/// Identifies an event scheduled on a [`Sequencer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScheduleId(u64);
+
+#[derive(Debug)]
+enum Command {
+    Schedule(ScheduleId, u64, SequencerEvent),
+    Cancel(ScheduleId),
+    Clear,
+}
+
+#[derive(Debug)]
+struct Shared {
+    commands: Mutex<Vec<Command>>,
+    next_id: AtomicU64,
+    /// Frame at the start of the next chunk to render.
+    position: AtomicU64,
+}
+
+/// Thread safe handle scheduling events on a [`Sequencer`] while it's being played.
+///
+/// Times are on the sequencer's timeline, which starts at zero and advances by exactly one
+/// frame per rendered frame. An event scheduled at a given time starts on the sample matching
+/// that time, as long as it's scheduled before that part of the timeline was rendered. Late
+/// events start as soon as possible.
+#[derive(Debug, Clone)]
+pub struct SequencerHandle {
+    shared: Arc<Shared>,
+    sample_rate: u32,
+    tempo: Option<TempoMap>,
+}
+
+impl SequencerHandle {
+    /// Schedule `event` to play at its start time.
+    #[must_use]
+    pub fn schedule(&self, mut event: SequencerEvent) -> ScheduleId {
+        event.pitch = clamp_pitch(event.pitch);
+        let id = ScheduleId(self.shared.next_id.fetch_add(1, Ordering::Relaxed));
+        let frame = time_to_frame(event.start, self.sample_rate);
+        self.push(Command::Schedule(id, frame, event));
+        id
+    }
+    /// Schedule `event` after snapping its start time to the tempo grid.
+    ///
+    /// Start times snap forward, never earlier than requested. Without a tempo map, the start
+    /// time is used as is.
+    #[must_use]
+    pub fn schedule_quantized(&self, mut event: SequencerEvent, quantize: Quantize) -> ScheduleId {
+        event.start = self.quantize(event.start, quantize);
+        self.schedule(event)
+    }
+    /// Cancel a scheduled event, stopping it if it's already playing.
+    pub fn cancel(&self, id: ScheduleId) {
+        self.push(Command::Cancel(id));
+    }
+    /// Cancel all the scheduled events and stop all the playing ones.
+    pub fn clear(&self) {
+        self.push(Command::Clear);
+    }
+    /// The time of the next frame to be rendered.
+    ///
+    /// This is ahead of what's heard by the latency of SFML's stream buffering, but events
+    /// scheduled at or after this time still start on the exact sample.
+    #[must_use]
+    pub fn render_position(&self) -> Time {
+        frame_to_time(
+            self.shared.position.load(Ordering::Relaxed),
+            self.sample_rate,
+        )
+    }
+    /// The tempo map used for quantization.
+    #[must_use]
+    pub fn tempo_map(&self) -> Option<&TempoMap> {
+        self.tempo.as_ref()
+    }
+    /// Set the tempo map used for quantization.
+    pub fn set_tempo_map(&mut self, tempo: Option<TempoMap>) {
+        self.tempo = tempo;
+    }
+    /// Snap `time` forward to the tempo grid.
+    #[must_use]
+    pub fn quantize(&self, time: Time, quantize: Quantize) -> Time {
+        let Some(tempo) = &self.tempo else {
+            return time;
+        };
+        let step = match quantize {
+            Quantize::Off => return time,
+            Quantize::Beats(n) => 1.0 / f64::from(n.max(1)),
+            Quantize::Bars => f64::from(tempo.signature().beats_per_bar),
+        };
+        let beat = tempo.beat_at(time);
+        let mut grid = (beat / step).floor() * step;
+        // Rounding in `beat_at` can land one step too early or too late
+        while tempo.time_at(grid) < time {
+            grid += step;
+        }
+        if grid >= step && tempo.time_at(grid - step) >= time {
+            grid -= step;
+        }
+        tempo.time_at(grid)
+    }
+    fn push(&self, command: Command) {
+        self.shared
+            .commands
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .push(command);
+    }
+}
+
+fn time_to_frame(time: Time, sample_rate: u32) -> u64 {
+    let micros = u128::try_from(time.as_microseconds()).unwrap_or(0);
+    // Round to the nearest frame
+    let frame = (micros * u128::from(sample_rate) + 500_000) / 1_000_000;
+    u64::try_from(frame).unwrap_or(u64::MAX)
+}
+
+fn frame_to_time(frame: u64, sample_rate: u32) -> Time {
+    let micros = u128::from(frame) * 1_000_000 / u128::from(sample_rate);
+    Time::microseconds(i64::try_from(micros).unwrap_or(i64::MAX))
+}
+
+#[derive(Debug)]
+struct Voice {
+    id: ScheduleId,
+    event: SequencerEvent,
+    /// Frame of the output timeline the voice starts on.
+    start: u64,
+    /// Position in the clip, in clip frames.
+    cursor: f64,
+    step: f64,
+    left: f32,
+    right: f32,
+}
+
+impl Voice {
+    fn new(id: ScheduleId, start: u64, event: SequencerEvent, sample_rate: u32) -> Self {
+        let pan = event.pan.clamp(-1.0, 1.0);
+        let (left, right) = if event.clip.channel_count == 1 {
+            // Constant power panning
+            let angle = (pan + 1.0) * FRAC_PI_4;
+            (
+                angle.cos() * std::f32::consts::SQRT_2,
+                angle.sin() * std::f32::consts::SQRT_2,
+            )
+        } else {
+            // Balance
+            ((1.0 - pan).min(1.0), (1.0 + pan).min(1.0))
+        };
+        let step =
+            f64::from(event.pitch) * f64::from(event.clip.sample_rate) / f64::from(sample_rate);
+        Self {
+            id,
+            left: left * event.gain,
+            right: right * event.gain,
+            event,
+            start,
+            cursor: 0.0,
+            step,
+        }
+    }
+    /// Mix the voice into `out`, which starts at frame `position` of the timeline.
+    ///
+    /// Returns whether the voice is still playing.
+    fn mix(&mut self, out: &mut [f32], position: u64) -> bool {
+        let clip = &self.event.clip;
+        let frames = clip.frame_count();
+        let stereo = clip.channel_count == 2;
+        let skip = usize::try_from(self.start.saturating_sub(position)).unwrap_or(usize::MAX);
+        for frame in out.chunks_exact_mut(2).skip(skip) {
+            #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let index = self.cursor as usize;
+            if index >= frames {
+                return false;
+            }
+            #[expect(clippy::cast_possible_truncation)]
+            let frac = (self.cursor - self.cursor.floor()) as f32;
+            let sample = |i: usize, channel: usize| {
+                let i = i.min(frames - 1);
+                if stereo {
+                    clip.samples[i * 2 + channel]
+                } else {
+                    clip.samples[i]
+                }
+            };
+            let lerp = |channel| {
+                let a = sample(index, channel);
+                a + (sample(index + 1, channel) - a) * frac
+            };
+            frame[0] += lerp(0) * self.left;
+            frame[1] += lerp(1) * self.right;
+            self.cursor += self.step;
+        }
+        true
+    }
+}
+
+/// Mixes [`SequencerEvent`]s scheduled on a timeline into a single stereo [`SoundStream`].
+///
+/// Triggering sounds from the game loop makes them start up to a frame late. A sequencer mixes
+/// its events itself, so that each one starts on the exact sample its start time falls on.
+/// Events are scheduled through a [`SequencerHandle`], from any thread, while the sequencer is
+/// being played by a [`SoundStreamPlayer`].
+///
+/// The sequencer never ends on its own, it plays silence while no event is playing. Seeking
+/// moves the timeline, stopping the events being played and dropping the ones that were
+/// scheduled before the new position.
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::audio::{Quantize, Sequencer, SequencerClip, SequencerEvent, SoundBuffer,
+/// #     SoundStreamPlayer, TempoMap};
+/// # use sfml::system::Time;
+/// let kick = SequencerClip::from_buffer(&SoundBuffer::from_file("kick.wav").unwrap());
+/// let mut sequencer = Sequencer::new(44_100);
+/// let mut handle = sequencer.handle();
+/// handle.set_tempo_map(Some(TempoMap::new(120.0)));
+/// let mut player = SoundStreamPlayer::new(&mut sequencer);
+/// player.play();
+/// // Four on the floor, starting on the next bar
+/// let bar = handle.quantize(handle.render_position(), Quantize::Bars);
+/// for beat in 0..4 {
+///     let start = bar + Time::milliseconds(500 * beat);
+///     let _ = handle.schedule(SequencerEvent::new(kick.clone(), start).with_gain(0.8));
+/// }
+/// ```
+///
+/// [`SoundStreamPlayer`]: crate::audio::SoundStreamPlayer
+#[derive(Debug)]
+pub struct Sequencer {
+    shared: Arc<Shared>,
+    sample_rate: u32,
+    position: u64,
+    /// Sorted by start frame.
+    pending: Vec<(ScheduleId, u64, SequencerEvent)>,
+    voices: Vec<Voice>,
+    mix: Vec<f32>,
+    chunk: Vec<i16>,
+    ditherer: Ditherer,
+}
+
+impl Sequencer {
+    /// Create an empty sequencer, playing at `sample_rate` Hz.
+    #[must_use]
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                commands: Mutex::new(Vec::new()),
+                next_id: AtomicU64::new(0),
+                position: AtomicU64::new(0),
+            }),
+            sample_rate: sample_rate.max(1),
+            position: 0,
+            pending: Vec::new(),
+            voices: Vec::new(),
+            mix: vec![0.0; CHUNK_FRAMES * 2],
+            chunk: Vec::with_capacity(CHUNK_FRAMES * 2),
+            ditherer: Ditherer::new(),
+        }
+    }
+    /// A handle to schedule events on this sequencer, from any thread.
+    #[must_use]
+    pub fn handle(&self) -> SequencerHandle {
+        SequencerHandle {
+            shared: Arc::clone(&self.shared),
+            sample_rate: self.sample_rate,
+            tempo: None,
+        }
+    }
+    /// The number of events playing or waiting to be played.
+    #[must_use]
+    pub fn event_count(&self) -> usize {
+        self.voices.len() + self.pending.len()
+    }
+    /// Render the next chunk of interleaved stereo samples.
+    ///
+    /// This is what [`SoundStream::get_data`] plays, exposed for offline rendering.
+    pub fn render(&mut self) -> &[f32] {
+        self.render_chunk();
+        &self.mix
+    }
+
+    fn render_chunk(&mut self) {
+        self.apply_commands();
+        self.mix.fill(0.0);
+        let end = self.position + CHUNK_FRAMES as u64;
+        let split = self.pending.partition_point(|&(_, start, _)| start < end);
+        for (id, start, event) in self.pending.drain(..split) {
+            // Late events start right away
+            let start = start.max(self.position);
+            self.voices
+                .push(Voice::new(id, start, event, self.sample_rate));
+        }
+        let position = self.position;
+        let mix = &mut self.mix;
+        self.voices.retain_mut(|voice| voice.mix(mix, position));
+        self.position = end;
+        self.shared.position.store(end, Ordering::Relaxed);
+    }
+    fn apply_commands(&mut self) {
+        // Never block the audio thread, commands wait for the next chunk if the lock is taken
+        let Ok(mut commands) = self.shared.commands.try_lock() else {
+            return;
+        };
+        for command in commands.drain(..) {
+            match command {
+                Command::Schedule(id, start, event) => {
+                    let index = self.pending.partition_point(|&(_, s, _)| s <= start);
+                    self.pending.insert(index, (id, start, event));
+                }
+                Command::Cancel(id) => {
+                    self.pending.retain(|&(i, _, _)| i != id);
+                    self.voices.retain(|v| v.id != id);
+                }
+                Command::Clear => {
+                    self.pending.clear();
+                    self.voices.clear();
+                }
+            }
+        }
+    }
+}
+
+impl SoundStream for Sequencer {
+    fn get_data(&mut self) -> (&[i16], bool) {
+        self.render_chunk();
+        self.chunk.clear();
+        self.ditherer.convert_into(&self.mix, &mut self.chunk);
+        (&self.chunk, true)
+    }
+    fn seek(&mut self, offset: Time) {
+        self.apply_commands();
+        self.position = time_to_frame(offset, self.sample_rate);
+        self.shared.position.store(self.position, Ordering::Relaxed);
+        self.voices.clear();
+        let position = self.position;
+        self.pending.retain(|&(_, start, _)| start >= position);
+    }
+    fn channel_count(&self) -> u32 {
+        2
+    }
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+    fn get_channel_map(&self) -> Vec<SoundChannel> {
+        vec![SoundChannel::FrontLeft, SoundChannel::FrontRight]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A single full scale mono impulse followed by silence.
+    fn impulse() -> SequencerClip {
+        SequencerClip::from_samples_f32(&[1.0, 0.0, 0.0, 0.0], 1, 1000)
+    }
+
+    fn nonzero_frames(chunk: &[f32]) -> Vec<usize> {
+        chunk
+            .chunks_exact(2)
+            .enumerate()
+            .filter(|(_, f)| f[0] != 0.0 || f[1] != 0.0)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    #[test]
+    fn events_start_on_exact_samples() {
+        let mut sequencer = Sequencer::new(1000);
+        let handle = sequencer.handle();
+        let _ = handle.schedule(SequencerEvent::new(impulse(), Time::milliseconds(100)));
+        let _ = handle.schedule(
+            SequencerEvent::new(impulse(), Time::milliseconds(1030))
+                .with_gain(0.5)
+                .with_pan(1.0),
+        );
+        assert_eq!(nonzero_frames(sequencer.render()), [100]);
+        assert_eq!(handle.render_position(), Time::milliseconds(1024));
+        let chunk = sequencer.render();
+        assert_eq!(nonzero_frames(chunk), [6]);
+        assert!(chunk[12].abs() < 1e-6);
+        assert!((chunk[13] - 0.5 * std::f32::consts::SQRT_2).abs() < 1e-6);
+        assert_eq!(sequencer.event_count(), 0);
+    }
+
+    #[test]
+    fn pitch_late_events_and_cancel() {
+        let clip = SequencerClip::from_samples_f32(&[1.0; 100], 1, 1000);
+        let mut sequencer = Sequencer::new(1000);
+        let handle = sequencer.handle();
+        let _ = handle.schedule(SequencerEvent::new(clip.clone(), Time::ZERO).with_pitch(2.0));
+        assert_eq!(
+            nonzero_frames(sequencer.render()),
+            (0..50).collect::<Vec<_>>()
+        );
+        // Scheduled in the past, so it starts right away
+        let id = handle.schedule(SequencerEvent::new(clip, Time::ZERO));
+        let _ = handle.schedule(SequencerEvent::new(impulse(), Time::seconds(100.0)));
+        assert_eq!(
+            nonzero_frames(sequencer.render()),
+            (0..100).collect::<Vec<_>>()
+        );
+        assert_eq!(sequencer.event_count(), 1);
+        handle.cancel(id);
+        handle.clear();
+        let _ = sequencer.render();
+        assert_eq!(sequencer.event_count(), 0);
+    }
+
+    #[test]
+    fn invalid_pitches_end() {
+        let event = SequencerEvent::new(impulse(), Time::ZERO);
+        assert_eq!(event.clone().with_pitch(0.0).pitch, 0.01);
+        assert_eq!(event.clone().with_pitch(f32::INFINITY).pitch, 100.0);
+        assert_eq!(event.clone().with_pitch(f32::NAN).pitch, 1.0);
+        let mut sequencer = Sequencer::new(1000);
+        let handle = sequencer.handle();
+        let clip = SequencerClip::from_samples_f32(&[1.0; 10], 1, 1000);
+        for pitch in [0.0, -1.0, f32::NAN, f32::NEG_INFINITY] {
+            // Set directly, bypassing `with_pitch`
+            let mut event = SequencerEvent::new(clip.clone(), Time::ZERO);
+            event.pitch = pitch;
+            let _ = handle.schedule(event);
+        }
+        // 10 frames at the slowest pitch last 1000 frames
+        let _ = sequencer.render();
+        assert_eq!(sequencer.event_count(), 0);
+    }
+
+    #[test]
+    fn quantization() {
+        let mut handle = Sequencer::new(44_100).handle();
+        let time = Time::milliseconds(1100);
+        assert_eq!(handle.quantize(time, Quantize::Bars), time);
+        handle.set_tempo_map(Some(
+            TempoMap::new(120.0).with_offset(Time::milliseconds(50)),
+        ));
+        assert_eq!(handle.quantize(time, Quantize::Off), time);
+        assert_eq!(
+            handle.quantize(time, Quantize::Beats(1)),
+            Time::milliseconds(1550)
+        );
+        assert_eq!(
+            handle.quantize(time, Quantize::Beats(4)),
+            Time::milliseconds(1175)
+        );
+        assert_eq!(
+            handle.quantize(time, Quantize::Bars),
+            Time::milliseconds(2050)
+        );
+        // Already on the grid
+        assert_eq!(
+            handle.quantize(Time::milliseconds(1050), Quantize::Beats(2)),
+            Time::milliseconds(1050)
+        );
+    }
+}
@@ -1,7 +1,12 @@
 use {
-    super::sound_channel::SoundChannel,
+    super::{
+        metadata::AudioTags,
+        resampler::{Resampler, ResamplerQuality},
+        sample_conversion::{Ditherer, samples_to_f32},
+        sound_channel::SoundChannel,
+    },
     crate::{
-        IntoSfResult, SfResult,
+        IntoSfResult, SfError, SfResult,
         cpp::{CppVector, FBox},
         ffi::{self},
         system::{InputStream, Time},
@@ -69,6 +74,21 @@ impl SoundBuffer {
         new.load_from_memory(data)?;
         Ok(new)
     }
+    /// Creates a new `SoundBuffer` from a file, along with the tags of the file if it has any.
+    ///
+    /// The tags are `None` if the file has none, or if they couldn't be read.
+    /// See [`AudioTags`] for the supported tag formats.
+    pub fn from_file_with_tags(filename: &str) -> SfResult<(FBox<Self>, Option<AudioTags>)> {
+        let new = Self::from_file(filename)?;
+        Ok((new, AudioTags::from_file(filename).ok()))
+    }
+    /// Creates a new `SoundBuffer` from a file in memory, along with its tags if it has any.
+    ///
+    /// See [`Self::from_file_with_tags`].
+    pub fn from_memory_with_tags(data: &[u8]) -> SfResult<(FBox<Self>, Option<AudioTags>)> {
+        let new = Self::from_memory(data)?;
+        Ok((new, AudioTags::from_memory(data).ok()))
+    }
     /// Creates a new `SoundBuffer` from a stream.
     ///
     /// See [`Self::load_from_stream`].
@@ -90,6 +110,19 @@ impl SoundBuffer {
         new.load_from_samples(samples, channel_count, sample_rate, channel_map)?;
         Ok(new)
     }
+    /// Creates a new `SoundBuffer` from a slice of normalized `f32` audio samples.
+    ///
+    /// See [`Self::load_from_samples_f32`].
+    pub fn from_samples_f32(
+        samples: &[f32],
+        channel_count: u32,
+        sample_rate: u32,
+        channel_map: &[SoundChannel],
+    ) -> SfResult<FBox<Self>> {
+        let mut new = Self::new()?;
+        new.load_from_samples_f32(samples, channel_count, sample_rate, channel_map)?;
+        Ok(new)
+    }
     /// Load sound data from a file.
     ///
     /// Here is a complete list of all the supported audio formats:
@@ -136,6 +169,20 @@ impl SoundBuffer {
         }
         .into_sf_result()
     }
+    /// Load the sound buffer from a slice of normalized `f32` audio samples.
+    ///
+    /// The samples are expected to be in the range `[-1, 1]`, values outside of it are clipped.
+    /// They are converted to 16 bits signed integers with dithering (see [`Ditherer`]).
+    pub fn load_from_samples_f32(
+        &mut self,
+        samples: &[f32],
+        channel_count: u32,
+        sample_rate: u32,
+        channel_map: &[SoundChannel],
+    ) -> SfResult<()> {
+        let samples = Ditherer::new().convert_slice(samples);
+        self.load_from_samples(&samples, channel_count, sample_rate, channel_map)
+    }
 }
 
 /// Query properties
@@ -164,6 +211,16 @@ impl SoundBuffer {
         unsafe { slice::from_raw_parts(ffi::audio::sfSoundBuffer_getSamples(self), len) }
     }
 
+    /// Get a copy of the samples stored in the buffer, converted to normalized `f32` samples.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sample count exceeds usize range
+    #[must_use]
+    pub fn samples_f32(&self) -> Vec<f32> {
+        samples_to_f32(self.samples())
+    }
+
     /// Get the number of channels used by a sound buffer
     ///
     /// If the sound is mono then the number of channels will
@@ -207,6 +264,37 @@ impl SoundBuffer {
     }
 }
 
+/// Sample rate conversion
+impl SoundBuffer {
+    /// Create a copy of this sound buffer converted to a different sample rate.
+    ///
+    /// The conversion uses a band-limited [`Resampler`], so buffers recorded at
+    /// different rates can be mixed together without aliasing.
+    /// The channel count and channel map are preserved.
+    ///
+    /// Returns an error if the buffer is empty, or `sample_rate` is zero.
+    pub fn resampled(&self, sample_rate: u32, quality: ResamplerQuality) -> SfResult<FBox<Self>> {
+        if self.channel_count() == 0 || self.sample_rate() == 0 || sample_rate == 0 {
+            return Err(SfError::CallFailed);
+        }
+        let mut resampler = Resampler::with_quality(
+            self.channel_count(),
+            self.sample_rate(),
+            sample_rate,
+            quality,
+        );
+        let mut samples = Vec::new();
+        resampler.process(&self.samples_f32(), &mut samples);
+        resampler.flush(&mut samples);
+        Self::from_samples_f32(
+            &samples,
+            self.channel_count(),
+            sample_rate,
+            self.channel_map(),
+        )
+    }
+}
+
 /// Saving
 impl SoundBuffer {
     /// Save a sound buffer to an audio file
@@ -1,5 +1,10 @@
 use {
-    super::{sound_channel::SoundChannel, sound_source::SoundSource},
+    super::{
+        callback_error::{CallbackContext, CallbackError, StreamError},
+        sample_conversion::Ditherer,
+        sound_channel::SoundChannel,
+        sound_source::SoundSource,
+    },
     crate::{
         cpp::CppVector,
         ffi::audio::{sfCustomSoundStream, sfCustomSoundStream_new, sfCustomSoundStream_play, sfCustomSoundStream_pause, sfCustomSoundStream_stop, sfCustomSoundStream_getPlayingOffset, sfCustomSoundStream_setPlayingOffset, sfCustomSoundStream_getChannelCount, sfCustomSoundStream_getSampleRate, sfCustomSoundStream_getChannelMap, sfCustomSoundStream_isLooping, sfCustomSoundStream_setLooping, sfCustomSoundStream_setPitch, sfCustomSoundStream_setVolume, sfCustomSoundStream_setPosition, sfCustomSoundStream_setRelativeToListener, sfCustomSoundStream_setMinDistance, sfCustomSoundStream_setAttenuation, sfCustomSoundStream_getPitch, sfCustomSoundStream_getVolume, sfCustomSoundStream_getPosition, sfCustomSoundStream_isRelativeToListener, sfCustomSoundStream_getMinDistance, sfCustomSoundStream_getAttenuation, sfCustomSoundStream_setPan, sfCustomSoundStream_setSpatializationEnabled, sfCustomSoundStream_setDirection, sfCustomSoundStream_setCone, sfCustomSoundStream_setVelocity, sfCustomSoundStream_setDopplerFactor, sfCustomSoundStream_setDirectionalAttenuationFactor, sfCustomSoundStream_setMaxDistance, sfCustomSoundStream_setMinGain, sfCustomSoundStream_setMaxGain, effect_processor_trampoline, sfCustomSoundStream_setEffectProcessor, sfCustomSoundStream_getPan, sfCustomSoundStream_isSpatializationEnabled, sfCustomSoundStream_getDirection, sfCustomSoundStream_getCone, sfCustomSoundStream_getVelocity, sfCustomSoundStream_getDopplerFactor, sfCustomSoundStream_getDirectionalAttenuationFactor, sfCustomSoundStream_getMaxDistance, sfCustomSoundStream_getMinGain, sfCustomSoundStream_getMaxGain, sfCustomSoundStream_getStatus, sfCustomSoundStream_del},
@@ -18,6 +23,18 @@ pub trait SoundStream: Send {
     /// Returns `(chunk, keep_playing)`, where `chunk` is the chunk of audio samples,
     /// and `keep_playing` tells the streaming loop whether to keep playing or to stop.
     fn get_data(&mut self) -> (&[i16], bool);
+    /// Request a new chunk of audio samples from the stream source, or fail.
+    ///
+    /// This is what [`SoundStreamPlayer`] calls. Returning an error stops playback, and the
+    /// error can then be retrieved with [`SoundStreamPlayer::last_error`].
+    ///
+    /// The default implementation calls [`get_data`], and never fails. Streams that can fail
+    /// should override this method, and implement [`get_data`] by ending the stream on error.
+    ///
+    /// [`get_data`]: SoundStream::get_data
+    fn try_get_data(&mut self) -> Result<(&[i16], bool), StreamError> {
+        Ok(self.get_data())
+    }
     /// Change the current playing position in the stream source.
     fn seek(&mut self, offset: Time);
     /// Return the number of channels of the stream.
@@ -31,6 +48,97 @@ pub trait SoundStream: Send {
     fn get_channel_map(&self) -> Vec<SoundChannel>;
 }
 
+/// Trait for streamed audio sources producing normalized `f32` samples.
+///
+/// This is the floating point counterpart of [`SoundStream`], for sources that synthesize or
+/// decode audio in `f32`. Samples are expected to be in the range `[-1, 1]`.
+///
+/// SFML plays 16 bits samples, so a `SoundStreamF32` has to be wrapped in an
+/// [`F32StreamAdapter`] to be played by a [`SoundStreamPlayer`].
+pub trait SoundStreamF32: Send {
+    /// Request a new chunk of audio samples from the stream source.
+    ///
+    /// Returns `(chunk, keep_playing)`, where `chunk` is the chunk of audio samples,
+    /// and `keep_playing` tells the streaming loop whether to keep playing or to stop.
+    fn get_data(&mut self) -> (&[f32], bool);
+    /// Change the current playing position in the stream source.
+    fn seek(&mut self, offset: Time);
+    /// Return the number of channels of the stream.
+    fn channel_count(&self) -> u32;
+    /// Get the stream sample rate of the stream.
+    fn sample_rate(&self) -> u32;
+    /// This is used to map a sample in the sample stream to a
+    /// position during spatialization.
+    ///
+    /// Return Map of position in sample frame to sound channel
+    fn get_channel_map(&self) -> Vec<SoundChannel>;
+}
+
+/// Adapter that turns a [`SoundStreamF32`] into a [`SoundStream`].
+///
+/// Every chunk is converted to 16 bits with a [`Ditherer`].
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::audio::{F32StreamAdapter, SoundStreamF32, SoundStreamPlayer};
+/// # fn play<S: SoundStreamF32>(my_stream: S) {
+/// let mut stream = F32StreamAdapter::new(my_stream);
+/// let mut player = SoundStreamPlayer::new(&mut stream);
+/// player.play();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct F32StreamAdapter<S> {
+    stream: S,
+    ditherer: Ditherer,
+    chunk: Vec<i16>,
+}
+
+impl<S: SoundStreamF32> F32StreamAdapter<S> {
+    /// Wrap `stream`.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            ditherer: Ditherer::new(),
+            chunk: Vec::new(),
+        }
+    }
+    /// Get a reference to the wrapped stream.
+    pub fn inner(&self) -> &S {
+        &self.stream
+    }
+    /// Get a mutable reference to the wrapped stream.
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+    /// Unwrap the wrapped stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S: SoundStreamF32> SoundStream for F32StreamAdapter<S> {
+    fn get_data(&mut self) -> (&[i16], bool) {
+        let (data, keep_playing) = self.stream.get_data();
+        self.chunk.clear();
+        self.ditherer.convert_into(data, &mut self.chunk);
+        (&self.chunk, keep_playing)
+    }
+    fn seek(&mut self, offset: Time) {
+        self.stream.seek(offset);
+    }
+    fn channel_count(&self) -> u32 {
+        self.stream.channel_count()
+    }
+    fn sample_rate(&self) -> u32 {
+        self.stream.sample_rate()
+    }
+    fn get_channel_map(&self) -> Vec<SoundChannel> {
+        self.stream.get_channel_map()
+    }
+}
+
 /// Player for custom streamed audio sources. See [`SoundStream`].
 #[derive(Debug)]
 pub struct SoundStreamPlayer<'a, S: SoundStream + 'a> {
@@ -40,6 +148,8 @@ pub struct SoundStreamPlayer<'a, S: SoundStream + 'a> {
     ///
     /// Not sure if `NonNull` can be used to be honest. Not gonna risk it.
     stream: *mut S,
+    /// User data of the callbacks, which also records their failures.
+    context: Box<CallbackContext<S>>,
     _borrow: PhantomData<&'a mut S>,
 }
 
@@ -47,31 +157,40 @@ unsafe extern "C" fn get_data_callback<S: SoundStream>(
     chunk: *mut crate::ffi::audio::sfSoundStreamChunk,
     user_data: *mut c_void,
 ) -> bool {
-    let stream: *mut S = user_data.cast();
+    let context: &CallbackContext<S> = unsafe { &*user_data.cast() };
+    let stream = context.target;
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| unsafe {
+        (*stream).try_get_data()
+    }));
+    let (data, keep_playing) = match result {
+        Ok(Ok(ret)) => ret,
+        Ok(Err(error)) => {
+            context.record(CallbackError::Stream(error.into()));
+            (&[][..], false)
+        }
+        Err(payload) => {
+            context.record(CallbackError::from_panic("get_data", &*payload));
+            (&[][..], false)
+        }
+    };
     unsafe {
-        let (data, keep_playing) =
-            if let Ok(ret) = panic::catch_unwind(panic::AssertUnwindSafe(|| (*stream).get_data())) { ret } else {
-                eprintln!("sound_stream: Stopping playback beacuse `get_data` panicked.");
-                (&[][..], false)
-            };
         (*chunk).samples = data.as_ptr();
         (*chunk).sample_count = data.len();
-        keep_playing
     }
+    keep_playing
 }
 
 unsafe extern "C" fn seek_callback<S: SoundStream>(
     offset: crate::ffi::system::sfTime,
     user_data: *mut c_void,
 ) {
-    let stream: *mut S = user_data.cast();
-    let result = unsafe {
-        panic::catch_unwind(panic::AssertUnwindSafe(|| {
-            (*stream).seek(Time::from_raw(offset));
-        }))
-    };
-    if result.is_err() {
-        eprintln!("sound_stream: Failed to seek because `seek` panicked.");
+    let context: &CallbackContext<S> = unsafe { &*user_data.cast() };
+    let stream = context.target;
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| unsafe {
+        (*stream).seek(Time::from_raw(offset));
+    }));
+    if let Err(payload) = result {
+        context.record(CallbackError::from_panic("seek", &*payload));
     }
 }
 
@@ -86,6 +205,8 @@ impl<'a, S: SoundStream> SoundStreamPlayer<'a, S> {
         let sample_rate = sound_stream.sample_rate();
         let channel_map = sound_stream.get_channel_map();
         let sound_stream: *mut S = sound_stream;
+        let context = CallbackContext::new(sound_stream);
+        let user_data: *const CallbackContext<S> = &*context;
         Self {
             handle: unsafe {
                 NonNull::new(sfCustomSoundStream_new(
@@ -95,11 +216,12 @@ impl<'a, S: SoundStream> SoundStreamPlayer<'a, S> {
                     sample_rate,
                     channel_map.as_ptr().cast(),
                     channel_map.len(),
-                    sound_stream.cast(),
+                    user_data.cast_mut().cast(),
                 ))
                 .expect("Failed to create SoundStreamPlayer")
             },
             stream: sound_stream,
+            context,
             _borrow: PhantomData,
         }
     }
@@ -132,6 +254,18 @@ impl<'a, S: SoundStream> SoundStreamPlayer<'a, S> {
             &mut *self.stream
         }
     }
+    /// The last failure of the stream's callbacks, if any.
+    ///
+    /// A stream stops playing when [`SoundStream::try_get_data`] returns an error, or when
+    /// [`SoundStream::get_data`] panics. A panic in [`SoundStream::seek`] is recorded too.
+    #[must_use]
+    pub fn last_error(&self) -> Option<CallbackError> {
+        self.context.last_error()
+    }
+    /// Forget the last failure of the stream's callbacks.
+    pub fn clear_error(&mut self) {
+        self.context.clear_error();
+    }
     /// Get the current playing position, from the beginning of the stream
     #[must_use]
     pub fn playing_offset(&self) -> Time {
@@ -316,3 +450,91 @@ impl<S: SoundStream> Drop for SoundStreamPlayer<'_, S> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{CallbackContext, CallbackError, SoundChannel, SoundStream, StreamError, Time},
+        crate::ffi::audio::sfSoundStreamChunk,
+    };
+
+    #[derive(Debug)]
+    enum Behavior {
+        Play,
+        Fail,
+        Panic,
+    }
+
+    #[derive(Debug)]
+    struct TestStream(Behavior);
+
+    impl SoundStream for TestStream {
+        fn get_data(&mut self) -> (&[i16], bool) {
+            self.try_get_data().unwrap_or((&[], false))
+        }
+        fn try_get_data(&mut self) -> Result<(&[i16], bool), StreamError> {
+            match self.0 {
+                Behavior::Play => Ok((&[1, 2, 3], true)),
+                Behavior::Fail => Err("decoder error".into()),
+                Behavior::Panic => panic!("out of samples"),
+            }
+        }
+        fn seek(&mut self, _offset: Time) {
+            panic!("can't seek");
+        }
+        fn channel_count(&self) -> u32 {
+            1
+        }
+        fn sample_rate(&self) -> u32 {
+            44_100
+        }
+        fn get_channel_map(&self) -> Vec<SoundChannel> {
+            vec![SoundChannel::Mono]
+        }
+    }
+
+    fn get_data(behavior: Behavior) -> (bool, usize, Option<CallbackError>) {
+        let mut stream = TestStream(behavior);
+        let context = CallbackContext::new(std::ptr::from_mut(&mut stream));
+        let mut chunk = sfSoundStreamChunk {
+            samples: std::ptr::null(),
+            sample_count: 0,
+        };
+        let user_data: *const CallbackContext<TestStream> = &*context;
+        let keep_playing = unsafe {
+            super::get_data_callback::<TestStream>(&mut chunk, user_data.cast_mut().cast())
+        };
+        (keep_playing, chunk.sample_count, context.last_error())
+    }
+
+    #[test]
+    fn callback_failures_are_recorded() {
+        let (keep_playing, samples, error) = get_data(Behavior::Play);
+        assert!(keep_playing);
+        assert_eq!(samples, 3);
+        assert!(error.is_none());
+
+        let (keep_playing, samples, error) = get_data(Behavior::Fail);
+        assert!(!keep_playing);
+        assert_eq!(samples, 0);
+        assert!(
+            matches!(error, Some(CallbackError::Stream(e)) if e.to_string() == "decoder error")
+        );
+
+        let (keep_playing, _, error) = get_data(Behavior::Panic);
+        assert!(!keep_playing);
+        let error = error.expect("the panic is recorded");
+        assert_eq!(error.to_string(), "`get_data` panicked: out of samples");
+
+        let mut stream = TestStream(Behavior::Play);
+        let context = CallbackContext::new(std::ptr::from_mut(&mut stream));
+        let user_data: *const CallbackContext<TestStream> = &*context;
+        unsafe { super::seek_callback::<TestStream>(0, user_data.cast_mut().cast()) };
+        assert!(matches!(
+            context.last_error(),
+            Some(CallbackError::Panicked { callback: "seek", message }) if message == "can't seek"
+        ));
+        context.clear_error();
+        assert!(context.last_error().is_none());
+    }
+}
@@ -0,0 +1,597 @@
+use {
+    super::{
+        SoundChannel, SoundStream, StreamError,
+        sample_conversion::{Ditherer, sample_to_f32},
+        sound_source::SoundSource,
+    },
+    crate::system::Time,
+    std::{
+        f64::consts::PI,
+        sync::{
+            Arc,
+            atomic::{AtomicU32, Ordering},
+        },
+    },
+};
+
+/// Duration of the segments overlapped by [`TimeStretcher`], in seconds.
+const SEGMENT_DURATION: f64 = 0.04;
+/// How far from its nominal position a segment may be taken, in seconds.
+const SEEK_TOLERANCE: f64 = 0.012;
+/// Sample rate assumed for the audio engine in effect processors, which aren't told about it.
+const ENGINE_RATE: u32 = 48_000;
+/// Range of the speed and pitch factors.
+const FACTOR_RANGE: (f32, f32) = (0.1, 10.0);
+
+/// Changes the speed and the pitch of interleaved `f32` samples independently.
+///
+/// Speed is changed with WSOLA (waveform similarity overlap-add): overlapping segments of the
+/// input are laid out at a different rate, each one taken where it best lines up with the
+/// previous one, so that the waveform stays continuous. Pitch is then shifted by stretching by
+/// the pitch factor and resampling back, which keeps the duration.
+///
+/// Both factors can be changed between calls to [`process`], the change takes effect on the
+/// next segment.
+///
+/// [`process`]: TimeStretcher::process
+#[derive(Debug, Clone)]
+pub struct TimeStretcher {
+    channels: usize,
+    speed: f32,
+    pitch: f32,
+    window: Vec<f32>,
+    /// Length of a segment, in frames.
+    segment: usize,
+    /// Distance between two output segments, in frames.
+    hop: usize,
+    tolerance: usize,
+    /// Buffered input, interleaved.
+    input: Vec<f32>,
+    /// Mono mix of `input`, used to find the best segments.
+    mono: Vec<f32>,
+    /// Nominal start of the next segment in `input`, in frames.
+    analysis: f64,
+    /// Natural continuation of the last segment in `input`, which the next one should match.
+    template: Option<usize>,
+    /// Overlap-add accumulator, one segment long.
+    overlap: Vec<f32>,
+    /// Stretched samples waiting to be resampled.
+    stretched: Vec<f32>,
+    /// Position of the resampler in `stretched`, in frames.
+    cursor: f64,
+    /// Number of output frames the input processed so far should give.
+    expected: f64,
+    produced: u64,
+}
+
+impl TimeStretcher {
+    /// Create a new `TimeStretcher` for audio with `channel_count` channels at `sample_rate`,
+    /// playing at the original speed and pitch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel_count` or `sample_rate` is zero.
+    #[must_use]
+    pub fn new(channel_count: u32, sample_rate: u32) -> Self {
+        assert!(
+            channel_count > 0 && sample_rate > 0,
+            "TimeStretcher channel count and sample rate must be non-zero"
+        );
+        #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let frames = |seconds: f64| (f64::from(sample_rate) * seconds) as usize;
+        let hop = frames(SEGMENT_DURATION / 2.0).max(16);
+        let segment = hop * 2;
+        // Periodic Hann window, which sums to one at 50% overlap
+        #[expect(clippy::cast_possible_truncation)]
+        let window = (0..segment)
+            .map(|i| (0.5 - 0.5 * (2.0 * PI * i as f64 / segment as f64).cos()) as f32)
+            .collect();
+        let channels = channel_count as usize;
+        Self {
+            channels,
+            speed: 1.0,
+            pitch: 1.0,
+            window,
+            segment,
+            hop,
+            tolerance: frames(SEEK_TOLERANCE).max(4),
+            input: Vec::new(),
+            mono: Vec::new(),
+            analysis: 0.0,
+            template: None,
+            overlap: vec![0.0; segment * channels],
+            stretched: Vec::new(),
+            cursor: 0.0,
+            expected: 0.0,
+            produced: 0,
+        }
+    }
+    /// The speed factor.
+    #[must_use]
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+    /// Set the speed factor, without changing the pitch.
+    ///
+    /// `2.0` plays twice as fast, `0.5` twice as slow. The factor is clamped to `[0.1, 10]`.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = clamp_factor(speed);
+    }
+    /// The pitch factor.
+    #[must_use]
+    pub fn pitch(&self) -> f32 {
+        self.pitch
+    }
+    /// Set the pitch factor, without changing the speed.
+    ///
+    /// `2.0` plays an octave higher, `0.5` an octave lower. The factor is clamped to `[0.1, 10]`.
+    pub fn set_pitch(&mut self, pitch: f32) {
+        self.pitch = clamp_factor(pitch);
+    }
+    /// The number of channels.
+    #[must_use]
+    pub fn channel_count(&self) -> u32 {
+        // Created from an `u32`
+        u32::try_from(self.channels).unwrap_or(u32::MAX)
+    }
+    /// Upper bound of the delay between input and output, in frames.
+    #[must_use]
+    pub fn latency(&self) -> usize {
+        self.segment + self.hop + self.tolerance + 4
+    }
+    /// Clear the internal state, so that the next input is processed as a new signal.
+    pub fn reset(&mut self) {
+        self.input.clear();
+        self.mono.clear();
+        self.analysis = 0.0;
+        self.template = None;
+        self.overlap.fill(0.0);
+        self.stretched.clear();
+        self.cursor = 0.0;
+        self.expected = 0.0;
+        self.produced = 0;
+    }
+    /// Process interleaved `input`, appending the available output to `output`.
+    ///
+    /// The output lags behind the input by up to [`latency`] frames, call [`flush`] at the end
+    /// of the signal to get the rest.
+    ///
+    /// [`latency`]: TimeStretcher::latency
+    /// [`flush`]: TimeStretcher::flush
+    pub fn process(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        let channels = self.channels;
+        let frames = input.len() / channels;
+        let input = &input[..frames * channels];
+        self.expected += frames as f64 / f64::from(self.speed);
+        self.input.extend_from_slice(input);
+        let scale = 1.0 / channels as f32;
+        self.mono.extend(
+            input
+                .chunks_exact(channels)
+                .map(|f| f.iter().sum::<f32>() * scale),
+        );
+        self.stretch();
+        self.resample(output, u64::MAX);
+    }
+    /// Process the remaining buffered input, appending the output to `output`, then reset.
+    pub fn flush(&mut self, output: &mut Vec<f32>) {
+        #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let expected = self.expected.round() as u64;
+        let padding = self.latency() * self.channels;
+        self.input.resize(self.input.len() + padding, 0.0);
+        self.mono
+            .resize(self.mono.len() + padding / self.channels, 0.0);
+        self.stretch();
+        self.resample(output, expected);
+        self.reset();
+    }
+
+    /// Lay out the segments of the buffered input into `stretched`.
+    fn stretch(&mut self) {
+        let channels = self.channels;
+        loop {
+            #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let nominal = self.analysis.round() as usize;
+            let needed = (nominal + self.tolerance + self.segment)
+                .max(self.template.map_or(0, |t| t + self.segment));
+            if self.mono.len() < needed {
+                break;
+            }
+            let start = self.best_segment(nominal);
+            let samples = &self.input[start * channels..(start + self.segment) * channels];
+            for (i, frame) in samples.chunks_exact(channels).enumerate() {
+                let weight = self.window[i];
+                for (acc, &s) in self.overlap[i * channels..].iter_mut().zip(frame) {
+                    *acc += s * weight;
+                }
+            }
+            let hop = self.hop * channels;
+            self.stretched.extend_from_slice(&self.overlap[..hop]);
+            self.overlap.copy_within(hop.., 0);
+            let len = self.overlap.len();
+            self.overlap[len - hop..].fill(0.0);
+            self.template = Some(start + self.hop);
+            self.analysis += self.hop as f64 * f64::from(self.speed / self.pitch);
+            // Drop the input no future segment can start in
+            #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let next = self.analysis.floor() as usize;
+            let consumed = (start + self.hop).min(next.saturating_sub(self.tolerance));
+            let _ = self.input.drain(..consumed * channels);
+            let _ = self.mono.drain(..consumed);
+            self.template = self.template.map(|t| t - consumed);
+            self.analysis -= consumed as f64;
+        }
+    }
+    /// Start of the segment around `nominal` that best continues the last one.
+    fn best_segment(&self, nominal: usize) -> usize {
+        let Some(template) = self.template else {
+            return nominal;
+        };
+        let template = &self.mono[template..template + self.hop];
+        let mut best = (nominal, f32::MIN);
+        for start in nominal.saturating_sub(self.tolerance)..=nominal + self.tolerance {
+            let candidate = &self.mono[start..start + self.hop];
+            // Every other sample is enough to compare waveforms
+            let (dot, energy) = template
+                .iter()
+                .zip(candidate)
+                .step_by(2)
+                .fold((0.0, 0.0), |(dot, energy), (&t, &c)| {
+                    (dot + t * c, energy + c * c)
+                });
+            let score = dot / (energy + 1e-9).sqrt();
+            if score > best.1 {
+                best = (start, score);
+            }
+        }
+        best.0
+    }
+    /// Resample `stretched` by the pitch factor into `output`, up to `limit` output frames.
+    fn resample(&mut self, output: &mut Vec<f32>, limit: u64) {
+        let channels = self.channels;
+        let frames = self.stretched.len() / channels;
+        let step = f64::from(self.pitch);
+        let at = |i: usize, c: usize| self.stretched[i.min(frames - 1) * channels + c];
+        while self.produced < limit && self.cursor + 2.0 < frames as f64 {
+            #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let index = self.cursor as usize;
+            #[expect(clippy::cast_possible_truncation)]
+            let t = (self.cursor - self.cursor.floor()) as f32;
+            for c in 0..channels {
+                let p0 = at(index.saturating_sub(1), c);
+                let (p1, p2, p3) = (at(index, c), at(index + 1, c), at(index + 2, c));
+                // Catmull-Rom spline
+                let a = -0.5 * p0 + 1.5 * p1 - 1.5 * p2 + 0.5 * p3;
+                let b = p0 - 2.5 * p1 + 2.0 * p2 - 0.5 * p3;
+                let d = -0.5 * p0 + 0.5 * p2;
+                output.push(((a * t + b) * t + d) * t + p1);
+            }
+            self.cursor += step;
+            self.produced += 1;
+        }
+        // Keep one frame before the cursor for the spline
+        #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let consumed = (self.cursor as usize).saturating_sub(1).min(frames);
+        let _ = self.stretched.drain(..consumed * channels);
+        self.cursor -= consumed as f64;
+    }
+}
+
+fn clamp_factor(factor: f32) -> f32 {
+    if factor.is_nan() {
+        1.0
+    } else {
+        factor.clamp(FACTOR_RANGE.0, FACTOR_RANGE.1)
+    }
+}
+
+#[derive(Debug)]
+struct Shared {
+    speed: AtomicU32,
+    pitch: AtomicU32,
+}
+
+/// Thread safe handle to the speed and pitch factors of a [`TimeStretchedStream`], or of a
+/// sound source it's [attached](TimeStretchControl::attach) to.
+#[derive(Debug, Clone)]
+pub struct TimeStretchControl {
+    shared: Arc<Shared>,
+}
+
+impl Default for TimeStretchControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeStretchControl {
+    /// Create a control with both factors at `1.0`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                speed: AtomicU32::new(1f32.to_bits()),
+                pitch: AtomicU32::new(1f32.to_bits()),
+            }),
+        }
+    }
+    /// The speed factor.
+    #[must_use]
+    pub fn speed(&self) -> f32 {
+        f32::from_bits(self.shared.speed.load(Ordering::Relaxed))
+    }
+    /// Set the speed factor, without changing the pitch.
+    ///
+    /// `2.0` plays twice as fast, `0.5` twice as slow. The factor is clamped to `[0.1, 10]`.
+    ///
+    /// For an attached source, call [`sync_speed`] to apply the change.
+    ///
+    /// [`sync_speed`]: TimeStretchControl::sync_speed
+    pub fn set_speed(&self, speed: f32) {
+        self.shared
+            .speed
+            .store(clamp_factor(speed).to_bits(), Ordering::Relaxed);
+    }
+    /// The pitch factor.
+    #[must_use]
+    pub fn pitch(&self) -> f32 {
+        f32::from_bits(self.shared.pitch.load(Ordering::Relaxed))
+    }
+    /// Set the pitch factor, without changing the speed.
+    ///
+    /// `2.0` plays an octave higher, `0.5` an octave lower. The factor is clamped to `[0.1, 10]`.
+    pub fn set_pitch(&self, pitch: f32) {
+        self.shared
+            .pitch
+            .store(clamp_factor(pitch).to_bits(), Ordering::Relaxed);
+    }
+    /// Control the speed and pitch of a sound source played by SFML, like a [`Music`].
+    ///
+    /// The speed is applied with [`SoundSource::set_pitch`], which changes both speed and pitch,
+    /// and an effect processor shifts the pitch back to the pitch factor. This replaces the
+    /// source's effect processor, and adds a delay of about 60 milliseconds.
+    ///
+    /// Since the source can't be reached from other threads, speed changes are only applied by
+    /// [`sync_speed`]. Pitch changes are picked up by the effect processor directly.
+    ///
+    /// [`Music`]: crate::audio::Music
+    /// [`sync_speed`]: TimeStretchControl::sync_speed
+    pub fn attach<S: SoundSource + ?Sized>(&self, source: &mut S) {
+        let shared = Arc::clone(&self.shared);
+        let mut stretcher: Option<TimeStretcher> = None;
+        let mut queue = Vec::new();
+        source.set_effect_processor(Some(Box::new(
+            move |input: &[f32],
+                  output: &mut [f32],
+                  _: &mut u32,
+                  output_count: &mut u32,
+                  channels| {
+                if stretcher
+                    .as_ref()
+                    .is_none_or(|s| s.channel_count() != channels)
+                {
+                    let s = TimeStretcher::new(channels.max(1), ENGINE_RATE);
+                    // Start with enough silence to cover the latency of the stretcher
+                    queue.clear();
+                    queue.resize(s.latency() * s.channels, 0.0);
+                    stretcher = Some(s);
+                }
+                let Some(stretcher) = &mut stretcher else {
+                    return;
+                };
+                let speed = f32::from_bits(shared.speed.load(Ordering::Relaxed));
+                let pitch = f32::from_bits(shared.pitch.load(Ordering::Relaxed));
+                stretcher.set_pitch(pitch / speed);
+                stretcher.process(input, &mut queue);
+                let len = (*output_count as usize * stretcher.channels).min(output.len());
+                let available = queue.len().min(len);
+                output[..available].copy_from_slice(&queue[..available]);
+                output[available..len].fill(0.0);
+                let _ = queue.drain(..available);
+                // Don't let rounding drift build up a delay
+                let max = 2 * stretcher.latency() * stretcher.channels;
+                if queue.len() > max {
+                    let _ = queue.drain(..queue.len() - max);
+                }
+            },
+        )));
+        self.sync_speed(source);
+    }
+    /// Apply the speed factor to an [attached](TimeStretchControl::attach) source.
+    pub fn sync_speed<S: SoundSource + ?Sized>(&self, source: &mut S) {
+        source.set_pitch(self.speed());
+    }
+}
+
+/// A [`SoundStream`] playing another stream at a different speed and pitch.
+///
+/// The speed and pitch can be changed while the stream is played, through the
+/// [`TimeStretchControl`] returned by [`control`].
+///
+/// The playing offset of a [`SoundStreamPlayer`] counts the time played, seeking to an offset
+/// seeks the wrapped stream to that offset scaled by the current speed.
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::audio::{SoundStream, SoundStreamPlayer, TimeStretchedStream};
+/// # fn play<S: SoundStream>(voice_over: S) {
+/// let mut stream = TimeStretchedStream::new(voice_over);
+/// let control = stream.control();
+/// control.set_speed(1.5);
+/// let mut player = SoundStreamPlayer::new(&mut stream);
+/// player.play();
+/// // Slow motion, without the deep voice
+/// control.set_speed(0.5);
+/// # }
+/// ```
+///
+/// [`control`]: TimeStretchedStream::control
+/// [`SoundStreamPlayer`]: crate::audio::SoundStreamPlayer
+#[derive(Debug)]
+pub struct TimeStretchedStream<S> {
+    stream: S,
+    stretcher: TimeStretcher,
+    control: TimeStretchControl,
+    ditherer: Ditherer,
+    input: Vec<f32>,
+    output: Vec<f32>,
+    chunk: Vec<i16>,
+}
+
+impl<S: SoundStream> TimeStretchedStream<S> {
+    /// Wrap `stream`, playing it at its original speed and pitch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the stream reports a channel count or sample rate of zero.
+    pub fn new(stream: S) -> Self {
+        let stretcher = TimeStretcher::new(stream.channel_count(), stream.sample_rate());
+        Self {
+            stream,
+            stretcher,
+            control: TimeStretchControl::new(),
+            ditherer: Ditherer::new(),
+            input: Vec::new(),
+            output: Vec::new(),
+            chunk: Vec::new(),
+        }
+    }
+    /// A handle to change the speed and pitch while the stream plays.
+    #[must_use]
+    pub fn control(&self) -> TimeStretchControl {
+        self.control.clone()
+    }
+    /// Get a reference to the wrapped stream.
+    pub fn inner(&self) -> &S {
+        &self.stream
+    }
+    /// Get a mutable reference to the wrapped stream.
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+    /// Unwrap the wrapped stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S: SoundStream> SoundStream for TimeStretchedStream<S> {
+    fn get_data(&mut self) -> (&[i16], bool) {
+        self.try_get_data().unwrap_or((&[], false))
+    }
+    fn try_get_data(&mut self) -> Result<(&[i16], bool), StreamError> {
+        self.stretcher.set_speed(self.control.speed());
+        self.stretcher.set_pitch(self.control.pitch());
+        self.output.clear();
+        self.chunk.clear();
+        let mut keep_playing = true;
+        // The stretcher holds back up to a few segments, so keep pulling until there is output
+        while self.output.is_empty() && keep_playing {
+            let (data, keep) = self.stream.try_get_data()?;
+            self.input.clear();
+            self.input.extend(data.iter().copied().map(sample_to_f32));
+            self.stretcher.process(&self.input, &mut self.output);
+            if !keep {
+                self.stretcher.flush(&mut self.output);
+                keep_playing = false;
+            }
+        }
+        self.ditherer.convert_into(&self.output, &mut self.chunk);
+        Ok((&self.chunk, keep_playing))
+    }
+    fn seek(&mut self, offset: Time) {
+        self.stream.seek(offset * self.control.speed());
+        self.stretcher.reset();
+    }
+    fn channel_count(&self) -> u32 {
+        self.stream.channel_count()
+    }
+    fn sample_rate(&self) -> u32 {
+        self.stream.sample_rate()
+    }
+    fn get_channel_map(&self) -> Vec<SoundChannel> {
+        self.stream.get_channel_map()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+mod tests {
+    use {super::TimeStretcher, std::f64::consts::PI};
+
+    const RATE: u32 = 8_000;
+
+    fn tone(frequency: f64, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .map(|i| (0.5 * (2.0 * PI * frequency * i as f64 / f64::from(RATE)).sin()) as f32)
+            .collect()
+    }
+
+    fn stretch(speed: f32, pitch: f32, input: &[f32]) -> Vec<f32> {
+        let mut stretcher = TimeStretcher::new(1, RATE);
+        stretcher.set_speed(speed);
+        stretcher.set_pitch(pitch);
+        let mut output = Vec::new();
+        for chunk in input.chunks(500) {
+            stretcher.process(chunk, &mut output);
+        }
+        stretcher.flush(&mut output);
+        output
+    }
+
+    /// Frequency estimated from the upward zero crossings, away from the edges.
+    fn frequency(samples: &[f32]) -> f64 {
+        let middle = &samples[samples.len() / 5..samples.len() * 4 / 5];
+        let crossings = middle.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0);
+        crossings.count() as f64 * f64::from(RATE) / middle.len() as f64
+    }
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64, what: &str) {
+        assert!(
+            (actual - expected).abs() <= expected * tolerance,
+            "{what}: {actual}, expected {expected}"
+        );
+    }
+
+    #[test]
+    fn speed_keeps_pitch() {
+        let input = tone(440.0, RATE as usize * 2);
+        for speed in [0.5, 0.8, 1.0, 1.5, 2.0] {
+            let output = stretch(speed, 1.0, &input);
+            let expected = input.len() as f64 / f64::from(speed);
+            assert_close(output.len() as f64, expected, 0.01, "length");
+            assert_close(frequency(&output), 440.0, 0.02, "frequency");
+        }
+    }
+
+    #[test]
+    fn pitch_keeps_speed() {
+        let input = tone(400.0, RATE as usize * 2);
+        for (speed, pitch) in [(1.0, 1.5), (1.0, 0.75), (2.0, 0.5), (0.5, 1.25)] {
+            let output = stretch(speed, pitch, &input);
+            let expected = input.len() as f64 / f64::from(speed);
+            assert_close(output.len() as f64, expected, 0.01, "length");
+            assert_close(
+                frequency(&output),
+                400.0 * f64::from(pitch),
+                0.02,
+                "frequency",
+            );
+        }
+    }
+
+    #[test]
+    fn output_stays_smooth_and_level() {
+        let input = tone(440.0, RATE as usize);
+        let output = stretch(0.7, 1.2, &input);
+        let middle = &output[output.len() / 5..output.len() * 4 / 5];
+        let peak = middle.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!((peak - 0.5).abs() < 0.05, "peak {peak}");
+        // A clean 528 Hz tone moves by at most 2π·528/8000·0.5 ≈ 0.21 per sample
+        let jump = middle
+            .windows(2)
+            .fold(0.0f32, |m, w| m.max((w[1] - w[0]).abs()));
+        assert!(jump < 0.25, "discontinuity of {jump}");
+    }
+}
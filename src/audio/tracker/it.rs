@@ -0,0 +1,445 @@
+use {
+    super::{
+        TrackerError,
+        module::{
+            Cell, CellBudget, Envelope, Instrument, ModuleData, Note, Pattern, Quirks, Sample,
+            SampleLoop, VolCmd,
+        },
+        reader::{Reader, to_usize},
+        s3m,
+    },
+    std::{collections::HashMap, sync::Arc},
+};
+
+const MAX_CHANNELS: usize = 64;
+
+pub(super) fn detect(data: &[u8]) -> bool {
+    data.starts_with(b"IMPM")
+}
+
+pub(super) fn load(data: &[u8]) -> Result<ModuleData, TrackerError> {
+    let mut r = Reader::at(data, 4)?;
+    let title = r.string(26)?;
+    r.seek(0x20)?;
+    let order_count = usize::from(r.u16_le()?);
+    let instrument_count = usize::from(r.u16_le()?);
+    let sample_count = usize::from(r.u16_le()?);
+    let pattern_count = usize::from(r.u16_le()?);
+    let _created_with = r.u16_le()?;
+    let compatible_with = r.u16_le()?;
+    let flags = r.u16_le()?;
+    let _special = r.u16_le()?;
+    let global_volume = r.u8()?.min(128);
+    let mix_volume = r.u8()?.min(128);
+    let speed = r.u8()?;
+    let tempo = r.u8()?;
+    r.seek(0x40)?;
+    let channel_pan = r.bytes(MAX_CHANNELS)?;
+    let channel_volume = r.bytes(MAX_CHANNELS)?;
+    let orders = r.bytes(order_count)?.to_vec();
+    let mut read_offsets = |count| -> Result<Vec<usize>, TrackerError> {
+        (0..count).map(|_| Ok(to_usize(r.u32_le()?))).collect()
+    };
+    let instrument_ptrs = read_offsets(instrument_count)?;
+    let sample_ptrs = read_offsets(sample_count)?;
+    let pattern_ptrs = read_offsets(pattern_count)?;
+
+    let instruments = if flags & 4 != 0 {
+        instrument_ptrs
+            .into_iter()
+            .map(|ptr| load_instrument(data, ptr, compatible_with >= 0x200))
+            .collect::<Result<_, _>>()?
+    } else {
+        Vec::new()
+    };
+    let samples = sample_ptrs
+        .into_iter()
+        .map(|ptr| load_sample(data, ptr))
+        .collect::<Result<_, _>>()?;
+    // Patterns at the same offset are decoded once, and empty ones share their cells
+    let mut budget = CellBudget::new();
+    let mut loaded = HashMap::new();
+    let mut channel_count = 1;
+    for &ptr in &pattern_ptrs {
+        if ptr != 0 && !loaded.contains_key(&ptr) {
+            let (pattern, used) = load_pattern(data, ptr, &mut budget)?;
+            channel_count = channel_count.max(used);
+            let _ = loaded.insert(ptr, pattern);
+        }
+    }
+    // Patterns are loaded with all 64 channels, only keep the ones actually used
+    for pattern in loaded.values_mut() {
+        pattern.cells = pattern
+            .cells
+            .chunks_exact(MAX_CHANNELS)
+            .flat_map(|row| &row[..channel_count])
+            .copied()
+            .collect();
+    }
+    let empty = Arc::from([]);
+    let patterns = pattern_ptrs
+        .iter()
+        .map(|ptr| {
+            loaded
+                .get(ptr)
+                .map_or_else(|| Pattern::empty(64, &empty), Pattern::clone)
+        })
+        .collect();
+
+    let stereo = flags & 1 != 0;
+    Ok(ModuleData {
+        title,
+        quirks: Quirks::It,
+        channel_count,
+        orders,
+        restart_order: 0,
+        patterns,
+        samples,
+        instruments,
+        initial_speed: speed,
+        initial_tempo: tempo,
+        initial_global_volume: f32::from(global_volume) / 128.0,
+        global_volume_max: 128,
+        mix_volume: f32::from(mix_volume) / 128.0,
+        linear_slides: flags & 8 != 0,
+        middle_note: 60,
+        channel_pan: channel_pan[..channel_count]
+            .iter()
+            .map(|&p| match p & 0x7F {
+                p @ 0..=64 if stereo => convert_pan(p),
+                _ => 128,
+            })
+            .collect(),
+        channel_volume: channel_volume[..channel_count]
+            .iter()
+            .map(|&v| v.min(64))
+            .collect(),
+    })
+}
+
+/// Convert a `0..=64` panning to `0..=255`.
+fn convert_pan(pan: u8) -> u8 {
+    pan.min(64).saturating_mul(4)
+}
+
+fn read_envelope(r: &mut Reader, is_pan: bool) -> Result<Option<Envelope>, TrackerError> {
+    let flags = r.u8()?;
+    let count = usize::from(r.u8()?.min(25));
+    let [loop_start, loop_end, sustain_start, sustain_end] = *r.bytes(4)? else {
+        return Err(TrackerError::Truncated);
+    };
+    let mut points = Vec::with_capacity(count);
+    for i in 0..25 {
+        let value = r.i8()?;
+        let tick = r.u16_le()?;
+        if i < count {
+            let value = if is_pan {
+                value.clamp(-32, 32) + 32
+            } else {
+                value.clamp(0, 64)
+            };
+            points.push((tick, u8::try_from(value).unwrap_or(0)));
+        }
+    }
+    r.skip(1)?;
+    if flags & 1 == 0 || count == 0 {
+        return Ok(None);
+    }
+    let point = |i: u8| usize::from(i).min(count - 1);
+    Ok(Some(Envelope {
+        points,
+        sustain: (flags & 4 != 0).then(|| (point(sustain_start), point(sustain_end))),
+        looping: (flags & 2 != 0).then(|| (point(loop_start), point(loop_end))),
+    }))
+}
+
+fn load_instrument(data: &[u8], ptr: usize, new_format: bool) -> Result<Instrument, TrackerError> {
+    let mut r = Reader::at(data, ptr)?;
+    if r.bytes(4)? != b"IMPI" {
+        return Err(TrackerError::Invalid("bad instrument header"));
+    }
+    r.seek(ptr + 0x14)?;
+    let fadeout = r.u16_le()?;
+    let (global_volume, pan) = if new_format {
+        r.skip(2)?;
+        let global_volume = r.u8()?.min(128);
+        let pan = r.u8()?;
+        (
+            f32::from(global_volume) / 128.0,
+            (pan & 0x80 == 0).then(|| convert_pan(pan)),
+        )
+    } else {
+        (1.0, None)
+    };
+    r.seek(ptr + 0x20)?;
+    let name = r.string(26)?;
+    r.seek(ptr + 0x40)?;
+    let keymap = r
+        .bytes(240)?
+        .chunks_exact(2)
+        .map(|k| (k[0].min(119), u16::from(k[1])))
+        .collect();
+    let (volume_envelope, pan_envelope) = if new_format {
+        r.seek(ptr + 0x130)?;
+        (read_envelope(&mut r, false)?, read_envelope(&mut r, true)?)
+    } else {
+        (None, None)
+    };
+    Ok(Instrument {
+        name,
+        keymap,
+        volume_envelope,
+        pan_envelope,
+        fadeout: f32::from(fadeout) / if new_format { 1024.0 } else { 512.0 },
+        global_volume,
+        pan,
+    })
+}
+
+fn load_sample(data: &[u8], ptr: usize) -> Result<Sample, TrackerError> {
+    let mut r = Reader::at(data, ptr)?;
+    if r.bytes(4)? != b"IMPS" {
+        return Err(TrackerError::Invalid("bad sample header"));
+    }
+    r.seek(ptr + 0x11)?;
+    let global_volume = r.u8()?.min(64);
+    let flags = r.u8()?;
+    let volume = r.u8()?.min(64);
+    let name = r.string(26)?;
+    let convert = r.u8()?;
+    let pan = r.u8()?;
+    let len = to_usize(r.u32_le()?);
+    let loop_start = to_usize(r.u32_le()?);
+    let loop_end = to_usize(r.u32_le()?);
+    let c5_speed = r.u32_le()?;
+    let sustain_start = to_usize(r.u32_le()?);
+    let sustain_end = to_usize(r.u32_le()?);
+    let data_ptr = to_usize(r.u32_le()?);
+
+    let is_16bit = flags & 2 != 0;
+    let data = if flags & 1 == 0 {
+        Vec::new()
+    } else if flags & 8 != 0 {
+        let mut r = Reader::at(data, data_ptr.min(data.len()))?;
+        // Bit 2 of the conversion flags marks IT 2.15 compression
+        decompress(&mut r, len, is_16bit, convert & 4 != 0)
+    } else {
+        let mut r = Reader::at(data, data_ptr.min(data.len()))?;
+        s3m::read_pcm(&mut r, len, is_16bit, flags & 4 != 0, convert & 1 == 0)
+    };
+    Ok(Sample {
+        name,
+        sample_loop: (flags & 0x10 != 0)
+            .then(|| SampleLoop::new(loop_start, loop_end, flags & 0x40 != 0, data.len()))
+            .flatten(),
+        sustain_loop: (flags & 0x20 != 0)
+            .then(|| SampleLoop::new(sustain_start, sustain_end, flags & 0x80 != 0, data.len()))
+            .flatten(),
+        data,
+        volume,
+        global_volume: f32::from(global_volume) / 64.0,
+        pan: (pan & 0x80 != 0).then(|| convert_pan(pan & 0x7F)),
+        base_rate: if c5_speed == 0 {
+            8363.0
+        } else {
+            f64::from(c5_speed)
+        },
+    })
+}
+
+/// Reads bits least significant first out of a compressed block.
+struct BitReader<'a> {
+    data: &'a [u8],
+    bit: usize,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, width: u32) -> u32 {
+        let mut value = 0;
+        for i in 0..width {
+            let byte = self.data.get(self.bit / 8).copied().unwrap_or(0);
+            value |= u32::from((byte >> (self.bit % 8)) & 1) << i;
+            self.bit += 1;
+        }
+        value
+    }
+}
+
+/// Decompress IT 2.14 / 2.15 compressed sample data.
+///
+/// The length comes from the sample header, so the output only grows as blocks are decoded, and
+/// ends early at the first empty or corrupt block.
+fn decompress(r: &mut Reader, len: usize, is_16bit: bool, it215: bool) -> Vec<f32> {
+    let (block_len, full_width, scale) = if is_16bit {
+        (0x4000, 17, 32768.0)
+    } else {
+        (0x8000, 9, 128.0)
+    };
+    let mut out = Vec::new();
+    while out.len() < len {
+        let Ok(packed_len) = r.u16_le() else {
+            break;
+        };
+        let mut bits = BitReader {
+            data: r.bytes_lossy(usize::from(packed_len)),
+            bit: 0,
+        };
+        let count = block_len.min(len - out.len());
+        let mut width: u32 = full_width;
+        let (mut d1, mut d2) = (0i32, 0i32);
+        let mut done = 0;
+        while done < count {
+            if bits.bit >= bits.data.len() * 8 {
+                break;
+            }
+            let value = bits.read(width);
+            if width < 7 {
+                if value == 1 << (width - 1) {
+                    let new = bits.read(if is_16bit { 4 } else { 3 }) + 1;
+                    width = if new < width { new } else { new + 1 };
+                    continue;
+                }
+            } else if width < full_width {
+                let border = (((1u32 << (full_width - 1)) - 1) >> (full_width - width))
+                    - if is_16bit { 8 } else { 4 };
+                let range = if is_16bit { 16 } else { 8 };
+                if value > border && value <= border + range {
+                    let new = value - border;
+                    width = if new < width { new } else { new + 1 };
+                    continue;
+                }
+            } else if value & (1 << (full_width - 1)) != 0 {
+                width = (value + 1) & 0xFF;
+                if width == 0 || width > full_width {
+                    break;
+                }
+                continue;
+            }
+            // Sign extend the value from `width` bits
+            let shift = 32 - width.min(full_width - 1);
+            #[expect(clippy::cast_possible_wrap)]
+            let delta = (value << shift) as i32 >> shift;
+            d1 = d1.wrapping_add(delta);
+            d2 = d2.wrapping_add(d1);
+            let sample = if it215 { d2 } else { d1 };
+            // Values wrap around at the sample width, like the original 8/16 bits arithmetic
+            let sample = (sample << (33 - full_width)) >> (33 - full_width);
+            out.push(sample as f32 / scale);
+            done += 1;
+        }
+        // A block cut short by corrupt data ends the sample
+        if done < count {
+            break;
+        }
+    }
+    out
+}
+
+/// Load the pattern at `ptr` with all 64 channels, returning it with the number of channels it
+/// uses.
+fn load_pattern(
+    data: &[u8],
+    ptr: usize,
+    budget: &mut CellBudget,
+) -> Result<(Pattern, usize), TrackerError> {
+    let mut r = Reader::at(data, ptr)?;
+    let packed_len = usize::from(r.u16_le()?);
+    let rows = usize::from(r.u16_le()?).clamp(1, 200);
+    r.skip(4)?;
+    let mut p = Reader::new(r.bytes_lossy(packed_len));
+    budget.take(rows * MAX_CHANNELS)?;
+    let mut cells = vec![Cell::default(); rows * MAX_CHANNELS];
+    let mut last_mask = [0u8; MAX_CHANNELS];
+    let mut last = [Cell::default(); MAX_CHANNELS];
+    let mut used = 1;
+    let mut row = 0;
+    while row < rows {
+        let Ok(what) = p.u8() else {
+            break;
+        };
+        if what == 0 {
+            row += 1;
+            continue;
+        }
+        let channel = usize::from((what - 1) & 0x3F);
+        if what & 0x80 != 0 {
+            last_mask[channel] = p.u8()?;
+        }
+        let mask = last_mask[channel];
+        let mut cell = Cell::default();
+        if mask & 1 != 0 {
+            last[channel].note = match p.u8()? {
+                n @ 0..=119 => Note::On(n),
+                255 => Note::Off,
+                254 => Note::Cut,
+                _ => Note::Fade,
+            };
+        }
+        if mask & 2 != 0 {
+            last[channel].instrument = p.u8()?;
+        }
+        if mask & 4 != 0 {
+            last[channel].volume = convert_volume(p.u8()?);
+        }
+        if mask & 8 != 0 {
+            let effect = p.u8()?;
+            let param = p.u8()?;
+            (last[channel].fx, last[channel].param) =
+                s3m::convert_effect(effect, param, Quirks::It);
+        }
+        if mask & 0x11 != 0 {
+            cell.note = last[channel].note;
+        }
+        if mask & 0x22 != 0 {
+            cell.instrument = last[channel].instrument;
+        }
+        if mask & 0x44 != 0 {
+            cell.volume = last[channel].volume;
+        }
+        if mask & 0x88 != 0 {
+            cell.fx = last[channel].fx;
+            cell.param = last[channel].param;
+        }
+        if cell != Cell::default() {
+            used = used.max(channel + 1);
+        }
+        cells[row * MAX_CHANNELS + channel] = cell;
+    }
+    Ok((
+        Pattern {
+            rows,
+            cells: cells.into(),
+        },
+        used,
+    ))
+}
+
+fn convert_volume(v: u8) -> VolCmd {
+    const PORTA: [u8; 10] = [0, 1, 4, 8, 16, 32, 64, 96, 128, 255];
+    match v {
+        0..=64 => VolCmd::SetVolume(v),
+        65..=74 => VolCmd::FineUp(v - 65),
+        75..=84 => VolCmd::FineDown(v - 75),
+        85..=94 => VolCmd::SlideUp(v - 85),
+        95..=104 => VolCmd::SlideDown(v - 95),
+        105..=114 => VolCmd::PortaDown((v - 105) * 4),
+        115..=124 => VolCmd::PortaUp((v - 115) * 4),
+        128..=192 => VolCmd::SetPan(convert_pan(v - 128)),
+        193..=202 => VolCmd::TonePorta(PORTA[usize::from(v - 193)]),
+        203..=212 => VolCmd::VibratoDepth(v - 203),
+        _ => VolCmd::None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decompress_stops_at_the_data() {
+        // A huge header length with no data doesn't allocate or pad
+        assert!(decompress(&mut Reader::new(&[]), usize::MAX, true, false).is_empty());
+        // An empty block ends the sample
+        assert!(decompress(&mut Reader::new(&[0, 0, 4, 0]), 1 << 30, false, false).is_empty());
+    }
+}
@@ -0,0 +1,565 @@
+//! Pure Rust playback of tracker modules (MOD, S3M, XM and IT).
+//!
+//! A [`TrackerModule`] is loaded once from memory or from a file, then played by any number of
+//! [`TrackerStream`]s, which implement [`SoundStream`] and can be handed to a
+//! [`SoundStreamPlayer`].
+//!
+//! # Usage example
+//!
+//! ```no_run
+//! use sfml::audio::{SoundStreamPlayer, TrackerModule, TrackerStream};
+//!
+//! let module = TrackerModule::from_file("song.xm").unwrap();
+//! let mut stream = TrackerStream::new(&module, 44_100);
+//! stream.set_looping(true);
+//! let control = stream.control();
+//! let mut player = SoundStreamPlayer::new(&mut stream);
+//! player.play();
+//! // Mute the drums while the song plays
+//! control.set_channel_muted(3, true);
+//! ```
+//!
+//! [`SoundStream`]: crate::audio::SoundStream
+//! [`SoundStreamPlayer`]: crate::audio::SoundStreamPlayer
+
+use {
+    module::ModuleData,
+    std::{error::Error, fmt, path::Path, sync::Arc},
+};
+
+pub use self::stream::{TrackerControl, TrackerPosition, TrackerStream};
+
+mod it;
+mod module;
+mod player;
+mod protracker;
+mod reader;
+mod s3m;
+mod stream;
+mod xm;
+
+/// Error that can happen when loading a tracker module
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackerError {
+    /// The data isn't a MOD, S3M, XM or IT module
+    UnknownFormat,
+    /// The data ended before the module was complete
+    Truncated,
+    /// The module contains an invalid value
+    Invalid(&'static str),
+    /// The file couldn't be read
+    Io(std::io::ErrorKind),
+}
+
+impl Error for TrackerError {}
+
+impl fmt::Display for TrackerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownFormat => write!(f, "Unknown tracker module format"),
+            Self::Truncated => write!(f, "Tracker module data is truncated"),
+            Self::Invalid(what) => write!(f, "Invalid tracker module: {what}"),
+            Self::Io(kind) => write!(f, "Failed to read tracker module: {kind}"),
+        }
+    }
+}
+
+/// The file format a [`TrackerModule`] was loaded from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleFormat {
+    /// Amiga module, with 4 to 32 channels
+    Mod,
+    /// Scream Tracker 3 module
+    S3m,
+    /// Fast Tracker 2 extended module
+    Xm,
+    /// Impulse Tracker module
+    It,
+}
+
+/// A loaded tracker module, ready to be played by a [`TrackerStream`].
+///
+/// Cloning a `TrackerModule` is cheap, the module data is shared.
+#[derive(Debug, Clone)]
+pub struct TrackerModule {
+    data: Arc<ModuleData>,
+    format: ModuleFormat,
+}
+
+impl TrackerModule {
+    /// Load a module from memory, detecting its format from its contents.
+    pub fn from_memory(data: &[u8]) -> Result<Self, TrackerError> {
+        let (format, data) = if xm::detect(data) {
+            (ModuleFormat::Xm, xm::load(data)?)
+        } else if it::detect(data) {
+            (ModuleFormat::It, it::load(data)?)
+        } else if s3m::detect(data) {
+            (ModuleFormat::S3m, s3m::load(data)?)
+        } else if protracker::detect(data) {
+            (ModuleFormat::Mod, protracker::load(data)?)
+        } else {
+            return Err(TrackerError::UnknownFormat);
+        };
+        if data.channel_count == 0 || data.orders.is_empty() {
+            return Err(TrackerError::Invalid("module has no channels or orders"));
+        }
+        Ok(Self {
+            data: Arc::new(data),
+            format,
+        })
+    }
+    /// Load a module from a file, detecting its format from its contents.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, TrackerError> {
+        let data = std::fs::read(path).map_err(|e| TrackerError::Io(e.kind()))?;
+        Self::from_memory(&data)
+    }
+    /// The title stored in the module.
+    #[must_use]
+    pub fn title(&self) -> &str {
+        &self.data.title
+    }
+    /// The format the module was loaded from.
+    #[must_use]
+    pub fn format(&self) -> ModuleFormat {
+        self.format
+    }
+    /// The number of channels of the module.
+    #[must_use]
+    pub fn channel_count(&self) -> usize {
+        self.data.channel_count
+    }
+    /// The number of entries in the order list.
+    #[must_use]
+    pub fn order_count(&self) -> usize {
+        self.data.orders.len()
+    }
+    /// The number of patterns in the module.
+    #[must_use]
+    pub fn pattern_count(&self) -> usize {
+        self.data.patterns.len()
+    }
+    /// The number of rows of pattern `pattern`, if it exists.
+    #[must_use]
+    pub fn pattern_rows(&self, pattern: usize) -> Option<usize> {
+        self.data.patterns.get(pattern).map(|p| p.rows)
+    }
+    /// The pattern played at order `order`, if it's a playable entry.
+    #[must_use]
+    pub fn order_pattern(&self, order: usize) -> Option<usize> {
+        self.data
+            .orders
+            .get(order)
+            .map(|&p| usize::from(p))
+            .filter(|&p| p < self.data.patterns.len())
+    }
+    /// The names of the samples of the module.
+    ///
+    /// Trackers traditionally use these as a free form message area.
+    pub fn sample_names(&self) -> impl Iterator<Item = &str> {
+        self.data.samples.iter().map(|s| s.name.as_str())
+    }
+    /// The names of the instruments of the module, empty for MOD and S3M.
+    pub fn instrument_names(&self) -> impl Iterator<Item = &str> {
+        self.data.instruments.iter().map(|i| i.name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        module::{Cell, Fx, Note, VolCmd},
+    };
+
+    /// A 4 channel, 2 pattern MOD playing a looped square wave on channel 0.
+    fn square_mod() -> Vec<u8> {
+        let mut data = b"square test".to_vec();
+        data.resize(20, 0);
+        for i in 0..31 {
+            let mut header = [0u8; 30];
+            if i == 0 {
+                header[..6].copy_from_slice(b"square");
+                // 32 words long, full volume, looped entirely
+                header[22..24].copy_from_slice(&32u16.to_be_bytes());
+                header[25] = 64;
+                header[28..30].copy_from_slice(&32u16.to_be_bytes());
+            }
+            data.extend_from_slice(&header);
+        }
+        data.push(2);
+        data.push(127);
+        let mut orders = [0u8; 128];
+        orders[1] = 1;
+        data.extend_from_slice(&orders);
+        data.extend_from_slice(b"M.K.");
+        for _ in 0..2 {
+            let mut pattern = vec![0u8; 64 * 4 * 4];
+            // C-2 (period 428) with sample 1
+            pattern[..4].copy_from_slice(&[0x01, 0xAC, 0x10, 0x00]);
+            data.extend_from_slice(&pattern);
+        }
+        data.extend((0..64).map(|i| if i < 32 { 0x60 } else { 0xA0 }));
+        data
+    }
+
+    fn load() -> TrackerModule {
+        TrackerModule::from_memory(&square_mod()).expect("valid module")
+    }
+
+    const SONG_FRAMES: usize = 2 * 64 * 6 * 882;
+
+    #[test]
+    fn load_mod() {
+        let module = load();
+        assert_eq!(module.format(), ModuleFormat::Mod);
+        assert_eq!(module.title(), "square test");
+        assert_eq!(module.channel_count(), 4);
+        assert_eq!(module.order_count(), 2);
+        assert_eq!(module.pattern_count(), 2);
+        assert_eq!(module.order_pattern(1), Some(1));
+        assert_eq!(module.sample_names().next(), Some("square"));
+        assert_eq!(
+            TrackerModule::from_memory(b"not a module").err(),
+            Some(TrackerError::UnknownFormat)
+        );
+    }
+
+    #[test]
+    fn playback_and_position() {
+        let module = load();
+        let mut stream = TrackerStream::new(&module, 44_100);
+        let chunk = stream.render();
+        assert!(chunk.iter().any(|s| s.abs() > 0.1));
+        // Channel 0 is panned left
+        let (left, right) = chunk.chunks_exact(2).fold((0.0f32, 0.0f32), |(l, r), f| {
+            (l.max(f[0].abs()), r.max(f[1].abs()))
+        });
+        assert!(left > right);
+        for _ in 0..10 {
+            let _ = stream.render();
+        }
+        let position = stream.position();
+        assert_eq!(position.order, 0);
+        assert!(position.row > 0);
+        assert_eq!(stream.control().position(), position);
+
+        stream.seek_to(1, 10);
+        assert_eq!(
+            stream.position(),
+            TrackerPosition {
+                order: 1,
+                pattern: 1,
+                row: 10
+            }
+        );
+        let control = stream.control();
+        control.seek_to(0, 32);
+        let _ = stream.render();
+        assert_eq!(stream.position().order, 0);
+        assert!(stream.position().row >= 32);
+    }
+
+    #[test]
+    fn muting() {
+        let module = load();
+        let mut stream = TrackerStream::new(&module, 44_100);
+        stream.set_channel_muted(0, true);
+        assert!(stream.is_channel_muted(0));
+        assert!(!stream.is_channel_muted(1));
+        assert!(stream.render().iter().all(|&s| s == 0.0));
+        stream.control().set_channel_muted(0, false);
+        assert!(stream.render().iter().any(|&s| s != 0.0));
+    }
+
+    #[test]
+    fn song_end_and_looping() {
+        let module = load();
+        let mut stream = TrackerStream::new(&module, 44_100);
+        let mut frames = 0;
+        loop {
+            let chunk = stream.render().len() / 2;
+            if chunk == 0 {
+                break;
+            }
+            frames += chunk;
+        }
+        assert_eq!(frames, SONG_FRAMES);
+        assert!(stream.is_finished());
+        assert!(stream.control().is_finished());
+
+        let mut stream = TrackerStream::new(&module, 44_100);
+        stream.set_looping(true);
+        let mut frames = 0;
+        let mut wrapped = false;
+        while frames < SONG_FRAMES * 2 {
+            let chunk = stream.render();
+            assert!(!chunk.is_empty());
+            frames += chunk.len() / 2;
+            wrapped |= frames > SONG_FRAMES && stream.position().order == 0;
+        }
+        assert!(wrapped);
+        assert!(!stream.is_finished());
+    }
+
+    fn cell(note: Note, instrument: u8, volume: VolCmd, fx: Fx, param: u8) -> Cell {
+        Cell {
+            note,
+            instrument,
+            volume,
+            fx,
+            param,
+        }
+    }
+
+    /// Pad `data` with zeros up to `len` bytes.
+    fn pad(data: &mut Vec<u8>, len: usize) {
+        assert!(data.len() <= len);
+        data.resize(len, 0);
+    }
+
+    /// A 2 channel S3M with a looped square wave, whose third pattern is the first one again.
+    fn s3m() -> Vec<u8> {
+        let mut data = b"s3m test".to_vec();
+        pad(&mut data, 0x1C);
+        data.extend_from_slice(&[0x1A, 16, 0, 0]);
+        // 2 orders, 1 sample, 3 patterns, unsigned samples
+        for value in [2u16, 1, 3, 0, 0x1320, 2] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(b"SCRM");
+        // Global volume, speed, tempo, stereo, no panning table
+        data.extend_from_slice(&[64, 6, 125, 0xB0, 0, 0]);
+        pad(&mut data, 0x40);
+        let mut channels = [255u8; 32];
+        channels[..2].copy_from_slice(&[0, 8]);
+        data.extend_from_slice(&channels);
+        data.extend_from_slice(&[0, 2]);
+        // The sample at 0x70, patterns at 0xE0, 0 and 0xE0
+        for paragraph in [7u16, 0x0E, 0, 0x0E] {
+            data.extend_from_slice(&paragraph.to_le_bytes());
+        }
+        pad(&mut data, 0x70);
+        data.push(1);
+        data.extend_from_slice(&[0; 12]);
+        // Data at 0xC0, 32 frames looped entirely, full volume, looped
+        data.extend_from_slice(&[0, 0x0C, 0]);
+        for value in [32u32, 0, 32] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&[64, 0, 0, 1]);
+        data.extend_from_slice(&8363u32.to_le_bytes());
+        pad(&mut data, 0xC0);
+        data.extend((0..32).map(|i| if i < 16 { 0xE0 } else { 0x20 }));
+        // C-4 with sample 1, volume 40 and A03 on channel 0, then a note cut on channel 1
+        let mut pattern = vec![0xE0, 0x40, 1, 40, 1, 3, 0, 0x21, 254, 0, 0];
+        pattern.resize(pattern.len() + 62, 0);
+        data.extend_from_slice(&u16::try_from(pattern.len()).expect("short").to_le_bytes());
+        data.extend(pattern);
+        data
+    }
+
+    /// The header of an XM with 2 channels and the orders 1, 0.
+    fn xm_header(pattern_count: u16) -> Vec<u8> {
+        let mut data = b"Extended Module: xm test".to_vec();
+        pad(&mut data, 37);
+        data.push(0x1A);
+        pad(&mut data, 58);
+        data.extend_from_slice(&0x0104u16.to_le_bytes());
+        data.extend_from_slice(&276u32.to_le_bytes());
+        // 2 orders, restart at 0, 2 channels, no instruments, linear slides, speed, tempo
+        for value in [2u16, 0, 2, pattern_count, 0, 1, 6, 125] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        let mut orders = [0u8; 256];
+        orders[0] = 1;
+        data.extend_from_slice(&orders);
+        data
+    }
+
+    fn xm_pattern(data: &mut Vec<u8>, rows: u16, packed: &[u8]) {
+        data.extend_from_slice(&9u32.to_le_bytes());
+        data.push(0);
+        data.extend_from_slice(&rows.to_le_bytes());
+        data.extend_from_slice(&u16::try_from(packed.len()).expect("short").to_le_bytes());
+        data.extend_from_slice(packed);
+    }
+
+    fn xm() -> Vec<u8> {
+        let mut data = xm_header(2);
+        // C-4, instrument 1, volume 32 and F03, then a key off on the next row
+        let mut packed = vec![49, 1, 0x30, 0xF, 3, 0x80, 0x81, 97];
+        packed.resize(packed.len() + 5, 0x80);
+        xm_pattern(&mut data, 4, &packed);
+        xm_pattern(&mut data, 64, &[]);
+        data
+    }
+
+    /// An IT with the orders 0, 2 and the patterns at `pattern_ptrs`, followed by `tail`.
+    fn it(pattern_ptrs: &[u32], tail: &[u8]) -> Vec<u8> {
+        let mut data = b"IMPMit test".to_vec();
+        pad(&mut data, 0x20);
+        let pattern_count = u16::try_from(pattern_ptrs.len()).expect("few patterns");
+        // 2 orders, no instruments or samples, created with 2.14, stereo and linear slides
+        for value in [2u16, 0, 0, pattern_count, 0x0214, 0x0214, 1 | 8, 0] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        // Global volume, mix volume, speed, tempo
+        data.extend_from_slice(&[128, 48, 6, 125]);
+        pad(&mut data, 0x40);
+        data.extend_from_slice(&[32; 64]);
+        data.extend_from_slice(&[64; 64]);
+        data.extend_from_slice(&[0, 2]);
+        for ptr in pattern_ptrs {
+            data.extend_from_slice(&ptr.to_le_bytes());
+        }
+        data.extend_from_slice(tail);
+        data
+    }
+
+    /// A 2 row IT pattern with a note on channel 2, repeated on the second row.
+    fn it_pattern() -> Vec<u8> {
+        let packed = [0x83, 0x0F, 60, 1, 40, 1, 4, 0, 0x83, 0x10, 0];
+        let mut pattern = Vec::new();
+        pattern.extend_from_slice(&u16::try_from(packed.len()).expect("short").to_le_bytes());
+        pattern.extend_from_slice(&2u16.to_le_bytes());
+        pattern.extend_from_slice(&[0; 4]);
+        pattern.extend_from_slice(&packed);
+        pattern
+    }
+
+    #[test]
+    fn load_s3m() {
+        let module = TrackerModule::from_memory(&s3m()).expect("valid module");
+        assert_eq!(module.format(), ModuleFormat::S3m);
+        assert_eq!(module.title(), "s3m test");
+        assert_eq!(module.channel_count(), 2);
+        assert_eq!(module.order_count(), 2);
+        assert_eq!(module.order_pattern(1), Some(2));
+        assert_eq!(module.pattern_count(), 3);
+        let data = &module.data;
+        assert_eq!(data.channel_pan, [0x33, 0xCC]);
+        assert_eq!(
+            data.cell(0, 0, 0),
+            cell(Note::On(48), 1, VolCmd::SetVolume(40), Fx::SetSpeed, 3)
+        );
+        assert_eq!(data.cell(0, 1, 1).note, Note::Cut);
+        // The empty pattern has no cells, and the repeated one shares the first one's
+        assert!(data.patterns[1].cells.is_empty());
+        assert!(Arc::ptr_eq(
+            &data.patterns[0].cells,
+            &data.patterns[2].cells
+        ));
+        let mut stream = TrackerStream::new(&module, 44_100);
+        assert!(stream.render().iter().any(|s| s.abs() > 0.1));
+    }
+
+    #[test]
+    fn load_xm() {
+        let module = TrackerModule::from_memory(&xm()).expect("valid module");
+        assert_eq!(module.format(), ModuleFormat::Xm);
+        assert_eq!(module.title(), "xm test");
+        assert_eq!(module.channel_count(), 2);
+        assert_eq!(module.order_pattern(0), Some(1));
+        assert_eq!(module.order_pattern(1), Some(0));
+        assert_eq!(module.pattern_rows(0), Some(4));
+        assert_eq!(module.pattern_rows(1), Some(64));
+        let data = &module.data;
+        assert!(data.linear_slides);
+        assert_eq!(
+            data.cell(0, 0, 0),
+            cell(Note::On(48), 1, VolCmd::SetVolume(32), Fx::SetSpeed, 3)
+        );
+        assert_eq!(data.cell(0, 0, 1), Cell::default());
+        assert_eq!(data.cell(0, 1, 0).note, Note::Off);
+        assert!(data.patterns[1].cells.is_empty());
+        let mut stream = TrackerStream::new(&module, 44_100);
+        while stream.position().order == 0 {
+            assert!(!stream.render().is_empty());
+        }
+        assert_eq!(stream.position().pattern, 0);
+    }
+
+    #[test]
+    fn load_it() {
+        let ptr = u32::try_from(it(&[0; 3], &[]).len()).expect("short");
+        let module =
+            TrackerModule::from_memory(&it(&[ptr, 0, ptr], &it_pattern())).expect("valid module");
+        assert_eq!(module.format(), ModuleFormat::It);
+        assert_eq!(module.title(), "it test");
+        // Only the channels up to the last one used are kept
+        assert_eq!(module.channel_count(), 3);
+        assert_eq!(module.order_pattern(1), Some(2));
+        assert_eq!(module.pattern_rows(0), Some(2));
+        assert_eq!(module.pattern_rows(1), Some(64));
+        let data = &module.data;
+        assert_eq!(
+            data.cell(0, 0, 2),
+            cell(Note::On(60), 1, VolCmd::SetVolume(40), Fx::SetSpeed, 4)
+        );
+        assert_eq!(
+            data.cell(0, 1, 2),
+            cell(Note::On(60), 0, VolCmd::None, Fx::None, 0)
+        );
+        assert!(data.patterns[1].cells.is_empty());
+        assert!(Arc::ptr_eq(
+            &data.patterns[0].cells,
+            &data.patterns[2].cells
+        ));
+        let mut stream = TrackerStream::new(&module, 44_100);
+        while stream.position().order == 0 {
+            assert!(!stream.render().is_empty());
+        }
+        assert_eq!(stream.position().pattern, 2);
+    }
+
+    #[test]
+    fn xm_pattern_allocations_are_bounded() {
+        // 65535 empty XM patterns of 256 rows share their cells
+        let mut data = xm_header(u16::MAX);
+        for _ in 0..u16::MAX {
+            xm_pattern(&mut data, 256, &[]);
+        }
+        let module = TrackerModule::from_memory(&data).expect("valid module");
+        let patterns = &module.data.patterns;
+        assert!(
+            patterns
+                .iter()
+                .all(|p| Arc::ptr_eq(&p.cells, &patterns[0].cells))
+        );
+
+        let mut data = xm_header(1);
+        xm_pattern(&mut data, 0, &[]);
+        assert_eq!(
+            TrackerModule::from_memory(&data).err(),
+            Some(TrackerError::Invalid("pattern row count out of range"))
+        );
+        // 256 rows of 2 channels can't fit in 4 bytes
+        let mut data = xm_header(1);
+        xm_pattern(&mut data, 256, &[0x80; 4]);
+        assert_eq!(
+            TrackerModule::from_memory(&data).err(),
+            Some(TrackerError::Invalid("truncated pattern data"))
+        );
+    }
+
+    #[test]
+    fn it_pattern_allocations_are_bounded() {
+        // IT pattern pointers that are null or repeated are decoded once
+        let header = it(&vec![0; usize::from(u16::MAX)], &[]).len();
+        let ptr = u32::try_from(header).expect("short");
+        let ptrs: Vec<_> = (0..u16::MAX)
+            .map(|i| if i % 2 == 0 { 0 } else { ptr })
+            .collect();
+        let module = TrackerModule::from_memory(&it(&ptrs, &it_pattern())).expect("valid module");
+        assert_eq!(module.pattern_count(), usize::from(u16::MAX));
+        // Distinct pointers into the same bytes, each read as a 200 row pattern, go over budget
+        let header = it(&[0; 1000], &[]).len();
+        let ptrs: Vec<_> = (0..1000)
+            .map(|i| u32::try_from(header).expect("short") + i)
+            .collect();
+        assert_eq!(
+            TrackerModule::from_memory(&it(&ptrs, &[0xC8, 0].repeat(1000))).err(),
+            Some(TrackerError::Invalid("patterns too large"))
+        );
+    }
+}
@@ -0,0 +1,310 @@
+//! Format independent representation of a tracker module.
+
+use {super::TrackerError, std::sync::Arc};
+
+/// Order list entry meaning "skip to the next order" (S3M and IT).
+pub(super) const ORDER_SKIP: u8 = 254;
+/// Order list entry meaning "end of song" (S3M and IT).
+pub(super) const ORDER_END: u8 = 255;
+
+/// Which tracker's playback quirks to emulate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Quirks {
+    Mod,
+    Xm,
+    S3m,
+    It,
+}
+
+impl Quirks {
+    /// S3M and IT encode fine slides in the parameter of regular slides, and share the memory
+    /// of a lot of effects.
+    pub(super) fn is_st3_style(self) -> bool {
+        matches!(self, Self::S3m | Self::It)
+    }
+}
+
+/// The note part of a pattern cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(super) enum Note {
+    #[default]
+    None,
+    /// Play a note. `0` is C-0.
+    On(u8),
+    /// Release the note, entering the release part of the envelopes.
+    Off,
+    /// Stop the note immediately.
+    Cut,
+    /// Start fading the note out.
+    Fade,
+}
+
+/// Volume column commands (XM and IT).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(super) enum VolCmd {
+    #[default]
+    None,
+    SetVolume(u8),
+    SlideUp(u8),
+    SlideDown(u8),
+    FineUp(u8),
+    FineDown(u8),
+    SetPan(u8),
+    PanSlideLeft(u8),
+    PanSlideRight(u8),
+    VibratoSpeed(u8),
+    VibratoDepth(u8),
+    TonePorta(u8),
+    PortaUp(u8),
+    PortaDown(u8),
+}
+
+/// Effect commands, normalized over all supported formats.
+///
+/// The parameter stays in the format's own encoding; the player interprets it according to the
+/// module's [`Quirks`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(super) enum Fx {
+    #[default]
+    None,
+    Arpeggio,
+    PortaUp,
+    PortaDown,
+    FinePortaUp,
+    FinePortaDown,
+    ExtraFinePortaUp,
+    ExtraFinePortaDown,
+    TonePorta,
+    Vibrato,
+    FineVibrato,
+    TonePortaVolSlide,
+    VibratoVolSlide,
+    Tremolo,
+    Tremor,
+    SetPan,
+    SetPanNibble,
+    PanSlide,
+    SampleOffset,
+    HighOffset,
+    VolSlide,
+    FineVolUp,
+    FineVolDown,
+    SetVolume,
+    ChannelVolume,
+    ChannelVolSlide,
+    GlobalVolume,
+    GlobalVolSlide,
+    PositionJump,
+    PatternBreak,
+    PatternLoop,
+    PatternDelay,
+    FinePatternDelay,
+    SetSpeed,
+    SetTempo,
+    NoteCut,
+    NoteDelay,
+    Retrig,
+    MultiRetrig,
+    KeyOff,
+    VibratoWaveform,
+    TremoloWaveform,
+}
+
+/// One cell of a pattern: what happens on one channel, on one row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(super) struct Cell {
+    pub note: Note,
+    /// 1-based instrument (or sample) number, `0` for none.
+    pub instrument: u8,
+    pub volume: VolCmd,
+    pub fx: Fx,
+    pub param: u8,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct Pattern {
+    pub rows: usize,
+    /// `rows * channel_count` cells, row major, or none for a pattern without notes. Patterns
+    /// loaded from the same data share their cells.
+    pub cells: Arc<[Cell]>,
+}
+
+/// The most cells the patterns of a module may have together. Real modules use far fewer, this
+/// only stops malformed files from claiming all the memory.
+const MAX_CELLS: usize = 1 << 22;
+
+/// Counts the cells allocated by the patterns of a module, to fail before going over
+/// [`MAX_CELLS`].
+#[derive(Debug)]
+pub(super) struct CellBudget(usize);
+
+impl CellBudget {
+    pub(super) fn new() -> Self {
+        Self(MAX_CELLS)
+    }
+    /// Take `cells` from the budget, or fail if there aren't that many left.
+    pub(super) fn take(&mut self, cells: usize) -> Result<(), TrackerError> {
+        self.0 = self
+            .0
+            .checked_sub(cells)
+            .ok_or(TrackerError::Invalid("patterns too large"))?;
+        Ok(())
+    }
+}
+
+impl Pattern {
+    /// A pattern of `rows` rows sharing the cells of `empty`, which has none.
+    pub(super) fn empty(rows: usize, empty: &Arc<[Cell]>) -> Self {
+        Self {
+            rows,
+            cells: Arc::clone(empty),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct SampleLoop {
+    pub start: usize,
+    pub end: usize,
+    pub ping_pong: bool,
+}
+
+impl SampleLoop {
+    /// Build a loop, discarding it if it's empty or out of bounds.
+    pub(super) fn new(start: usize, end: usize, ping_pong: bool, len: usize) -> Option<Self> {
+        let end = end.min(len);
+        (start + 1 < end).then_some(Self {
+            start,
+            end,
+            ping_pong,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct Sample {
+    pub name: String,
+    /// Mono sample data, normalized to `[-1, 1]`.
+    pub data: Vec<f32>,
+    pub sample_loop: Option<SampleLoop>,
+    /// Loop active until the note is released (IT).
+    pub sustain_loop: Option<SampleLoop>,
+    /// Default note volume, `0..=64`.
+    pub volume: u8,
+    /// Sample global volume, `0..=1`.
+    pub global_volume: f32,
+    /// Default panning, `0..=255`.
+    pub pan: Option<u8>,
+    /// Playback rate of the sample at the module's middle note.
+    pub base_rate: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct Envelope {
+    /// `(tick, value)` pairs, values in `0..=64`.
+    pub points: Vec<(u16, u8)>,
+    /// Sustain range, as point indices.
+    pub sustain: Option<(usize, usize)>,
+    /// Loop range, as point indices.
+    pub looping: Option<(usize, usize)>,
+}
+
+impl Envelope {
+    /// Value of the envelope at `tick`, in `0..=1`.
+    pub(super) fn value_at(&self, tick: u16) -> f32 {
+        let Some(&(first_tick, first_value)) = self.points.first() else {
+            return 1.0;
+        };
+        if tick <= first_tick {
+            return f32::from(first_value) / 64.0;
+        }
+        for pair in self.points.windows(2) {
+            let ((t0, v0), (t1, v1)) = (pair[0], pair[1]);
+            if tick <= t1 {
+                if t1 <= t0 {
+                    return f32::from(v1) / 64.0;
+                }
+                let frac = f32::from(tick - t0) / f32::from(t1 - t0);
+                return (f32::from(v0) + (f32::from(v1) - f32::from(v0)) * frac) / 64.0;
+            }
+        }
+        self.points
+            .last()
+            .map_or(1.0, |&(_, v)| f32::from(v) / 64.0)
+    }
+    /// Advance `tick` by one, honoring the sustain range while `released` is false and the
+    /// loop range.
+    pub(super) fn advance(&self, tick: u16, released: bool) -> u16 {
+        let next = tick.saturating_add(1);
+        let range = match (self.sustain, released) {
+            (Some(sustain), false) => Some(sustain),
+            _ => self.looping,
+        };
+        if let Some((start, end)) = range {
+            let end_tick = self.points.get(end).map_or(0, |p| p.0);
+            if tick >= end_tick {
+                return self.points.get(start).map_or(0, |p| p.0);
+            }
+        }
+        let last = self.points.last().map_or(0, |p| p.0);
+        next.min(last)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Instrument {
+    pub name: String,
+    /// For each note, the note actually played and the 1-based sample number (`0` for none).
+    pub keymap: Vec<(u8, u16)>,
+    pub volume_envelope: Option<Envelope>,
+    pub pan_envelope: Option<Envelope>,
+    /// Amount subtracted from the fadeout volume every tick, the fadeout volume starting at `1`.
+    pub fadeout: f32,
+    /// Instrument global volume, `0..=1`.
+    pub global_volume: f32,
+    /// Default panning, `0..=255`.
+    pub pan: Option<u8>,
+}
+
+/// A fully loaded tracker module.
+#[derive(Debug, Clone)]
+pub(super) struct ModuleData {
+    pub title: String,
+    pub quirks: Quirks,
+    pub channel_count: usize,
+    pub orders: Vec<u8>,
+    /// Order to continue from when the end of the order list is reached.
+    pub restart_order: usize,
+    pub patterns: Vec<Pattern>,
+    pub samples: Vec<Sample>,
+    /// Empty if the module uses samples directly.
+    pub instruments: Vec<Instrument>,
+    pub initial_speed: u8,
+    pub initial_tempo: u8,
+    /// `0..=1`
+    pub initial_global_volume: f32,
+    /// Maximum value of the global volume effects.
+    pub global_volume_max: u8,
+    /// Overall amplification, `0..=1`.
+    pub mix_volume: f32,
+    pub linear_slides: bool,
+    /// Note at which samples play at their base rate.
+    pub middle_note: u8,
+    /// Initial channel panning, `0..=255`.
+    pub channel_pan: Vec<u8>,
+    /// Initial channel volume, `0..=64`.
+    pub channel_volume: Vec<u8>,
+}
+
+impl ModuleData {
+    pub(super) fn cell(&self, pattern: usize, row: usize, channel: usize) -> Cell {
+        self.patterns
+            .get(pattern)
+            .and_then(|p| p.cells.get(row * self.channel_count + channel))
+            .copied()
+            .unwrap_or_default()
+    }
+    pub(super) fn pattern_rows(&self, pattern: usize) -> usize {
+        self.patterns.get(pattern).map_or(64, |p| p.rows.max(1))
+    }
+}
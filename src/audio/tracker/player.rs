@@ -0,0 +1,1035 @@
+//! The tick based playback engine shared by all module formats.
+
+use {
+    super::module::{Cell, Fx, ModuleData, Note, ORDER_END, ORDER_SKIP, Quirks, VolCmd},
+    std::{f64::consts::PI, sync::Arc},
+};
+
+const MIN_PERIOD: f64 = 1.0;
+const MAX_PERIOD: f64 = 65535.0;
+const MAX_LINEAR_PERIOD: f64 = 10_000.0;
+
+/// A position in the song.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(super) struct SongPos {
+    pub order: usize,
+    pub row: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Channel {
+    /// 0-based index of the playing sample.
+    sample: Option<usize>,
+    /// 1-based number of the last instrument (or sample) used on this channel.
+    instrument: u8,
+    active: bool,
+    pos: f64,
+    backwards: bool,
+    period: f64,
+    target_period: f64,
+    /// Temporary period offset (vibrato), reset every tick.
+    period_delta: f64,
+    /// Temporary semitone offset (arpeggio), reset every tick.
+    arpeggio: u8,
+    volume: i16,
+    /// Temporary volume offset (tremolo, tremor), reset every tick.
+    volume_delta: i16,
+    channel_volume: i16,
+    pan: i16,
+    released: bool,
+    fading: bool,
+    fade: f32,
+    volume_env_tick: u16,
+    pan_env_tick: u16,
+
+    cell: Cell,
+    delayed: bool,
+    vibrato_pos: u8,
+    vibrato_wave: u8,
+    tremolo_pos: u8,
+    tremolo_wave: u8,
+    tremor_count: u8,
+    retrig_count: u8,
+    loop_row: usize,
+    loop_count: u8,
+
+    mem_porta_up: u8,
+    mem_porta_down: u8,
+    mem_fine_porta: u8,
+    mem_tone_porta: u8,
+    mem_vibrato: u8,
+    mem_vol_slide: u8,
+    mem_channel_vol_slide: u8,
+    mem_global_vol_slide: u8,
+    mem_pan_slide: u8,
+    mem_offset: u8,
+    mem_high_offset: u8,
+    mem_tremolo: u8,
+    mem_tremor: u8,
+    mem_arpeggio: u8,
+    mem_retrig: u8,
+
+    /// Mixing parameters, computed once per tick.
+    step: f64,
+    gain_left: f32,
+    gain_right: f32,
+}
+
+/// Plays a [`ModuleData`], producing interleaved stereo `f32` frames.
+#[derive(Debug, Clone)]
+pub(super) struct Player {
+    module: Arc<ModuleData>,
+    sample_rate: f64,
+    channels: Vec<Channel>,
+    pos: SongPos,
+    /// Pattern played at `pos`.
+    pattern: usize,
+    tick: u32,
+    speed: u32,
+    tempo: u32,
+    global_volume: f32,
+    pattern_delay: u32,
+    extra_ticks: u32,
+    /// Order to jump to at the end of the row.
+    jump: Option<usize>,
+    /// Row of the next pattern to break to at the end of the row.
+    break_row: Option<usize>,
+    /// Row of the current pattern a pattern loop jumps back to at the end of the row.
+    loop_back: Option<usize>,
+    /// Rows visited since the song started or last looped, indexed by order then row.
+    visited: Vec<Vec<bool>>,
+    /// Frames left to mix before the next tick.
+    tick_frames: usize,
+    tick_remainder: f64,
+    finished: bool,
+    /// Whether reaching the end of the song starts it again.
+    pub looping: bool,
+    /// Bit `n` mutes channel `n`.
+    pub muted: u64,
+    rng: u32,
+    gain: f32,
+}
+
+impl Player {
+    pub(super) fn new(module: Arc<ModuleData>, sample_rate: u32) -> Self {
+        let channel_scale = (module.channel_count.max(4) as f32 / 4.0).sqrt();
+        let mut player = Self {
+            sample_rate: f64::from(sample_rate.max(1)),
+            channels: Vec::new(),
+            pos: SongPos::default(),
+            pattern: 0,
+            tick: 0,
+            speed: 6,
+            tempo: 125,
+            global_volume: 1.0,
+            pattern_delay: 0,
+            extra_ticks: 0,
+            jump: None,
+            break_row: None,
+            loop_back: None,
+            visited: Vec::new(),
+            tick_frames: 0,
+            tick_remainder: 0.0,
+            finished: false,
+            looping: false,
+            muted: 0,
+            rng: 0x1234_5678,
+            gain: module.mix_volume / channel_scale,
+            module,
+        };
+        player.restart();
+        player
+    }
+    /// Reset the whole playback state and start from the first order.
+    pub(super) fn restart(&mut self) {
+        let m = &self.module;
+        self.channels = (0..m.channel_count)
+            .map(|c| Channel {
+                pan: i16::from(m.channel_pan.get(c).copied().unwrap_or(128)),
+                channel_volume: i16::from(m.channel_volume.get(c).copied().unwrap_or(64)),
+                fade: 1.0,
+                ..Default::default()
+            })
+            .collect();
+        self.speed = u32::from(m.initial_speed.max(1));
+        self.tempo = u32::from(m.initial_tempo.max(32));
+        self.global_volume = m.initial_global_volume;
+        self.jump_to(SongPos::default());
+    }
+    /// Continue playback from `pos`, keeping the playing notes.
+    pub(super) fn jump_to(&mut self, pos: SongPos) {
+        self.visited = self.module.orders.iter().map(|_| Vec::new()).collect();
+        self.tick = 0;
+        self.tick_frames = 0;
+        self.pattern_delay = 0;
+        self.extra_ticks = 0;
+        self.jump = None;
+        self.break_row = None;
+        self.loop_back = None;
+        self.finished = false;
+        for channel in &mut self.channels {
+            channel.loop_count = 0;
+            channel.loop_row = 0;
+        }
+        match self.normalize(pos) {
+            Some(pos) => self.enter(pos),
+            None => self.finished = true,
+        }
+    }
+    pub(super) fn position(&self) -> SongPos {
+        self.pos
+    }
+    pub(super) fn pattern(&self) -> usize {
+        self.pattern
+    }
+    pub(super) fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Resolve skip markers, returning `None` if `pos` is past the end of the song.
+    fn normalize(&self, mut pos: SongPos) -> Option<SongPos> {
+        let orders = &self.module.orders;
+        loop {
+            match orders.get(pos.order).copied() {
+                None | Some(ORDER_END) => return None,
+                Some(ORDER_SKIP) => {
+                    pos.order += 1;
+                    pos.row = 0;
+                }
+                Some(p) if usize::from(p) >= self.module.patterns.len() => return None,
+                Some(p) => {
+                    let rows = self.module.pattern_rows(usize::from(p));
+                    if pos.row >= rows {
+                        pos.row = 0;
+                    }
+                    return Some(pos);
+                }
+            }
+        }
+    }
+    fn enter(&mut self, pos: SongPos) {
+        self.pos = pos;
+        self.pattern = usize::from(self.module.orders[pos.order]);
+        let rows = self.module.pattern_rows(self.pattern);
+        let visited = &mut self.visited[pos.order];
+        if visited.len() < rows {
+            visited.resize(rows, false);
+        }
+        visited[pos.row] = true;
+    }
+    fn has_visited(&self, pos: SongPos) -> bool {
+        self.visited
+            .get(pos.order)
+            .and_then(|rows| rows.get(pos.row))
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Move to the next row, detecting the end of the song.
+    fn advance_row(&mut self) {
+        let mut wrapped = false;
+        let pos = self.pos;
+        let loop_back = self.loop_back.take();
+        let (jump, break_row) = (self.jump.take(), self.break_row.take());
+        let next = if let Some(row) = loop_back {
+            SongPos {
+                order: pos.order,
+                row,
+            }
+        } else if jump.is_some() || break_row.is_some() {
+            SongPos {
+                order: jump.unwrap_or(pos.order + 1),
+                row: break_row.unwrap_or(0),
+            }
+        } else if pos.row + 1 >= self.module.pattern_rows(self.pattern) {
+            SongPos {
+                order: pos.order + 1,
+                row: 0,
+            }
+        } else {
+            SongPos {
+                order: pos.order,
+                row: pos.row + 1,
+            }
+        };
+        let next = match self.normalize(next) {
+            Some(next) => next,
+            None => {
+                wrapped = true;
+                self.normalize(SongPos {
+                    order: self.module.restart_order,
+                    row: 0,
+                })
+                .unwrap_or_default()
+            }
+        };
+        let in_pattern_loop = loop_back.is_some() || self.channels.iter().any(|c| c.loop_count > 0);
+        if wrapped || (!in_pattern_loop && self.has_visited(next)) {
+            if !self.looping {
+                self.finished = true;
+                return;
+            }
+            // Looping is seamless: only forget where we've been, the channels keep playing
+            for rows in &mut self.visited {
+                rows.clear();
+            }
+        }
+        self.enter(next);
+    }
+
+    /// Render interleaved stereo frames into `out`, returning the number of frames written.
+    ///
+    /// Fewer frames than requested are written only when the song ended.
+    pub(super) fn render(&mut self, out: &mut [f32]) -> usize {
+        out.fill(0.0);
+        let frames = out.len() / 2;
+        let mut written = 0;
+        while written < frames {
+            if self.tick_frames == 0 {
+                if self.finished {
+                    break;
+                }
+                self.process_tick();
+                if self.finished {
+                    break;
+                }
+            }
+            let n = self.tick_frames.min(frames - written);
+            self.mix(&mut out[written * 2..(written + n) * 2]);
+            self.tick_frames -= n;
+            written += n;
+        }
+        written
+    }
+    /// Advance playback by `frames` frames without producing any sound.
+    pub(super) fn skip(&mut self, frames: u64) {
+        let mut left = frames;
+        while left > 0 {
+            if self.tick_frames == 0 {
+                if self.finished {
+                    return;
+                }
+                self.process_tick();
+                if self.finished {
+                    return;
+                }
+            }
+            let n = self
+                .tick_frames
+                .min(usize::try_from(left).unwrap_or(usize::MAX));
+            for c in 0..self.channels.len() {
+                for _ in 0..n {
+                    if !self.advance_sample(c) {
+                        break;
+                    }
+                }
+            }
+            self.tick_frames -= n;
+            left -= n as u64;
+        }
+    }
+
+    fn process_tick(&mut self) {
+        let row_ticks = self.speed * (1 + self.pattern_delay) + self.extra_ticks;
+        if self.tick >= row_ticks {
+            self.tick = 0;
+            self.pattern_delay = 0;
+            self.extra_ticks = 0;
+            self.advance_row();
+            if self.finished {
+                return;
+            }
+        }
+        if self.tick == 0 {
+            self.start_row();
+        } else {
+            for c in 0..self.channels.len() {
+                self.channel_tick(c, self.tick);
+            }
+        }
+        for c in 0..self.channels.len() {
+            self.update_mixing(c);
+        }
+        self.tick += 1;
+        let frames = self.sample_rate * 2.5 / f64::from(self.tempo) + self.tick_remainder;
+        #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let whole = frames as usize;
+        self.tick_remainder = frames - whole as f64;
+        self.tick_frames = whole.max(1);
+    }
+
+    fn start_row(&mut self) {
+        let (pattern, row) = (self.pattern, self.pos.row);
+        for c in 0..self.channels.len() {
+            let cell = self.module.cell(pattern, row, c);
+            let ch = &mut self.channels[c];
+            ch.cell = cell;
+            ch.period_delta = 0.0;
+            ch.volume_delta = 0;
+            ch.arpeggio = 0;
+            ch.delayed = cell.fx == Fx::NoteDelay && cell.param > 0;
+            if !ch.delayed {
+                self.trigger(c);
+            }
+            self.row_effects(c);
+        }
+    }
+
+    fn quirks(&self) -> Quirks {
+        self.module.quirks
+    }
+    /// Apply effect memory: a zero parameter reuses the last non zero one.
+    ///
+    /// MOD only has memory for a few effects.
+    fn remember(&self, slot: &mut u8, param: u8, always: bool) -> u8 {
+        if param != 0 {
+            *slot = param;
+        }
+        if always || self.quirks() != Quirks::Mod {
+            *slot
+        } else {
+            param
+        }
+    }
+
+    /// Period of `note` played with sample `sample`.
+    fn note_period(&self, note: u8, sample: usize) -> f64 {
+        let base_rate = self
+            .module
+            .samples
+            .get(sample)
+            .map_or(8363.0, |s| s.base_rate);
+        let semitones = f64::from(self.module.middle_note) - f64::from(note.min(119));
+        if self.module.linear_slides {
+            semitones * 64.0 - 768.0 * (base_rate / 8363.0).log2()
+        } else {
+            428.0 * (semitones / 12.0).exp2() * 8363.0 / base_rate
+        }
+    }
+    fn frequency(&self, period: f64, arpeggio: u8) -> f64 {
+        let freq = if self.module.linear_slides {
+            8363.0 * (-period / 768.0).exp2()
+        } else {
+            8363.0 * 428.0 / period.max(MIN_PERIOD)
+        };
+        freq * (f64::from(arpeggio) / 12.0).exp2()
+    }
+    /// Slide the period of channel `c`. `units` are Amiga period units, positive is up in pitch.
+    fn slide_period(&mut self, c: usize, units: f64) {
+        let linear = self.module.linear_slides;
+        let ch = &mut self.channels[c];
+        if linear {
+            ch.period = (ch.period - units * 4.0).clamp(-MAX_LINEAR_PERIOD, MAX_LINEAR_PERIOD);
+        } else {
+            ch.period = (ch.period - units).clamp(MIN_PERIOD, MAX_PERIOD);
+        }
+    }
+
+    /// Resolve the instrument and note of a cell to a sample and the note to play it at.
+    fn resolve(&self, instrument: u8, note: u8) -> Option<(u8, usize)> {
+        let index = usize::from(instrument).checked_sub(1)?;
+        if self.module.instruments.is_empty() {
+            return (index < self.module.samples.len()).then_some((note, index));
+        }
+        let inst = self.module.instruments.get(index)?;
+        let &(note, sample) = inst.keymap.get(usize::from(note))?;
+        let sample = usize::from(sample).checked_sub(1)?;
+        (sample < self.module.samples.len()).then_some((note, sample))
+    }
+    fn instrument(&self, c: usize) -> Option<&super::module::Instrument> {
+        let index = usize::from(self.channels[c].instrument).checked_sub(1)?;
+        self.module.instruments.get(index)
+    }
+
+    /// Handle the note, instrument and volume column of the current cell.
+    fn trigger(&mut self, c: usize) {
+        let cell = self.channels[c].cell;
+        let is_porta = matches!(cell.fx, Fx::TonePorta | Fx::TonePortaVolSlide)
+            || matches!(cell.volume, VolCmd::TonePorta(_));
+        if cell.instrument != 0 {
+            self.channels[c].instrument = cell.instrument;
+        }
+        let instrument = self.channels[c].instrument;
+        match cell.note {
+            Note::On(note) => {
+                if let Some((note, sample)) = self.resolve(instrument, note) {
+                    let period = self.note_period(note, sample);
+                    let ch = &mut self.channels[c];
+                    if is_porta && ch.active {
+                        ch.target_period = period;
+                    } else {
+                        ch.sample = Some(sample);
+                        ch.period = period;
+                        ch.target_period = period;
+                        ch.pos = 0.0;
+                        ch.backwards = false;
+                        ch.active = true;
+                        ch.released = false;
+                        ch.fading = false;
+                        ch.fade = 1.0;
+                        ch.volume_env_tick = 0;
+                        ch.pan_env_tick = 0;
+                        ch.retrig_count = 0;
+                        ch.tremor_count = 0;
+                        if ch.vibrato_wave & 4 == 0 {
+                            ch.vibrato_pos = 0;
+                        }
+                        if ch.tremolo_wave & 4 == 0 {
+                            ch.tremolo_pos = 0;
+                        }
+                        if cell.fx == Fx::SampleOffset {
+                            self.apply_offset(c);
+                        }
+                    }
+                } else if !is_porta {
+                    self.channels[c].active = false;
+                }
+            }
+            Note::Off => self.release(c),
+            Note::Cut => self.channels[c].active = false,
+            Note::Fade => self.channels[c].fading = true,
+            Note::None => {}
+        }
+        if cell.instrument != 0 {
+            // A lone instrument resets the volume and panning to the defaults
+            let sample = match cell.note {
+                Note::On(note) => self.resolve(instrument, note).map(|(_, s)| s),
+                _ => self.channels[c].sample,
+            };
+            let inst_pan = self.instrument(c).and_then(|i| i.pan);
+            if let Some(sample) = sample.and_then(|s| self.module.samples.get(s)) {
+                let ch = &mut self.channels[c];
+                ch.volume = i16::from(sample.volume);
+                if let Some(pan) = sample.pan.or(inst_pan) {
+                    ch.pan = i16::from(pan);
+                }
+                if self.module.quirks == Quirks::Xm {
+                    ch.released = false;
+                    ch.fading = false;
+                    ch.fade = 1.0;
+                    ch.volume_env_tick = 0;
+                    ch.pan_env_tick = 0;
+                }
+            }
+        }
+        let ch = &mut self.channels[c];
+        match cell.volume {
+            VolCmd::SetVolume(v) => ch.volume = i16::from(v.min(64)),
+            VolCmd::SetPan(p) => ch.pan = i16::from(p),
+            _ => {}
+        }
+    }
+    fn release(&mut self, c: usize) {
+        let has_envelope = self
+            .instrument(c)
+            .is_some_and(|i| i.volume_envelope.is_some());
+        let quirks = self.quirks();
+        let ch = &mut self.channels[c];
+        ch.released = true;
+        if has_envelope || quirks == Quirks::It {
+            ch.fading = true;
+        } else {
+            ch.volume = 0;
+        }
+    }
+    fn apply_offset(&mut self, c: usize) {
+        let mut slot = self.channels[c].mem_offset;
+        let param = self.remember(&mut slot, self.channels[c].cell.param, true);
+        let ch = &mut self.channels[c];
+        ch.mem_offset = slot;
+        let offset = usize::from(ch.mem_high_offset) << 16 | usize::from(param) << 8;
+        let len = ch
+            .sample
+            .and_then(|s| self.module.samples.get(s))
+            .map_or(0, |s| s.data.len());
+        if offset < len {
+            ch.pos = offset as f64;
+        } else if self.module.quirks != Quirks::It {
+            ch.active = false;
+        }
+    }
+
+    /// Signed slide amount of a `Dxy` style parameter on `tick`.
+    fn slide_amount(&self, param: u8, tick: u32) -> i16 {
+        let (x, y) = (i16::from(param >> 4), i16::from(param & 0x0F));
+        if self.quirks().is_st3_style() {
+            if y == 0xF && x != 0 {
+                return if tick == 0 { x } else { 0 };
+            }
+            if x == 0xF && y != 0 {
+                return if tick == 0 { -y } else { 0 };
+            }
+        }
+        match (tick, x) {
+            (0, _) => 0,
+            (_, 0) => -y,
+            _ => x,
+        }
+    }
+    fn volume_slide(&mut self, c: usize, param: u8, tick: u32) {
+        let mut slot = self.channels[c].mem_vol_slide;
+        let param = self.remember(&mut slot, param, false);
+        let amount = self.slide_amount(param, tick);
+        let ch = &mut self.channels[c];
+        ch.mem_vol_slide = slot;
+        ch.volume = (ch.volume + amount).clamp(0, 64);
+    }
+    fn porta(&mut self, c: usize, param: u8, tick: u32, up: bool) {
+        let mut up_slot = self.channels[c].mem_porta_up;
+        let mut down_slot = self.channels[c].mem_porta_down;
+        let param = if self.quirks().is_st3_style() {
+            // Scream Tracker shares the memory of both directions
+            let param = self.remember(&mut up_slot, param, false);
+            down_slot = up_slot;
+            param
+        } else if up {
+            self.remember(&mut up_slot, param, false)
+        } else {
+            self.remember(&mut down_slot, param, false)
+        };
+        self.channels[c].mem_porta_up = up_slot;
+        self.channels[c].mem_porta_down = down_slot;
+        let units = if self.quirks().is_st3_style() && param >= 0xE0 {
+            if tick != 0 {
+                return;
+            }
+            let fine = f64::from(param & 0x0F);
+            if param >= 0xF0 { fine } else { fine / 4.0 }
+        } else if tick == 0 {
+            return;
+        } else {
+            f64::from(param)
+        };
+        self.slide_period(c, if up { units } else { -units });
+    }
+    fn tone_porta(&mut self, c: usize, param: u8, tick: u32) {
+        let mut slot = self.channels[c].mem_tone_porta;
+        let speed = f64::from(self.remember(&mut slot, param, true));
+        let linear = self.module.linear_slides;
+        let ch = &mut self.channels[c];
+        ch.mem_tone_porta = slot;
+        if tick == 0 {
+            return;
+        }
+        let speed = if linear { speed * 4.0 } else { speed };
+        if ch.period < ch.target_period {
+            ch.period = (ch.period + speed).min(ch.target_period);
+        } else {
+            ch.period = (ch.period - speed).max(ch.target_period);
+        }
+    }
+    fn waveform(&mut self, wave: u8, pos: u8) -> i16 {
+        match wave & 3 {
+            0 => {
+                let angle = f64::from(pos) * PI / 32.0;
+                #[expect(clippy::cast_possible_truncation)]
+                let value = (angle.sin() * 255.0).round() as i16;
+                value
+            }
+            1 => 255 - i16::from(pos) * 8,
+            2 if pos < 32 => 255,
+            2 => -255,
+            _ => {
+                self.rng ^= self.rng << 13;
+                self.rng ^= self.rng >> 17;
+                self.rng ^= self.rng << 5;
+                i16::from(i8::from_le_bytes([self.rng.to_le_bytes()[0]])) * 2
+            }
+        }
+    }
+    fn vibrato(&mut self, c: usize, param: u8, tick: u32, fine: bool) {
+        let ch = &mut self.channels[c];
+        if param & 0xF0 != 0 {
+            ch.mem_vibrato = (ch.mem_vibrato & 0x0F) | (param & 0xF0);
+        }
+        if param & 0x0F != 0 {
+            ch.mem_vibrato = (ch.mem_vibrato & 0xF0) | (param & 0x0F);
+        }
+        let (speed, depth) = (ch.mem_vibrato >> 4, ch.mem_vibrato & 0x0F);
+        let (wave, pos) = (ch.vibrato_wave, ch.vibrato_pos);
+        let value = f64::from(self.waveform(wave, pos));
+        let depth = f64::from(depth) / if fine { 512.0 } else { 128.0 };
+        let scale = if self.module.linear_slides { 4.0 } else { 1.0 };
+        let ch = &mut self.channels[c];
+        ch.period_delta = value * depth * scale;
+        if tick != 0 {
+            ch.vibrato_pos = (ch.vibrato_pos + speed) & 63;
+        }
+    }
+    fn tremolo(&mut self, c: usize, param: u8, tick: u32) {
+        let mut slot = self.channels[c].mem_tremolo;
+        let param = self.remember(&mut slot, param, false);
+        let (speed, depth) = (param >> 4, param & 0x0F);
+        let (wave, pos) = (self.channels[c].tremolo_wave, self.channels[c].tremolo_pos);
+        let value = self.waveform(wave, pos);
+        let ch = &mut self.channels[c];
+        ch.mem_tremolo = slot;
+        ch.volume_delta = value * i16::from(depth) / 64;
+        if tick != 0 {
+            ch.tremolo_pos = (ch.tremolo_pos + speed) & 63;
+        }
+    }
+    fn retrigger(&mut self, c: usize) {
+        let ch = &mut self.channels[c];
+        if ch.sample.is_some() {
+            ch.pos = 0.0;
+            ch.backwards = false;
+            ch.active = true;
+        }
+    }
+
+    /// Effects that happen once per row, on its first tick.
+    fn row_effects(&mut self, c: usize) {
+        let Cell { fx, param, .. } = self.channels[c].cell;
+        let (x, y) = (param >> 4, param & 0x0F);
+        let max_global = f32::from(self.module.global_volume_max.max(1));
+        match fx {
+            Fx::SetSpeed if param > 0 => self.speed = u32::from(param),
+            Fx::SetTempo if param >= 32 => self.tempo = u32::from(param),
+            Fx::PositionJump => self.jump = Some(usize::from(param)),
+            Fx::PatternBreak => self.break_row = Some(usize::from(param)),
+            Fx::PatternLoop => {
+                let row = self.pos.row;
+                let ch = &mut self.channels[c];
+                if y == 0 {
+                    ch.loop_row = row;
+                } else {
+                    let jump = if ch.loop_count == 0 {
+                        ch.loop_count = y;
+                        true
+                    } else {
+                        ch.loop_count -= 1;
+                        ch.loop_count > 0
+                    };
+                    if jump {
+                        self.loop_back = Some(ch.loop_row);
+                    }
+                }
+            }
+            Fx::PatternDelay if self.pattern_delay == 0 => self.pattern_delay = u32::from(y),
+            Fx::FinePatternDelay => self.extra_ticks += u32::from(y),
+            Fx::GlobalVolume => {
+                self.global_volume =
+                    f32::from(param.min(self.module.global_volume_max)) / max_global;
+            }
+            Fx::SetVolume => self.channels[c].volume = i16::from(param.min(64)),
+            Fx::ChannelVolume => self.channels[c].channel_volume = i16::from(param.min(64)),
+            Fx::SetPan => self.channels[c].pan = i16::from(param),
+            Fx::SetPanNibble => self.channels[c].pan = i16::from(y * 17),
+            Fx::FinePortaUp | Fx::FinePortaDown => {
+                let mut slot = self.channels[c].mem_fine_porta;
+                let param = self.remember(&mut slot, param, false);
+                self.channels[c].mem_fine_porta = slot;
+                let units = f64::from(param);
+                self.slide_period(c, if fx == Fx::FinePortaUp { units } else { -units });
+            }
+            Fx::ExtraFinePortaUp => self.slide_period(c, f64::from(param) / 4.0),
+            Fx::ExtraFinePortaDown => self.slide_period(c, -f64::from(param) / 4.0),
+            Fx::FineVolUp | Fx::FineVolDown => {
+                let ch = &mut self.channels[c];
+                let amount = i16::from(param);
+                let amount = if fx == Fx::FineVolUp { amount } else { -amount };
+                ch.volume = (ch.volume + amount).clamp(0, 64);
+            }
+            Fx::HighOffset => self.channels[c].mem_high_offset = y,
+            Fx::VibratoWaveform => self.channels[c].vibrato_wave = y,
+            Fx::TremoloWaveform => self.channels[c].tremolo_wave = y,
+            Fx::Arpeggio if param != 0 => self.channels[c].mem_arpeggio = param,
+            Fx::KeyOff if param == 0 => self.release(c),
+            Fx::NoteCut if x == 0 && y == 0 && !self.quirks().is_st3_style() => {
+                self.channels[c].volume = 0;
+            }
+            _ => {}
+        }
+        match self.channels[c].cell.volume {
+            VolCmd::FineUp(v) => {
+                let ch = &mut self.channels[c];
+                ch.volume = (ch.volume + i16::from(v)).min(64);
+            }
+            VolCmd::FineDown(v) => {
+                let ch = &mut self.channels[c];
+                ch.volume = (ch.volume - i16::from(v)).max(0);
+            }
+            VolCmd::VibratoSpeed(v) => {
+                let ch = &mut self.channels[c];
+                ch.mem_vibrato = (ch.mem_vibrato & 0x0F) | (v << 4);
+            }
+            _ => {}
+        }
+        // Effects that also run on the first tick
+        self.channel_tick(c, 0);
+    }
+
+    /// Effects that run on every tick, `tick` being `0` on the first one.
+    fn channel_tick(&mut self, c: usize, tick: u32) {
+        let cell = self.channels[c].cell;
+        let (fx, param) = (cell.fx, cell.param);
+        let y = param & 0x0F;
+        if tick != 0 {
+            let ch = &mut self.channels[c];
+            ch.period_delta = 0.0;
+            ch.volume_delta = 0;
+            ch.arpeggio = 0;
+        }
+        if self.channels[c].delayed && tick == u32::from(param) {
+            self.channels[c].delayed = false;
+            self.trigger(c);
+        }
+        match fx {
+            Fx::Arpeggio => {
+                let param = self.channels[c].mem_arpeggio;
+                self.channels[c].arpeggio = match tick % 3 {
+                    0 => 0,
+                    1 => param >> 4,
+                    _ => param & 0x0F,
+                };
+            }
+            Fx::PortaUp => self.porta(c, param, tick, true),
+            Fx::PortaDown => self.porta(c, param, tick, false),
+            Fx::TonePorta => self.tone_porta(c, param, tick),
+            Fx::Vibrato => self.vibrato(c, param, tick, false),
+            Fx::FineVibrato => self.vibrato(c, param, tick, true),
+            Fx::TonePortaVolSlide => {
+                self.tone_porta(c, 0, tick);
+                self.volume_slide(c, param, tick);
+            }
+            Fx::VibratoVolSlide => {
+                self.vibrato(c, 0, tick, false);
+                self.volume_slide(c, param, tick);
+            }
+            Fx::Tremolo => self.tremolo(c, param, tick),
+            Fx::Tremor => {
+                let mut slot = self.channels[c].mem_tremor;
+                let param = self.remember(&mut slot, param, true);
+                let ch = &mut self.channels[c];
+                ch.mem_tremor = slot;
+                let (on, off) = ((param >> 4) + 1, (param & 0x0F) + 1);
+                if ch.tremor_count >= on {
+                    ch.volume_delta = -ch.volume;
+                }
+                ch.tremor_count = (ch.tremor_count + 1) % (on + off);
+            }
+            Fx::VolSlide => self.volume_slide(c, param, tick),
+            Fx::ChannelVolSlide => {
+                let mut slot = self.channels[c].mem_channel_vol_slide;
+                let param = self.remember(&mut slot, param, true);
+                let amount = self.slide_amount(param, tick);
+                let ch = &mut self.channels[c];
+                ch.mem_channel_vol_slide = slot;
+                ch.channel_volume = (ch.channel_volume + amount).clamp(0, 64);
+            }
+            Fx::GlobalVolSlide => {
+                let mut slot = self.channels[c].mem_global_vol_slide;
+                let param = self.remember(&mut slot, param, true);
+                self.channels[c].mem_global_vol_slide = slot;
+                let amount = f32::from(self.slide_amount(param, tick));
+                let max = f32::from(self.module.global_volume_max.max(1));
+                self.global_volume = (self.global_volume + amount / max).clamp(0.0, 1.0);
+            }
+            Fx::PanSlide if tick != 0 => {
+                let mut slot = self.channels[c].mem_pan_slide;
+                let param = self.remember(&mut slot, param, true);
+                let scale = if self.quirks().is_st3_style() { 4 } else { 1 };
+                let ch = &mut self.channels[c];
+                ch.mem_pan_slide = slot;
+                let amount = if param >> 4 != 0 {
+                    i16::from(param >> 4)
+                } else {
+                    -i16::from(param & 0x0F)
+                };
+                ch.pan = (ch.pan + amount * scale).clamp(0, 255);
+            }
+            Fx::NoteCut if tick != 0 && tick == u32::from(y) => self.channels[c].volume = 0,
+            Fx::KeyOff if tick != 0 && tick == u32::from(param) => self.release(c),
+            Fx::Retrig if y != 0 && tick != 0 && tick % u32::from(y) == 0 => self.retrigger(c),
+            Fx::MultiRetrig => {
+                let mut slot = self.channels[c].mem_retrig;
+                let param = self.remember(&mut slot, param, true);
+                self.channels[c].mem_retrig = slot;
+                let interval = (param & 0x0F).max(1);
+                let ch = &mut self.channels[c];
+                ch.retrig_count += 1;
+                if ch.retrig_count >= interval {
+                    ch.retrig_count = 0;
+                    ch.volume = match param >> 4 {
+                        0x1..=0x5 => ch.volume - (1 << ((param >> 4) - 1)),
+                        0x6 => ch.volume * 2 / 3,
+                        0x7 => ch.volume / 2,
+                        0x9..=0xD => ch.volume + (1 << ((param >> 4) - 9)),
+                        0xE => ch.volume * 3 / 2,
+                        0xF => ch.volume * 2,
+                        _ => ch.volume,
+                    }
+                    .clamp(0, 64);
+                    self.retrigger(c);
+                }
+            }
+            _ => {}
+        }
+        match cell.volume {
+            VolCmd::SlideUp(v) if tick != 0 => {
+                let ch = &mut self.channels[c];
+                ch.volume = (ch.volume + i16::from(v)).min(64);
+            }
+            VolCmd::SlideDown(v) if tick != 0 => {
+                let ch = &mut self.channels[c];
+                ch.volume = (ch.volume - i16::from(v)).max(0);
+            }
+            VolCmd::PanSlideLeft(v) if tick != 0 => {
+                let ch = &mut self.channels[c];
+                ch.pan = (ch.pan - i16::from(v)).max(0);
+            }
+            VolCmd::PanSlideRight(v) if tick != 0 => {
+                let ch = &mut self.channels[c];
+                ch.pan = (ch.pan + i16::from(v)).min(255);
+            }
+            VolCmd::VibratoDepth(v) => self.vibrato(c, v, tick, false),
+            VolCmd::TonePorta(v) => self.tone_porta(c, v, tick),
+            VolCmd::PortaUp(v) if tick != 0 => self.slide_period(c, f64::from(v)),
+            VolCmd::PortaDown(v) if tick != 0 => self.slide_period(c, -f64::from(v)),
+            _ => {}
+        }
+    }
+
+    /// Compute the step and gains used to mix channel `c` during the coming tick.
+    fn update_mixing(&mut self, c: usize) {
+        let module = Arc::clone(&self.module);
+        let ch = &self.channels[c];
+        let instrument = usize::from(ch.instrument)
+            .checked_sub(1)
+            .and_then(|i| module.instruments.get(i));
+        let Some(sample) = ch.sample.and_then(|s| module.samples.get(s)) else {
+            self.channels[c].active = false;
+            return;
+        };
+        let (volume_env, pan_env, fadeout, inst_volume) = match instrument {
+            Some(inst) => (
+                inst.volume_envelope
+                    .as_ref()
+                    .map_or(1.0, |e| e.value_at(ch.volume_env_tick)),
+                inst.pan_envelope
+                    .as_ref()
+                    .map(|e| e.value_at(ch.pan_env_tick)),
+                inst.fadeout,
+                inst.global_volume,
+            ),
+            None => (1.0, None, 0.0, 1.0),
+        };
+        let frequency = self.frequency(ch.period + ch.period_delta, ch.arpeggio);
+        let volume = f32::from((ch.volume + ch.volume_delta).clamp(0, 64)) / 64.0
+            * volume_env
+            * ch.fade
+            * f32::from(ch.channel_volume)
+            / 64.0
+            * sample.global_volume
+            * inst_volume
+            * self.global_volume
+            * self.gain;
+        let mut pan = f32::from(ch.pan) / 255.0;
+        if let Some(env) = pan_env {
+            // The envelope moves the panning away from its base, as far as the edges allow
+            pan += (env - 0.5) * 2.0 * (0.5 - (pan - 0.5).abs());
+        }
+        let angle = pan.clamp(0.0, 1.0) * std::f32::consts::FRAC_PI_2;
+        let muted = c < 64 && self.muted & (1u64 << c) != 0;
+
+        let ch = &mut self.channels[c];
+        ch.step = frequency / self.sample_rate;
+        let volume = if muted { 0.0 } else { volume };
+        ch.gain_left = volume * angle.cos();
+        ch.gain_right = volume * angle.sin();
+        if let Some(inst) = instrument {
+            if let Some(env) = &inst.volume_envelope {
+                ch.volume_env_tick = env.advance(ch.volume_env_tick, ch.released);
+            }
+            if let Some(env) = &inst.pan_envelope {
+                ch.pan_env_tick = env.advance(ch.pan_env_tick, ch.released);
+            }
+        }
+        if ch.fading {
+            ch.fade = (ch.fade - fadeout).max(0.0);
+            if ch.fade <= 0.0 {
+                ch.active = false;
+            }
+        }
+    }
+
+    /// Move the sample position of channel `c` by one frame, returning whether it still plays.
+    fn advance_sample(&mut self, c: usize) -> bool {
+        let module = &self.module;
+        let ch = &mut self.channels[c];
+        if !ch.active {
+            return false;
+        }
+        let Some(sample) = ch.sample.and_then(|s| module.samples.get(s)) else {
+            ch.active = false;
+            return false;
+        };
+        let sample_loop = match sample.sustain_loop {
+            Some(sustain) if !ch.released => Some(sustain),
+            _ => sample.sample_loop,
+        };
+        if ch.backwards {
+            ch.pos -= ch.step;
+        } else {
+            ch.pos += ch.step;
+        }
+        match sample_loop {
+            Some(lp) => {
+                let (start, end) = (lp.start as f64, lp.end as f64);
+                if lp.ping_pong {
+                    let last = end - 1.0;
+                    if !ch.backwards && ch.pos >= last {
+                        ch.pos = (2.0 * last - ch.pos).max(start);
+                        ch.backwards = true;
+                    } else if ch.backwards && ch.pos < start {
+                        ch.pos = (2.0 * start - ch.pos).min(last);
+                        ch.backwards = false;
+                    }
+                } else if ch.pos >= end {
+                    ch.pos = start + (ch.pos - end) % (end - start);
+                }
+            }
+            None => {
+                if ch.pos >= sample.data.len() as f64 {
+                    ch.active = false;
+                }
+            }
+        }
+        ch.active
+    }
+
+    fn mix(&mut self, out: &mut [f32]) {
+        for c in 0..self.channels.len() {
+            if !self.channels[c].active {
+                continue;
+            }
+            let (gain_left, gain_right) = (self.channels[c].gain_left, self.channels[c].gain_right);
+            let module = Arc::clone(&self.module);
+            let Some(sample) = self.channels[c].sample.and_then(|s| module.samples.get(s)) else {
+                continue;
+            };
+            let data = &sample.data;
+            let loop_start = sample.sample_loop.map(|l| l.start);
+            for frame in out.chunks_exact_mut(2) {
+                let pos = self.channels[c].pos;
+                #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let index = pos as usize;
+                #[expect(clippy::cast_possible_truncation)]
+                let frac = (pos - index as f64) as f32;
+                let s0 = data.get(index).copied().unwrap_or(0.0);
+                let s1 = data
+                    .get(index + 1)
+                    .or_else(|| loop_start.and_then(|s| data.get(s)))
+                    .copied()
+                    .unwrap_or(0.0);
+                let value = s0 + (s1 - s0) * frac;
+                frame[0] += value * gain_left;
+                frame[1] += value * gain_right;
+                if !self.advance_sample(c) {
+                    break;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use super::{
+    TrackerError,
+    module::{Cell, Fx, ModuleData, Note, Pattern, Quirks, Sample, SampleLoop},
+    reader::Reader,
+};
+
+const SAMPLE_COUNT: usize = 31;
+const TAG_OFFSET: usize = 1080;
+
+/// Channel count for the format tag at offset 1080, if it's a known one.
+fn channel_count(tag: &[u8]) -> Option<usize> {
+    match tag {
+        b"M.K." | b"M!K!" | b"M&K!" | b"FLT4" | b"4CHN" | b"N.T." => Some(4),
+        b"FLT8" | b"CD81" | b"OKTA" | b"OCTA" => Some(8),
+        [a, b'C', b'H', b'N'] if a.is_ascii_digit() => Some(usize::from(a - b'0')),
+        [a, b, b'C', b'H' | b'N'] if a.is_ascii_digit() && b.is_ascii_digit() => {
+            Some(usize::from(a - b'0') * 10 + usize::from(b - b'0'))
+        }
+        _ => None,
+    }
+    .filter(|&n| (1..=32).contains(&n))
+}
+
+pub(super) fn detect(data: &[u8]) -> bool {
+    data.get(TAG_OFFSET..TAG_OFFSET + 4)
+        .and_then(channel_count)
+        .is_some()
+}
+
+/// Note number of an Amiga period, `428` being the middle note `48`.
+fn period_to_note(period: u16) -> Note {
+    if period == 0 {
+        return Note::None;
+    }
+    let note = 48.0 + 12.0 * (428.0 / f64::from(period)).log2();
+    #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    Note::On(note.round().clamp(0.0, 119.0) as u8)
+}
+
+pub(super) fn load(data: &[u8]) -> Result<ModuleData, TrackerError> {
+    let channels = data
+        .get(TAG_OFFSET..TAG_OFFSET + 4)
+        .and_then(channel_count)
+        .ok_or(TrackerError::UnknownFormat)?;
+    let mut r = Reader::new(data);
+    let title = r.string(20)?;
+    let mut headers = Vec::with_capacity(SAMPLE_COUNT);
+    for _ in 0..SAMPLE_COUNT {
+        let name = r.string(22)?;
+        let len = usize::from(r.u16_be()?) * 2;
+        // Finetune is a signed nibble, in eighths of a semitone
+        let finetune = i8::from_le_bytes([(r.u8()? & 0x0F) << 4]) >> 4;
+        let volume = r.u8()?.min(64);
+        let loop_start = usize::from(r.u16_be()?) * 2;
+        let loop_len = usize::from(r.u16_be()?) * 2;
+        headers.push((name, len, finetune, volume, loop_start, loop_len));
+    }
+    let song_len = usize::from(r.u8()?).clamp(1, 128);
+    let restart = usize::from(r.u8()?);
+    let order_table = r.bytes(128)?;
+    r.skip(4)?;
+    let orders = order_table[..song_len].to_vec();
+    let pattern_count = order_table
+        .iter()
+        .map(|&p| usize::from(p) + 1)
+        .max()
+        .unwrap_or(1);
+
+    let mut patterns = Vec::with_capacity(pattern_count);
+    for _ in 0..pattern_count {
+        let raw = r.bytes(64 * channels * 4)?;
+        let cells = raw.chunks_exact(4).map(parse_cell).collect();
+        patterns.push(Pattern { rows: 64, cells });
+    }
+
+    let mut samples = Vec::with_capacity(SAMPLE_COUNT);
+    for (name, len, finetune, volume, loop_start, loop_len) in headers {
+        // Samples may be cut short in badly ripped files, keep what's there
+        let raw = r.bytes_lossy(len);
+        let data: Vec<f32> = raw
+            .iter()
+            .map(|&b| f32::from(i8::from_le_bytes([b])) / 128.0)
+            .collect();
+        let sample_loop = if loop_len > 2 {
+            SampleLoop::new(loop_start, loop_start + loop_len, false, data.len())
+        } else {
+            None
+        };
+        samples.push(Sample {
+            name,
+            data,
+            sample_loop,
+            sustain_loop: None,
+            volume,
+            global_volume: 1.0,
+            pan: None,
+            base_rate: 8363.0 * 2f64.powf(f64::from(finetune) / 96.0),
+        });
+    }
+
+    Ok(ModuleData {
+        title,
+        quirks: Quirks::Mod,
+        channel_count: channels,
+        restart_order: if restart < song_len { restart } else { 0 },
+        orders,
+        patterns,
+        samples,
+        instruments: Vec::new(),
+        initial_speed: 6,
+        initial_tempo: 125,
+        initial_global_volume: 1.0,
+        global_volume_max: 64,
+        mix_volume: 0.5,
+        linear_slides: false,
+        middle_note: 48,
+        // Amiga panning: LRRL, softened a bit for headphones
+        channel_pan: (0..channels)
+            .map(|c| if matches!(c % 4, 0 | 3) { 64 } else { 192 })
+            .collect(),
+        channel_volume: vec![64; channels],
+    })
+}
+
+fn parse_cell(raw: &[u8]) -> Cell {
+    let period = u16::from(raw[0] & 0x0F) << 8 | u16::from(raw[1]);
+    let instrument = (raw[0] & 0xF0) | (raw[2] >> 4);
+    let (fx, param) = convert_effect(raw[2] & 0x0F, raw[3]);
+    Cell {
+        note: period_to_note(period),
+        instrument,
+        volume: Default::default(),
+        fx,
+        param,
+    }
+}
+
+/// Convert a MOD effect. XM uses the same encoding for its first 16 effects.
+pub(super) fn convert_effect(effect: u8, param: u8) -> (Fx, u8) {
+    let (x, y) = (param >> 4, param & 0x0F);
+    match effect {
+        0x0 if param != 0 => (Fx::Arpeggio, param),
+        0x1 => (Fx::PortaUp, param),
+        0x2 => (Fx::PortaDown, param),
+        0x3 => (Fx::TonePorta, param),
+        0x4 => (Fx::Vibrato, param),
+        0x5 => (Fx::TonePortaVolSlide, param),
+        0x6 => (Fx::VibratoVolSlide, param),
+        0x7 => (Fx::Tremolo, param),
+        0x8 => (Fx::SetPan, param),
+        0x9 => (Fx::SampleOffset, param),
+        0xA => (Fx::VolSlide, param),
+        0xB => (Fx::PositionJump, param),
+        0xC => (Fx::SetVolume, param),
+        // Pattern break rows are stored as decimal digits
+        0xD => (Fx::PatternBreak, x * 10 + y),
+        0xE => match x {
+            0x1 => (Fx::FinePortaUp, y),
+            0x2 => (Fx::FinePortaDown, y),
+            0x4 => (Fx::VibratoWaveform, y),
+            0x6 => (Fx::PatternLoop, y),
+            0x7 => (Fx::TremoloWaveform, y),
+            0x8 => (Fx::SetPanNibble, y),
+            0x9 => (Fx::Retrig, y),
+            0xA => (Fx::FineVolUp, y),
+            0xB => (Fx::FineVolDown, y),
+            0xC => (Fx::NoteCut, y),
+            0xD => (Fx::NoteDelay, y),
+            0xE => (Fx::PatternDelay, y),
+            _ => (Fx::None, 0),
+        },
+        0xF if param < 0x20 => (Fx::SetSpeed, param),
+        0xF => (Fx::SetTempo, param),
+        _ => (Fx::None, 0),
+    }
+}
@@ -0,0 +1,86 @@
+use super::TrackerError;
+
+/// Bounds checked little/big endian reader over module data.
+pub(super) struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(super) fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+    pub(super) fn at(data: &'a [u8], pos: usize) -> Result<Self, TrackerError> {
+        if pos > data.len() {
+            return Err(TrackerError::Truncated);
+        }
+        Ok(Self { data, pos })
+    }
+    pub(super) fn pos(&self) -> usize {
+        self.pos
+    }
+    pub(super) fn seek(&mut self, pos: usize) -> Result<(), TrackerError> {
+        if pos > self.data.len() {
+            return Err(TrackerError::Truncated);
+        }
+        self.pos = pos;
+        Ok(())
+    }
+    pub(super) fn skip(&mut self, len: usize) -> Result<(), TrackerError> {
+        self.seek(self.pos.checked_add(len).ok_or(TrackerError::Truncated)?)
+    }
+    pub(super) fn bytes(&mut self, len: usize) -> Result<&'a [u8], TrackerError> {
+        let end = self.pos.checked_add(len).ok_or(TrackerError::Truncated)?;
+        let bytes = self
+            .data
+            .get(self.pos..end)
+            .ok_or(TrackerError::Truncated)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+    /// Read up to `len` bytes, stopping at the end of the data.
+    pub(super) fn bytes_lossy(&mut self, len: usize) -> &'a [u8] {
+        let end = self.pos.saturating_add(len).min(self.data.len());
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        bytes
+    }
+    pub(super) fn u8(&mut self) -> Result<u8, TrackerError> {
+        Ok(self.bytes(1)?[0])
+    }
+    pub(super) fn i8(&mut self) -> Result<i8, TrackerError> {
+        Ok(i8::from_le_bytes([self.u8()?]))
+    }
+    pub(super) fn u16_le(&mut self) -> Result<u16, TrackerError> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+    pub(super) fn u16_be(&mut self) -> Result<u16, TrackerError> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+    pub(super) fn u32_le(&mut self) -> Result<u32, TrackerError> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+    /// Read a fixed size, nul padded string.
+    pub(super) fn string(&mut self, len: usize) -> Result<String, TrackerError> {
+        Ok(decode_string(self.bytes(len)?))
+    }
+}
+
+/// Decode a nul padded string as Latin-1, trimming padding.
+pub(super) fn decode_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    bytes[..end]
+        .iter()
+        .map(|&b| char::from(b))
+        .collect::<String>()
+        .trim_end()
+        .to_owned()
+}
+
+/// Widen a `u32` read from a file to `usize`.
+pub(super) fn to_usize(value: u32) -> usize {
+    usize::try_from(value).unwrap_or(usize::MAX)
+}
@@ -0,0 +1,279 @@
+use {
+    super::{
+        TrackerError,
+        module::{
+            Cell, CellBudget, Fx, ModuleData, Note, Pattern, Quirks, Sample, SampleLoop, VolCmd,
+        },
+        reader::{Reader, to_usize},
+    },
+    std::{collections::HashMap, sync::Arc},
+};
+
+pub(super) fn detect(data: &[u8]) -> bool {
+    data.get(0x2C..0x30) == Some(b"SCRM")
+}
+
+pub(super) fn load(data: &[u8]) -> Result<ModuleData, TrackerError> {
+    let mut r = Reader::new(data);
+    let title = r.string(28)?;
+    r.seek(0x20)?;
+    let order_count = usize::from(r.u16_le()?);
+    let sample_count = usize::from(r.u16_le()?);
+    let pattern_count = usize::from(r.u16_le()?);
+    let _flags = r.u16_le()?;
+    let _version = r.u16_le()?;
+    let unsigned_samples = r.u16_le()? == 2;
+    r.seek(0x30)?;
+    let global_volume = r.u8()?.min(64);
+    let speed = r.u8()?;
+    let tempo = r.u8()?;
+    let master_volume = r.u8()?;
+    let _ultra_click = r.u8()?;
+    let has_pan_table = r.u8()? == 0xFC;
+    r.seek(0x40)?;
+    let channel_settings = r.bytes(32)?;
+    let orders = r.bytes(order_count)?.to_vec();
+    let mut sample_ptrs = Vec::with_capacity(sample_count);
+    for _ in 0..sample_count {
+        sample_ptrs.push(usize::from(r.u16_le()?) * 16);
+    }
+    let mut pattern_ptrs = Vec::with_capacity(pattern_count);
+    for _ in 0..pattern_count {
+        pattern_ptrs.push(usize::from(r.u16_le()?) * 16);
+    }
+    let pan_table = if has_pan_table {
+        Some(r.bytes(32)?)
+    } else {
+        None
+    };
+
+    // Only channels up to the last enabled one are kept, disabled channels are set to 255
+    let channel_count = channel_settings
+        .iter()
+        .rposition(|&c| c < 16)
+        .map_or(0, |c| c + 1);
+    let stereo = master_volume & 0x80 != 0;
+    let channel_pan = (0..channel_count)
+        .map(|c| match pan_table.map(|t| t[c]) {
+            Some(p) if p & 0x20 != 0 => (p & 0x0F) * 17,
+            _ if !stereo => 128,
+            _ if channel_settings[c] & 0x0F < 8 => 0x33,
+            _ => 0xCC,
+        })
+        .collect();
+
+    let mut samples = Vec::with_capacity(sample_count);
+    for ptr in sample_ptrs {
+        samples.push(load_sample(data, ptr, unsigned_samples)?);
+    }
+    // Patterns at the same offset are decoded once, and empty ones share their cells
+    let empty = Arc::from([]);
+    let mut budget = CellBudget::new();
+    let mut loaded = HashMap::new();
+    let mut patterns = Vec::with_capacity(pattern_count);
+    for ptr in pattern_ptrs {
+        let pattern = if ptr == 0 {
+            Pattern::empty(64, &empty)
+        } else if let Some(pattern) = loaded.get(&ptr) {
+            Pattern::clone(pattern)
+        } else {
+            budget.take(64 * channel_count)?;
+            let pattern = load_pattern(data, ptr, channel_count)?;
+            let _ = loaded.insert(ptr, pattern.clone());
+            pattern
+        };
+        patterns.push(pattern);
+    }
+
+    Ok(ModuleData {
+        title,
+        quirks: Quirks::S3m,
+        channel_count,
+        orders,
+        restart_order: 0,
+        patterns,
+        samples,
+        instruments: Vec::new(),
+        initial_speed: speed,
+        initial_tempo: tempo,
+        initial_global_volume: f32::from(global_volume) / 64.0,
+        global_volume_max: 64,
+        mix_volume: f32::from((master_volume & 0x7F).max(0x10)) / 128.0,
+        linear_slides: false,
+        middle_note: 48,
+        channel_pan,
+        channel_volume: vec![64; channel_count],
+    })
+}
+
+fn load_sample(data: &[u8], ptr: usize, unsigned: bool) -> Result<Sample, TrackerError> {
+    if ptr == 0 {
+        return Ok(Sample::default());
+    }
+    let mut r = Reader::at(data, ptr)?;
+    let kind = r.u8()?;
+    r.skip(12)?;
+    let hi = usize::from(r.u8()?);
+    let lo = usize::from(r.u16_le()?);
+    let data_ptr = (hi << 16 | lo) * 16;
+    let len = to_usize(r.u32_le()?);
+    let loop_start = to_usize(r.u32_le()?);
+    let loop_end = to_usize(r.u32_le()?);
+    let volume = r.u8()?.min(64);
+    r.skip(2)?;
+    let flags = r.u8()?;
+    let c2spd = r.u32_le()?;
+    r.skip(12)?;
+    let name = r.string(28)?;
+    if kind != 1 {
+        return Ok(Sample {
+            name,
+            ..Default::default()
+        });
+    }
+    let is_16bit = flags & 4 != 0;
+    let is_stereo = flags & 2 != 0;
+    let mut r = Reader::at(data, data_ptr.min(data.len()))?;
+    let data = read_pcm(&mut r, len, is_16bit, is_stereo, unsigned);
+    let sample_loop = if flags & 1 != 0 {
+        SampleLoop::new(loop_start, loop_end, false, data.len())
+    } else {
+        None
+    };
+    Ok(Sample {
+        name,
+        data,
+        sample_loop,
+        sustain_loop: None,
+        volume,
+        global_volume: 1.0,
+        pan: None,
+        base_rate: if c2spd == 0 { 8363.0 } else { f64::from(c2spd) },
+    })
+}
+
+/// Read uncompressed PCM data, mixing stereo samples (stored as two consecutive blocks) to mono.
+///
+/// Truncated data is kept up to where it ends.
+pub(super) fn read_pcm(
+    r: &mut Reader,
+    len: usize,
+    is_16bit: bool,
+    is_stereo: bool,
+    unsigned: bool,
+) -> Vec<f32> {
+    let read_block = |r: &mut Reader| -> Vec<f32> {
+        if is_16bit {
+            r.bytes_lossy(len.saturating_mul(2))
+                .chunks_exact(2)
+                .map(|b| {
+                    let v = i16::from_le_bytes([b[0], b[1]]);
+                    let v = if unsigned { v ^ i16::MIN } else { v };
+                    f32::from(v) / 32768.0
+                })
+                .collect()
+        } else {
+            r.bytes_lossy(len)
+                .iter()
+                .map(|&v| {
+                    let v = if unsigned { v ^ 0x80 } else { v };
+                    f32::from(i8::from_le_bytes([v])) / 128.0
+                })
+                .collect()
+        }
+    };
+    let mut left = read_block(r);
+    if is_stereo {
+        let right = read_block(r);
+        for (l, r) in left.iter_mut().zip(right) {
+            *l = (*l + r) * 0.5;
+        }
+    }
+    left
+}
+
+fn load_pattern(data: &[u8], ptr: usize, channels: usize) -> Result<Pattern, TrackerError> {
+    let mut cells = vec![Cell::default(); 64 * channels];
+    let mut r = Reader::at(data, ptr)?;
+    let _packed_len = r.u16_le()?;
+    let mut row = 0;
+    while row < 64 {
+        let what = r.u8()?;
+        if what == 0 {
+            row += 1;
+            continue;
+        }
+        let mut cell = Cell::default();
+        if what & 0x20 != 0 {
+            cell.note = match r.u8()? {
+                255 => Note::None,
+                254 => Note::Cut,
+                n => Note::On((n >> 4) * 12 + (n & 0x0F).min(11)),
+            };
+            cell.instrument = r.u8()?;
+        }
+        if what & 0x40 != 0 {
+            cell.volume = VolCmd::SetVolume(r.u8()?.min(64));
+        }
+        if what & 0x80 != 0 {
+            let effect = r.u8()?;
+            let param = r.u8()?;
+            (cell.fx, cell.param) = convert_effect(effect, param, Quirks::S3m);
+        }
+        let channel = usize::from(what & 0x1F);
+        if channel < channels {
+            cells[row * channels + channel] = cell;
+        }
+    }
+    Ok(Pattern {
+        rows: 64,
+        cells: cells.into(),
+    })
+}
+
+/// Convert a lettered effect (`A` being `1`), shared by S3M and IT.
+pub(super) fn convert_effect(effect: u8, param: u8, quirks: Quirks) -> (Fx, u8) {
+    let is_it = quirks == Quirks::It;
+    let (x, y) = (param >> 4, param & 0x0F);
+    match effect {
+        1 if param != 0 => (Fx::SetSpeed, param),
+        2 => (Fx::PositionJump, param),
+        3 if is_it => (Fx::PatternBreak, param),
+        3 => (Fx::PatternBreak, x * 10 + y),
+        4 => (Fx::VolSlide, param),
+        5 => (Fx::PortaDown, param),
+        6 => (Fx::PortaUp, param),
+        7 => (Fx::TonePorta, param),
+        8 => (Fx::Vibrato, param),
+        9 => (Fx::Tremor, param),
+        10 => (Fx::Arpeggio, param),
+        11 => (Fx::VibratoVolSlide, param),
+        12 => (Fx::TonePortaVolSlide, param),
+        13 => (Fx::ChannelVolume, param),
+        14 => (Fx::ChannelVolSlide, param),
+        15 => (Fx::SampleOffset, param),
+        // IT pans right with the low nibble, the player follows XM and pans right with the high one
+        16 => (Fx::PanSlide, y << 4 | x),
+        17 => (Fx::MultiRetrig, param),
+        18 => (Fx::Tremolo, param),
+        19 => match x {
+            0x3 => (Fx::VibratoWaveform, y),
+            0x4 => (Fx::TremoloWaveform, y),
+            0x6 => (Fx::FinePatternDelay, y),
+            0x8 => (Fx::SetPanNibble, y),
+            0xA if is_it => (Fx::HighOffset, y),
+            0xB => (Fx::PatternLoop, y),
+            0xC => (Fx::NoteCut, y),
+            0xD => (Fx::NoteDelay, y),
+            0xE => (Fx::PatternDelay, y),
+            _ => (Fx::None, 0),
+        },
+        20 if param >= 0x20 => (Fx::SetTempo, param),
+        21 => (Fx::FineVibrato, param),
+        22 => (Fx::GlobalVolume, param),
+        23 => (Fx::GlobalVolSlide, param),
+        24 if is_it => (Fx::SetPan, param),
+        24 => (Fx::SetPan, param.min(0x80).saturating_mul(2)),
+        _ => (Fx::None, 0),
+    }
+}
@@ -0,0 +1,283 @@
+use {
+    super::{
+        TrackerModule,
+        player::{Player, SongPos},
+    },
+    crate::{
+        audio::{SoundChannel, SoundStream, sample_conversion::Ditherer},
+        system::Time,
+    },
+    std::sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+};
+
+/// Number of frames rendered per chunk handed to SFML.
+const CHUNK_FRAMES: usize = 2048;
+const NO_SEEK: u64 = u64::MAX;
+
+/// A position in the song of a tracker module
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TrackerPosition {
+    /// Index in the order list
+    pub order: usize,
+    /// Pattern played at `order`
+    pub pattern: usize,
+    /// Row in `pattern`
+    pub row: usize,
+}
+
+impl TrackerPosition {
+    fn pack(self) -> u64 {
+        // Orders, patterns and rows all fit in 16 bits in every supported format
+        let field = |v: usize| u64::from(u16::try_from(v).unwrap_or(u16::MAX));
+        field(self.order) << 32 | field(self.pattern) << 16 | field(self.row)
+    }
+    fn unpack(packed: u64) -> Self {
+        let field = |shift: u32| usize::from(u16::try_from(packed >> shift & 0xFFFF).unwrap_or(0));
+        Self {
+            order: field(32),
+            pattern: field(16),
+            row: field(0),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Shared {
+    /// Bit `n` mutes channel `n`.
+    muted: AtomicU64,
+    looping: AtomicBool,
+    /// Packed [`TrackerPosition`] of the last rendered row.
+    position: AtomicU64,
+    /// Packed order and row to seek to, or [`NO_SEEK`].
+    seek: AtomicU64,
+    finished: AtomicBool,
+}
+
+/// Thread safe handle controlling a [`TrackerStream`] while it's being played.
+///
+/// A [`SoundStreamPlayer`] borrows its stream mutably for as long as it plays, a
+/// `TrackerControl` obtained beforehand with [`TrackerStream::control`] can still query the
+/// position, seek and mute channels in the meantime. Changes are picked up when the next chunk
+/// of audio is rendered.
+///
+/// [`SoundStreamPlayer`]: crate::audio::SoundStreamPlayer
+#[derive(Debug, Clone)]
+pub struct TrackerControl {
+    shared: Arc<Shared>,
+}
+
+impl TrackerControl {
+    /// The position of the row being rendered.
+    ///
+    /// This is ahead of what's heard by the latency of SFML's stream buffering.
+    #[must_use]
+    pub fn position(&self) -> TrackerPosition {
+        TrackerPosition::unpack(self.shared.position.load(Ordering::Relaxed))
+    }
+    /// Continue playback at row `row` of order `order`.
+    pub fn seek_to(&self, order: usize, row: usize) {
+        let field = |v: usize| u64::from(u32::try_from(v).unwrap_or(u32::MAX));
+        self.shared
+            .seek
+            .store(field(order) << 32 | field(row), Ordering::Relaxed);
+    }
+    /// Mute or unmute channel `channel`.
+    ///
+    /// Only the first 64 channels can be muted, which covers all supported formats.
+    pub fn set_channel_muted(&self, channel: usize, muted: bool) {
+        if channel >= 64 {
+            return;
+        }
+        if muted {
+            let _ = self.shared.muted.fetch_or(1 << channel, Ordering::Relaxed);
+        } else {
+            let _ = self
+                .shared
+                .muted
+                .fetch_and(!(1 << channel), Ordering::Relaxed);
+        }
+    }
+    /// Whether channel `channel` is muted.
+    #[must_use]
+    pub fn is_channel_muted(&self, channel: usize) -> bool {
+        channel < 64 && self.shared.muted.load(Ordering::Relaxed) & (1 << channel) != 0
+    }
+    /// Set whether the song starts again when it ends.
+    ///
+    /// When looping, playback continues seamlessly from the module's own loop point (its restart
+    /// position, or the target of its last backward jump).
+    pub fn set_looping(&self, looping: bool) {
+        self.shared.looping.store(looping, Ordering::Relaxed);
+    }
+    /// Whether the song starts again when it ends.
+    #[must_use]
+    pub fn is_looping(&self) -> bool {
+        self.shared.looping.load(Ordering::Relaxed)
+    }
+    /// Whether the song reached its end (only possible when not looping).
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.shared.finished.load(Ordering::Relaxed)
+    }
+}
+
+/// Plays a [`TrackerModule`] as a stereo [`SoundStream`].
+///
+/// See the [module documentation](crate::audio::tracker) for an example.
+#[derive(Debug)]
+pub struct TrackerStream {
+    player: Player,
+    module: TrackerModule,
+    shared: Arc<Shared>,
+    sample_rate: u32,
+    mix: Vec<f32>,
+    chunk: Vec<i16>,
+    ditherer: Ditherer,
+}
+
+impl TrackerStream {
+    /// Create a stream playing `module` from its start, at `sample_rate` Hz.
+    ///
+    /// The stream doesn't loop by default.
+    #[must_use]
+    pub fn new(module: &TrackerModule, sample_rate: u32) -> Self {
+        let player = Player::new(Arc::clone(&module.data), sample_rate);
+        let shared = Arc::new(Shared {
+            muted: AtomicU64::new(0),
+            looping: AtomicBool::new(false),
+            position: AtomicU64::new(0),
+            seek: AtomicU64::new(NO_SEEK),
+            finished: AtomicBool::new(false),
+        });
+        let stream = Self {
+            player,
+            module: module.clone(),
+            shared,
+            sample_rate: sample_rate.max(1),
+            mix: vec![0.0; CHUNK_FRAMES * 2],
+            chunk: Vec::with_capacity(CHUNK_FRAMES * 2),
+            ditherer: Ditherer::new(),
+        };
+        stream.publish();
+        stream
+    }
+    /// The module played by this stream.
+    #[must_use]
+    pub fn module(&self) -> &TrackerModule {
+        &self.module
+    }
+    /// A handle to control this stream from another thread while it plays.
+    #[must_use]
+    pub fn control(&self) -> TrackerControl {
+        TrackerControl {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+    /// The position of the row being rendered.
+    #[must_use]
+    pub fn position(&self) -> TrackerPosition {
+        TrackerPosition {
+            order: self.player.position().order,
+            pattern: self.player.pattern(),
+            row: self.player.position().row,
+        }
+    }
+    /// Continue playback at row `row` of order `order`.
+    ///
+    /// Notes already playing keep playing, like when a module jumps on its own.
+    /// A position past the end of the song ends it.
+    pub fn seek_to(&mut self, order: usize, row: usize) {
+        self.player.jump_to(SongPos { order, row });
+        self.publish();
+    }
+    /// Mute or unmute channel `channel`.
+    ///
+    /// Only the first 64 channels can be muted, which covers all supported formats.
+    pub fn set_channel_muted(&mut self, channel: usize, muted: bool) {
+        self.control().set_channel_muted(channel, muted);
+    }
+    /// Whether channel `channel` is muted.
+    #[must_use]
+    pub fn is_channel_muted(&self, channel: usize) -> bool {
+        self.control().is_channel_muted(channel)
+    }
+    /// Set whether the song starts again when it ends.
+    ///
+    /// When looping, playback continues seamlessly from the module's own loop point (its restart
+    /// position, or the target of its last backward jump).
+    pub fn set_looping(&mut self, looping: bool) {
+        self.shared.looping.store(looping, Ordering::Relaxed);
+    }
+    /// Whether the song starts again when it ends.
+    #[must_use]
+    pub fn is_looping(&self) -> bool {
+        self.shared.looping.load(Ordering::Relaxed)
+    }
+    /// Whether the song reached its end (only possible when not looping).
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.player.is_finished()
+    }
+    /// Render the next chunk of interleaved stereo samples, in `[-1, 1]`.
+    ///
+    /// This is what [`SoundStream::get_data`] plays, exposed for offline rendering. The returned
+    /// slice is empty once the song ended.
+    pub fn render(&mut self) -> &[f32] {
+        let frames = self.render_chunk();
+        &self.mix[..frames * 2]
+    }
+
+    fn render_chunk(&mut self) -> usize {
+        self.apply_requests();
+        let frames = self.player.render(&mut self.mix);
+        self.publish();
+        frames
+    }
+    fn apply_requests(&mut self) {
+        let seek = self.shared.seek.swap(NO_SEEK, Ordering::Relaxed);
+        if seek != NO_SEEK {
+            let order = usize::try_from(seek >> 32).unwrap_or(usize::MAX);
+            let row = usize::try_from(seek & 0xFFFF_FFFF).unwrap_or(usize::MAX);
+            self.player.jump_to(SongPos { order, row });
+        }
+        self.player.looping = self.shared.looping.load(Ordering::Relaxed);
+        self.player.muted = self.shared.muted.load(Ordering::Relaxed);
+    }
+    fn publish(&self) {
+        self.shared
+            .position
+            .store(self.position().pack(), Ordering::Relaxed);
+        self.shared
+            .finished
+            .store(self.player.is_finished(), Ordering::Relaxed);
+    }
+}
+
+impl SoundStream for TrackerStream {
+    fn get_data(&mut self) -> (&[i16], bool) {
+        let frames = self.render_chunk();
+        self.chunk.clear();
+        self.ditherer
+            .convert_into(&self.mix[..frames * 2], &mut self.chunk);
+        (&self.chunk, frames > 0)
+    }
+    fn seek(&mut self, offset: Time) {
+        self.apply_requests();
+        self.player.restart();
+        let frames = offset.as_microseconds() * i64::from(self.sample_rate) / 1_000_000;
+        self.player.skip(u64::try_from(frames).unwrap_or(0));
+        self.publish();
+    }
+    fn channel_count(&self) -> u32 {
+        2
+    }
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+    fn get_channel_map(&self) -> Vec<SoundChannel> {
+        vec![SoundChannel::FrontLeft, SoundChannel::FrontRight]
+    }
+}
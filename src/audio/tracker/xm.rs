@@ -0,0 +1,357 @@
+use {
+    super::{
+        TrackerError,
+        module::{
+            Cell, Envelope, Fx, Instrument, ModuleData, Note, Pattern, Quirks, Sample, SampleLoop,
+            VolCmd,
+        },
+        protracker,
+        reader::{Reader, to_usize},
+    },
+    std::sync::Arc,
+};
+
+const MAGIC: &[u8] = b"Extended Module: ";
+
+pub(super) fn detect(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+pub(super) fn load(data: &[u8]) -> Result<ModuleData, TrackerError> {
+    let mut r = Reader::at(data, MAGIC.len())?;
+    let title = r.string(20)?;
+    r.seek(58)?;
+    let version = r.u16_le()?;
+    if version < 0x0104 {
+        return Err(TrackerError::Invalid(
+            "XM versions older than 1.04 are not supported",
+        ));
+    }
+    let header_start = r.pos();
+    let header_size = to_usize(r.u32_le()?);
+    let song_len = usize::from(r.u16_le()?).min(256);
+    let restart = usize::from(r.u16_le()?);
+    let channel_count = usize::from(r.u16_le()?);
+    let pattern_count = usize::from(r.u16_le()?);
+    let instrument_count = usize::from(r.u16_le()?);
+    let flags = r.u16_le()?;
+    let speed = r.u16_le()?;
+    let tempo = r.u16_le()?;
+    let orders = r.bytes(256)?[..song_len].to_vec();
+    if channel_count > 64 {
+        return Err(TrackerError::Invalid("too many channels"));
+    }
+    r.seek(header_start.saturating_add(header_size))?;
+
+    let empty = Arc::from([]);
+    let mut patterns = Vec::with_capacity(pattern_count);
+    for _ in 0..pattern_count {
+        patterns.push(load_pattern(&mut r, channel_count, &empty)?);
+    }
+
+    let mut samples = Vec::new();
+    let mut instruments = Vec::with_capacity(instrument_count);
+    for _ in 0..instrument_count {
+        instruments.push(load_instrument(&mut r, &mut samples)?);
+    }
+
+    Ok(ModuleData {
+        title,
+        quirks: Quirks::Xm,
+        channel_count,
+        restart_order: if restart < song_len { restart } else { 0 },
+        orders,
+        patterns,
+        samples,
+        instruments,
+        initial_speed: u8::try_from(speed).unwrap_or(31),
+        initial_tempo: u8::try_from(tempo).unwrap_or(255),
+        initial_global_volume: 1.0,
+        global_volume_max: 64,
+        mix_volume: 0.5,
+        linear_slides: flags & 1 != 0,
+        middle_note: 48,
+        channel_pan: vec![128; channel_count],
+        channel_volume: vec![64; channel_count],
+    })
+}
+
+/// Load a pattern, sharing the cells of `empty` if it has no data.
+fn load_pattern(
+    r: &mut Reader,
+    channels: usize,
+    empty: &Arc<[Cell]>,
+) -> Result<Pattern, TrackerError> {
+    let start = r.pos();
+    let header_len = to_usize(r.u32_le()?);
+    let _packing = r.u8()?;
+    let rows = usize::from(r.u16_le()?);
+    let packed_len = usize::from(r.u16_le()?);
+    if !(1..=256).contains(&rows) {
+        return Err(TrackerError::Invalid("pattern row count out of range"));
+    }
+    r.seek(start.saturating_add(header_len))?;
+    if packed_len == 0 {
+        return Ok(Pattern::empty(rows, empty));
+    }
+    // Every cell takes at least a byte, so the data bounds the cells allocated
+    if rows * channels > packed_len {
+        return Err(TrackerError::Invalid("truncated pattern data"));
+    }
+    let mut p = Reader::new(r.bytes(packed_len)?);
+    let mut cells = vec![Cell::default(); rows * channels];
+    for cell in &mut cells {
+        let first = p.u8()?;
+        let mask = if first & 0x80 != 0 { first } else { 0x1F };
+        let mut next = |bit: u8| -> Result<u8, TrackerError> {
+            match mask & bit {
+                0 => Ok(0),
+                _ if first & 0x80 == 0 && bit == 1 => Ok(first),
+                _ => p.u8(),
+            }
+        };
+        let note = next(1)?;
+        let instrument = next(2)?;
+        let volume = next(4)?;
+        let effect = next(8)?;
+        let param = next(16)?;
+        cell.note = match note {
+            0 => Note::None,
+            97 => Note::Off,
+            n => Note::On(n - 1),
+        };
+        cell.instrument = instrument;
+        cell.volume = convert_volume(volume);
+        (cell.fx, cell.param) = convert_effect(effect, param);
+    }
+    Ok(Pattern {
+        rows,
+        cells: cells.into(),
+    })
+}
+
+fn convert_volume(v: u8) -> VolCmd {
+    let y = v & 0x0F;
+    match v >> 4 {
+        0x1..=0x4 => VolCmd::SetVolume(v - 0x10),
+        0x5 if v == 0x50 => VolCmd::SetVolume(64),
+        0x6 => VolCmd::SlideDown(y),
+        0x7 => VolCmd::SlideUp(y),
+        0x8 => VolCmd::FineDown(y),
+        0x9 => VolCmd::FineUp(y),
+        0xA => VolCmd::VibratoSpeed(y),
+        0xB => VolCmd::VibratoDepth(y),
+        0xC => VolCmd::SetPan(y * 17),
+        0xD => VolCmd::PanSlideLeft(y),
+        0xE => VolCmd::PanSlideRight(y),
+        0xF => VolCmd::TonePorta(y << 4),
+        _ => VolCmd::None,
+    }
+}
+
+fn convert_effect(effect: u8, param: u8) -> (Fx, u8) {
+    let (x, y) = (param >> 4, param & 0x0F);
+    match effect {
+        0x0..=0xF => protracker::convert_effect(effect, param),
+        // G
+        16 => (Fx::GlobalVolume, param),
+        // H
+        17 => (Fx::GlobalVolSlide, param),
+        // K
+        20 => (Fx::KeyOff, param),
+        // P
+        25 => (Fx::PanSlide, param),
+        // R
+        27 => (Fx::MultiRetrig, param),
+        // T
+        29 => (Fx::Tremor, param),
+        // X
+        33 => match x {
+            1 => (Fx::ExtraFinePortaUp, y),
+            2 => (Fx::ExtraFinePortaDown, y),
+            _ => (Fx::None, 0),
+        },
+        _ => (Fx::None, 0),
+    }
+}
+
+fn read_envelope(
+    raw: &[u8],
+    count: u8,
+    sustain: u8,
+    loop_start: u8,
+    loop_end: u8,
+    kind: u8,
+) -> Option<Envelope> {
+    if kind & 1 == 0 || count == 0 {
+        return None;
+    }
+    let count = usize::from(count.min(12));
+    let points = raw
+        .chunks_exact(4)
+        .take(count)
+        .map(|p| {
+            let tick = u16::from_le_bytes([p[0], p[1]]);
+            let value = u16::from_le_bytes([p[2], p[3]]).min(64);
+            (tick, u8::try_from(value).unwrap_or(64))
+        })
+        .collect();
+    let point = |i: u8| usize::from(i).min(count - 1);
+    Some(Envelope {
+        points,
+        sustain: (kind & 2 != 0).then(|| (point(sustain), point(sustain))),
+        looping: (kind & 4 != 0).then(|| (point(loop_start), point(loop_end))),
+    })
+}
+
+fn load_instrument(r: &mut Reader, samples: &mut Vec<Sample>) -> Result<Instrument, TrackerError> {
+    let start = r.pos();
+    let size = to_usize(r.u32_le()?);
+    let name = r.string(22)?;
+    let _kind = r.u8()?;
+    let sample_count = usize::from(r.u16_le()?);
+    let mut instrument = Instrument {
+        name,
+        keymap: Vec::new(),
+        volume_envelope: None,
+        pan_envelope: None,
+        fadeout: 0.0,
+        global_volume: 1.0,
+        pan: None,
+    };
+    if sample_count == 0 {
+        r.seek(start.saturating_add(size))?;
+        return Ok(instrument);
+    }
+    let sample_header_size = to_usize(r.u32_le()?);
+    let keymap = r.bytes(96)?;
+    let volume_points = r.bytes(48)?;
+    let pan_points = r.bytes(48)?;
+    let [
+        vol_count,
+        pan_count,
+        vol_sustain,
+        vol_loop_start,
+        vol_loop_end,
+        pan_sustain,
+        pan_loop_start,
+        pan_loop_end,
+        vol_kind,
+        pan_kind,
+    ] = *r.bytes(10)?
+    else {
+        return Err(TrackerError::Truncated);
+    };
+    r.skip(4)?;
+    let fadeout = r.u16_le()?;
+    r.seek(start.saturating_add(size))?;
+
+    let first_sample = samples.len();
+    instrument.keymap = (0..120u8)
+        .map(|note| {
+            let s = keymap.get(usize::from(note)).copied().unwrap_or(0);
+            let index = first_sample + usize::from(s) + 1;
+            (note, u16::try_from(index).unwrap_or(0))
+        })
+        .collect();
+    instrument.volume_envelope = read_envelope(
+        volume_points,
+        vol_count,
+        vol_sustain,
+        vol_loop_start,
+        vol_loop_end,
+        vol_kind,
+    );
+    instrument.pan_envelope = read_envelope(
+        pan_points,
+        pan_count,
+        pan_sustain,
+        pan_loop_start,
+        pan_loop_end,
+        pan_kind,
+    );
+    instrument.fadeout = f32::from(fadeout) / 32768.0;
+
+    let mut headers = Vec::with_capacity(sample_count);
+    for _ in 0..sample_count {
+        let header_start = r.pos();
+        let len = to_usize(r.u32_le()?);
+        let loop_start = to_usize(r.u32_le()?);
+        let loop_len = to_usize(r.u32_le()?);
+        let volume = r.u8()?.min(64);
+        let finetune = r.i8()?;
+        let kind = r.u8()?;
+        let pan = r.u8()?;
+        let relative_note = r.i8()?;
+        r.skip(1)?;
+        let name = r.string(22)?;
+        r.seek(header_start.saturating_add(sample_header_size))?;
+        headers.push((
+            len,
+            loop_start,
+            loop_len,
+            volume,
+            finetune,
+            kind,
+            pan,
+            relative_note,
+            name,
+        ));
+    }
+    for (len, loop_start, loop_len, volume, finetune, kind, pan, relative_note, name) in headers {
+        let is_16bit = kind & 0x10 != 0;
+        let raw = r.bytes_lossy(len);
+        let data = decode_delta(raw, is_16bit);
+        // Loop points are in bytes
+        let width = if is_16bit { 2 } else { 1 };
+        let sample_loop = match kind & 3 {
+            0 => None,
+            looping => SampleLoop::new(
+                loop_start / width,
+                (loop_start + loop_len) / width,
+                looping == 2,
+                data.len(),
+            ),
+        };
+        let semitones = f64::from(relative_note) + f64::from(finetune) / 128.0;
+        samples.push(Sample {
+            name,
+            data,
+            sample_loop,
+            sustain_loop: None,
+            volume,
+            global_volume: 1.0,
+            pan: Some(pan),
+            base_rate: 8363.0 * 2f64.powf(semitones / 12.0),
+        });
+    }
+    // Keymap entries pointing past this instrument's samples are invalid
+    let end = samples.len();
+    for (_, sample) in &mut instrument.keymap {
+        if usize::from(*sample) > end {
+            *sample = 0;
+        }
+    }
+    Ok(instrument)
+}
+
+/// Decode delta encoded sample data.
+fn decode_delta(raw: &[u8], is_16bit: bool) -> Vec<f32> {
+    if is_16bit {
+        let mut acc = 0i16;
+        raw.chunks_exact(2)
+            .map(|b| {
+                acc = acc.wrapping_add(i16::from_le_bytes([b[0], b[1]]));
+                f32::from(acc) / 32768.0
+            })
+            .collect()
+    } else {
+        let mut acc = 0i8;
+        raw.iter()
+            .map(|&b| {
+                acc = acc.wrapping_add(i8::from_le_bytes([b]));
+                f32::from(acc) / 128.0
+            })
+            .collect()
+    }
+}
@@ -379,5 +379,7 @@ pub enum sfPrimitiveType {
 
 type sfColor = Color;
 type sfStencilValue = StencilValue;
+type sfVkInstance = crate::window::vulkan::VkInstance;
+type sfVkAllocationCallbacks = crate::window::vulkan::VkAllocationCallbacks;
 
 include!("graphics_bindgen.rs");
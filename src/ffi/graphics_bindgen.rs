@@ -202,6 +202,7 @@ pub fn sfRenderTexture_drawCircleShape(renderTexture: *mut sfRenderTexture, obje
 pub fn sfRenderTexture_drawConvexShape(renderTexture: *mut sfRenderTexture, object: *const sfConvexShape, states: *const sfRenderStates);
 pub fn sfRenderTexture_drawRectangleShape(renderTexture: *mut sfRenderTexture, object: *const sfRectangleShape, states: *const sfRenderStates);
 pub fn sfRenderTexture_drawVertexBuffer(renderTexture: *mut sfRenderTexture, object: *const sfVertexBuffer, states: *const sfRenderStates);
+pub fn sfRenderTexture_drawVertexBufferRange(renderTexture: *mut sfRenderTexture, object: *const sfVertexBuffer, firstVertex: usize, vertexCount: usize, states: *const sfRenderStates);
 pub fn sfRenderTexture_drawPrimitives(renderTexture: *mut sfRenderTexture, vertices: *const sfVertex, vertexCount: usize, type_: sfPrimitiveType, states: *const sfRenderStates);
 pub fn sfRenderTexture_pushGLStates(renderTexture: *mut sfRenderTexture);
 pub fn sfRenderTexture_popGLStates(renderTexture: *mut sfRenderTexture);
@@ -239,6 +240,7 @@ pub fn sfRenderWindow_setMouseCursor(window: *mut sfRenderWindow, cursor: *const
 pub fn sfRenderWindow_setKeyRepeatEnabled(renderWindow: *mut sfRenderWindow, enabled: bool);
 pub fn sfRenderWindow_setActive(renderWindow: *mut sfRenderWindow, active: bool) -> bool;
 pub fn sfRenderWindow_requestFocus(renderWindow: *mut sfRenderWindow);
+pub fn sfRenderWindow_createVulkanSurface(renderWindow: *mut sfRenderWindow, instance: sfVkInstance, surface: *mut u64, allocator: *const sfVkAllocationCallbacks) -> bool;
 pub fn sfRenderWindow_hasFocus(renderWindow: *const sfRenderWindow) -> bool;
 pub fn sfRenderWindow_display(renderWindow: *mut sfRenderWindow);
 pub fn sfRenderWindow_setFramerateLimit(renderWindow: *mut sfRenderWindow, limit: c_uint);
@@ -263,6 +265,7 @@ pub fn sfRenderWindow_drawCircleShape(renderWindow: *mut sfRenderWindow, object:
 pub fn sfRenderWindow_drawConvexShape(renderWindow: *mut sfRenderWindow, object: *const sfConvexShape, states: *const sfRenderStates);
 pub fn sfRenderWindow_drawRectangleShape(renderWindow: *mut sfRenderWindow, object: *const sfRectangleShape, states: *const sfRenderStates);
 pub fn sfRenderWindow_drawVertexBuffer(renderWindow: *mut sfRenderWindow, object: *const sfVertexBuffer, states: *const sfRenderStates);
+pub fn sfRenderWindow_drawVertexBufferRange(renderWindow: *mut sfRenderWindow, object: *const sfVertexBuffer, firstVertex: usize, vertexCount: usize, states: *const sfRenderStates);
 pub fn sfRenderWindow_drawPrimitives(renderWindow: *mut sfRenderWindow, vertices: *const sfVertex, vertexCount: usize, type_: sfPrimitiveType, states: *const sfRenderStates);
 pub fn sfRenderWindow_pushGLStates(renderWindow: *mut sfRenderWindow);
 pub fn sfRenderWindow_popGLStates(renderWindow: *mut sfRenderWindow);
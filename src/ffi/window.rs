@@ -457,7 +457,7 @@ pub enum Key {
 }
 
 #[repr(C)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Scancode {
@@ -615,6 +615,34 @@ pub enum Scancode {
 type sfScancode = Scancode;
 type sfKeyCode = Key;
 
+/// Implement `TryFrom<i32>` for `repr(C)` enums whose variants take all the values from the
+/// first one to the last one. Values out of that range are given back as the error.
+macro_rules! impl_try_from_i32 {
+    ($($ty:ty: $first:expr, $last:expr;)*) => {$(
+        impl TryFrom<i32> for $ty {
+            type Error = i32;
+            fn try_from(value: i32) -> Result<Self, i32> {
+                if ($first as i32..=$last as i32).contains(&value) {
+                    // SAFETY: The enum is `repr(C)`, and its variants are contiguous from
+                    // `$first` to `$last`, so `value` is one of them
+                    Ok(unsafe { std::mem::transmute::<i32, Self>(value) })
+                } else {
+                    Err(value)
+                }
+            }
+        }
+    )*};
+}
+
+impl_try_from_i32! {
+    Key: Key::Unknown, Key::Pause;
+    Scancode: Scancode::Unknown, Scancode::LaunchMediaSelect;
+    MouseButton: MouseButton::Left, MouseButton::Extra2;
+    MouseWheel: MouseWheel::VerticalWheel, MouseWheel::HorizontalWheel;
+    JoystickAxis: JoystickAxis::X, JoystickAxis::PovY;
+    sfSensorType: sfSensorType::Accelerometer, sfSensorType::Orientation;
+}
+
 // Window handle is HWND (HWND__*) on Windows
 #[cfg(target_os = "windows")]
 pub type sfWindowHandle = *mut c_void;
@@ -643,5 +671,8 @@ pub enum sfSensorType {
 }
 
 type sfGlFunctionPointer = *const c_void;
+type sfVulkanFunctionPointer = Option<crate::window::vulkan::VulkanFunctionPointer>;
+type sfVkInstance = crate::window::vulkan::VkInstance;
+type sfVkAllocationCallbacks = crate::window::vulkan::VkAllocationCallbacks;
 
 include!("window_bindgen.rs");
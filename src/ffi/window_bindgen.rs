@@ -58,6 +58,10 @@ pub fn sfVideoMode_getFullscreenModes() -> *const sfVideoModeVector;
 pub fn sfVideoMode_isValid(mode: sfVideoMode) -> bool;
 pub fn sfVideoModeVector_getLength(vec: *const sfVideoModeVector) -> usize;
 pub fn sfVideoModeVector_getData(vec: *const sfVideoModeVector) -> *const sfVideoMode;
+// Vulkan.cpp
+pub fn sfVulkan_isAvailable(requireGraphics: bool) -> bool;
+pub fn sfVulkan_getFunction(name: *const c_char) -> sfVulkanFunctionPointer;
+pub fn sfVulkan_getGraphicsRequiredInstanceExtensions(count: *mut usize) -> *const *const c_char;
 // Window.cpp
 pub fn sfWindow_new() -> *mut sfWindow;
 pub fn sfWindow_del(window: *mut sfWindow);
@@ -84,6 +88,7 @@ pub fn sfWindow_setVerticalSyncEnabled(window: *mut sfWindow, enabled: bool);
 pub fn sfWindow_setKeyRepeatEnabled(window: *mut sfWindow, enabled: bool);
 pub fn sfWindow_setActive(window: *mut sfWindow, active: bool) -> bool;
 pub fn sfWindow_requestFocus(window: *mut sfWindow);
+pub fn sfWindow_createVulkanSurface(window: *mut sfWindow, instance: sfVkInstance, surface: *mut u64, allocator: *const sfVkAllocationCallbacks) -> bool;
 pub fn sfWindow_hasFocus(window: *const sfWindow) -> bool;
 pub fn sfWindow_display(window: *mut sfWindow);
 pub fn sfWindow_setFramerateLimit(window: *mut sfWindow, limit: c_uint);
@@ -0,0 +1,635 @@
+use {
+    crate::{
+        cpp::FBox,
+        graphics::{
+            Color, Drawable, FloatRect, IntRect, PrimitiveType, RcTexture, RenderStates,
+            RenderTarget, SpriteSheet, Texture, Transformable, Vertex,
+            transformation::{Transformation, impl_transformable},
+        },
+        system::{Time, Vector2f},
+    },
+    std::{cell::RefCell, collections::HashMap, fmt, rc::Weak},
+};
+
+/// How an [`AnimationClip`] plays once it reaches its last frame.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum PlayMode {
+    /// Start again from the first frame
+    #[default]
+    Loop,
+    /// Play the frames back to the first one, then forward again, and so on
+    PingPong,
+    /// Stay on the last frame, and finish
+    Once,
+}
+
+/// A frame of an [`AnimationClip`]: an area of the texture, shown for some time.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SpriteFrame {
+    /// The area of the texture to show
+    pub rect: IntRect,
+    /// Where the area is drawn, for frames trimmed of their transparent borders
+    pub offset: Vector2f,
+    /// How long the frame is shown
+    pub duration: Time,
+}
+
+impl SpriteFrame {
+    /// A frame showing `rect` for `duration`, without offset.
+    #[must_use]
+    pub fn new(rect: IntRect, duration: Time) -> Self {
+        Self {
+            rect,
+            offset: Vector2f::new(0., 0.),
+            duration,
+        }
+    }
+}
+
+/// A sequence of frames played by an [`AnimatedSprite`].
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct AnimationClip {
+    /// The frames, in order
+    pub frames: Vec<SpriteFrame>,
+    /// How the clip plays past its last frame
+    pub mode: PlayMode,
+}
+
+impl AnimationClip {
+    /// A clip playing `frames` with `mode`.
+    #[must_use]
+    pub fn new(frames: Vec<SpriteFrame>, mode: PlayMode) -> Self {
+        Self { frames, mode }
+    }
+    /// The time to play each frame once.
+    #[must_use]
+    pub fn duration(&self) -> Time {
+        self.frames
+            .iter()
+            .fold(Time::ZERO, |total, frame| total + frame.duration)
+    }
+}
+
+type FrameCallback = Box<dyn FnMut(&str, usize)>;
+
+/// A callback run when a clip reaches one of its frames.
+struct FrameEvent {
+    clip: String,
+    frame: usize,
+    callback: FrameCallback,
+}
+
+impl fmt::Debug for FrameEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FrameEvent")
+            .field("clip", &self.clip)
+            .field("frame", &self.frame)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The playback state shared by [`AnimatedSprite`] and [`RcAnimatedSprite`].
+#[derive(Debug)]
+struct Animator {
+    clips: HashMap<String, AnimationClip>,
+    clip: Option<String>,
+    frame: usize,
+    elapsed: Time,
+    backwards: bool,
+    playing: bool,
+    finished: bool,
+    speed: f32,
+    events: Vec<FrameEvent>,
+}
+
+impl Default for Animator {
+    fn default() -> Self {
+        Self {
+            clips: HashMap::new(),
+            clip: None,
+            frame: 0,
+            elapsed: Time::ZERO,
+            backwards: false,
+            playing: false,
+            finished: false,
+            speed: 1.,
+            events: Vec::new(),
+        }
+    }
+}
+
+impl Animator {
+    fn current_clip(&self) -> Option<&AnimationClip> {
+        self.clips.get(self.clip.as_ref()?)
+    }
+    fn current_frame(&self) -> Option<&SpriteFrame> {
+        self.current_clip()?.frames.get(self.frame)
+    }
+    fn play(&mut self, name: &str) -> bool {
+        if !self.clips.contains_key(name) {
+            return false;
+        }
+        if !(self.playing && self.clip.as_deref() == Some(name)) {
+            self.clip = Some(name.to_owned());
+            self.restart();
+        }
+        true
+    }
+    fn restart(&mut self) {
+        self.frame = 0;
+        self.elapsed = Time::ZERO;
+        self.backwards = false;
+        self.finished = false;
+        self.playing = self.current_frame().is_some();
+        if self.playing {
+            self.fire_events();
+        }
+    }
+    fn fire_events(&mut self) {
+        let Some(clip) = &self.clip else {
+            return;
+        };
+        for event in &mut self.events {
+            if event.clip == *clip && event.frame == self.frame {
+                (event.callback)(clip, self.frame);
+            }
+        }
+    }
+    /// Advance by `delta`, running the events of the frames reached.
+    fn update(&mut self, delta: Time) {
+        let Some(clip) = self.current_clip() else {
+            return;
+        };
+        let (count, mode, length) = (clip.frames.len(), clip.mode, clip.duration());
+        // The first and last frames are shown once per ping-pong cycle, the others twice
+        let ends = clip
+            .frames
+            .first()
+            .map_or(Time::ZERO, |frame| frame.duration)
+            + clip
+                .frames
+                .last()
+                .map_or(Time::ZERO, |frame| frame.duration);
+        if !self.playing || length <= Time::ZERO {
+            return;
+        }
+        let delta = (delta * self.speed).as_microseconds();
+        self.elapsed = Time::microseconds(self.elapsed.as_microseconds().saturating_add(delta));
+        if count == 1 && mode != PlayMode::Once {
+            self.elapsed %= length;
+            return;
+        }
+        // Whole cycles come back to the same frame, so all of them but one are skipped, which
+        // still runs the event of each frame once
+        let cycle = match mode {
+            PlayMode::Loop => Some(length),
+            PlayMode::PingPong => Some(length + length - ends),
+            PlayMode::Once => None,
+        };
+        if let Some(cycle) = cycle {
+            if self.elapsed >= cycle + cycle {
+                self.elapsed = self.elapsed % cycle + cycle;
+            }
+        }
+        while self.playing {
+            let duration = self
+                .current_frame()
+                .map_or(Time::ZERO, |frame| frame.duration);
+            if self.elapsed < duration {
+                break;
+            }
+            self.elapsed -= duration;
+            match mode {
+                PlayMode::Loop => self.frame = (self.frame + 1) % count,
+                PlayMode::Once if self.frame + 1 < count => self.frame += 1,
+                PlayMode::Once => {
+                    self.playing = false;
+                    self.finished = true;
+                    self.elapsed = Time::ZERO;
+                    break;
+                }
+                PlayMode::PingPong => {
+                    if self.frame == count - 1 {
+                        self.backwards = true;
+                    } else if self.frame == 0 {
+                        self.backwards = false;
+                    }
+                    if self.backwards {
+                        self.frame -= 1;
+                    } else {
+                        self.frame += 1;
+                    }
+                }
+            }
+            self.fire_events();
+        }
+    }
+    /// The quad of the current frame.
+    fn vertices(&self, color: Color) -> Option<[Vertex; 4]> {
+        let frame = self.current_frame()?;
+        let rect = frame.rect.as_other::<f32>();
+        let (left, top) = (frame.offset.x, frame.offset.y);
+        let (right, bottom) = (left + rect.size.x.abs(), top + rect.size.y.abs());
+        let (u0, v0) = (rect.position.x, rect.position.y);
+        let (u1, v1) = (u0 + rect.size.x, v0 + rect.size.y);
+        let vertex = |x, y, u, v| Vertex::new(Vector2f::new(x, y), color, Vector2f::new(u, v));
+        Some([
+            vertex(left, top, u0, v0),
+            vertex(left, bottom, u0, v1),
+            vertex(right, top, u1, v0),
+            vertex(right, bottom, u1, v1),
+        ])
+    }
+    fn draw(
+        &self,
+        texture: &Texture,
+        color: Color,
+        transformation: &Transformation,
+        target: &mut dyn RenderTarget,
+        states: &RenderStates,
+    ) {
+        if let Some(vertices) = self.vertices(color) {
+            let mut states = *states;
+            states.transform.combine(transformation.transform());
+            states.texture = Some(texture);
+            target.draw_primitives(&vertices, PrimitiveType::TRIANGLE_STRIP, &states);
+        }
+    }
+}
+
+macro_rules! animated_sprite_methods {
+    () => {
+        /// Add a clip called `name`, replacing any clip of that name.
+        pub fn add_clip(&mut self, name: impl Into<String>, clip: AnimationClip) {
+            let name = name.into();
+            if self.animator.clip.as_ref() == Some(&name) {
+                self.animator.playing = false;
+                self.animator.clip = None;
+            }
+            let _ = self.animator.clips.insert(name, clip);
+        }
+        /// Add the clips of the tags of `sheet`.
+        pub fn add_clips(&mut self, sheet: &SpriteSheet) {
+            for (name, clip) in sheet.clips() {
+                self.add_clip(name, clip);
+            }
+        }
+        /// The clip called `name`.
+        #[must_use]
+        pub fn clip(&self, name: &str) -> Option<&AnimationClip> {
+            self.animator.clips.get(name)
+        }
+        /// Play the clip called `name` from its first frame, returning whether it exists.
+        ///
+        /// Playing the clip that is already playing doesn't restart it, so this can be called
+        /// every frame with the clip that should be shown.
+        pub fn play(&mut self, name: &str) -> bool {
+            self.animator.play(name)
+        }
+        /// Play the current clip again from its first frame.
+        pub fn restart(&mut self) {
+            self.animator.restart();
+        }
+        /// Pause the current clip, keeping its frame.
+        pub fn pause(&mut self) {
+            self.animator.playing = false;
+        }
+        /// Resume the current clip, unless it is finished.
+        pub fn resume(&mut self) {
+            self.animator.playing =
+                !self.animator.finished && self.animator.current_frame().is_some();
+        }
+        /// Whether a clip is playing.
+        #[must_use]
+        pub fn is_playing(&self) -> bool {
+            self.animator.playing
+        }
+        /// Whether the current clip is a [`PlayMode::Once`] clip that reached its end.
+        #[must_use]
+        pub fn is_finished(&self) -> bool {
+            self.animator.finished
+        }
+        /// The name of the current clip.
+        #[must_use]
+        pub fn current_clip(&self) -> Option<&str> {
+            self.animator.clip.as_deref()
+        }
+        /// The index of the current frame in the current clip.
+        #[must_use]
+        pub fn current_frame(&self) -> usize {
+            self.animator.frame
+        }
+        /// Set how fast the clips play, 1 being their normal speed.
+        ///
+        /// Negative speeds are treated as 0.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `speed` isn't finite.
+        pub fn set_speed(&mut self, speed: f32) {
+            assert!(
+                speed.is_finite(),
+                "The speed of an animation must be finite"
+            );
+            self.animator.speed = speed.max(0.);
+        }
+        /// How fast the clips play.
+        #[must_use]
+        pub fn speed(&self) -> f32 {
+            self.animator.speed
+        }
+        /// Run `callback` whenever the clip `clip` reaches its frame `frame`, with the name of
+        /// the clip and the frame.
+        ///
+        /// Callbacks run during [`play`](Self::play), [`restart`](Self::restart) and
+        /// [`update`](Self::update), once for each frame reached, even when an update skips
+        /// frames. This is how sounds or hitboxes are synchronized with an animation.
+        pub fn add_frame_event(
+            &mut self,
+            clip: impl Into<String>,
+            frame: usize,
+            callback: impl FnMut(&str, usize) + 'static,
+        ) {
+            self.animator.events.push(FrameEvent {
+                clip: clip.into(),
+                frame,
+                callback: Box::new(callback),
+            });
+        }
+        /// Remove all the frame events.
+        pub fn clear_frame_events(&mut self) {
+            self.animator.events.clear();
+        }
+        /// Advance the current clip by `delta`.
+        ///
+        /// Should be called every frame.
+        pub fn update(&mut self, delta: Time) {
+            self.animator.update(delta);
+        }
+        /// Set the color the texture is modulated with.
+        pub fn set_color(&mut self, color: Color) {
+            self.color = color;
+        }
+        /// The color the texture is modulated with.
+        #[must_use]
+        pub fn color(&self) -> Color {
+            self.color
+        }
+        /// The bounds of the current frame, before transformations.
+        ///
+        /// Empty if no clip is playing.
+        #[must_use]
+        pub fn local_bounds(&self) -> FloatRect {
+            match self.animator.current_frame() {
+                Some(frame) => {
+                    let size = frame.rect.size.as_other::<f32>();
+                    FloatRect::new(frame.offset, Vector2f::new(size.x.abs(), size.y.abs()))
+                }
+                None => FloatRect::default(),
+            }
+        }
+        /// The bounds of the current frame, after transformations.
+        #[must_use]
+        pub fn global_bounds(&self) -> FloatRect {
+            self.transform().transform_rect(self.local_bounds())
+        }
+    };
+}
+
+/// A sprite playing named clips of frames from a texture.
+///
+/// Clips are sequences of [`SpriteFrame`]s, each showing an area of the texture for some time,
+/// played in a loop, back and forth, or once. They are usually made from a [`SpriteSheet`],
+/// cut from a grid or loaded from an Aseprite export. The sprite shows nothing until a clip is
+/// [played](Self::play).
+///
+/// See [`RcAnimatedSprite`] for a version holding an [`RcTexture`].
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::graphics::*;
+/// # use sfml::system::Clock;
+/// # let mut window: RenderWindow = unimplemented!();
+/// let sheet = SpriteSheet::from_aseprite_file("hero.json").unwrap();
+/// let texture = Texture::from_file("hero.png").unwrap();
+/// let mut hero = AnimatedSprite::new(&texture);
+/// hero.add_clips(&sheet);
+/// hero.add_frame_event("walk", 2, |_, _| println!("Step"));
+/// hero.play("walk");
+/// let mut clock = Clock::new().unwrap();
+/// while window.is_open() {
+///     hero.update(clock.restart());
+///     window.clear(Color::BLACK);
+///     window.draw(&hero);
+///     window.display();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AnimatedSprite<'texture> {
+    texture: &'texture Texture,
+    animator: Animator,
+    color: Color,
+    transformation: Transformation,
+}
+
+impl<'texture> AnimatedSprite<'texture> {
+    /// Create a sprite drawing from `texture`, without clips.
+    #[must_use]
+    pub fn new(texture: &'texture Texture) -> Self {
+        Self {
+            texture,
+            animator: Animator::default(),
+            color: Color::WHITE,
+            transformation: Transformation::default(),
+        }
+    }
+    /// Change the texture, keeping the clips.
+    pub fn set_texture(&mut self, texture: &'texture Texture) {
+        self.texture = texture;
+    }
+    /// The texture of the sprite.
+    #[must_use]
+    pub fn texture(&self) -> &'texture Texture {
+        self.texture
+    }
+    animated_sprite_methods!();
+}
+
+impl Drawable for AnimatedSprite<'_> {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn RenderTarget,
+        states: &RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        self.animator.draw(
+            self.texture,
+            self.color,
+            &self.transformation,
+            target,
+            states,
+        );
+    }
+}
+
+impl_transformable!(AnimatedSprite<'_>, transformation);
+
+/// An [`AnimatedSprite`] holding an [`RcTexture`] (reference counted)
+///
+/// The sprite isn't drawn once the texture is dropped.
+#[derive(Debug)]
+pub struct RcAnimatedSprite {
+    texture: Weak<RefCell<FBox<Texture>>>,
+    animator: Animator,
+    color: Color,
+    transformation: Transformation,
+}
+
+impl RcAnimatedSprite {
+    /// Create a sprite drawing from `texture`, without clips.
+    #[must_use]
+    pub fn new(texture: &RcTexture) -> Self {
+        Self {
+            texture: texture.downgrade(),
+            animator: Animator::default(),
+            color: Color::WHITE,
+            transformation: Transformation::default(),
+        }
+    }
+    /// Change the texture, keeping the clips.
+    pub fn set_texture(&mut self, texture: &RcTexture) {
+        self.texture = texture.downgrade();
+    }
+    /// Whether the texture is still alive.
+    #[must_use]
+    pub fn texture_exists(&self) -> bool {
+        self.texture.strong_count() != 0
+    }
+    animated_sprite_methods!();
+}
+
+impl Drawable for RcAnimatedSprite {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn RenderTarget,
+        states: &RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        if let Some(texture) = self.texture.upgrade() {
+            self.animator.draw(
+                &texture.borrow(),
+                self.color,
+                &self.transformation,
+                target,
+                states,
+            );
+        }
+    }
+}
+
+impl_transformable!(RcAnimatedSprite, transformation);
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{AnimationClip, Animator, FrameEvent, PlayMode, SpriteFrame},
+        crate::{
+            graphics::{Color, IntRect},
+            system::{Time, Vector2f, Vector2i},
+        },
+        std::{cell::RefCell, rc::Rc},
+    };
+
+    fn animator(mode: PlayMode) -> Animator {
+        let frames = (0..3)
+            .map(|i| {
+                let rect = IntRect::new(Vector2i::new(i * 10, 0), Vector2i::new(10, 20));
+                SpriteFrame::new(rect, Time::milliseconds(100 * (i + 1)))
+            })
+            .collect();
+        let mut animator = Animator::default();
+        let _ = animator
+            .clips
+            .insert("clip".into(), AnimationClip::new(frames, mode));
+        animator
+    }
+
+    /// The frames shown after each of `steps` updates of 100 milliseconds.
+    fn frames(animator: &mut Animator, steps: usize) -> Vec<usize> {
+        (0..steps)
+            .map(|_| {
+                animator.update(Time::milliseconds(100));
+                animator.frame
+            })
+            .collect()
+    }
+
+    #[test]
+    fn clips_follow_their_mode() {
+        let mut looping = animator(PlayMode::Loop);
+        assert!(!looping.play("missing"));
+        assert!(looping.play("clip"));
+        assert_eq!(frames(&mut looping, 7), [1, 1, 2, 2, 2, 0, 1]);
+
+        let mut ping_pong = animator(PlayMode::PingPong);
+        assert!(ping_pong.play("clip"));
+        assert_eq!(frames(&mut ping_pong, 10), [1, 1, 2, 2, 2, 1, 1, 0, 1, 1]);
+
+        let mut once = animator(PlayMode::Once);
+        assert!(once.play("clip"));
+        assert_eq!(frames(&mut once, 6), [1, 1, 2, 2, 2, 2]);
+        assert!(once.finished && !once.playing);
+        // Playing a finished clip starts it over
+        assert!(once.play("clip"));
+        assert_eq!((once.frame, once.playing), (0, true));
+
+        let vertices = once.vertices(Color::RED).expect("a frame is shown");
+        assert_eq!(vertices[3].position, Vector2f::new(10., 20.));
+        assert_eq!(vertices[3].tex_coords, Vector2f::new(10., 20.));
+    }
+
+    #[test]
+    fn frame_events_run_for_each_frame_reached() {
+        let mut animator = animator(PlayMode::Loop);
+        let reached = Rc::new(RefCell::new(Vec::new()));
+        for frame in [0, 2] {
+            let reached = Rc::clone(&reached);
+            animator.events.push(FrameEvent {
+                clip: "clip".into(),
+                frame,
+                callback: Box::new(move |clip, frame| {
+                    assert_eq!(clip, "clip");
+                    reached.borrow_mut().push(frame);
+                }),
+            });
+        }
+        assert!(animator.play("clip"));
+        assert!(animator.play("clip"));
+        assert_eq!(*reached.borrow(), [0]);
+        // A long update skips past frames 1, 2 and 0, and still runs their events
+        animator.update(Time::milliseconds(750));
+        assert_eq!(animator.frame, 1);
+        assert_eq!(*reached.borrow(), [0, 2, 0]);
+        // Whole cycles are skipped, running the events once
+        animator.update(Time::seconds(600.));
+        assert_eq!(animator.frame, 1);
+        assert_eq!(*reached.borrow(), [0, 2, 0, 2, 0]);
+    }
+
+    #[test]
+    fn huge_updates_skip_whole_cycles() {
+        for mode in [PlayMode::Loop, PlayMode::PingPong] {
+            let mut animator = animator(mode);
+            assert!(animator.play("clip"));
+            animator.update(Time::microseconds(i64::MAX));
+            animator.update(Time::microseconds(i64::MAX));
+            assert!(animator.playing);
+        }
+        // A ping-pong cycle of the frames 0, 1, 2 and 1 lasts 800 milliseconds
+        let mut ping_pong = animator(PlayMode::PingPong);
+        assert!(ping_pong.play("clip"));
+        ping_pong.update(Time::milliseconds(800 * 1000 + 350));
+        assert_eq!((ping_pong.frame, ping_pong.backwards), (2, false));
+        ping_pong.update(Time::milliseconds(800 * 1000 + 300));
+        assert_eq!((ping_pong.frame, ping_pong.backwards), (1, true));
+    }
+}
@@ -0,0 +1,390 @@
+//! A small JSON reader for the map and animation formats loaded by the graphics module, and the
+//! syntax errors it shares with the Tiled XML reader.
+
+use crate::{LoadError, graphics::Color};
+
+/// The reason given for members of the wrong type or out of range.
+pub(crate) const INVALID_MEMBER: &str = "invalid member value";
+
+/// A parsed JSON value. Objects keep their keys in order.
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+/// A JSON or XML syntax error, with the line it was found on, starting at 1.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct SyntaxError {
+    pub(crate) line: usize,
+    pub(crate) reason: &'static str,
+}
+
+impl SyntaxError {
+    /// An error found at byte `pos` of `text`.
+    pub(crate) fn at(text: &[u8], pos: usize, reason: &'static str) -> Self {
+        let end = pos.min(text.len());
+        let line = 1 + text[..end].iter().filter(|&&b| b == b'\n').count();
+        Self { line, reason }
+    }
+}
+
+/// Documents nested deeper than this are rejected, rather than overflowing the stack.
+pub(crate) const MAX_DEPTH: usize = 128;
+
+impl From<SyntaxError> for LoadError {
+    fn from(error: SyntaxError) -> Self {
+        Self::Syntax {
+            line: error.line,
+            reason: error.reason,
+        }
+    }
+}
+
+impl Value {
+    /// The value of `key`, if this is an object holding it.
+    pub(crate) fn get(&self, key: &str) -> Option<&Self> {
+        match self {
+            Self::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(string) => Some(string),
+            _ => None,
+        }
+    }
+    pub(crate) fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+    pub(crate) fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+    pub(crate) fn as_array(&self) -> Option<&[Self]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+    pub(crate) fn as_object(&self) -> Option<&[(String, Self)]> {
+        match self {
+            Self::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+    /// The value as an unsigned integer, if it is a whole number that fits.
+    pub(crate) fn as_u32(&self) -> Option<u32> {
+        let number = self.as_f64()?;
+        #[expect(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            reason = "the range and fraction are checked"
+        )]
+        (number.fract() == 0. && (0. ..=f64::from(u32::MAX)).contains(&number))
+            .then_some(number as u32)
+    }
+    /// The value as a signed integer, if it is a whole number that fits.
+    pub(crate) fn as_i64(&self) -> Option<i64> {
+        let number = self.as_f64()?;
+        #[expect(
+            clippy::cast_possible_truncation,
+            clippy::cast_precision_loss,
+            reason = "the range and fraction are checked"
+        )]
+        (number.fract() == 0. && (i64::MIN as f64..i64::MAX as f64).contains(&number))
+            .then_some(number as i64)
+    }
+    /// The array of `key`, empty if this object doesn't hold it, or `None` if it isn't an
+    /// array.
+    pub(crate) fn array_or_empty(&self, key: &str) -> Option<&[Self]> {
+        self.get(key).map_or(Some(&[]), Self::as_array)
+    }
+    /// The unsigned integer of `key`, or why it can't be read.
+    pub(crate) fn required_u32(&self, key: &str) -> Result<u32, &'static str> {
+        self.get(key)
+            .ok_or("missing member")?
+            .as_u32()
+            .ok_or(INVALID_MEMBER)
+    }
+
+    /// Parse a JSON document.
+    pub(crate) fn parse(text: &str) -> Result<Self, SyntaxError> {
+        let mut parser = Parser {
+            text: text.as_bytes(),
+            pos: 0,
+        };
+        let value = parser.value(0)?;
+        parser.skip_whitespace();
+        if parser.pos < parser.text.len() {
+            return Err(parser.error("unexpected data after the document"));
+        }
+        Ok(value)
+    }
+}
+
+/// Parse a `#RRGGBB` color, or a color with 8 digits with the alpha channel first, as written
+/// by Tiled, or last, as written by Aseprite. The `#` is optional.
+pub(crate) fn parse_color(text: &str, alpha_first: bool) -> Option<Color> {
+    let hex = text.strip_prefix('#').unwrap_or(text);
+    if !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    match hex.len() {
+        6 => Some(Color::from(value << 8 | 0xFF)),
+        8 if alpha_first => Some(Color::from(value.rotate_left(8))),
+        8 => Some(Color::from(value)),
+        _ => None,
+    }
+}
+
+struct Parser<'a> {
+    text: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, reason: &'static str) -> SyntaxError {
+        SyntaxError::at(self.text, self.pos, reason)
+    }
+    fn skip_whitespace(&mut self) {
+        while self
+            .text
+            .get(self.pos)
+            .is_some_and(|b| b" \t\r\n".contains(b))
+        {
+            self.pos += 1;
+        }
+    }
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.text.get(self.pos).copied()
+    }
+    fn expect(&mut self, byte: u8, reason: &'static str) -> Result<(), SyntaxError> {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(reason))
+        }
+    }
+    fn literal(&mut self, word: &str, value: Value) -> Result<Value, SyntaxError> {
+        if self.text[self.pos..].starts_with(word.as_bytes()) {
+            self.pos += word.len();
+            Ok(value)
+        } else {
+            Err(self.error("invalid literal"))
+        }
+    }
+    fn value(&mut self, depth: usize) -> Result<Value, SyntaxError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nested too deeply"));
+        }
+        match self.peek() {
+            Some(b'{') => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(Value::Object(members));
+                }
+                loop {
+                    if self.peek() != Some(b'"') {
+                        return Err(self.error("expected a key"));
+                    }
+                    let key = self.string()?;
+                    self.expect(b':', "expected ':'")?;
+                    members.push((key, self.value(depth + 1)?));
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(Value::Object(members));
+                        }
+                        _ => return Err(self.error("expected ',' or '}'")),
+                    }
+                }
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut values = Vec::new();
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(Value::Array(values));
+                }
+                loop {
+                    values.push(self.value(depth + 1)?);
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(Value::Array(values));
+                        }
+                        _ => return Err(self.error("expected ',' or ']'")),
+                    }
+                }
+            }
+            Some(b'"') => self.string().map(Value::String),
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'n') => self.literal("null", Value::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("expected a value")),
+            None => Err(self.error("unexpected end of document")),
+        }
+    }
+    fn number(&mut self) -> Result<Value, SyntaxError> {
+        let start = self.pos;
+        while self
+            .text
+            .get(self.pos)
+            .is_some_and(|b| b"+-.eE0123456789".contains(b))
+        {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.text[start..self.pos])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .map(Value::Number)
+            .ok_or_else(|| self.error("invalid number"))
+    }
+    /// Read a string, starting at its opening quote.
+    fn string(&mut self) -> Result<String, SyntaxError> {
+        self.pos += 1;
+        let mut bytes = Vec::new();
+        loop {
+            let Some(&byte) = self.text.get(self.pos) else {
+                return Err(self.error("unterminated string"));
+            };
+            self.pos += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let Some(&escape) = self.text.get(self.pos) else {
+                        return Err(self.error("unterminated string"));
+                    };
+                    self.pos += 1;
+                    let unescaped = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    let mut buffer = [0; 4];
+                    bytes.extend_from_slice(unescaped.encode_utf8(&mut buffer).as_bytes());
+                }
+                _ => bytes.push(byte),
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| self.error("invalid UTF-8"))
+    }
+    /// Read the digits of a `\u` escape, and of the low surrogate following a high one.
+    fn unicode_escape(&mut self) -> Result<char, SyntaxError> {
+        let Some(high) = self.hex4() else {
+            return Err(self.error("invalid unicode escape"));
+        };
+        let code = if (0xD800..0xDC00).contains(&high) {
+            let low = if self.text[self.pos..].starts_with(b"\\u") {
+                self.pos += 2;
+                self.hex4()
+            } else {
+                None
+            };
+            match low {
+                Some(low @ 0xDC00..0xE000) => 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00),
+                _ => return Err(self.error("invalid surrogate pair")),
+            }
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))
+    }
+    fn hex4(&mut self) -> Option<u32> {
+        let digits = self.text.get(self.pos..self.pos + 4)?;
+        let code = u32::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()?;
+        self.pos += 4;
+        Some(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{SyntaxError, Value, parse_color},
+        crate::graphics::Color,
+    };
+
+    #[test]
+    fn parses_documents() {
+        let value = Value::parse(
+            r#" {"a": [1, -2.5e1, true, null], "b": {"c": "x\"é\ud83d\ude00\n"}, "d": []} "#,
+        )
+        .expect("valid JSON");
+        let a = value.get("a").and_then(Value::as_array).expect("array");
+        assert_eq!(a[0].as_u32(), Some(1));
+        assert_eq!(a[1].as_f64(), Some(-25.));
+        assert_eq!(a[1].as_u32(), None);
+        assert_eq!(a[2].as_bool(), Some(true));
+        assert_eq!(a[3], Value::Null);
+        let c = value.get("b").and_then(|b| b.get("c"));
+        assert_eq!(c.and_then(Value::as_str), Some("x\"é😀\n"));
+        assert_eq!(value.get("d"), Some(&Value::Array(Vec::new())));
+        assert_eq!(value.array_or_empty("d"), Some(&[][..]));
+        assert_eq!(value.array_or_empty("missing"), Some(&[][..]));
+        assert_eq!(value.array_or_empty("b"), None);
+        assert_eq!(a[1].as_i64(), Some(-25));
+        assert_eq!(Value::Number(0.5).as_i64(), None);
+        let object = Value::parse(r#"{"n": 7, "s": "7"}"#).expect("valid JSON");
+        assert_eq!(object.required_u32("n"), Ok(7));
+        assert_eq!(object.required_u32("s"), Err("invalid member value"));
+        assert_eq!(object.required_u32("x"), Err("missing member"));
+    }
+
+    #[test]
+    fn parses_colors() {
+        assert_eq!(parse_color("#102030", true), Some(Color::rgb(16, 32, 48)));
+        assert_eq!(
+            parse_color("80102030", true),
+            Some(Color::rgba(16, 32, 48, 128))
+        );
+        assert_eq!(
+            parse_color("#10203080", false),
+            Some(Color::rgba(16, 32, 48, 128))
+        );
+        assert_eq!(parse_color("#+10203", true), None);
+        assert_eq!(parse_color("#1020", true), None);
+    }
+
+    #[test]
+    fn reports_errors() {
+        let error = |text| Value::parse(text).expect_err("invalid JSON");
+        assert_eq!(
+            error("{\n\"a\": 1,\n}"),
+            SyntaxError {
+                line: 3,
+                reason: "expected a key"
+            }
+        );
+        assert_eq!(error("[1 2]").reason, "expected ',' or ']'");
+        assert_eq!(error("\"abc").reason, "unterminated string");
+        assert_eq!(error("[1] x").reason, "unexpected data after the document");
+        assert_eq!(error(&"[".repeat(200)).reason, "nested too deeply");
+    }
+}
@@ -6,6 +6,7 @@ use crate::system::Vector2f;
 pub use self::blend_mode::BlendMode;
 pub use {
     self::{
+        animated_sprite::{AnimatedSprite, AnimationClip, PlayMode, RcAnimatedSprite, SpriteFrame},
         circle_shape::CircleShape,
         color::Color,
         convex_shape::ConvexShape,
@@ -14,6 +15,8 @@ pub use {
         font::{Font, Info as FontInfo},
         glyph::Glyph,
         image::{Image, PixelAccessError},
+        nine_slice::{Margins, NineSlice, RcNineSlice, SliceMode},
+        particle_system::{Affector, Emitter, EmitterShape, Particle, ParticleSystem},
         primitive_type::PrimitiveType,
         rc_font::RcFont,
         rc_sprite::RcSprite,
@@ -28,18 +31,31 @@ pub use {
         shader::Shader,
         shape::Shape,
         sprite::Sprite,
+        sprite_batch::{BatchSortMode, SpriteBatch},
+        sprite_sheet::{FrameTag, SliceKey, SpriteSheet, SpriteSlice},
         text::Text,
+        text_layout::{
+            FontMetrics, GlyphMetrics, LayoutLine, LayoutSettings, PositionedGlyph, TextAlign,
+            TextBox, TextLayout, WrapMode,
+        },
         text_style::TextStyle,
         texture::Texture,
+        texture_atlas::{
+            AtlasBuilder, AtlasEntry, AtlasError, AtlasLayout, AtlasSettings, PackAlgorithm,
+            TextureAtlas,
+        },
+        tile_map::{Tile, TileMap, TileSheet},
         transform::Transform,
         transformable::Transformable,
         vertex::Vertex,
         vertex_buffer::{VertexBuffer, VertexBufferUsage},
+        vertex_ring_buffer::{VertexRange, VertexRingBuffer},
         view::View,
     },
     crate::ffi::graphics::ShaderType,
 };
 
+mod animated_sprite;
 pub mod blend_mode;
 mod circle_shape;
 mod color;
@@ -50,6 +66,9 @@ mod font;
 pub mod glsl;
 mod glyph;
 mod image;
+mod json;
+mod nine_slice;
+mod particle_system;
 mod primitive_type;
 mod rc_font;
 mod rc_sprite;
@@ -64,13 +83,21 @@ mod render_window;
 mod shader;
 mod shape;
 mod sprite;
+mod sprite_batch;
+mod sprite_sheet;
 mod text;
+mod text_layout;
 mod text_style;
 mod texture;
+mod texture_atlas;
+mod tile_map;
+pub mod tiled;
 mod transform;
 mod transformable;
+mod transformation;
 mod vertex;
 mod vertex_buffer;
+mod vertex_ring_buffer;
 mod view;
 
 /// Compute the bounding rectangle of the vertex array.
@@ -0,0 +1,542 @@
+use {
+    crate::{
+        cpp::FBox,
+        graphics::{
+            Color, Drawable, FloatRect, IntRect, PrimitiveType, RcTexture, RenderStates,
+            RenderTarget, Texture, Transformable, Vertex,
+            transformation::{Transformation, impl_transformable},
+        },
+        system::Vector2f,
+    },
+    std::{cell::RefCell, rc::Weak},
+};
+
+/// How the edges or the centre of a [`NineSlice`] fill their area.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum SliceMode {
+    /// Stretch the slice over its area
+    #[default]
+    Stretch,
+    /// Repeat the slice at its size, cutting the last repetition short.
+    ///
+    /// If tiling would take more than 262 144 quads over the whole [`NineSlice`], every slice is
+    /// stretched instead.
+    Tile,
+}
+
+/// The widths of the borders of a [`NineSlice`], in texture pixels.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Margins {
+    /// The width of the left border
+    pub left: u32,
+    /// The height of the top border
+    pub top: u32,
+    /// The width of the right border
+    pub right: u32,
+    /// The height of the bottom border
+    pub bottom: u32,
+}
+
+impl Margins {
+    /// Create margins from the width of each border.
+    #[must_use]
+    pub const fn new(left: u32, top: u32, right: u32, bottom: u32) -> Self {
+        Self {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+    /// Create margins with the same width on all sides.
+    #[must_use]
+    pub const fn uniform(width: u32) -> Self {
+        Self::new(width, width, width, width)
+    }
+}
+
+/// The geometry shared by [`NineSlice`] and [`RcNineSlice`].
+#[derive(Clone, Debug)]
+struct Slices {
+    texture_rect: IntRect,
+    margins: Margins,
+    size: Vector2f,
+    edge_mode: SliceMode,
+    center_mode: SliceMode,
+    color: Color,
+    vertices: Vec<Vertex>,
+}
+
+/// A stretch of one axis of a nine-slice: the range it covers and the texture range it shows.
+#[derive(Clone, Copy, Debug)]
+struct Piece {
+    start: f32,
+    end: f32,
+    tex_start: f32,
+    tex_end: f32,
+}
+
+impl Slices {
+    fn new(texture_rect: IntRect, margins: Margins) -> Self {
+        let mut slices = Self {
+            texture_rect,
+            margins,
+            size: texture_rect.size.as_other(),
+            edge_mode: SliceMode::Stretch,
+            center_mode: SliceMode::Stretch,
+            color: Color::WHITE,
+            vertices: Vec::new(),
+        };
+        slices.update();
+        slices
+    }
+    /// Rebuild the vertices, as two triangles per quad.
+    fn update(&mut self) {
+        let rect = self.texture_rect.as_other::<f32>();
+        let margins = self.margins;
+        let columns = axis_pieces(
+            self.size.x,
+            rect.position.x,
+            rect.size.x,
+            margins.left as f32,
+            margins.right as f32,
+        );
+        let rows = axis_pieces(
+            self.size.y,
+            rect.position.y,
+            rect.size.y,
+            margins.top as f32,
+            margins.bottom as f32,
+        );
+        let (edge_mode, center_mode) = (self.edge_mode, self.center_mode);
+        let band_mode = |row_band, column_band| match (row_band, column_band) {
+            (1, 1) => center_mode,
+            (1, _) | (_, 1) => edge_mode,
+            _ => SliceMode::Stretch,
+        };
+        let quads: f32 = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(row_band, row)| {
+                columns
+                    .iter()
+                    .enumerate()
+                    .map(move |(column_band, column)| {
+                        let mode = band_mode(row_band, column_band);
+                        tile_count(*row, mode) * tile_count(*column, mode)
+                    })
+            })
+            .sum();
+        self.vertices.clear();
+        for (row_band, row) in rows.iter().enumerate() {
+            for (column_band, column) in columns.iter().enumerate() {
+                let mode = if quads > MAX_QUADS {
+                    SliceMode::Stretch
+                } else {
+                    band_mode(row_band, column_band)
+                };
+                for y in split(*row, mode) {
+                    for x in split(*column, mode) {
+                        let corner = |x: f32, y: f32, u: f32, v: f32| Vertex {
+                            position: Vector2f::new(x, y),
+                            color: self.color,
+                            tex_coords: Vector2f::new(u, v),
+                        };
+                        let top_left = corner(x.start, y.start, x.tex_start, y.tex_start);
+                        let top_right = corner(x.end, y.start, x.tex_end, y.tex_start);
+                        let bottom_left = corner(x.start, y.end, x.tex_start, y.tex_end);
+                        let bottom_right = corner(x.end, y.end, x.tex_end, y.tex_end);
+                        self.vertices.extend([
+                            top_left,
+                            top_right,
+                            bottom_left,
+                            bottom_left,
+                            top_right,
+                            bottom_right,
+                        ]);
+                    }
+                }
+            }
+        }
+    }
+    fn draw(
+        &self,
+        texture: &Texture,
+        transformation: &Transformation,
+        target: &mut dyn RenderTarget,
+        states: &RenderStates,
+    ) {
+        let mut states = *states;
+        states.transform.combine(transformation.transform());
+        states.texture = Some(texture);
+        target.draw_primitives(&self.vertices, PrimitiveType::TRIANGLES, &states);
+    }
+}
+
+/// Split `size` into the two borders and the middle band, shrinking the borders if they don't
+/// fit. Empty bands are kept with a length of 0, and a size that isn't finite empties them all.
+fn axis_pieces(size: f32, tex_start: f32, tex_size: f32, first: f32, last: f32) -> Vec<Piece> {
+    let size = if size.is_finite() { size.max(0.) } else { 0. };
+    let shrink = if first + last > size {
+        size / (first + last)
+    } else {
+        1.
+    };
+    let (first_size, last_size) = (first * shrink, last * shrink);
+    let tex_end = tex_start + tex_size;
+    [
+        Piece {
+            start: 0.,
+            end: first_size,
+            tex_start,
+            tex_end: tex_start + first,
+        },
+        Piece {
+            start: first_size,
+            end: size - last_size,
+            tex_start: tex_start + first,
+            tex_end: tex_end - last,
+        },
+        Piece {
+            start: size - last_size,
+            end: size,
+            tex_start: tex_end - last,
+            tex_end,
+        },
+    ]
+    .into_iter()
+    // Keeping the empty pieces keeps the band indices, while drawing nothing
+    .map(|piece| {
+        if piece.end > piece.start {
+            piece
+        } else {
+            Piece {
+                end: piece.start,
+                ..piece
+            }
+        }
+    })
+    .collect()
+}
+
+/// The most quads a nine-slice is cut into, beyond which all its slices are stretched instead.
+const MAX_QUADS: f32 = 262_144.;
+
+/// The number of pieces [`split`] cuts `piece` into.
+fn tile_count(piece: Piece, mode: SliceMode) -> f32 {
+    let tile = piece.tex_end - piece.tex_start;
+    if piece.end <= piece.start {
+        0.
+    } else if mode == SliceMode::Stretch || tile <= 0. {
+        1.
+    } else {
+        ((piece.end - piece.start) / tile).ceil()
+    }
+}
+
+/// Cut a piece into repetitions of its texture range for [`SliceMode::Tile`].
+fn split(piece: Piece, mode: SliceMode) -> Vec<Piece> {
+    let tile = piece.tex_end - piece.tex_start;
+    #[expect(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        reason = "the count is at most MAX_QUADS"
+    )]
+    let count = tile_count(piece, mode) as usize;
+    if count <= 1 {
+        return vec![piece; count];
+    }
+    (0..count)
+        .map(|i| {
+            #[expect(clippy::cast_precision_loss, reason = "i is below MAX_QUADS")]
+            let start = piece.start + tile * i as f32;
+            let end = (start + tile).min(piece.end);
+            Piece {
+                start,
+                end,
+                tex_start: piece.tex_start,
+                tex_end: piece.tex_start + (end - start),
+            }
+        })
+        .collect()
+}
+
+macro_rules! nine_slice_methods {
+    () => {
+        /// Set the part of the texture to show, including the borders.
+        pub fn set_texture_rect(&mut self, rect: IntRect) {
+            self.slices.texture_rect = rect;
+            self.slices.update();
+        }
+        /// The part of the texture shown, including the borders.
+        #[must_use]
+        pub fn texture_rect(&self) -> IntRect {
+            self.slices.texture_rect
+        }
+        /// Set the widths of the borders, in texture pixels.
+        pub fn set_margins(&mut self, margins: Margins) {
+            self.slices.margins = margins;
+            self.slices.update();
+        }
+        /// The widths of the borders, in texture pixels.
+        #[must_use]
+        pub fn margins(&self) -> Margins {
+            self.slices.margins
+        }
+        /// Set the size of the drawn panel, before transformations.
+        ///
+        /// The borders keep their size, unless the panel is smaller than them, in which case
+        /// they are shrunk to fit.
+        pub fn set_size<S: Into<Vector2f>>(&mut self, size: S) {
+            self.slices.size = size.into();
+            self.slices.update();
+        }
+        /// The size of the drawn panel, before transformations.
+        #[must_use]
+        pub fn size(&self) -> Vector2f {
+            self.slices.size
+        }
+        /// Set how the four edges fill their area.
+        pub fn set_edge_mode(&mut self, mode: SliceMode) {
+            self.slices.edge_mode = mode;
+            self.slices.update();
+        }
+        /// How the four edges fill their area.
+        #[must_use]
+        pub fn edge_mode(&self) -> SliceMode {
+            self.slices.edge_mode
+        }
+        /// Set how the centre fills its area.
+        pub fn set_center_mode(&mut self, mode: SliceMode) {
+            self.slices.center_mode = mode;
+            self.slices.update();
+        }
+        /// How the centre fills its area.
+        #[must_use]
+        pub fn center_mode(&self) -> SliceMode {
+            self.slices.center_mode
+        }
+        /// Set the color the texture is modulated with.
+        ///
+        /// By default, the color is opaque white.
+        pub fn set_color(&mut self, color: Color) {
+            self.slices.color = color;
+            for vertex in &mut self.slices.vertices {
+                vertex.color = color;
+            }
+        }
+        /// The color the texture is modulated with.
+        #[must_use]
+        pub fn color(&self) -> Color {
+            self.slices.color
+        }
+        /// The bounds of the panel, before transformations.
+        #[must_use]
+        pub fn local_bounds(&self) -> FloatRect {
+            FloatRect::new(Vector2f::new(0., 0.), self.slices.size)
+        }
+        /// The bounds of the panel, after transformations.
+        #[must_use]
+        pub fn global_bounds(&self) -> FloatRect {
+            self.transform().transform_rect(self.local_bounds())
+        }
+    };
+}
+
+/// A textured panel whose borders keep their size when it is resized.
+///
+/// The texture rect is cut into nine slices by the [`Margins`]: the four corners are drawn at
+/// their size, the four edges are stretched or tiled along their length, and the centre is
+/// stretched or tiled both ways, to fill any [`size`](Self::set_size). This is how frames,
+/// buttons and dialog boxes are usually drawn.
+///
+/// See [`RcNineSlice`] for a version holding an [`RcTexture`].
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::graphics::*;
+/// # let mut window: RenderWindow = unimplemented!();
+/// let texture = Texture::from_file("panel.png").unwrap();
+/// let rect = IntRect::new((0, 0).into(), (48, 48).into());
+/// let mut panel = NineSlice::new(&texture, rect, Margins::uniform(16));
+/// panel.set_size((300., 120.));
+/// panel.set_center_mode(SliceMode::Tile);
+/// panel.set_position((20., 20.));
+/// window.draw(&panel);
+/// ```
+#[derive(Clone, Debug)]
+pub struct NineSlice<'texture> {
+    texture: &'texture Texture,
+    slices: Slices,
+    transformation: Transformation,
+}
+
+impl<'texture> NineSlice<'texture> {
+    /// Create a panel showing `texture_rect` of `texture`, with the given border widths.
+    ///
+    /// The panel starts at the size of `texture_rect`.
+    #[must_use]
+    pub fn new(texture: &'texture Texture, texture_rect: IntRect, margins: Margins) -> Self {
+        Self {
+            texture,
+            slices: Slices::new(texture_rect, margins),
+            transformation: Transformation::default(),
+        }
+    }
+    /// Change the texture, keeping the texture rect.
+    pub fn set_texture(&mut self, texture: &'texture Texture) {
+        self.texture = texture;
+    }
+    /// The texture of the panel.
+    #[must_use]
+    pub fn texture(&self) -> &'texture Texture {
+        self.texture
+    }
+    nine_slice_methods!();
+}
+
+impl Drawable for NineSlice<'_> {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn RenderTarget,
+        states: &RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        self.slices
+            .draw(self.texture, &self.transformation, target, states);
+    }
+}
+
+impl_transformable!(NineSlice<'_>, transformation);
+
+/// A [`NineSlice`] holding an [`RcTexture`] (reference counted)
+///
+/// The panel isn't drawn once the texture is dropped.
+#[derive(Clone, Debug)]
+pub struct RcNineSlice {
+    texture: Weak<RefCell<FBox<Texture>>>,
+    slices: Slices,
+    transformation: Transformation,
+}
+
+impl RcNineSlice {
+    /// Create a panel showing `texture_rect` of `texture`, with the given border widths.
+    ///
+    /// The panel starts at the size of `texture_rect`.
+    #[must_use]
+    pub fn new(texture: &RcTexture, texture_rect: IntRect, margins: Margins) -> Self {
+        Self {
+            texture: texture.downgrade(),
+            slices: Slices::new(texture_rect, margins),
+            transformation: Transformation::default(),
+        }
+    }
+    /// Change the texture, keeping the texture rect.
+    pub fn set_texture(&mut self, texture: &RcTexture) {
+        self.texture = texture.downgrade();
+    }
+    /// Whether the texture is still alive.
+    #[must_use]
+    pub fn texture_exists(&self) -> bool {
+        self.texture.strong_count() != 0
+    }
+    nine_slice_methods!();
+}
+
+impl Drawable for RcNineSlice {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn RenderTarget,
+        states: &RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        if let Some(texture) = self.texture.upgrade() {
+            self.slices
+                .draw(&texture.borrow(), &self.transformation, target, states);
+        }
+    }
+}
+
+impl_transformable!(RcNineSlice, transformation);
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{Margins, SliceMode, Slices},
+        crate::{graphics::IntRect, system::Vector2f},
+    };
+
+    /// The positions and texture coordinates of the top left corner of each quad.
+    fn quads(slices: &Slices) -> Vec<((f32, f32), (f32, f32))> {
+        slices
+            .vertices
+            .chunks(6)
+            .map(|quad| {
+                let (p, t) = (quad[0].position, quad[0].tex_coords);
+                ((p.x, p.y), (t.x, t.y))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn borders_keep_their_size() {
+        let rect = IntRect::new((10, 0).into(), (30, 30).into());
+        let mut slices = Slices::new(rect, Margins::new(5, 10, 5, 10));
+        slices.size = Vector2f::new(100., 50.);
+        slices.update();
+        assert_eq!(slices.vertices.len(), 9 * 6);
+        let bottom_right = &slices.vertices[8 * 6..];
+        assert_eq!(bottom_right[0].position, Vector2f::new(95., 40.));
+        assert_eq!(bottom_right[5].position, Vector2f::new(100., 50.));
+        assert_eq!(bottom_right[0].tex_coords, Vector2f::new(35., 20.));
+        assert_eq!(bottom_right[5].tex_coords, Vector2f::new(40., 30.));
+        // The centre stretches the 20 by 10 middle of the texture
+        let center = &slices.vertices[4 * 6..5 * 6];
+        assert_eq!(center[5].position, Vector2f::new(95., 40.));
+        assert_eq!(center[5].tex_coords, Vector2f::new(35., 20.));
+
+        // Borders wider than the panel are shrunk
+        slices.size = Vector2f::new(5., 50.);
+        slices.update();
+        assert_eq!(slices.vertices.len(), 6 * 6);
+        assert_eq!(slices.vertices[5].position, Vector2f::new(2.5, 10.));
+    }
+
+    #[test]
+    fn tiles_are_cut_at_the_end() {
+        let rect = IntRect::new((0, 0).into(), (12, 12).into());
+        let mut slices = Slices::new(rect, Margins::uniform(4));
+        slices.size = Vector2f::new(18., 12.);
+        slices.edge_mode = SliceMode::Tile;
+        slices.center_mode = SliceMode::Tile;
+        slices.update();
+        // 10 pixels of middle make a 4 pixel tile, another one and a 2 pixel one
+        let top: Vec<_> = quads(&slices).into_iter().filter(|q| q.0.1 == 0.).collect();
+        assert_eq!(
+            top,
+            [
+                ((0., 0.), (0., 0.)),
+                ((4., 0.), (4., 0.)),
+                ((8., 0.), (4., 0.)),
+                ((12., 0.), (4., 0.)),
+                ((14., 0.), (8., 0.)),
+            ]
+        );
+        let last_tile = &slices.vertices[3 * 6..4 * 6];
+        assert_eq!(last_tile[5].position, Vector2f::new(14., 4.));
+        assert_eq!(last_tile[5].tex_coords, Vector2f::new(6., 4.));
+        assert_eq!(slices.vertices.len(), 5 * 3 * 6);
+
+        // A 4 pixel tile over a full HD screen still tiles
+        slices.size = Vector2f::new(1920., 1080.);
+        slices.update();
+        assert_eq!(slices.vertices.len(), 480 * 270 * 6);
+
+        // Too many tiles stretch, and sizes that aren't finite draw nothing
+        slices.size = Vector2f::new(1e6, 12.);
+        slices.update();
+        assert_eq!(slices.vertices.len(), 9 * 6);
+        slices.size = Vector2f::new(f32::INFINITY, 12.);
+        slices.update();
+        assert!(slices.vertices.is_empty());
+    }
+}
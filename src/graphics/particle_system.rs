@@ -0,0 +1,667 @@
+use {
+    crate::{
+        graphics::{
+            BlendMode, Color, Drawable, FloatRect, IntRect, PrimitiveType, RenderStates,
+            RenderTarget, Texture, Transformable, Vertex,
+            transformation::{Transformation, impl_transformable},
+        },
+        system::{Angle, Time, Vector2f},
+    },
+    std::{f32::consts::TAU, ops::Range},
+};
+
+/// A particle of a [`ParticleSystem`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Particle {
+    /// The position of the centre of the particle
+    pub position: Vector2f,
+    /// The velocity, in units per second
+    pub velocity: Vector2f,
+    /// The rotation around the centre
+    pub rotation: Angle,
+    /// The rotation speed, per second
+    pub spin: Angle,
+    /// The size of the drawn quad
+    pub size: Vector2f,
+    /// The color the quad is filled or its texture modulated with
+    pub color: Color,
+    /// The area of the texture shown on the quad
+    pub texture_rect: IntRect,
+    /// The time since the particle was emitted
+    pub age: Time,
+    /// The time after which the particle disappears
+    pub lifetime: Time,
+}
+
+impl Particle {
+    /// The fraction of its lifetime the particle has lived, from 0 to 1.
+    #[must_use]
+    pub fn life(&self) -> f32 {
+        if self.lifetime <= Time::ZERO {
+            1.
+        } else {
+            (self.age.as_seconds() / self.lifetime.as_seconds()).clamp(0., 1.)
+        }
+    }
+}
+
+/// The area an [`Emitter`] emits particles from, relative to its position.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum EmitterShape {
+    /// The position of the emitter
+    #[default]
+    Point,
+    /// A segment between two points
+    Line {
+        /// The first end of the segment
+        start: Vector2f,
+        /// The second end of the segment
+        end: Vector2f,
+    },
+    /// A disc centred on the position
+    Circle {
+        /// The radius of the disc
+        radius: f32,
+    },
+    /// A rectangle centred on the position
+    Rectangle {
+        /// The size of the rectangle
+        size: Vector2f,
+    },
+}
+
+/// Emits the particles of a [`ParticleSystem`], at a steady rate or in bursts.
+///
+/// The new particles get random values within the ranges of the emitter. Ranges whose start
+/// and end are equal give every particle the same value.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Emitter {
+    /// The position of the emitter, in the coordinates of the system
+    pub position: Vector2f,
+    /// The area the particles start from
+    pub shape: EmitterShape,
+    /// The number of particles emitted per second
+    pub rate: f32,
+    /// The direction the particles move to
+    pub direction: Angle,
+    /// The width of the cone of directions around `direction`
+    pub spread: Angle,
+    /// The speed of the particles, in units per second
+    pub speed: Range<f32>,
+    /// The lifetime of the particles
+    pub lifetime: Range<Time>,
+    /// The rotation of the particles
+    pub rotation: Range<Angle>,
+    /// The rotation speed of the particles, per second
+    pub spin: Range<Angle>,
+    /// The size of the particles
+    pub size: Vector2f,
+    /// The color of the particles
+    pub color: Color,
+    /// The area of the texture shown on the particles
+    pub texture_rect: IntRect,
+}
+
+impl Default for Emitter {
+    /// An emitter of white 4 by 4 particles going up at 50 units per second for a second, with
+    /// a rate of 0.
+    fn default() -> Self {
+        Self {
+            position: Vector2f::new(0., 0.),
+            shape: EmitterShape::Point,
+            rate: 0.,
+            direction: Angle::degrees(-90.),
+            spread: Angle::default(),
+            speed: 50.0..50.0,
+            lifetime: Time::seconds(1.)..Time::seconds(1.),
+            rotation: Angle::default()..Angle::default(),
+            spin: Angle::default()..Angle::default(),
+            size: Vector2f::new(4., 4.),
+            color: Color::WHITE,
+            texture_rect: IntRect::default(),
+        }
+    }
+}
+
+/// Changes the particles of a [`ParticleSystem`] over time.
+///
+/// Gradients are lists of keys at fractions of the lifetime of the particles, from 0 to 1, in
+/// increasing order. Values between keys are interpolated, and values before the first key or
+/// after the last are those of the key.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Affector {
+    /// Accelerate the particles, in units per second squared
+    Gravity(Vector2f),
+    /// Slow the particles down, losing this fraction of their velocity per second
+    Drag(f32),
+    /// Set the color of the particles over their life
+    ColorOverLife(Vec<(f32, Color)>),
+    /// Set the size of the particles over their life
+    SizeOverLife(Vec<(f32, Vector2f)>),
+    /// Rotate the particles by this angle per second, on top of their spin
+    Rotation(Angle),
+    /// Show these areas of a texture atlas one after the other, evenly over the life of the
+    /// particles
+    Frames(Vec<IntRect>),
+}
+
+impl Affector {
+    fn apply(&self, particle: &mut Particle, delta: f32) {
+        match self {
+            Self::Gravity(acceleration) => particle.velocity += *acceleration * delta,
+            Self::Drag(drag) => particle.velocity *= (-drag * delta).exp(),
+            Self::ColorOverLife(keys) => {
+                if let Some(color) = sample(keys, particle.life(), lerp_color) {
+                    particle.color = color;
+                }
+            }
+            Self::SizeOverLife(keys) => {
+                if let Some(size) = sample(keys, particle.life(), |a, b, t| a + (b - a) * t) {
+                    particle.size = size;
+                }
+            }
+            Self::Rotation(speed) => particle.rotation += *speed * delta,
+            Self::Frames(frames) => {
+                if !frames.is_empty() {
+                    #[expect(
+                        clippy::cast_possible_truncation,
+                        clippy::cast_precision_loss,
+                        clippy::cast_sign_loss,
+                        reason = "the index is clamped to the frames"
+                    )]
+                    let index =
+                        ((particle.life() * frames.len() as f32) as usize).min(frames.len() - 1);
+                    particle.texture_rect = frames[index];
+                }
+            }
+        }
+    }
+}
+
+/// The value of a gradient at `t`.
+fn sample<T: Copy>(keys: &[(f32, T)], t: f32, lerp: impl Fn(T, T, f32) -> T) -> Option<T> {
+    let (&(first_t, first), &(last_t, last)) = (keys.first()?, keys.last()?);
+    if t <= first_t {
+        return Some(first);
+    }
+    if t >= last_t {
+        return Some(last);
+    }
+    let next = keys.iter().position(|&(key_t, _)| key_t > t)?;
+    let ((start_t, start), (end_t, end)) = (keys[next - 1], keys[next]);
+    Some(lerp(start, end, (t - start_t) / (end_t - start_t)))
+}
+
+fn lerp_color(a: Color, b: Color, t: f32) -> Color {
+    #[expect(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        reason = "the result is between two u8 values"
+    )]
+    let channel = |a: u8, b: u8| (f32::from(a) + (f32::from(b) - f32::from(a)) * t).round() as u8;
+    Color::rgba(
+        channel(a.r, b.r),
+        channel(a.g, b.g),
+        channel(a.b, b.b),
+        channel(a.a, b.a),
+    )
+}
+
+/// The xorshift32 generator giving the particles their random values.
+#[derive(Clone, Copy, Debug)]
+struct Random {
+    state: u32,
+}
+
+impl Random {
+    const fn new(seed: u32) -> Self {
+        // Xorshift gets stuck on a zero state
+        Self {
+            state: if seed == 0 { 0x9E37_79B9 } else { seed },
+        }
+    }
+    /// A number from 0 to 1, excluded.
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "24 bits fit in the mantissa of an f32"
+    )]
+    fn next(&mut self) -> f32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        (x >> 8) as f32 / (1 << 24) as f32
+    }
+    fn range(&mut self, range: &Range<f32>) -> f32 {
+        range.start + (range.end - range.start) * self.next()
+    }
+}
+
+#[derive(Clone, Debug)]
+struct EmitterState {
+    emitter: Emitter,
+    enabled: bool,
+    /// The fraction of a particle left over from the previous updates
+    pending: f32,
+}
+
+/// A cloud of small quads emitted, moved and faded on the CPU, such as sparks, smoke or rain.
+///
+/// [`Emitter`]s add particles, at a rate or in [bursts](Self::burst), with random values from
+/// their ranges. [`Affector`]s then change the particles every [`update`](Self::update), until
+/// they reach the end of their lifetime. All the particles are drawn at once, from a single
+/// vertex array, untextured or showing areas of one texture.
+///
+/// The random values come from a generator seeded with [`set_seed`](Self::set_seed), so a
+/// system updated with the same times always gives the same particles.
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::graphics::*;
+/// # use sfml::system::{Angle, Clock, Time, Vector2f};
+/// # let mut window: RenderWindow = unimplemented!();
+/// let mut sparks = ParticleSystem::new();
+/// sparks.set_blend_mode(BlendMode::ADD);
+/// let fountain = sparks.add_emitter(Emitter {
+///     position: Vector2f::new(400., 500.),
+///     shape: EmitterShape::Circle { radius: 10. },
+///     rate: 200.,
+///     spread: Angle::degrees(30.),
+///     speed: 150.0..250.0,
+///     lifetime: Time::seconds(1.)..Time::seconds(2.),
+///     color: Color::YELLOW,
+///     ..Emitter::default()
+/// });
+/// sparks.add_affector(Affector::Gravity(Vector2f::new(0., 200.)));
+/// sparks.add_affector(Affector::ColorOverLife(vec![
+///     (0., Color::YELLOW),
+///     (1., Color::rgba(255, 0, 0, 0)),
+/// ]));
+/// sparks.burst(fountain, 100);
+/// let mut clock = Clock::new().unwrap();
+/// while window.is_open() {
+///     sparks.update(clock.restart());
+///     window.clear(Color::BLACK);
+///     window.draw(&sparks);
+///     window.display();
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ParticleSystem<'texture> {
+    texture: Option<&'texture Texture>,
+    emitters: Vec<EmitterState>,
+    affectors: Vec<Affector>,
+    particles: Vec<Particle>,
+    max_particles: usize,
+    random: Random,
+    blend_mode: BlendMode,
+    vertices: Vec<Vertex>,
+    transformation: Transformation,
+}
+
+impl Default for ParticleSystem<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'texture> ParticleSystem<'texture> {
+    /// Create an untextured system without emitters, drawn with [`BlendMode::ALPHA`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            texture: None,
+            emitters: Vec::new(),
+            affectors: Vec::new(),
+            particles: Vec::new(),
+            max_particles: 10_000,
+            random: Random::new(0),
+            blend_mode: BlendMode::ALPHA,
+            vertices: Vec::new(),
+            transformation: Transformation::default(),
+        }
+    }
+    /// Create a system whose particles show areas of `texture`.
+    #[must_use]
+    pub fn with_texture(texture: &'texture Texture) -> Self {
+        Self {
+            texture: Some(texture),
+            ..Self::new()
+        }
+    }
+    /// Set the texture of the particles, or draw them untextured with `None`.
+    pub fn set_texture(&mut self, texture: Option<&'texture Texture>) {
+        self.texture = texture;
+    }
+    /// The texture of the particles.
+    #[must_use]
+    pub fn texture(&self) -> Option<&'texture Texture> {
+        self.texture
+    }
+    /// Restart the random values of the particles from `seed`.
+    pub fn set_seed(&mut self, seed: u32) {
+        self.random = Random::new(seed);
+    }
+    /// Set how the particles are blended with what is behind them, such as
+    /// [`BlendMode::ADD`] for glowing particles.
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+    /// How the particles are blended with what is behind them.
+    #[must_use]
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+    /// Set the number of particles above which emitters stop emitting. The default is 10000.
+    pub fn set_max_particles(&mut self, max_particles: usize) {
+        self.max_particles = max_particles;
+    }
+    /// The number of particles above which emitters stop emitting.
+    #[must_use]
+    pub fn max_particles(&self) -> usize {
+        self.max_particles
+    }
+
+    /// Add an emitter, returning its index.
+    pub fn add_emitter(&mut self, emitter: Emitter) -> usize {
+        self.emitters.push(EmitterState {
+            emitter,
+            enabled: true,
+            pending: 0.,
+        });
+        self.emitters.len() - 1
+    }
+    /// The emitter at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[must_use]
+    pub fn emitter(&self, index: usize) -> &Emitter {
+        &self.emitters[index].emitter
+    }
+    /// The emitter at `index`, to move it or change its settings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn emitter_mut(&mut self, index: usize) -> &mut Emitter {
+        &mut self.emitters[index].emitter
+    }
+    /// Start or stop the steady emission of the emitter at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_emitter_enabled(&mut self, index: usize, enabled: bool) {
+        self.emitters[index].enabled = enabled;
+    }
+    /// Emit `count` particles at once from the emitter at `index`, whether it is enabled or not.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn burst(&mut self, index: usize, count: usize) {
+        let count = count.min(self.max_particles.saturating_sub(self.particles.len()));
+        for _ in 0..count {
+            let particle = self.emit(index);
+            self.particles.push(particle);
+        }
+        self.update_vertices();
+    }
+    /// Add an affector, applied after the ones already added.
+    pub fn add_affector(&mut self, affector: Affector) {
+        self.affectors.push(affector);
+    }
+    /// Remove all the affectors.
+    pub fn clear_affectors(&mut self) {
+        self.affectors.clear();
+    }
+    /// The particles alive, oldest first.
+    #[must_use]
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+    /// Remove all the particles.
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.vertices.clear();
+    }
+
+    /// Age, affect and move the particles by `delta`, then emit new ones.
+    ///
+    /// Should be called every frame.
+    pub fn update(&mut self, delta: Time) {
+        let seconds = delta.as_seconds();
+        self.particles.retain_mut(|particle| {
+            particle.age += delta;
+            particle.age < particle.lifetime
+        });
+        for particle in &mut self.particles {
+            for affector in &self.affectors {
+                affector.apply(particle, seconds);
+            }
+            particle.position += particle.velocity * seconds;
+            particle.rotation += particle.spin * seconds;
+        }
+        for index in 0..self.emitters.len() {
+            let state = &mut self.emitters[index];
+            if !state.enabled {
+                continue;
+            }
+            state.pending += state.emitter.rate.max(0.) * seconds;
+            #[expect(
+                clippy::cast_possible_truncation,
+                clippy::cast_sign_loss,
+                reason = "the count is positive, and capped right after"
+            )]
+            let count = state.pending.floor() as usize;
+            state.pending = state.pending.fract();
+            let count = count.min(self.max_particles.saturating_sub(self.particles.len()));
+            for _ in 0..count {
+                let particle = self.emit(index);
+                self.particles.push(particle);
+            }
+        }
+        self.update_vertices();
+    }
+    /// The bounds of the particles, before transformations.
+    #[must_use]
+    pub fn local_bounds(&self) -> FloatRect {
+        let Some(first) = self.vertices.first() else {
+            return FloatRect::default();
+        };
+        let (mut min, mut max) = (first.position, first.position);
+        for vertex in &self.vertices {
+            min = Vector2f::new(min.x.min(vertex.position.x), min.y.min(vertex.position.y));
+            max = Vector2f::new(max.x.max(vertex.position.x), max.y.max(vertex.position.y));
+        }
+        FloatRect::new(min, max - min)
+    }
+    /// The bounds of the particles, after transformations.
+    #[must_use]
+    pub fn global_bounds(&self) -> FloatRect {
+        self.transform().transform_rect(self.local_bounds())
+    }
+
+    /// A new particle from the emitter at `index`.
+    fn emit(&mut self, index: usize) -> Particle {
+        let emitter = &self.emitters[index].emitter;
+        let random = &mut self.random;
+        let offset = match emitter.shape {
+            EmitterShape::Point => Vector2f::new(0., 0.),
+            EmitterShape::Line { start, end } => start + (end - start) * random.next(),
+            EmitterShape::Circle { radius } => {
+                // The square root spreads the particles evenly over the disc
+                let distance = radius * random.next().sqrt();
+                let angle = random.next() * TAU;
+                Vector2f::new(angle.cos(), angle.sin()) * distance
+            }
+            EmitterShape::Rectangle { size } => Vector2f::new(
+                (random.next() - 0.5) * size.x,
+                (random.next() - 0.5) * size.y,
+            ),
+        };
+        let direction = emitter.direction + emitter.spread * (random.next() - 0.5);
+        let speed = random.range(&emitter.speed);
+        let lifetime = &emitter.lifetime;
+        let rotation = &emitter.rotation;
+        let spin = &emitter.spin;
+        Particle {
+            position: emitter.position + offset,
+            velocity: Vector2f::new(direction.as_radians().cos(), direction.as_radians().sin())
+                * speed,
+            rotation: rotation.start + (rotation.end - rotation.start) * random.next(),
+            spin: spin.start + (spin.end - spin.start) * random.next(),
+            size: emitter.size,
+            color: emitter.color,
+            texture_rect: emitter.texture_rect,
+            age: Time::ZERO,
+            lifetime: lifetime.start + (lifetime.end - lifetime.start) * random.next(),
+        }
+    }
+    /// Rebuild the quads of the particles.
+    fn update_vertices(&mut self) {
+        self.vertices.clear();
+        for particle in &self.particles {
+            let (sin, cos) = particle.rotation.as_radians().sin_cos();
+            let half = particle.size / 2.;
+            let corner = |x: f32, y: f32| {
+                particle.position + Vector2f::new(x * cos - y * sin, x * sin + y * cos)
+            };
+            let rect = particle.texture_rect.as_other::<f32>();
+            let (u0, v0) = (rect.position.x, rect.position.y);
+            let (u1, v1) = (u0 + rect.size.x, v0 + rect.size.y);
+            let vertex =
+                |position, u, v| Vertex::new(position, particle.color, Vector2f::new(u, v));
+            let top_left = vertex(corner(-half.x, -half.y), u0, v0);
+            let top_right = vertex(corner(half.x, -half.y), u1, v0);
+            let bottom_left = vertex(corner(-half.x, half.y), u0, v1);
+            let bottom_right = vertex(corner(half.x, half.y), u1, v1);
+            self.vertices.extend([
+                top_left,
+                top_right,
+                bottom_left,
+                bottom_left,
+                top_right,
+                bottom_right,
+            ]);
+        }
+    }
+}
+
+impl Drawable for ParticleSystem<'_> {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn RenderTarget,
+        states: &RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        if self.vertices.is_empty() {
+            return;
+        }
+        let mut states = *states;
+        states.transform.combine(self.transform());
+        states.blend_mode = self.blend_mode;
+        if let Some(texture) = self.texture {
+            states.texture = Some(texture);
+        }
+        target.draw_primitives(&self.vertices, PrimitiveType::TRIANGLES, &states);
+    }
+}
+
+impl_transformable!(ParticleSystem<'_>, transformation);
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{Affector, Emitter, EmitterShape, ParticleSystem},
+        crate::{
+            graphics::{Color, IntRect},
+            system::{Angle, Time, Vector2f, Vector2i},
+        },
+    };
+
+    fn system(seed: u32) -> ParticleSystem<'static> {
+        let mut system = ParticleSystem::new();
+        system.set_seed(seed);
+        let _ = system.add_emitter(Emitter {
+            position: Vector2f::new(100., 50.),
+            shape: EmitterShape::Rectangle {
+                size: Vector2f::new(20., 10.),
+            },
+            rate: 40.,
+            spread: Angle::degrees(90.),
+            speed: 10.0..30.0,
+            lifetime: Time::milliseconds(500)..Time::milliseconds(1500),
+            spin: Angle::degrees(-90.)..Angle::degrees(90.),
+            ..Emitter::default()
+        });
+        system.add_affector(Affector::Gravity(Vector2f::new(0., 20.)));
+        system.add_affector(Affector::Drag(0.5));
+        system.add_affector(Affector::ColorOverLife(vec![
+            (0., Color::WHITE),
+            (1., Color::TRANSPARENT),
+        ]));
+        system
+    }
+
+    #[test]
+    fn seeded_systems_are_deterministic() {
+        let (mut a, mut b, mut c) = (system(7), system(7), system(8));
+        for _ in 0..30 {
+            for system in [&mut a, &mut b, &mut c] {
+                system.update(Time::milliseconds(50));
+            }
+        }
+        // 60 particles were emitted in 1.5 seconds, the oldest have died
+        assert!(!a.particles().is_empty() && a.particles().len() < 60);
+        assert_eq!(a.particles(), b.particles());
+        assert_ne!(a.particles(), c.particles());
+        assert_eq!(a.vertices.len(), a.particles().len() * 6);
+        for particle in a.particles() {
+            // Emitted at most 10 by 5 away, then moved by 45 and fallen by 22.5 at most
+            let distance = particle.position - Vector2f::new(100., 50.);
+            assert!(particle.age < particle.lifetime);
+            assert!(
+                distance.x.abs() <= 55. && distance.y.abs() <= 72.5,
+                "{particle:?}"
+            );
+            // The color fades linearly over the life of the particle
+            let alpha = 255. * (1. - particle.life());
+            assert!(
+                (f32::from(particle.color.a) - alpha).abs() <= 1.,
+                "{particle:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn affectors_change_particles() {
+        let mut system = ParticleSystem::new();
+        let emitter = system.add_emitter(Emitter {
+            speed: 0.0..0.0,
+            lifetime: Time::seconds(4.)..Time::seconds(4.),
+            ..Emitter::default()
+        });
+        let frame = |x| IntRect::new(Vector2i::new(x, 0), Vector2i::new(8, 8));
+        system.add_affector(Affector::Gravity(Vector2f::new(0., 10.)));
+        system.add_affector(Affector::SizeOverLife(vec![
+            (0.5, Vector2f::new(4., 4.)),
+            (1., Vector2f::new(8., 2.)),
+        ]));
+        system.add_affector(Affector::Rotation(Angle::degrees(45.)));
+        system.add_affector(Affector::Frames(vec![frame(0), frame(8), frame(16)]));
+        system.burst(emitter, 1);
+        system.update(Time::seconds(3.));
+        let particle = system.particles()[0];
+        assert_eq!(particle.velocity, Vector2f::new(0., 30.));
+        assert_eq!(particle.position, Vector2f::new(0., 90.));
+        assert_eq!(particle.size, Vector2f::new(6., 3.));
+        assert!((particle.rotation.as_degrees() - 135.).abs() < 1e-3);
+        assert_eq!(particle.texture_rect, frame(16));
+        system.update(Time::seconds(1.));
+        assert!(system.particles().is_empty());
+    }
+}
@@ -247,6 +247,19 @@ pub trait RenderTarget {
     /// Draw primitives defined by a vertex buffer
     fn draw_vertex_buffer(&mut self, vertex_buffer: &VertexBuffer, rs: &RenderStates);
 
+    /// Draw `count` vertices of a vertex buffer, starting from the vertex `first`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is outside of the [`VertexBuffer::vertex_count`] vertices of the buffer.
+    fn draw_vertex_buffer_range(
+        &mut self,
+        vertex_buffer: &VertexBuffer,
+        first: usize,
+        count: usize,
+        rs: &RenderStates,
+    );
+
     /// Draw primitives defined by an array of vertices.
     fn draw_primitives(&mut self, vertices: &[Vertex], ty: PrimitiveType, rs: &RenderStates);
 }
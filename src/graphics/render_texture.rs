@@ -200,6 +200,20 @@ impl RenderTarget for RenderTexture {
     fn draw_vertex_buffer(&mut self, vertex_buffer: &VertexBuffer, rs: &RenderStates) {
         unsafe { ffi::sfRenderTexture_drawVertexBuffer(self, vertex_buffer, rs) }
     }
+    fn draw_vertex_buffer_range(
+        &mut self,
+        vertex_buffer: &VertexBuffer,
+        first: usize,
+        count: usize,
+        rs: &RenderStates,
+    ) {
+        assert!(
+            first
+                .checked_add(count)
+                .is_some_and(|end| end <= vertex_buffer.vertex_count())
+        );
+        unsafe { ffi::sfRenderTexture_drawVertexBufferRange(self, vertex_buffer, first, count, rs) }
+    }
     fn draw_primitives(&mut self, vertices: &[Vertex], ty: PrimitiveType, rs: &RenderStates) {
         let len = vertices.len();
         unsafe {
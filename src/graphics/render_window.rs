@@ -9,7 +9,9 @@ use crate::{
     },
     system::{SfStrConv, Time, Vector2f, Vector2i, Vector2u},
     window::{
-        ContextSettings, Cursor, Event, Handle, Style, VideoMode, thread_safety,
+        ContextSettings, Cursor, Event, EventHandler, EventSource, Handle, Style, VideoMode,
+        event_recording, thread_safety,
+        vulkan::{VkAllocationCallbacks, VkInstance, VkSurfaceKHR},
         window_enums::State,
     },
 };
@@ -102,6 +104,9 @@ impl RenderWindow {
     ///
     /// Returns `Some(event)` if an event was returned, or `None` if the event queue was empty
     pub fn poll_event(&mut self) -> Option<Event> {
+        if let Some(event) = event_recording::take_injected_event(self.as_injection_key()) {
+            return Some(event);
+        }
         let mut event = std::mem::MaybeUninit::uninit();
         let have_event = unsafe { ffi::sfRenderWindow_pollEvent(self, event.as_mut_ptr()) };
         if have_event {
@@ -122,6 +127,9 @@ impl RenderWindow {
     ///
     /// Returns `Some(event)` or `None` if an error has occured
     pub fn wait_event(&mut self, timeout: Time) -> Option<Event> {
+        if let Some(event) = event_recording::take_injected_event(self.as_injection_key()) {
+            return Some(event);
+        }
         let mut event = std::mem::MaybeUninit::uninit();
         let have_event = unsafe {
             ffi::sfRenderWindow_waitEvent(self, event.as_mut_ptr(), timeout.as_microseconds())
@@ -132,6 +140,52 @@ impl RenderWindow {
             None
         }
     }
+
+    /// Call the method of `handler` matching each pending event, until the event queue is empty
+    ///
+    /// This replaces the usual `while let Some(event) = window.poll_event()` loop.
+    /// See [`EventHandler`] for an example.
+    ///
+    /// Returns the number of events handled
+    pub fn handle_events<H: EventHandler + ?Sized>(&mut self, handler: &mut H) -> usize {
+        let mut count = 0;
+        while let Some(event) = self.poll_event() {
+            event.dispatch(handler);
+            count += 1;
+        }
+        count
+    }
+
+    /// Wait for an event, then handle it and all the other pending events
+    ///
+    /// This is the blocking version of [`Self::handle_events`], waiting like
+    /// [`Self::wait_event`] with `timeout` if no event is pending.
+    ///
+    /// Returns the number of events handled, which is 0 if the timeout was reached
+    pub fn wait_and_handle_events<H: EventHandler + ?Sized>(
+        &mut self,
+        handler: &mut H,
+        timeout: Time,
+    ) -> usize {
+        let Some(event) = self.wait_event(timeout) else {
+            return 0;
+        };
+        event.dispatch(handler);
+        1 + self.handle_events(handler)
+    }
+
+    /// Add a synthetic event to the event queue of the window
+    ///
+    /// Synthetic events are returned by [`Self::poll_event`] and [`Self::wait_event`] before
+    /// the events of the system, in the order they were pushed.
+    /// This lets tests simulate user input.
+    pub fn push_event(&mut self, event: Event) {
+        event_recording::inject_event(self.as_injection_key(), event);
+    }
+
+    fn as_injection_key(&self) -> *const () {
+        std::ptr::from_ref(self).cast()
+    }
 }
 
 /// Rendering. See also [`RenderTarget`], which `RenderWindow` implements.
@@ -412,6 +466,32 @@ impl RenderWindow {
     pub fn request_focus(&mut self) {
         unsafe { ffi::sfRenderWindow_requestFocus(self) }
     }
+
+    /// Create a Vulkan rendering surface for the window.
+    ///
+    /// `instance` must have the [`vulkan::graphics_required_instance_extensions`] enabled.
+    /// `allocator` are the Vulkan allocation callbacks to use, or null for the default ones.
+    ///
+    /// Returns the new surface, which has to be destroyed with `vkDestroySurfaceKHR`
+    /// before the window.
+    ///
+    /// [`vulkan::graphics_required_instance_extensions`]:
+    ///     crate::window::vulkan::graphics_required_instance_extensions
+    ///
+    /// # Safety
+    ///
+    /// `instance` must be a valid Vulkan instance, and `allocator` either null or valid
+    /// allocation callbacks.
+    pub unsafe fn create_vulkan_surface(
+        &mut self,
+        instance: VkInstance,
+        allocator: *const VkAllocationCallbacks,
+    ) -> SfResult<VkSurfaceKHR> {
+        let mut surface = 0;
+        unsafe { ffi::sfRenderWindow_createVulkanSurface(self, instance, &mut surface, allocator) }
+            .into_sf_result()?;
+        Ok(surface)
+    }
 }
 
 /// System integration
@@ -503,6 +583,28 @@ impl RenderTarget for RenderWindow {
     fn draw_vertex_buffer(&mut self, vertex_buffer: &VertexBuffer, render_states: &RenderStates) {
         unsafe { ffi::sfRenderWindow_drawVertexBuffer(self, vertex_buffer, render_states) }
     }
+    fn draw_vertex_buffer_range(
+        &mut self,
+        vertex_buffer: &VertexBuffer,
+        first: usize,
+        count: usize,
+        render_states: &RenderStates,
+    ) {
+        assert!(
+            first
+                .checked_add(count)
+                .is_some_and(|end| end <= vertex_buffer.vertex_count())
+        );
+        unsafe {
+            ffi::sfRenderWindow_drawVertexBufferRange(
+                self,
+                vertex_buffer,
+                first,
+                count,
+                render_states,
+            );
+        }
+    }
     fn draw_primitives(&mut self, vertices: &[Vertex], ty: PrimitiveType, rs: &RenderStates) {
         unsafe {
             ffi::sfRenderWindow_drawPrimitives(
@@ -528,8 +630,18 @@ impl RenderTarget for RenderWindow {
     }
 }
 
+impl EventSource for RenderWindow {
+    fn poll_event(&mut self) -> Option<Event> {
+        RenderWindow::poll_event(self)
+    }
+    fn wait_event(&mut self, timeout: Time) -> Option<Event> {
+        RenderWindow::wait_event(self, timeout)
+    }
+}
+
 impl Drop for RenderWindow {
     fn drop(&mut self) {
+        event_recording::forget_injected_events(self.as_injection_key());
         unsafe {
             ffi::sfRenderWindow_del(self);
         }
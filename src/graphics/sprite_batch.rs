@@ -0,0 +1,370 @@
+use {
+    crate::{
+        SfResult,
+        graphics::{
+            Color, IntRect, PrimitiveType, RenderStates, RenderTarget, Sprite, Texture, Transform,
+            Transformable, Vertex, VertexRingBuffer,
+        },
+        system::Vector2f,
+    },
+    std::ops::Range,
+};
+
+/// The number of vertices of a quad, drawn as two triangles.
+const QUAD_VERTICES: usize = 6;
+
+/// The order in which a [`SpriteBatch`] draws its quads.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum BatchSortMode {
+    /// Draw the quads in the order they were pushed, merging consecutive quads with the same
+    /// texture
+    Submission,
+    /// Draw the quads from the lowest depth to the highest, grouping quads of the same depth by
+    /// texture. Quads with the same depth and texture keep the order they were pushed in.
+    #[default]
+    Depth,
+    /// Group all the quads by texture, with one draw call per texture, and draw each group from
+    /// the lowest depth to the highest. Only suitable when quads of different textures don't
+    /// overlap.
+    Texture,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Quad {
+    texture: usize,
+    depth: f32,
+    vertices: [Vertex; 4],
+}
+
+#[derive(Debug)]
+enum Storage {
+    Array,
+    Stream(VertexRingBuffer),
+}
+
+/// Draws many textured quads in a few draw calls.
+///
+/// Each sprite drawn on its own costs a draw call. A batch accumulates quads with [`push`] and
+/// [`push_sprite`], transformed on the CPU, then [`flush`] sorts them by depth and texture and
+/// draws each run of quads with the same texture at once, through
+/// [`draw_primitives`](RenderTarget::draw_primitives) or a streaming vertex buffer.
+///
+/// [`push`]: Self::push
+/// [`push_sprite`]: Self::push_sprite
+/// [`flush`]: Self::flush
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::graphics::*;
+/// # let mut window: RenderWindow = unimplemented!();
+/// # let texture: Texture = unimplemented!();
+/// # let bullets: Vec<sfml::system::Vector2f> = Vec::new();
+/// let mut batch = SpriteBatch::new();
+/// while window.is_open() {
+///     window.clear(Color::BLACK);
+///     for &position in &bullets {
+///         let mut transform = Transform::IDENTITY;
+///         transform.translate(position);
+///         let rect = IntRect::new((0, 0).into(), (8, 8).into());
+///         batch.push(&texture, &transform, rect, Color::WHITE, 0.);
+///     }
+///     batch.flush(&mut window, &RenderStates::DEFAULT).unwrap();
+///     window.display();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SpriteBatch<'texture> {
+    textures: Vec<&'texture Texture>,
+    quads: Vec<Quad>,
+    vertices: Vec<Vertex>,
+    sort_mode: BatchSortMode,
+    storage: Storage,
+    draw_calls: usize,
+}
+
+impl Default for SpriteBatch<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'texture> SpriteBatch<'texture> {
+    /// Create a batch that draws vertex arrays, with [`BatchSortMode::Depth`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            textures: Vec::new(),
+            quads: Vec::new(),
+            vertices: Vec::new(),
+            sort_mode: BatchSortMode::Depth,
+            storage: Storage::Array,
+            draw_calls: 0,
+        }
+    }
+    /// Create a batch that streams its quads to a [`VertexRingBuffer`] of `capacity` quads.
+    ///
+    /// [`begin_frame`](Self::begin_frame) has to be called at the start of every frame. Runs of
+    /// quads bigger than the buffer are split into several draw calls, and the quads that don't
+    /// fit in the space left in the frame are drawn from a vertex array instead.
+    pub fn with_vertex_buffer(capacity: usize) -> SfResult<Self> {
+        let ring = VertexRingBuffer::new(PrimitiveType::TRIANGLES, capacity * QUAD_VERTICES)?;
+        Ok(Self {
+            storage: Storage::Stream(ring),
+            ..Self::new()
+        })
+    }
+    /// Set the order in which the quads are drawn.
+    pub fn set_sort_mode(&mut self, sort_mode: BatchSortMode) {
+        self.sort_mode = sort_mode;
+    }
+    /// The order in which the quads are drawn.
+    #[must_use]
+    pub fn sort_mode(&self) -> BatchSortMode {
+        self.sort_mode
+    }
+
+    /// Add a quad showing `texture_rect` of `texture`, tinted with `color`.
+    ///
+    /// The quad is the size of `texture_rect`, with its top left corner at the origin, and is
+    /// placed by `transform`, like a sprite. Quads with a lower `depth` are drawn first, and end
+    /// up behind the others.
+    pub fn push(
+        &mut self,
+        texture: &'texture Texture,
+        transform: &Transform,
+        texture_rect: IntRect,
+        color: Color,
+        depth: f32,
+    ) {
+        let texture = self.texture_index(texture);
+        self.push_quad(texture, transform, texture_rect, color, depth);
+    }
+    /// Add a quad that looks like `sprite`.
+    ///
+    /// Sprites without a texture are skipped.
+    pub fn push_sprite(&mut self, sprite: &Sprite<'texture>, depth: f32) {
+        if let Some(texture) = sprite.texture() {
+            self.push(
+                texture,
+                sprite.transform(),
+                sprite.texture_rect(),
+                sprite.color(),
+                depth,
+            );
+        }
+    }
+    /// Start a new frame, making the space of the vertex buffer reusable.
+    ///
+    /// Does nothing for batches that draw vertex arrays.
+    pub fn begin_frame(&mut self) {
+        if let Storage::Stream(ring) = &mut self.storage {
+            ring.begin_frame();
+        }
+    }
+    /// Draw all the quads to `target`, and remove them from the batch.
+    ///
+    /// The texture of `states` is replaced by the texture of each run of quads, its other
+    /// fields apply to all the quads. Fails if the quads couldn't be streamed to the vertex
+    /// buffer.
+    pub fn flush(&mut self, target: &mut dyn RenderTarget, states: &RenderStates) -> SfResult<()> {
+        self.draw_calls = 0;
+        let runs = self.build_runs();
+        let mut states = *states;
+        let mut result = Ok(());
+        'runs: for (texture, range) in runs {
+            states.texture = Some(self.textures[texture]);
+            let vertices = &self.vertices[range];
+            match &mut self.storage {
+                Storage::Array => {
+                    target.draw_primitives(vertices, PrimitiveType::TRIANGLES, &states);
+                    self.draw_calls += 1;
+                }
+                Storage::Stream(ring) => {
+                    for chunk in vertices.chunks(ring.capacity().max(QUAD_VERTICES)) {
+                        if chunk.len() > ring.remaining() {
+                            // The space of this frame can't be reused before it ends
+                            target.draw_primitives(chunk, PrimitiveType::TRIANGLES, &states);
+                            self.draw_calls += 1;
+                            continue;
+                        }
+                        match ring.push(chunk) {
+                            Ok(range) => ring.draw(target, range, &states),
+                            Err(error) => {
+                                result = Err(error);
+                                break 'runs;
+                            }
+                        }
+                        self.draw_calls += 1;
+                    }
+                }
+            }
+        }
+        self.clear();
+        result
+    }
+    /// Remove all the quads without drawing them.
+    pub fn clear(&mut self) {
+        self.quads.clear();
+        self.textures.clear();
+    }
+    /// The number of quads waiting to be drawn.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.quads.len()
+    }
+    /// Whether there are no quads waiting to be drawn.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.quads.is_empty()
+    }
+    /// The number of draw calls issued by the last [`flush`](Self::flush).
+    #[must_use]
+    pub fn draw_calls(&self) -> usize {
+        self.draw_calls
+    }
+
+    fn texture_index(&mut self, texture: &'texture Texture) -> usize {
+        // Consecutive quads usually share their texture
+        if let Some(last) = self.quads.last() {
+            if std::ptr::eq(self.textures[last.texture], texture) {
+                return last.texture;
+            }
+        }
+        match self.textures.iter().position(|&t| std::ptr::eq(t, texture)) {
+            Some(index) => index,
+            None => {
+                self.textures.push(texture);
+                self.textures.len() - 1
+            }
+        }
+    }
+    fn push_quad(
+        &mut self,
+        texture: usize,
+        transform: &Transform,
+        texture_rect: IntRect,
+        color: Color,
+        depth: f32,
+    ) {
+        let rect = texture_rect.as_other::<f32>();
+        let size = Vector2f::new(rect.size.x.abs(), rect.size.y.abs());
+        let (left, top) = (rect.position.x, rect.position.y);
+        let (right, bottom) = (left + rect.size.x, top + rect.size.y);
+        let corner = |x, y, u, v| Vertex {
+            position: transform_point(transform, Vector2f::new(x, y)),
+            color,
+            tex_coords: Vector2f::new(u, v),
+        };
+        self.quads.push(Quad {
+            texture,
+            depth,
+            vertices: [
+                corner(0., 0., left, top),
+                corner(size.x, 0., right, top),
+                corner(0., size.y, left, bottom),
+                corner(size.x, size.y, right, bottom),
+            ],
+        });
+    }
+    /// Sort the quads and write their vertices, returning the texture and vertex range of each
+    /// run of quads to draw at once.
+    fn build_runs(&mut self) -> Vec<(usize, Range<usize>)> {
+        match self.sort_mode {
+            BatchSortMode::Submission => {}
+            BatchSortMode::Depth => self
+                .quads
+                .sort_by(|a, b| a.depth.total_cmp(&b.depth).then(a.texture.cmp(&b.texture))),
+            BatchSortMode::Texture => self
+                .quads
+                .sort_by(|a, b| a.texture.cmp(&b.texture).then(a.depth.total_cmp(&b.depth))),
+        }
+        self.vertices.clear();
+        let mut runs: Vec<(usize, Range<usize>)> = Vec::new();
+        for quad in &self.quads {
+            let [top_left, top_right, bottom_left, bottom_right] = quad.vertices;
+            let start = self.vertices.len();
+            self.vertices.extend([
+                top_left,
+                top_right,
+                bottom_left,
+                bottom_left,
+                top_right,
+                bottom_right,
+            ]);
+            match runs.last_mut() {
+                Some((texture, range)) if *texture == quad.texture => range.end += QUAD_VERTICES,
+                _ => runs.push((quad.texture, start..start + QUAD_VERTICES)),
+            }
+        }
+        runs
+    }
+}
+
+/// Same as [`Transform::transform_point`], without going through SFML, since it's done for every
+/// corner of every quad.
+fn transform_point(transform: &Transform, point: Vector2f) -> Vector2f {
+    let m = transform.matrix();
+    Vector2f::new(
+        m[0] * point.x + m[4] * point.y + m[12],
+        m[1] * point.x + m[5] * point.y + m[13],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{BatchSortMode, SpriteBatch},
+        crate::{
+            graphics::{Color, IntRect, Transform},
+            system::Vector2f,
+        },
+    };
+
+    fn rect() -> IntRect {
+        IntRect::new((16, 0).into(), (16, 8).into())
+    }
+
+    #[test]
+    fn quads_are_transformed() {
+        let mut batch = SpriteBatch::new();
+        let transform = Transform::new(2., 0., 10., 0., 1., 20., 0., 0., 1.);
+        batch.push_quad(0, &transform, rect(), Color::RED, 0.);
+        let runs = batch.build_runs();
+        assert_eq!(runs, [(0, 0..6)]);
+        let corners: Vec<_> = batch.vertices.iter().map(|v| v.position).collect();
+        let expected = [
+            (10., 20.),
+            (42., 20.),
+            (10., 28.),
+            (10., 28.),
+            (42., 20.),
+            (42., 28.),
+        ];
+        assert_eq!(corners, expected.map(Vector2f::from));
+        assert_eq!(batch.vertices[5].tex_coords, Vector2f::new(32., 8.));
+        assert!(batch.vertices.iter().all(|v| v.color == Color::RED));
+    }
+
+    #[test]
+    fn runs_follow_the_sort_mode() {
+        let textures_and_depths = [(0, 1.), (1, 0.), (0, 0.), (1, 1.), (0, 1.)];
+        let runs = |mode| {
+            let mut batch = SpriteBatch::new();
+            batch.set_sort_mode(mode);
+            for (texture, depth) in textures_and_depths {
+                batch.push_quad(texture, &Transform::IDENTITY, rect(), Color::WHITE, depth);
+            }
+            batch.build_runs()
+        };
+        assert_eq!(
+            runs(BatchSortMode::Submission),
+            [(0, 0..6), (1, 6..12), (0, 12..18), (1, 18..24), (0, 24..30)]
+        );
+        assert_eq!(
+            runs(BatchSortMode::Depth),
+            [(0, 0..6), (1, 6..12), (0, 12..24), (1, 24..30)]
+        );
+        assert_eq!(runs(BatchSortMode::Texture), [(0, 0..18), (1, 18..30)]);
+    }
+}
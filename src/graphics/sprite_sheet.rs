@@ -0,0 +1,419 @@
+use {
+    crate::{
+        LoadError,
+        graphics::{
+            AnimationClip, Color, IntRect, PlayMode, SpriteFrame,
+            json::{INVALID_MEMBER, Value, parse_color},
+        },
+        system::{Time, Vector2i, Vector2u},
+    },
+    std::{
+        fs,
+        ops::Range,
+        path::{Path, PathBuf},
+    },
+};
+
+/// A named range of frames of a [`SpriteSheet`], played as an [`AnimationClip`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FrameTag {
+    /// The name of the clip
+    pub name: String,
+    /// The frames of the clip
+    pub frames: Range<usize>,
+    /// Whether the frames are played last to first
+    pub reverse: bool,
+    /// How the clip is played
+    pub mode: PlayMode,
+}
+
+/// A named area of a [`SpriteSheet`], such as a hitbox or the borders of a panel.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SpriteSlice {
+    /// The name of the slice
+    pub name: String,
+    /// The color of the slice in the editor
+    pub color: Color,
+    /// The user data of the slice
+    pub data: String,
+    /// The areas of the slice, each from its frame until the next key
+    pub keys: Vec<SliceKey>,
+}
+
+impl SpriteSlice {
+    /// The area of the slice on `frame`, if the slice starts there or earlier.
+    #[must_use]
+    pub fn key_at(&self, frame: usize) -> Option<&SliceKey> {
+        self.keys.iter().rev().find(|key| key.frame <= frame)
+    }
+}
+
+/// The area of a [`SpriteSlice`] from a given frame on.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SliceKey {
+    /// The first frame using this area
+    pub frame: usize,
+    /// The area, in the coordinates of the untrimmed frame
+    pub bounds: IntRect,
+    /// The centre of a nine-slice, relative to `bounds`
+    pub center: Option<IntRect>,
+    /// The pivot point, relative to `bounds`
+    pub pivot: Option<Vector2i>,
+}
+
+/// The frames of an animated sprite in one texture, with the clips and slices made of them.
+///
+/// A sheet is either cut from a grid with [`grid`], or loaded from the JSON exported by the
+/// Aseprite pixel art editor with [`from_aseprite_file`]. Its [`clips`] are then added to an
+/// [`AnimatedSprite`].
+///
+/// [`grid`]: Self::grid
+/// [`from_aseprite_file`]: Self::from_aseprite_file
+/// [`clips`]: Self::clips
+/// [`AnimatedSprite`]: crate::graphics::AnimatedSprite
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct SpriteSheet {
+    /// The image of the sheet, joined to the directory of the sheet
+    pub image: Option<PathBuf>,
+    /// The frames, in order
+    pub frames: Vec<SpriteFrame>,
+    /// The clips, made of ranges of frames
+    pub tags: Vec<FrameTag>,
+    /// The slices
+    pub slices: Vec<SpriteSlice>,
+}
+
+impl SpriteSheet {
+    /// Cut a texture of `texture_size` into frames of `frame_size`, row by row, each shown for
+    /// `duration`.
+    ///
+    /// Frames that don't fully fit are left out. The sheet has no tags; clips are made with
+    /// [`clip`](Self::clip).
+    #[must_use]
+    pub fn grid(texture_size: Vector2u, frame_size: Vector2u, duration: Time) -> Self {
+        let mut frames = Vec::new();
+        if frame_size.x > 0 && frame_size.y > 0 {
+            for row in 0..texture_size.y / frame_size.y {
+                for column in 0..texture_size.x / frame_size.x {
+                    let position = Vector2u::new(column * frame_size.x, row * frame_size.y);
+                    frames.push(SpriteFrame::new(
+                        IntRect::new(position.as_other(), frame_size.as_other()),
+                        duration,
+                    ));
+                }
+            }
+        }
+        Self {
+            frames,
+            ..Self::default()
+        }
+    }
+    /// Load the JSON data exported by Aseprite, with either the array or hash frame layout.
+    ///
+    /// Frame tags become [`FrameTag`]s: the `pingpong` directions are played with
+    /// [`PlayMode::PingPong`], tags repeated once with [`PlayMode::Once`], and other tags with
+    /// [`PlayMode::Loop`].
+    pub fn from_aseprite_file(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        Self::from_aseprite_memory(&text, path.parent().unwrap_or(Path::new("")))
+    }
+    /// Load the JSON data exported by Aseprite, from memory.
+    ///
+    /// The image path is relative to `dir`. See [`from_aseprite_file`](Self::from_aseprite_file).
+    pub fn from_aseprite_memory(text: &str, dir: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let root = Value::parse(text)?;
+        let frames: Vec<_> = match root.get("frames") {
+            Some(Value::Array(frames)) => frames.iter().map(frame).collect(),
+            Some(Value::Object(frames)) => frames.iter().map(|(_, value)| frame(value)).collect(),
+            _ => Err(LoadError::Invalid("missing frames")),
+        }?;
+        let meta = root.get("meta").unwrap_or(&Value::Null);
+        let tags = meta
+            .array_or_empty("frameTags")
+            .ok_or(INVALID)?
+            .iter()
+            .map(|value| tag(value, frames.len()))
+            .collect::<Result<_, _>>()?;
+        let slices = meta
+            .array_or_empty("slices")
+            .ok_or(INVALID)?
+            .iter()
+            .map(slice)
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            image: meta
+                .get("image")
+                .and_then(Value::as_str)
+                .map(|image| dir.as_ref().join(image)),
+            frames,
+            tags,
+            slices,
+        })
+    }
+
+    /// A clip playing `frames` in order with `mode`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frames` is out of bounds.
+    #[must_use]
+    pub fn clip(&self, frames: Range<usize>, mode: PlayMode) -> AnimationClip {
+        AnimationClip::new(self.frames[frames].to_vec(), mode)
+    }
+    /// The clips of the tags, by name.
+    #[must_use]
+    pub fn clips(&self) -> Vec<(String, AnimationClip)> {
+        self.tags
+            .iter()
+            .map(|tag| {
+                let mut clip = self.clip(tag.frames.clone(), tag.mode);
+                if tag.reverse {
+                    clip.frames.reverse();
+                }
+                (tag.name.clone(), clip)
+            })
+            .collect()
+    }
+    /// The tag called `name`.
+    #[must_use]
+    pub fn tag(&self, name: &str) -> Option<&FrameTag> {
+        self.tags.iter().find(|tag| tag.name == name)
+    }
+    /// The slice called `name`.
+    #[must_use]
+    pub fn slice(&self, name: &str) -> Option<&SpriteSlice> {
+        self.slices.iter().find(|slice| slice.name == name)
+    }
+}
+
+const INVALID: LoadError = LoadError::Invalid(INVALID_MEMBER);
+
+fn integer(value: &Value, key: &str) -> Result<i32, LoadError> {
+    value
+        .get(key)
+        .ok_or(LoadError::Invalid("missing member"))?
+        .as_i64()
+        .and_then(|number| i32::try_from(number).ok())
+        .ok_or(INVALID)
+}
+
+/// A `{ "x", "y", "w", "h" }` rectangle.
+fn rect(value: &Value) -> Result<IntRect, LoadError> {
+    Ok(IntRect::new(
+        Vector2i::new(integer(value, "x")?, integer(value, "y")?),
+        Vector2i::new(integer(value, "w")?, integer(value, "h")?),
+    ))
+}
+
+fn frame(value: &Value) -> Result<SpriteFrame, LoadError> {
+    if value.get("rotated").and_then(Value::as_bool) == Some(true) {
+        return Err(LoadError::Unsupported("rotated frames"));
+    }
+    let rect = rect(value.get("frame").ok_or(INVALID)?)?;
+    let offset = match value.get("spriteSourceSize") {
+        Some(source) => Vector2i::new(integer(source, "x")?, integer(source, "y")?),
+        None => Vector2i::new(0, 0),
+    };
+    let duration = value.required_u32("duration").map_err(LoadError::Invalid)?;
+    Ok(SpriteFrame {
+        rect,
+        offset: offset.as_other::<f32>(),
+        duration: Time::milliseconds(duration.try_into().map_err(|_| INVALID)?),
+    })
+}
+
+fn tag(value: &Value, frame_count: usize) -> Result<FrameTag, LoadError> {
+    let from = value.required_u32("from").map_err(LoadError::Invalid)? as usize;
+    let to = value.required_u32("to").map_err(LoadError::Invalid)? as usize;
+    if from > to || to >= frame_count {
+        return Err(LoadError::Invalid("tag frames out of bounds"));
+    }
+    let (reverse, ping_pong) = match value.get("direction").and_then(Value::as_str) {
+        Some("forward") | None => (false, false),
+        Some("reverse") => (true, false),
+        Some("pingpong") => (false, true),
+        Some("pingpong_reverse") => (true, true),
+        Some(_) => return Err(LoadError::Invalid("unknown tag direction")),
+    };
+    // Aseprite stores the repeat count as a string, 0 or missing meaning forever
+    let repeat = value.get("repeat").and_then(|repeat| match repeat {
+        Value::String(text) => text.parse().ok(),
+        value => value.as_u32(),
+    });
+    let mode = if ping_pong {
+        PlayMode::PingPong
+    } else if repeat == Some(1) {
+        PlayMode::Once
+    } else {
+        PlayMode::Loop
+    };
+    Ok(FrameTag {
+        name: value
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_owned(),
+        frames: from..to + 1,
+        reverse,
+        mode,
+    })
+}
+
+fn slice(value: &Value) -> Result<SpriteSlice, LoadError> {
+    let keys = value
+        .array_or_empty("keys")
+        .ok_or(INVALID)?
+        .iter()
+        .map(|key| {
+            Ok(SliceKey {
+                frame: key.required_u32("frame").map_err(LoadError::Invalid)? as usize,
+                bounds: rect(key.get("bounds").ok_or(INVALID)?)?,
+                center: key.get("center").map(rect).transpose()?,
+                pivot: key
+                    .get("pivot")
+                    .map(|pivot| {
+                        Ok::<_, LoadError>(Vector2i::new(
+                            integer(pivot, "x")?,
+                            integer(pivot, "y")?,
+                        ))
+                    })
+                    .transpose()?,
+            })
+        })
+        .collect::<Result<_, LoadError>>()?;
+    let string = |key| {
+        value
+            .get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_owned()
+    };
+    Ok(SpriteSlice {
+        name: string("name"),
+        color: value
+            .get("color")
+            .map(|color| {
+                color
+                    .as_str()
+                    .and_then(|color| parse_color(color, false))
+                    .ok_or(INVALID)
+            })
+            .transpose()?
+            .unwrap_or(Color::BLUE),
+        data: string("data"),
+        keys,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::SpriteSheet,
+        crate::{
+            LoadError,
+            graphics::{Color, IntRect, PlayMode},
+            system::{Time, Vector2f, Vector2i, Vector2u},
+        },
+    };
+
+    const ASEPRITE: &str = r##"{ "frames": {
+   "hero 0.aseprite": {
+    "frame": { "x": 0, "y": 0, "w": 14, "h": 20 },
+    "rotated": false, "trimmed": true,
+    "spriteSourceSize": { "x": 1, "y": 4, "w": 14, "h": 20 },
+    "sourceSize": { "w": 16, "h": 24 },
+    "duration": 100
+   },
+   "hero 1.aseprite": {
+    "frame": { "x": 14, "y": 0, "w": 16, "h": 24 },
+    "rotated": false, "trimmed": false,
+    "spriteSourceSize": { "x": 0, "y": 0, "w": 16, "h": 24 },
+    "sourceSize": { "w": 16, "h": 24 },
+    "duration": 150
+   },
+   "hero 2.aseprite": {
+    "frame": { "x": 30, "y": 0, "w": 16, "h": 24 },
+    "spriteSourceSize": { "x": 0, "y": 0, "w": 16, "h": 24 },
+    "duration": 100
+   }
+ },
+ "meta": {
+  "app": "https://www.aseprite.org/",
+  "image": "hero.png",
+  "size": { "w": 46, "h": 24 },
+  "frameTags": [
+   { "name": "idle", "from": 0, "to": 0, "direction": "forward", "color": "#000000ff" },
+   { "name": "walk", "from": 0, "to": 2, "direction": "pingpong_reverse" },
+   { "name": "die", "from": 1, "to": 2, "direction": "reverse", "repeat": "1" }
+  ],
+  "slices": [
+   { "name": "hitbox", "color": "#ff0000ff", "data": "solid", "keys": [
+     { "frame": 0, "bounds": { "x": 2, "y": 4, "w": 12, "h": 20 } },
+     { "frame": 2, "bounds": { "x": 3, "y": 4, "w": 10, "h": 20 }, "pivot": { "x": 5, "y": 20 } }
+   ] }
+  ]
+ }
+}"##;
+
+    #[test]
+    fn loads_aseprite_json() {
+        let sheet = SpriteSheet::from_aseprite_memory(ASEPRITE, "sprites").expect("valid sheet");
+        assert_eq!(sheet.image.as_deref(), Some("sprites/hero.png".as_ref()));
+        assert_eq!(sheet.frames.len(), 3);
+        assert_eq!(
+            sheet.frames[0].rect,
+            IntRect::new(Vector2i::new(0, 0), Vector2i::new(14, 20))
+        );
+        assert_eq!(sheet.frames[0].offset, Vector2f::new(1., 4.));
+        assert_eq!(sheet.frames[1].duration, Time::milliseconds(150));
+
+        let clips = sheet.clips();
+        let names: Vec<_> = clips.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["idle", "walk", "die"]);
+        assert_eq!(clips[0].1.mode, PlayMode::Loop);
+        assert_eq!(clips[1].1.mode, PlayMode::PingPong);
+        assert_eq!(clips[2].1.mode, PlayMode::Once);
+        // Reversed clips start from their last frame
+        assert_eq!(clips[1].1.frames[0], sheet.frames[2]);
+        assert_eq!(clips[2].1.frames, [sheet.frames[2], sheet.frames[1]]);
+
+        let hitbox = sheet.slice("hitbox").expect("hitbox slice");
+        assert_eq!(hitbox.color, Color::RED);
+        assert_eq!(hitbox.data, "solid");
+        assert_eq!(hitbox.key_at(1).map(|key| key.bounds.size.x), Some(12));
+        assert_eq!(
+            hitbox.key_at(2).and_then(|key| key.pivot),
+            Some(Vector2i::new(5, 20))
+        );
+    }
+
+    #[test]
+    fn cuts_grids_and_rejects_bad_sheets() {
+        let sheet = SpriteSheet::grid(
+            Vector2u::new(70, 40),
+            Vector2u::new(32, 16),
+            Time::milliseconds(80),
+        );
+        let positions: Vec<_> = sheet.frames.iter().map(|f| f.rect.position).collect();
+        assert_eq!(
+            positions,
+            [(0, 0), (32, 0), (0, 16), (32, 16)].map(|(x, y)| Vector2i::new(x, y))
+        );
+        assert_eq!(sheet.clip(1..3, PlayMode::Once).frames.len(), 2);
+
+        assert_eq!(
+            SpriteSheet::from_aseprite_memory("{ \"frames\": [ { \"frame\": {} } ] }", ""),
+            Err(LoadError::Invalid("missing member"))
+        );
+        let out_of_bounds = ASEPRITE.replace("\"to\": 2, \"direction\": \"reverse\"", "\"to\": 3");
+        assert_eq!(
+            SpriteSheet::from_aseprite_memory(&out_of_bounds, ""),
+            Err(LoadError::Invalid("tag frames out of bounds"))
+        );
+        assert!(matches!(
+            SpriteSheet::from_aseprite_memory("{ \"frames\": ", ""),
+            Err(LoadError::Syntax { line: 1, .. })
+        ));
+    }
+}
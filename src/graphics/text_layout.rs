@@ -0,0 +1,808 @@
+use {
+    crate::{
+        graphics::{
+            Color, Drawable, FloatRect, Font, PrimitiveType, RenderStates, RenderTarget, Text,
+            Transformable, Vertex,
+            transformation::{Transformation, impl_transformable},
+        },
+        system::Vector2f,
+    },
+    std::ops::Range,
+};
+
+/// The measurements of the glyphs of a font at a given size, which is all a [`TextLayout`]
+/// needs.
+///
+/// Implemented for fonts by [`FontMetrics`]. Implementing it with fixed values lays text out
+/// without loading a font, such as in tests.
+pub trait GlyphMetrics {
+    /// The horizontal distance from the start of `character` to the start of the next one.
+    fn advance(&self, character: char) -> f32;
+    /// The adjustment of the advance of `first` when it is followed by `second`.
+    fn kerning(&self, first: char, second: char) -> f32 {
+        let _ = (first, second);
+        0.
+    }
+    /// The vertical distance between two lines.
+    fn line_spacing(&self) -> f32;
+    /// The distance from the top of a line to its baseline.
+    fn ascent(&self) -> f32;
+}
+
+/// The [`GlyphMetrics`] of a [`Font`] at a character size, with kerning from
+/// [`Font::kerning`].
+///
+/// Lines are placed like those of a [`Text`], with the first baseline at the character size.
+#[derive(Clone, Copy, Debug)]
+pub struct FontMetrics<'font> {
+    font: &'font Font,
+    character_size: u32,
+}
+
+impl<'font> FontMetrics<'font> {
+    /// The metrics of `font` at `character_size`.
+    #[must_use]
+    pub fn new(font: &'font Font, character_size: u32) -> Self {
+        Self {
+            font,
+            character_size,
+        }
+    }
+}
+
+impl GlyphMetrics for FontMetrics<'_> {
+    fn advance(&self, character: char) -> f32 {
+        self.font
+            .glyph(u32::from(character), self.character_size, false, 0.)
+            .advance()
+    }
+    fn kerning(&self, first: char, second: char) -> f32 {
+        self.font
+            .kerning(u32::from(first), u32::from(second), self.character_size)
+    }
+    fn line_spacing(&self) -> f32 {
+        self.font.line_spacing(self.character_size)
+    }
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "character sizes are far below 2^24"
+    )]
+    fn ascent(&self) -> f32 {
+        self.character_size as f32
+    }
+}
+
+/// How the lines of a [`TextLayout`] are placed within its width.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum TextAlign {
+    /// Against the left edge
+    #[default]
+    Left,
+    /// Centred
+    Center,
+    /// Against the right edge
+    Right,
+    /// Stretched to both edges by widening the spaces, except for the last line of each
+    /// paragraph
+    Justify,
+}
+
+/// Where the lines of a [`TextLayout`] may break when they are too wide.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum WrapMode {
+    /// Between words, and within words that don't fit on a line by themselves
+    #[default]
+    Word,
+    /// Between any two characters
+    Character,
+    /// Only at line feeds
+    None,
+}
+
+/// The settings of a [`TextLayout`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct LayoutSettings {
+    /// The width lines wrap at, and are aligned within. Without it, lines only break at line
+    /// feeds and are aligned within the widest one.
+    pub max_width: Option<f32>,
+    /// The number of lines after which the text is cut off
+    pub max_lines: Option<usize>,
+    /// How the lines are aligned
+    pub align: TextAlign,
+    /// Where the lines may break
+    pub wrap: WrapMode,
+    /// The text ending the last line when the text is cut off
+    pub ellipsis: String,
+    /// The factor the line spacing of the font is multiplied by
+    pub line_spacing: f32,
+}
+
+impl Default for LayoutSettings {
+    fn default() -> Self {
+        Self {
+            max_width: None,
+            max_lines: None,
+            align: TextAlign::Left,
+            wrap: WrapMode::Word,
+            ellipsis: "\u{2026}".to_owned(),
+            line_spacing: 1.,
+        }
+    }
+}
+
+/// A character of a [`TextLayout`], at its place.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PositionedGlyph {
+    /// The character
+    pub character: char,
+    /// The byte index of the character in the laid out text, or `None` for the ellipsis
+    pub index: Option<usize>,
+    /// The start of the character on the baseline
+    pub position: Vector2f,
+    /// The horizontal space the character takes, including kerning and justification
+    pub advance: f32,
+}
+
+/// A line of a [`TextLayout`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct LayoutLine {
+    /// The bytes of the laid out text shown on the line, without the spaces it wrapped at
+    pub range: Range<usize>,
+    /// The glyphs of the line, as indices in [`TextLayout::glyphs`]
+    pub glyphs: Range<usize>,
+    /// The top left corner of the line, after alignment
+    pub position: Vector2f,
+    /// The width of the line, without trailing spaces
+    pub width: f32,
+    /// The vertical position of the baseline
+    pub baseline: f32,
+    /// The space added to each space of the line to justify it
+    pub word_spacing: f32,
+    /// Whether the line ends with the ellipsis
+    pub ellipsis: bool,
+}
+
+/// Text broken into lines and aligned within a width, with the position of each character.
+///
+/// The layout only needs the [`GlyphMetrics`] of a font. It is drawn as one [`Text`] per line
+/// with [`texts`](Self::texts), or as a vertex array of glyph quads with
+/// [`vertices`](Self::vertices). [`TextBox`] does the latter as a drawable.
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::graphics::*;
+/// # let mut window: RenderWindow = unimplemented!();
+/// let font = Font::from_file("font.ttf").unwrap();
+/// let settings = LayoutSettings {
+///     max_width: Some(300.),
+///     max_lines: Some(3),
+///     align: TextAlign::Justify,
+///     ..LayoutSettings::default()
+/// };
+/// let string = "A long line of dialogue, wrapped at word boundaries within the box.";
+/// let layout = TextLayout::new(string, &FontMetrics::new(&font, 20), &settings);
+/// println!("{} lines, {:?} in total", layout.lines().len(), layout.size());
+/// for text in layout.texts(&font, 20) {
+///     window.draw(&text);
+/// }
+/// ```
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct TextLayout {
+    lines: Vec<LayoutLine>,
+    glyphs: Vec<PositionedGlyph>,
+    line_height: f32,
+    size: Vector2f,
+    truncated: bool,
+}
+
+/// The byte index of the characters of the ellipsis, which aren't in the laid out text.
+const ELLIPSIS: usize = usize::MAX;
+
+/// A line being laid out: the characters with their byte index, before alignment.
+struct RawLine {
+    characters: Vec<(usize, char)>,
+    /// Whether the line ends a paragraph, and isn't justified
+    last: bool,
+    ellipsis: bool,
+}
+
+impl TextLayout {
+    /// Lay `text` out with `metrics` and `settings`.
+    ///
+    /// Line feeds start new paragraphs. Carriage returns are ignored.
+    #[must_use]
+    pub fn new(text: &str, metrics: &impl GlyphMetrics, settings: &LayoutSettings) -> Self {
+        let mut lines = Vec::new();
+        let mut offset = 0;
+        for paragraph in text.split('\n') {
+            let characters: Vec<_> = paragraph
+                .char_indices()
+                .filter(|&(_, c)| c != '\r')
+                .map(|(i, c)| (offset + i, c))
+                .collect();
+            offset += paragraph.len() + 1;
+            wrap_paragraph(&characters, metrics, settings, &mut lines);
+        }
+        let mut truncated = false;
+        if let Some(max_lines) = settings.max_lines {
+            if lines.len() > max_lines {
+                truncated = true;
+                lines.truncate(max_lines);
+                if let Some(last) = lines.last_mut() {
+                    add_ellipsis(last, metrics, settings);
+                }
+            }
+        }
+        let mut layout = Self {
+            line_height: metrics.line_spacing() * settings.line_spacing,
+            truncated,
+            ..Self::default()
+        };
+        layout.place(&lines, metrics, settings);
+        layout
+    }
+    /// The lines, from top to bottom.
+    #[must_use]
+    pub fn lines(&self) -> &[LayoutLine] {
+        &self.lines
+    }
+    /// The characters of all the lines, in order, including spaces.
+    #[must_use]
+    pub fn glyphs(&self) -> &[PositionedGlyph] {
+        &self.glyphs
+    }
+    /// The distance between two lines.
+    #[must_use]
+    pub fn line_height(&self) -> f32 {
+        self.line_height
+    }
+    /// The size of the laid out text: the maximum width, or the width of the widest line
+    /// without one, by the height of the lines.
+    #[must_use]
+    pub fn size(&self) -> Vector2f {
+        self.size
+    }
+    /// Whether lines were cut off by [`LayoutSettings::max_lines`].
+    #[must_use]
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+    /// The words of `line`, as ranges of indices in [`glyphs`](Self::glyphs).
+    fn words(&self, line: &LayoutLine) -> Vec<Range<usize>> {
+        let mut words = Vec::new();
+        let mut start = None;
+        for index in line.glyphs.clone() {
+            match (self.glyphs[index].character.is_whitespace(), start) {
+                (false, None) => start = Some(index),
+                (true, Some(first)) => {
+                    words.push(first..index);
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(first) = start {
+            words.push(first..line.glyphs.end);
+        }
+        words
+    }
+    /// The layout as texts of `font` at `character_size`: one per line, or one per word on
+    /// justified lines.
+    ///
+    /// The texts are positioned in the coordinates of the layout, and should be drawn with the
+    /// same transform. `font` should be the font the layout was measured with.
+    #[must_use]
+    pub fn texts<'font>(&self, font: &'font Font, character_size: u32) -> Vec<Text<'font>> {
+        let mut texts = Vec::new();
+        for line in &self.lines {
+            let segments = if line.word_spacing > 0. {
+                self.words(line)
+            } else {
+                vec![line.glyphs.clone()]
+            };
+            for segment in segments.into_iter().filter(|s| !s.is_empty()) {
+                let string: String = self.glyphs[segment.clone()]
+                    .iter()
+                    .map(|glyph| glyph.character)
+                    .collect();
+                let mut text = Text::new(string.as_str(), font, character_size);
+                let x = self.glyphs[segment.start].position.x;
+                text.set_position(Vector2f::new(x, line.position.y));
+                texts.push(text);
+            }
+        }
+        texts
+    }
+    /// The layout as quads of the glyphs of `font` at `character_size`, in `color`, to be
+    /// drawn as [`PrimitiveType::TRIANGLES`] with the texture of the font at that size.
+    ///
+    /// `font` should be the font the layout was measured with.
+    #[must_use]
+    pub fn vertices(&self, font: &Font, character_size: u32, color: Color) -> Vec<Vertex> {
+        let mut vertices = Vec::new();
+        for glyph in self.glyphs.iter().filter(|g| !g.character.is_whitespace()) {
+            let font_glyph = font.glyph(u32::from(glyph.character), character_size, false, 0.);
+            let bounds = font_glyph.bounds();
+            let rect = font_glyph.texture_rect().as_other::<f32>();
+            let (left, top) = (
+                glyph.position.x + bounds.position.x,
+                glyph.position.y + bounds.position.y,
+            );
+            let (right, bottom) = (left + bounds.size.x, top + bounds.size.y);
+            let (u0, v0) = (rect.position.x, rect.position.y);
+            let (u1, v1) = (u0 + rect.size.x, v0 + rect.size.y);
+            let vertex = |x, y, u, v| Vertex::new(Vector2f::new(x, y), color, Vector2f::new(u, v));
+            let top_left = vertex(left, top, u0, v0);
+            let top_right = vertex(right, top, u1, v0);
+            let bottom_left = vertex(left, bottom, u0, v1);
+            let bottom_right = vertex(right, bottom, u1, v1);
+            vertices.extend([
+                top_left,
+                top_right,
+                bottom_left,
+                bottom_left,
+                top_right,
+                bottom_right,
+            ]);
+        }
+        vertices
+    }
+
+    /// Align the lines and place their glyphs.
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "line and space counts are far below 2^24"
+    )]
+    fn place(&mut self, lines: &[RawLine], metrics: &impl GlyphMetrics, settings: &LayoutSettings) {
+        let widths: Vec<_> = lines
+            .iter()
+            .map(|line| advances(&line.characters, metrics).1)
+            .collect();
+        let box_width = settings
+            .max_width
+            .unwrap_or_else(|| widths.iter().copied().fold(0., f32::max));
+        for (number, (line, &width)) in lines.iter().zip(&widths).enumerate() {
+            let top = number as f32 * self.line_height;
+            let baseline = top + metrics.ascent();
+            let free = (box_width - width).max(0.);
+            let content = trimmed_len(&line.characters);
+            let spaces = line.characters[..content]
+                .iter()
+                .filter(|&&(_, c)| c == ' ')
+                .count();
+            let (left, extra_space) = match settings.align {
+                TextAlign::Left => (0., 0.),
+                TextAlign::Center => (free / 2., 0.),
+                TextAlign::Right => (free, 0.),
+                TextAlign::Justify if line.last || spaces == 0 => (0., 0.),
+                TextAlign::Justify => (0., free / spaces as f32),
+            };
+            let first_glyph = self.glyphs.len();
+            let (advances, _) = advances(&line.characters, metrics);
+            let mut x = left;
+            for (position, (&(index, character), advance)) in
+                line.characters.iter().zip(advances).enumerate()
+            {
+                let advance = if character == ' ' && position < content {
+                    advance + extra_space
+                } else {
+                    advance
+                };
+                self.glyphs.push(PositionedGlyph {
+                    character,
+                    index: (index != ELLIPSIS).then_some(index),
+                    position: Vector2f::new(x, baseline),
+                    advance,
+                });
+                x += advance;
+            }
+            let mut shown = line.characters[..content]
+                .iter()
+                .filter(|&&(index, _)| index != ELLIPSIS);
+            let range = match (shown.next(), shown.next_back()) {
+                (Some(&(first, _)), Some(&(last, c))) => first..last + c.len_utf8(),
+                (Some(&(first, c)), None) => first..first + c.len_utf8(),
+                _ => 0..0,
+            };
+            self.lines.push(LayoutLine {
+                range,
+                glyphs: first_glyph..self.glyphs.len(),
+                position: Vector2f::new(left, top),
+                width: width + extra_space * spaces as f32,
+                baseline,
+                word_spacing: extra_space,
+                ellipsis: line.ellipsis,
+            });
+        }
+        self.size = Vector2f::new(box_width, lines.len() as f32 * self.line_height);
+    }
+}
+
+/// The advance of each character, kerned with the next one, and the width of the characters
+/// without trailing whitespace.
+fn advances(characters: &[(usize, char)], metrics: &impl GlyphMetrics) -> (Vec<f32>, f32) {
+    let advances: Vec<f32> = characters
+        .iter()
+        .enumerate()
+        .map(|(i, &(_, c))| {
+            let kerning = characters
+                .get(i + 1)
+                .map_or(0., |&(_, next)| metrics.kerning(c, next));
+            metrics.advance(c) + kerning
+        })
+        .collect();
+    let content = trimmed_len(characters);
+    let mut width: f32 = advances[..content].iter().sum();
+    // The last character isn't followed by anything on the line
+    if let (Some(&(_, last)), Some(&(_, next))) = (
+        content.checked_sub(1).map(|i| &characters[i]),
+        characters.get(content),
+    ) {
+        width -= metrics.kerning(last, next);
+    }
+    (advances, width)
+}
+
+/// The number of characters before the trailing whitespace.
+fn trimmed_len(characters: &[(usize, char)]) -> usize {
+    characters
+        .iter()
+        .rposition(|&(_, c)| !c.is_whitespace())
+        .map_or(0, |i| i + 1)
+}
+
+/// Break a paragraph into lines.
+fn wrap_paragraph(
+    characters: &[(usize, char)],
+    metrics: &impl GlyphMetrics,
+    settings: &LayoutSettings,
+    lines: &mut Vec<RawLine>,
+) {
+    let max_width = match settings.max_width {
+        Some(width) if settings.wrap != WrapMode::None => width,
+        _ => f32::INFINITY,
+    };
+    let mut start = 0;
+    loop {
+        let mut end = characters.len();
+        let mut next = end;
+        let mut pen = 0.;
+        let mut previous = None;
+        for (i, &(_, c)) in characters.iter().enumerate().skip(start) {
+            if let Some(previous) = previous {
+                pen += metrics.kerning(previous, c);
+            }
+            previous = Some(c);
+            // Spaces may hang past the edge
+            if !c.is_whitespace() && pen + metrics.advance(c) > max_width && i > start {
+                // The indentation of the line isn't a place to break it
+                let word = characters[start..i]
+                    .iter()
+                    .position(|&(_, c)| !c.is_whitespace())
+                    .map_or(i, |j| start + j);
+                let space = characters[word..i]
+                    .iter()
+                    .rposition(|&(_, c)| c.is_whitespace())
+                    .map(|j| word + j);
+                (end, next) = match (settings.wrap, space) {
+                    (WrapMode::Word, Some(space)) => (space, space + 1),
+                    _ => (i, i),
+                };
+                break;
+            }
+            pen += metrics.advance(c);
+        }
+        let last = end == characters.len();
+        lines.push(RawLine {
+            characters: characters[start..end].to_vec(),
+            last,
+            ellipsis: false,
+        });
+        if last {
+            return;
+        }
+        start = next;
+        if settings.wrap == WrapMode::Word {
+            while characters
+                .get(start)
+                .is_some_and(|&(_, c)| c.is_whitespace())
+            {
+                start += 1;
+            }
+        }
+    }
+}
+
+/// End `line` with the ellipsis, removing characters until it fits.
+fn add_ellipsis(line: &mut RawLine, metrics: &impl GlyphMetrics, settings: &LayoutSettings) {
+    let ellipsis: Vec<_> = settings.ellipsis.chars().map(|c| (ELLIPSIS, c)).collect();
+    let max_width = settings.max_width.unwrap_or(f32::INFINITY);
+    loop {
+        line.characters.truncate(trimmed_len(&line.characters));
+        let mut candidate = line.characters.clone();
+        candidate.extend_from_slice(&ellipsis);
+        if line.characters.is_empty() || advances(&candidate, metrics).1 <= max_width {
+            line.characters = candidate;
+            break;
+        }
+        let _ = line.characters.pop();
+    }
+    line.last = true;
+    line.ellipsis = true;
+}
+
+/// Text laid out within a width and drawn as a vertex array of glyph quads.
+///
+/// This is a [`TextLayout`] of a [`Font`], kept up to date when its string or settings change.
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::graphics::*;
+/// # let mut window: RenderWindow = unimplemented!();
+/// let font = Font::from_file("font.ttf").unwrap();
+/// let settings = LayoutSettings {
+///     max_width: Some(400.),
+///     align: TextAlign::Center,
+///     ..LayoutSettings::default()
+/// };
+/// let mut dialogue = TextBox::new("Welcome, traveller!", &font, 24, settings);
+/// dialogue.set_position((200., 400.));
+/// window.draw(&dialogue);
+/// ```
+#[derive(Debug)]
+pub struct TextBox<'font> {
+    font: &'font Font,
+    character_size: u32,
+    string: String,
+    settings: LayoutSettings,
+    color: Color,
+    layout: TextLayout,
+    vertices: Vec<Vertex>,
+    transformation: Transformation,
+}
+
+impl<'font> TextBox<'font> {
+    /// Lay `string` out with `font` at `character_size`.
+    #[must_use]
+    pub fn new(
+        string: &str,
+        font: &'font Font,
+        character_size: u32,
+        settings: LayoutSettings,
+    ) -> Self {
+        let mut text_box = Self {
+            font,
+            character_size,
+            string: string.to_owned(),
+            settings,
+            color: Color::WHITE,
+            layout: TextLayout::default(),
+            vertices: Vec::new(),
+            transformation: Transformation::default(),
+        };
+        text_box.update();
+        text_box
+    }
+    /// Set the text.
+    pub fn set_string(&mut self, string: &str) {
+        string.clone_into(&mut self.string);
+        self.update();
+    }
+    /// The text.
+    #[must_use]
+    pub fn string(&self) -> &str {
+        &self.string
+    }
+    /// Set the font.
+    pub fn set_font(&mut self, font: &'font Font) {
+        self.font = font;
+        self.update();
+    }
+    /// The font.
+    #[must_use]
+    pub fn font(&self) -> &'font Font {
+        self.font
+    }
+    /// Set the character size, in pixels.
+    pub fn set_character_size(&mut self, character_size: u32) {
+        self.character_size = character_size;
+        self.update();
+    }
+    /// The character size, in pixels.
+    #[must_use]
+    pub fn character_size(&self) -> u32 {
+        self.character_size
+    }
+    /// Set how the text is laid out.
+    pub fn set_settings(&mut self, settings: LayoutSettings) {
+        self.settings = settings;
+        self.update();
+    }
+    /// How the text is laid out.
+    #[must_use]
+    pub fn settings(&self) -> &LayoutSettings {
+        &self.settings
+    }
+    /// Set the color of the text.
+    pub fn set_fill_color(&mut self, color: Color) {
+        self.color = color;
+        for vertex in &mut self.vertices {
+            vertex.color = color;
+        }
+    }
+    /// The color of the text.
+    #[must_use]
+    pub fn fill_color(&self) -> Color {
+        self.color
+    }
+    /// The layout of the text, to find its lines and characters.
+    #[must_use]
+    pub fn layout(&self) -> &TextLayout {
+        &self.layout
+    }
+    /// The bounds of the laid out text, before transformations.
+    #[must_use]
+    pub fn local_bounds(&self) -> FloatRect {
+        FloatRect::new(Vector2f::new(0., 0.), self.layout.size())
+    }
+    /// The bounds of the laid out text, after transformations.
+    #[must_use]
+    pub fn global_bounds(&self) -> FloatRect {
+        self.transform().transform_rect(self.local_bounds())
+    }
+    fn update(&mut self) {
+        let metrics = FontMetrics::new(self.font, self.character_size);
+        self.layout = TextLayout::new(&self.string, &metrics, &self.settings);
+        self.vertices = self
+            .layout
+            .vertices(self.font, self.character_size, self.color);
+    }
+}
+
+impl Drawable for TextBox<'_> {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn RenderTarget,
+        states: &RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        let mut states = *states;
+        states.transform.combine(self.transform());
+        states.texture = Some(self.font.texture(self.character_size));
+        target.draw_primitives(&self.vertices, PrimitiveType::TRIANGLES, &states);
+    }
+}
+
+impl_transformable!(TextBox<'_>, transformation);
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{GlyphMetrics, LayoutSettings, TextAlign, TextLayout, WrapMode},
+        crate::system::Vector2f,
+    };
+
+    /// Letters are 10 wide and spaces 5, with `AV` kerned by -2.
+    struct Metrics;
+
+    impl GlyphMetrics for Metrics {
+        fn advance(&self, character: char) -> f32 {
+            if character == ' ' { 5. } else { 10. }
+        }
+        fn kerning(&self, first: char, second: char) -> f32 {
+            if (first, second) == ('A', 'V') {
+                -2.
+            } else {
+                0.
+            }
+        }
+        fn line_spacing(&self) -> f32 {
+            20.
+        }
+        fn ascent(&self) -> f32 {
+            15.
+        }
+    }
+
+    fn layout(text: &str, settings: &LayoutSettings) -> TextLayout {
+        TextLayout::new(text, &Metrics, settings)
+    }
+
+    fn line_texts(text: &str, layout: &TextLayout) -> Vec<String> {
+        let lines = layout.lines().iter();
+        lines
+            .map(|line| text[line.range.clone()].to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn lines_wrap_and_align() {
+        let text = "aaa bb cccc";
+        let mut settings = LayoutSettings {
+            max_width: Some(60.),
+            ..LayoutSettings::default()
+        };
+        let left = layout(text, &settings);
+        assert_eq!(line_texts(text, &left), ["aaa bb", "cccc"]);
+        assert_eq!(left.size(), Vector2f::new(60., 40.));
+        let metrics: Vec<_> = left.lines().iter().map(|l| [l.width, l.baseline]).collect();
+        assert_eq!(metrics, [[55., 15.], [40., 35.]]);
+
+        let mut starts = |align| {
+            settings.align = align;
+            let layout = layout(text, &settings);
+            let starts: Vec<_> = layout.lines().iter().map(|l| l.position.x).collect();
+            (starts, layout)
+        };
+        assert_eq!(starts(TextAlign::Center).0, [2.5, 10.]);
+        assert_eq!(starts(TextAlign::Right).0, [5., 20.]);
+        let (justify_starts, justified) = starts(TextAlign::Justify);
+        assert_eq!(justify_starts, [0., 0.]);
+        // The space of the first line takes the 5 units left, the last line isn't justified
+        let spacing: Vec<_> = justified.lines().iter().map(|l| l.word_spacing).collect();
+        assert_eq!(spacing, [5., 0.]);
+        assert_eq!(justified.glyphs()[4].position, Vector2f::new(40., 15.));
+        assert_eq!(justified.lines()[0].width, 60.);
+    }
+
+    #[test]
+    fn long_words_break_between_characters() {
+        let text = "abcdefg hi";
+        let mut settings = LayoutSettings {
+            max_width: Some(30.),
+            ..LayoutSettings::default()
+        };
+        assert_eq!(
+            line_texts(text, &layout(text, &settings)),
+            ["abc", "def", "g", "hi"]
+        );
+        let indented = "  abcdef";
+        assert_eq!(
+            line_texts(indented, &layout(indented, &settings)),
+            ["  ab", "cde", "f"]
+        );
+        settings.wrap = WrapMode::Character;
+        assert_eq!(
+            line_texts(text, &layout(text, &settings)),
+            ["abc", "def", "g h", "i"]
+        );
+        settings.wrap = WrapMode::None;
+        assert_eq!(line_texts(text, &layout(text, &settings)), ["abcdefg hi"]);
+
+        // Without a width, lines only break at line feeds
+        let text = "ab\r\ncde";
+        let unbounded = layout(text, &LayoutSettings::default());
+        assert_eq!(line_texts(text, &unbounded), ["ab", "cde"]);
+        assert_eq!(unbounded.size(), Vector2f::new(30., 40.));
+    }
+
+    #[test]
+    fn cut_off_text_ends_with_ellipsis() {
+        let text = "aaa bb cccc dd";
+        let settings = LayoutSettings {
+            max_width: Some(60.),
+            max_lines: Some(2),
+            ellipsis: "...".to_owned(),
+            ..LayoutSettings::default()
+        };
+        let layout = layout(text, &settings);
+        assert!(layout.is_truncated());
+        assert_eq!(line_texts(text, &layout), ["aaa bb", "ccc"]);
+        let last = &layout.lines()[1];
+        assert!(last.ellipsis);
+        let glyphs = &layout.glyphs()[last.glyphs.clone()];
+        let shown: String = glyphs.iter().map(|g| g.character).collect();
+        assert_eq!(shown, "ccc...");
+        assert_eq!(glyphs[3].index, None);
+        assert_eq!(glyphs[2].index, Some(9));
+    }
+
+    #[test]
+    fn kerning_moves_glyphs() {
+        let layout = layout("AVA", &LayoutSettings::default());
+        let positions: Vec<_> = layout.glyphs().iter().map(|g| g.position.x).collect();
+        assert_eq!(positions, [0., 8., 18.]);
+        assert_eq!(layout.lines()[0].width, 28.);
+    }
+}
@@ -0,0 +1,834 @@
+use {
+    crate::{
+        LoadError, SfError,
+        cpp::FBox,
+        graphics::{Image, IntRect, Sprite, Texture},
+        system::Vector2u,
+    },
+    std::{collections::HashMap, error::Error, fmt, fs, io, path::Path},
+};
+
+/// The algorithm used by an [`AtlasBuilder`] to place images on its pages.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum PackAlgorithm {
+    /// Keep track of the top edge of the placed images, and put each image as low as possible.
+    ///
+    /// Fast, and good when the images have similar heights.
+    Skyline,
+    /// Keep track of all the free rectangles, and put each image in the one it fits best.
+    ///
+    /// Slower, but wastes less space with images of various sizes.
+    #[default]
+    MaxRects,
+}
+
+/// How an [`AtlasBuilder`] lays out its images.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AtlasSettings {
+    /// The algorithm used to place the images
+    pub algorithm: PackAlgorithm,
+    /// The number of empty pixels between two images
+    pub padding: u32,
+    /// The number of times the edge pixels of each image are repeated around it, to avoid
+    /// bleeding with smooth filtering
+    pub extrude: u32,
+    /// The largest width and height of a page, or `None` for [`Texture::maximum_size`]
+    pub max_page_size: Option<u32>,
+}
+
+impl Default for AtlasSettings {
+    fn default() -> Self {
+        Self {
+            algorithm: PackAlgorithm::MaxRects,
+            padding: 1,
+            extrude: 0,
+            max_page_size: None,
+        }
+    }
+}
+
+/// Error returned when building an atlas fails.
+#[derive(Debug, Clone, Copy)]
+pub enum AtlasError {
+    /// The image with this index doesn't fit on a page, with its extrusion
+    TooLarge(usize),
+    /// The image with this index has an empty name, a name with a line break, or a name starting
+    /// or ending with whitespace
+    InvalidName(usize),
+    /// The image with this index has the same name as a previous one
+    DuplicateName(usize),
+    /// An image or a texture couldn't be created
+    Sfml(SfError),
+}
+
+impl Error for AtlasError {}
+
+impl fmt::Display for AtlasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooLarge(index) => write!(f, "Atlas image {index} is too large for a page"),
+            Self::InvalidName(index) => write!(f, "Atlas image {index} has an invalid name"),
+            Self::DuplicateName(index) => write!(f, "Atlas image {index} has a duplicate name"),
+            Self::Sfml(error) => write!(f, "Failed to create atlas page: {error}"),
+        }
+    }
+}
+
+impl From<SfError> for AtlasError {
+    fn from(error: SfError) -> Self {
+        Self::Sfml(error)
+    }
+}
+
+/// Where an image ended up in an atlas.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AtlasEntry {
+    /// The name the image was added with
+    pub name: String,
+    /// The index of the page holding the image
+    pub page: usize,
+    /// The area of the page covered by the image, without padding or extrusion
+    pub rect: IntRect,
+}
+
+/// The page sizes of an atlas and the place of each of its images, without any pixels.
+///
+/// Layouts are computed on the CPU with [`pack`], so they can be made and checked without a
+/// graphics context, and saved as text with [`to_text`], to be loaded back along with the saved
+/// pages.
+///
+/// [`pack`]: Self::pack
+/// [`to_text`]: Self::to_text
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct AtlasLayout {
+    pages: Vec<Vector2u>,
+    entries: Vec<AtlasEntry>,
+    index: HashMap<String, usize>,
+}
+
+const LAYOUT_HEADER: &str = "atlas 1";
+
+impl AtlasLayout {
+    /// Lay out images of the given names and sizes on as few pages as possible.
+    ///
+    /// The entries of the layout are in the same order as `images`. Each page is as small as
+    /// its images allow. If [`AtlasSettings::max_page_size`] is `None`, this queries
+    /// [`Texture::maximum_size`], which needs a graphics context.
+    pub fn pack<'name>(
+        images: impl IntoIterator<Item = (&'name str, Vector2u)>,
+        settings: &AtlasSettings,
+    ) -> Result<Self, AtlasError> {
+        let max = settings.max_page_size.unwrap_or_else(Texture::maximum_size);
+        let (padding, extrude) = (settings.padding, settings.extrude);
+        let mut layout = Self::default();
+        let mut cells = Vec::new();
+        for (i, (name, size)) in images.into_iter().enumerate() {
+            if !valid_name(name) {
+                return Err(AtlasError::InvalidName(i));
+            }
+            if layout.index.contains_key(name) {
+                return Err(AtlasError::DuplicateName(i));
+            }
+            let framed = |side: u32| side.checked_add(2 * extrude).filter(|&side| side <= max);
+            let (Some(width), Some(height)) = (framed(size.x), framed(size.y)) else {
+                return Err(AtlasError::TooLarge(i));
+            };
+            let _ = layout.index.insert(name.to_owned(), i);
+            layout.entries.push(AtlasEntry {
+                name: name.to_owned(),
+                page: 0,
+                rect: IntRect::default(),
+            });
+            cells.push((i, size, width, height));
+        }
+        // Placing the big images first leaves the small ones to fill the gaps
+        cells.sort_by_key(|&(_, _, w, h)| {
+            std::cmp::Reverse((w.max(h), u64::from(w) * u64::from(h)))
+        });
+        // The padding trails each image, so pages are one padding larger and trimmed afterwards
+        let bin = max.saturating_add(padding);
+        let mut packers: Vec<Packer> = Vec::new();
+        for (i, size, width, height) in cells {
+            let (page, x, y) = if size.x == 0 || size.y == 0 {
+                (0, 0, 0)
+            } else {
+                let (w, h) = (width + padding, height + padding);
+                let found = packers
+                    .iter_mut()
+                    .enumerate()
+                    .find_map(|(page, packer)| packer.insert(w, h).map(|(x, y)| (page, x, y)));
+                match found {
+                    Some(found) => found,
+                    None => {
+                        let mut packer = Packer::new(settings.algorithm, bin);
+                        let (x, y) = packer.insert(w, h).ok_or(AtlasError::TooLarge(i))?;
+                        packers.push(packer);
+                        (packers.len() - 1, x, y)
+                    }
+                }
+            };
+            if layout.pages.len() <= page {
+                layout.pages.resize(page + 1, Vector2u::new(1, 1));
+            }
+            let extent = &mut layout.pages[page];
+            extent.x = extent.x.max(x + width);
+            extent.y = extent.y.max(y + height);
+            layout.entries[i].page = page;
+            layout.entries[i].rect = IntRect::new(
+                Vector2u::new(x + extrude, y + extrude).as_other(),
+                size.as_other(),
+            );
+        }
+        Ok(layout)
+    }
+    /// The size of each page, in pixels.
+    #[must_use]
+    pub fn pages(&self) -> &[Vector2u] {
+        &self.pages
+    }
+    /// The place of each image.
+    #[must_use]
+    pub fn entries(&self) -> &[AtlasEntry] {
+        &self.entries
+    }
+    /// The place of the image called `name`.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&AtlasEntry> {
+        self.index.get(name).map(|&i| &self.entries[i])
+    }
+
+    /// Write the layout as text.
+    ///
+    /// The text starts with an `atlas 1` line, followed by a `page <width> <height>` line for
+    /// each page and a `sprite <page> <x> <y> <width> <height> <name>` line for each image.
+    #[must_use]
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{LAYOUT_HEADER}\n");
+        for page in &self.pages {
+            text.push_str(&format!("page {} {}\n", page.x, page.y));
+        }
+        for entry in &self.entries {
+            let IntRect { position, size } = entry.rect;
+            text.push_str(&format!(
+                "sprite {} {} {} {} {} {}\n",
+                entry.page, position.x, position.y, size.x, size.y, entry.name
+            ));
+        }
+        text
+    }
+    /// Read a layout written by [`to_text`](Self::to_text).
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn from_text(text: &str) -> Result<Self, LoadError> {
+        let mut layout = Self::default();
+        let mut header = false;
+        for (i, line) in text.lines().enumerate() {
+            let invalid = |reason| LoadError::Syntax {
+                line: i + 1,
+                reason,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if !header {
+                if line != LAYOUT_HEADER {
+                    return Err(invalid("expected 'atlas 1'"));
+                }
+                header = true;
+                continue;
+            }
+            let (kind, rest) = line.split_once(' ').ok_or(invalid("missing fields"))?;
+            match kind {
+                "page" => {
+                    let [width, height] = parse_fields(rest).ok_or(invalid("invalid page"))?.0;
+                    if width == 0 || height == 0 {
+                        return Err(invalid("empty page"));
+                    }
+                    layout.pages.push(Vector2u::new(width, height));
+                }
+                "sprite" => {
+                    let ([page, x, y, width, height], name) =
+                        parse_fields(rest).ok_or(invalid("invalid sprite"))?;
+                    let page = page as usize;
+                    let bounds = layout.pages.get(page).ok_or(invalid("unknown page"))?;
+                    if x.checked_add(width).is_none_or(|right| right > bounds.x)
+                        || y.checked_add(height).is_none_or(|bottom| bottom > bounds.y)
+                    {
+                        return Err(invalid("sprite outside of its page"));
+                    }
+                    if !valid_name(name) {
+                        return Err(invalid("invalid sprite name"));
+                    }
+                    if layout.index.contains_key(name) {
+                        return Err(invalid("duplicate sprite name"));
+                    }
+                    let _ = layout.index.insert(name.to_owned(), layout.entries.len());
+                    layout.entries.push(AtlasEntry {
+                        name: name.to_owned(),
+                        page,
+                        rect: IntRect::new(
+                            Vector2u::new(x, y).as_other(),
+                            Vector2u::new(width, height).as_other(),
+                        ),
+                    });
+                }
+                _ => return Err(invalid("unknown line kind")),
+            }
+        }
+        if header {
+            Ok(layout)
+        } else {
+            Err(LoadError::Syntax {
+                line: 1,
+                reason: "expected 'atlas 1'",
+            })
+        }
+    }
+    /// Save the layout to the file at `path`.
+    ///
+    /// See [`to_text`](Self::to_text).
+    pub fn save_to_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+    /// Load a layout from the file at `path`.
+    ///
+    /// See [`from_text`](Self::from_text).
+    pub fn load_from_file(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        Self::from_text(&fs::read_to_string(path)?)
+    }
+}
+
+/// Packs many images into a few atlas pages.
+///
+/// Images are added with [`add`], then [`build`] lays them out with [`AtlasLayout::pack`], copies
+/// them to their pages, and uploads the pages as textures. Images that don't fit on one page
+/// spill over to more pages.
+///
+/// [`add`]: Self::add
+/// [`build`]: Self::build
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::graphics::*;
+/// # let mut window: RenderWindow = unimplemented!();
+/// let player = Image::from_file("player.png").unwrap();
+/// let coin = Image::from_file("coin.png").unwrap();
+/// let mut builder = AtlasBuilder::new();
+/// builder.add("player", &player);
+/// builder.add("coin", &coin);
+/// let atlas = builder.build().unwrap();
+/// atlas.layout().save_to_file("atlas.txt").unwrap();
+/// let coin = atlas.sprite("coin").unwrap();
+/// window.draw(&coin);
+/// ```
+#[derive(Debug, Default)]
+pub struct AtlasBuilder<'image> {
+    settings: AtlasSettings,
+    images: Vec<(String, &'image Image)>,
+}
+
+impl<'image> AtlasBuilder<'image> {
+    /// Create a builder with the default settings.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create a builder with the given settings.
+    #[must_use]
+    pub fn with_settings(settings: AtlasSettings) -> Self {
+        Self {
+            settings,
+            images: Vec::new(),
+        }
+    }
+    /// The settings used to lay out the images.
+    #[must_use]
+    pub fn settings(&self) -> &AtlasSettings {
+        &self.settings
+    }
+    /// Change the settings used to lay out the images.
+    pub fn set_settings(&mut self, settings: AtlasSettings) {
+        self.settings = settings;
+    }
+    /// Add `image`, to be found under `name` in the atlas.
+    pub fn add(&mut self, name: impl Into<String>, image: &'image Image) {
+        self.images.push((name.into(), image));
+    }
+    /// The number of images added.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+    /// Whether no image was added.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    /// Lay out the images, without copying them.
+    pub fn layout(&self) -> Result<AtlasLayout, AtlasError> {
+        AtlasLayout::pack(
+            self.images
+                .iter()
+                .map(|(name, image)| (name.as_str(), image.size())),
+            &self.settings,
+        )
+    }
+    /// Lay out the images and copy them to page images, to be saved along with the layout.
+    pub fn build_images(&self) -> Result<(AtlasLayout, Vec<FBox<Image>>), AtlasError> {
+        let layout = self.layout()?;
+        let mut pages: Vec<Vec<u8>> = layout
+            .pages
+            .iter()
+            .map(|size| vec![0; size.x as usize * size.y as usize * 4])
+            .collect();
+        for (entry, (_, image)) in layout.entries.iter().zip(&self.images) {
+            blit(
+                &mut pages[entry.page],
+                layout.pages[entry.page].x as usize,
+                image.pixel_data(),
+                entry.rect,
+                self.settings.extrude as usize,
+            );
+        }
+        let images = layout
+            .pages
+            .iter()
+            .zip(&pages)
+            // SAFETY: Each buffer holds the 4 bytes of every pixel of its page
+            .map(|(&size, pixels)| unsafe { Image::from_pixels(size, pixels) })
+            .collect::<Result<_, _>>()?;
+        Ok((layout, images))
+    }
+    /// Lay out the images, copy them to pages, and upload the pages as textures.
+    pub fn build(&self) -> Result<TextureAtlas, AtlasError> {
+        let (layout, images) = self.build_images()?;
+        let textures = images
+            .iter()
+            .map(|image| Texture::from_image(image, false, IntRect::default()))
+            .collect::<Result<_, _>>()?;
+        Ok(TextureAtlas::new(layout, textures))
+    }
+}
+
+/// The textures made by an [`AtlasBuilder`], with the place of each image.
+#[derive(Debug)]
+pub struct TextureAtlas {
+    layout: AtlasLayout,
+    textures: Vec<FBox<Texture>>,
+}
+
+impl TextureAtlas {
+    /// Put together a layout and its page textures, such as a layout loaded with
+    /// [`AtlasLayout::load_from_file`] and textures loaded from the saved pages.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there isn't one texture per page of the layout.
+    #[must_use]
+    pub fn new(layout: AtlasLayout, textures: Vec<FBox<Texture>>) -> Self {
+        assert_eq!(
+            layout.pages.len(),
+            textures.len(),
+            "An atlas needs one texture per page"
+        );
+        Self { layout, textures }
+    }
+    /// The place of each image.
+    #[must_use]
+    pub fn layout(&self) -> &AtlasLayout {
+        &self.layout
+    }
+    /// The number of pages.
+    #[must_use]
+    pub fn page_count(&self) -> usize {
+        self.textures.len()
+    }
+    /// The texture of the page at index `page`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `page` is out of bounds.
+    #[must_use]
+    pub fn texture(&self, page: usize) -> &Texture {
+        &self.textures[page]
+    }
+    /// The texture of the page at index `page`, to change its filtering or wrapping.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `page` is out of bounds.
+    pub fn texture_mut(&mut self, page: usize) -> &mut Texture {
+        &mut self.textures[page]
+    }
+    /// The texture holding the image called `name`, and its area in the texture.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<(&Texture, IntRect)> {
+        let entry = self.layout.get(name)?;
+        Some((&self.textures[entry.page], entry.rect))
+    }
+    /// A sprite showing the image called `name`.
+    #[must_use]
+    pub fn sprite(&self, name: &str) -> Option<Sprite<'_>> {
+        let (texture, rect) = self.get(name)?;
+        Some(Sprite::with_texture_and_rect(texture, rect))
+    }
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty() && name.trim() == name && !name.contains(['\n', '\r'])
+}
+
+/// Parse `N` space separated numbers, returning them with the rest of the line.
+fn parse_fields<const N: usize>(text: &str) -> Option<([u32; N], &str)> {
+    let mut fields = [0; N];
+    let mut rest = text;
+    for field in &mut fields {
+        let (number, tail) = rest.split_once(' ').unwrap_or((rest, ""));
+        *field = number.parse().ok()?;
+        rest = tail;
+    }
+    Some((fields, rest.trim()))
+}
+
+/// Copy the RGBA `pixels` of an image of `rect.size` to `rect` on a page, repeating its edge
+/// pixels `extrude` times around it.
+#[expect(clippy::cast_sign_loss, reason = "atlas rects are never negative")]
+fn blit(page: &mut [u8], page_width: usize, pixels: &[u8], rect: IntRect, extrude: usize) {
+    let [x, y, width, height] =
+        [rect.position.x, rect.position.y, rect.size.x, rect.size.y].map(|n| n as usize);
+    if width == 0 || height == 0 {
+        return;
+    }
+    for row in 0..height + 2 * extrude {
+        let source_y = row.saturating_sub(extrude).min(height - 1);
+        let source = &pixels[source_y * width * 4..(source_y + 1) * width * 4];
+        let start = ((y + row - extrude) * page_width + x - extrude) * 4;
+        let target = &mut page[start..start + (width + 2 * extrude) * 4];
+        for (column, pixel) in target.chunks_exact_mut(4).enumerate() {
+            let source_x = column.saturating_sub(extrude).min(width - 1);
+            pixel.copy_from_slice(&source[source_x * 4..source_x * 4 + 4]);
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Packer {
+    Skyline(Skyline),
+    MaxRects(MaxRects),
+}
+
+impl Packer {
+    fn new(algorithm: PackAlgorithm, size: u32) -> Self {
+        match algorithm {
+            PackAlgorithm::Skyline => Self::Skyline(Skyline {
+                size,
+                segments: vec![Segment {
+                    x: 0,
+                    y: 0,
+                    width: size,
+                }],
+            }),
+            PackAlgorithm::MaxRects => Self::MaxRects(MaxRects {
+                free: vec![Area {
+                    x: 0,
+                    y: 0,
+                    width: size,
+                    height: size,
+                }],
+            }),
+        }
+    }
+    /// Find a place for a `width` by `height` rectangle and mark it as used.
+    fn insert(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        match self {
+            Self::Skyline(skyline) => skyline.insert(width, height),
+            Self::MaxRects(max_rects) => max_rects.insert(width, height),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Segment {
+    x: u32,
+    y: u32,
+    width: u32,
+}
+
+/// Bottom-left skyline packing: the top edge of the used space is kept as horizontal segments.
+#[derive(Debug)]
+struct Skyline {
+    size: u32,
+    segments: Vec<Segment>,
+}
+
+impl Skyline {
+    /// The lowest `y` a rectangle starting at segment `index` can be put at.
+    fn fit(&self, index: usize, width: u32, height: u32) -> Option<u32> {
+        let x = self.segments[index].x;
+        if x + width > self.size {
+            return None;
+        }
+        let mut y = 0;
+        let mut covered = 0;
+        for segment in &self.segments[index..] {
+            if covered >= width {
+                break;
+            }
+            y = y.max(segment.y);
+            covered += segment.width;
+        }
+        (y + height <= self.size).then_some(y)
+    }
+    fn insert(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let (index, y) = (0..self.segments.len())
+            .filter_map(|i| self.fit(i, width, height).map(|y| (i, y)))
+            .min_by_key(|&(i, y)| (y + height, self.segments[i].width))?;
+        let x = self.segments[index].x;
+        self.segments.insert(
+            index,
+            Segment {
+                x,
+                y: y + height,
+                width,
+            },
+        );
+        // Cut the segments now under the new one
+        let end = x + width;
+        let next = index + 1;
+        while next < self.segments.len() && self.segments[next].x < end {
+            let segment = &mut self.segments[next];
+            let overlap = end - segment.x;
+            if segment.width <= overlap {
+                let _ = self.segments.remove(next);
+            } else {
+                segment.x += overlap;
+                segment.width -= overlap;
+                break;
+            }
+        }
+        self.segments.dedup_by(|next, previous| {
+            let merge = next.y == previous.y;
+            if merge {
+                previous.width += next.width;
+            }
+            merge
+        });
+        Some((x, y))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Area {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+impl Area {
+    fn right(self) -> u32 {
+        self.x + self.width
+    }
+    fn bottom(self) -> u32 {
+        self.y + self.height
+    }
+    fn intersects(self, other: Self) -> bool {
+        self.x < other.right()
+            && other.x < self.right()
+            && self.y < other.bottom()
+            && other.y < self.bottom()
+    }
+    fn contains(self, other: Self) -> bool {
+        self.x <= other.x
+            && self.y <= other.y
+            && other.right() <= self.right()
+            && other.bottom() <= self.bottom()
+    }
+}
+
+/// Maximal rectangles packing with the best short side fit heuristic: the free space is kept as
+/// all the largest free rectangles, which may overlap.
+#[derive(Debug)]
+struct MaxRects {
+    free: Vec<Area>,
+}
+
+impl MaxRects {
+    fn insert(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let best = self
+            .free
+            .iter()
+            .filter(|free| free.width >= width && free.height >= height)
+            .min_by_key(|free| {
+                let (dx, dy) = (free.width - width, free.height - height);
+                (dx.min(dy), dx.max(dy))
+            })?;
+        let used = Area {
+            x: best.x,
+            y: best.y,
+            width,
+            height,
+        };
+        let mut split = Vec::new();
+        self.free.retain(|&free| {
+            if !free.intersects(used) {
+                return true;
+            }
+            if used.x > free.x {
+                split.push(Area {
+                    width: used.x - free.x,
+                    ..free
+                });
+            }
+            if used.right() < free.right() {
+                split.push(Area {
+                    x: used.right(),
+                    width: free.right() - used.right(),
+                    ..free
+                });
+            }
+            if used.y > free.y {
+                split.push(Area {
+                    height: used.y - free.y,
+                    ..free
+                });
+            }
+            if used.bottom() < free.bottom() {
+                split.push(Area {
+                    y: used.bottom(),
+                    height: free.bottom() - used.bottom(),
+                    ..free
+                });
+            }
+            false
+        });
+        self.free.extend(split);
+        // Drop the free rectangles inside other ones
+        let mut i = 0;
+        while i < self.free.len() {
+            let area = self.free[i];
+            let redundant = self
+                .free
+                .iter()
+                .enumerate()
+                .any(|(j, &other)| j != i && other.contains(area) && (other != area || j < i));
+            if redundant {
+                let _ = self.free.swap_remove(i);
+            } else {
+                i += 1;
+            }
+        }
+        Some((used.x, used.y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{AtlasError, AtlasLayout, AtlasSettings, PackAlgorithm, blit},
+        crate::LoadError,
+        crate::graphics::IntRect,
+        crate::system::Vector2u,
+    };
+
+    fn sizes() -> Vec<(String, Vector2u)> {
+        (0..40u32)
+            .map(|i| {
+                let size = Vector2u::new(8 + i * 7 % 29, 4 + i * 11 % 37);
+                (format!("image {i}"), size)
+            })
+            .collect()
+    }
+
+    fn pack(algorithm: PackAlgorithm, max: u32) -> Result<AtlasLayout, AtlasError> {
+        let settings = AtlasSettings {
+            algorithm,
+            padding: 2,
+            extrude: 1,
+            max_page_size: Some(max),
+        };
+        let sizes = sizes();
+        AtlasLayout::pack(sizes.iter().map(|(n, s)| (n.as_str(), *s)), &settings)
+    }
+
+    #[test]
+    fn packed_images_do_not_overlap() {
+        for algorithm in [PackAlgorithm::Skyline, PackAlgorithm::MaxRects] {
+            for max in [64, 256] {
+                let layout = pack(algorithm, max).expect("images fit");
+                let limit = i32::try_from(max).expect("small page size");
+                assert_eq!(layout.entries().len(), 40);
+                assert_eq!(max == 256, layout.pages().len() == 1);
+                // Grow each rect by its extrusion and trailing padding before checking for overlaps
+                let framed: Vec<_> = layout
+                    .entries()
+                    .iter()
+                    .map(|e| {
+                        let r = e.rect;
+                        let (x, y) = (r.position.x - 1, r.position.y - 1);
+                        (e.page, x, y, x + r.size.x + 4, y + r.size.y + 4)
+                    })
+                    .collect();
+                for (i, entry) in layout.entries().iter().enumerate() {
+                    let page = layout.pages()[entry.page].as_other::<i32>();
+                    assert!(page.x <= limit && page.y <= limit);
+                    let (_, left, top, right, bottom) = framed[i];
+                    assert!(left >= 0 && top >= 0);
+                    assert!(right - 2 <= page.x && bottom - 2 <= page.y);
+                    for &(page, l, t, r, b) in &framed[i + 1..] {
+                        let apart = page != entry.page
+                            || r <= left
+                            || right <= l
+                            || b <= top
+                            || bottom <= t;
+                        assert!(apart, "{algorithm:?} {max}: {entry:?} overlaps");
+                    }
+                }
+                assert_eq!(layout.get("image 3"), Some(&layout.entries()[3]));
+            }
+        }
+        assert!(matches!(
+            pack(PackAlgorithm::MaxRects, 40),
+            Err(AtlasError::TooLarge(_))
+        ));
+    }
+
+    #[test]
+    fn layout_text_round_trip() {
+        let layout = pack(PackAlgorithm::Skyline, 64).expect("images fit");
+        let text = layout.to_text();
+        assert!(text.starts_with("atlas 1\npage "));
+        assert_eq!(AtlasLayout::from_text(&text).expect("valid layout"), layout);
+        let invalid = |text| match AtlasLayout::from_text(text) {
+            Err(LoadError::Syntax { line, reason }) => (line, reason),
+            other => panic!("{other:?}"),
+        };
+        assert_eq!(
+            invalid("atlas 1\npage 8 8\nsprite 0 4 4 5 1 a"),
+            (3, "sprite outside of its page")
+        );
+        assert_eq!(invalid("atlas 1\nsprite 0 0 0 1 1 a"), (2, "unknown page"));
+        assert_eq!(invalid("# comment\nsprite"), (2, "expected 'atlas 1'"));
+    }
+
+    #[test]
+    fn blit_extrudes_edges() {
+        let pixels: Vec<u8> = (1..=4).flat_map(|p| [p; 4]).collect();
+        let mut page = vec![0; 5 * 4 * 4];
+        let rect = IntRect::new((2, 1).into(), (2, 2).into());
+        blit(&mut page, 5, &pixels, rect, 1);
+        let red: Vec<u8> = page.chunks(4).map(|p| p[0]).collect();
+        assert_eq!(
+            red,
+            [
+                0, 1, 1, 2, 2, //
+                0, 1, 1, 2, 2, //
+                0, 3, 3, 4, 4, //
+                0, 3, 3, 4, 4, //
+            ]
+        );
+    }
+}
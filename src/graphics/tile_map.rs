@@ -0,0 +1,809 @@
+use {
+    crate::{
+        SfResult,
+        cpp::FBox,
+        graphics::{
+            Color, Drawable, FloatRect, IntRect, PrimitiveType, RenderStates, RenderTarget,
+            Texture, Transformable, Vertex, VertexBuffer, VertexBufferUsage,
+            tiled::{Gid, LayerKind, TiledMap, Tileset},
+            transformation::{Transformation, impl_transformable},
+        },
+        system::{Time, Vector2f, Vector2i, Vector2u},
+    },
+    std::{collections::HashMap, ops::Range},
+};
+
+/// A tile of a [`TileMap`]: a tile of its [`TileSheet`], possibly flipped.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Tile {
+    /// The index of the tile in the sheet, row by row
+    pub id: u32,
+    /// Mirror the tile left to right
+    pub flip_horizontal: bool,
+    /// Mirror the tile top to bottom
+    pub flip_vertical: bool,
+    /// Swap the x and y axes of the tile, before the other flips
+    pub flip_diagonal: bool,
+}
+
+impl Tile {
+    /// The tile `id` of the sheet, without flips.
+    #[must_use]
+    pub const fn new(id: u32) -> Self {
+        Self {
+            id,
+            flip_horizontal: false,
+            flip_vertical: false,
+            flip_diagonal: false,
+        }
+    }
+    /// The tile of a Tiled tile layer, if it belongs to the tileset starting at `first_gid`.
+    #[must_use]
+    pub fn from_gid(gid: Gid, first_gid: u32) -> Option<Self> {
+        if gid.is_empty() || gid.id() < first_gid {
+            return None;
+        }
+        Some(Self {
+            id: gid.id() - first_gid,
+            flip_horizontal: gid.flipped_horizontally(),
+            flip_vertical: gid.flipped_vertically(),
+            flip_diagonal: gid.flipped_diagonally(),
+        })
+    }
+}
+
+/// How the tiles of a [`TileMap`] are laid out in its texture.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TileSheet {
+    /// The size of a tile, in pixels
+    pub tile_size: Vector2u,
+    /// The number of tiles in a row
+    pub columns: u32,
+    /// The space around the tiles, in pixels
+    pub margin: u32,
+    /// The space between two tiles, in pixels
+    pub spacing: u32,
+}
+
+impl TileSheet {
+    /// The area of the texture showing the tile `id`.
+    ///
+    /// Coordinates and sizes too large for an [`IntRect`] are clamped to `i32::MAX`.
+    #[must_use]
+    pub fn tile_rect(&self, id: u32) -> IntRect {
+        let columns = self.columns.max(1);
+        let (column, row) = (id % columns, id / columns);
+        let clamp = |value: u32| i32::try_from(value).unwrap_or(i32::MAX);
+        let offset = |index: u32, tile_size: u32| {
+            let stride = tile_size.saturating_add(self.spacing);
+            clamp(self.margin.saturating_add(index.saturating_mul(stride)))
+        };
+        IntRect::new(
+            Vector2i::new(
+                offset(column, self.tile_size.x),
+                offset(row, self.tile_size.y),
+            ),
+            Vector2i::new(clamp(self.tile_size.x), clamp(self.tile_size.y)),
+        )
+    }
+}
+
+impl From<&Tileset> for TileSheet {
+    fn from(tileset: &Tileset) -> Self {
+        Self {
+            tile_size: tileset.tile_size,
+            columns: tileset.columns,
+            margin: tileset.margin,
+            spacing: tileset.spacing,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct MapLayer {
+    tiles: Vec<Option<Tile>>,
+    color: Color,
+    visible: bool,
+}
+
+#[derive(Clone, Debug)]
+struct Animation {
+    frames: Vec<(u32, Time)>,
+    length: Time,
+}
+
+impl Animation {
+    /// The tile shown at `time`.
+    fn frame_at(&self, time: Time) -> u32 {
+        let mut time = time.as_microseconds() % self.length.as_microseconds().max(1);
+        for &(id, duration) in &self.frames {
+            if time < duration.as_microseconds() {
+                return id;
+            }
+            time -= duration.as_microseconds();
+        }
+        self.frames[0].0
+    }
+}
+
+#[derive(Debug, Default)]
+struct Chunk {
+    buffer: Option<FBox<VertexBuffer>>,
+    vertex_count: usize,
+    dirty: bool,
+    /// The animated tiles of the chunk, which need a rebuild when their frame changes
+    animated: Vec<u32>,
+}
+
+/// A grid of tiles from one texture, drawn from vertex buffers.
+///
+/// The map is cut into square chunks of [`chunk_size`] tiles, each with its own
+/// [`VertexBuffer`] holding all the layers. Changing a tile only marks its chunk as dirty, and
+/// dirty chunks are rebuilt by [`update`], which also advances the animated tiles. Only the
+/// chunks in the view of the target are drawn.
+///
+/// Maps made with Tiled are loaded with [`TiledMap`] and turned into a `TileMap` with
+/// [`from_tiled`].
+///
+/// [`chunk_size`]: Self::set_chunk_size
+/// [`update`]: Self::update
+/// [`from_tiled`]: Self::from_tiled
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::graphics::*;
+/// # use sfml::system::{Clock, Time, Vector2u};
+/// # let mut window: RenderWindow = unimplemented!();
+/// let texture = Texture::from_file("tiles.png").unwrap();
+/// let sheet = TileSheet {
+///     tile_size: Vector2u::new(16, 16),
+///     columns: 8,
+///     margin: 0,
+///     spacing: 0,
+/// };
+/// let mut map = TileMap::new(&texture, sheet, Vector2u::new(100, 100));
+/// let ground = map.add_layer();
+/// for x in 0..100 {
+///     map.set_tile(ground, x, 99, Some(Tile::new(3)));
+/// }
+/// // Water cycles through tiles 8 to 10
+/// map.set_animation(8, [(8, Time::milliseconds(200)), (9, Time::milliseconds(200)), (10, Time::milliseconds(200))]);
+/// let mut clock = Clock::new().unwrap();
+/// while window.is_open() {
+///     map.update(clock.restart()).unwrap();
+///     window.clear(Color::BLACK);
+///     window.draw(&map);
+///     window.display();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct TileMap<'texture> {
+    texture: &'texture Texture,
+    grid: TileGrid,
+    transformation: Transformation,
+}
+
+/// The part of a [`TileMap`] that doesn't need its texture: the tiles, their animations and the
+/// chunks built from them.
+#[derive(Debug)]
+struct TileGrid {
+    sheet: TileSheet,
+    size: Vector2u,
+    cell_size: Vector2f,
+    layers: Vec<MapLayer>,
+    animations: HashMap<u32, Animation>,
+    time: Time,
+    chunk_size: u32,
+    chunks: Vec<Chunk>,
+}
+
+impl<'texture> TileMap<'texture> {
+    /// Create a map of `size` tiles without layers, with cells the size of the tiles of `sheet`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of chunks of the map overflows `usize`.
+    #[must_use]
+    pub fn new(texture: &'texture Texture, sheet: TileSheet, size: Vector2u) -> Self {
+        Self {
+            texture,
+            grid: TileGrid::new(sheet, size),
+            transformation: Transformation::default(),
+        }
+    }
+    /// Create a map showing the tile layers of `map` that use the tileset at index `tileset`,
+    /// drawn with `texture`, the image of the tileset.
+    ///
+    /// Each tile layer, including the layers of groups, becomes a layer of the map, with its
+    /// visibility and opacity. Tiles of other tilesets are left out, and the tile animations
+    /// of the tileset are set up.
+    ///
+    /// The map takes the size of its largest tile layer rather than the size in the header of
+    /// the file, which is the same for maps saved by Tiled. Smaller layers are padded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tileset` is out of bounds.
+    #[must_use]
+    pub fn from_tiled(map: &TiledMap, tileset: usize, texture: &'texture Texture) -> Self {
+        Self {
+            texture,
+            grid: TileGrid::from_tiled(map, tileset),
+            transformation: Transformation::default(),
+        }
+    }
+
+    /// Add an empty layer on top of the others, returning its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of tiles of the map overflows `usize`.
+    pub fn add_layer(&mut self) -> usize {
+        self.grid.add_layer()
+    }
+    /// The number of layers.
+    #[must_use]
+    pub fn layer_count(&self) -> usize {
+        self.grid.layers.len()
+    }
+    /// The size of the map, in tiles.
+    #[must_use]
+    pub fn size(&self) -> Vector2u {
+        self.grid.size
+    }
+    /// Set the size of a cell of the grid.
+    ///
+    /// Tiles are drawn at the bottom left of their cell, so tiles taller than the cells
+    /// overlap the row above. By default, the cells are the size of the tiles.
+    pub fn set_cell_size(&mut self, cell_size: Vector2f) {
+        self.grid.cell_size = cell_size;
+        self.grid.mark_all_dirty();
+    }
+    /// The size of a cell of the grid.
+    #[must_use]
+    pub fn cell_size(&self) -> Vector2f {
+        self.grid.cell_size
+    }
+    /// Set the width and height of the chunks, in tiles.
+    ///
+    /// Smaller chunks are cheaper to rebuild and cull better, but need more draw calls. The
+    /// default is 16.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0, or if the number of chunks overflows `usize`.
+    pub fn set_chunk_size(&mut self, chunk_size: u32) {
+        self.grid.set_chunk_size(chunk_size);
+    }
+    /// The width and height of the chunks, in tiles.
+    #[must_use]
+    pub fn chunk_size(&self) -> u32 {
+        self.grid.chunk_size
+    }
+
+    /// Set the tile at column `x` and row `y` of `layer`, or remove it with `None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `layer` or the position is out of bounds.
+    pub fn set_tile(&mut self, layer: usize, x: u32, y: u32, tile: Option<Tile>) {
+        self.grid.set_tile(layer, x, y, tile);
+    }
+    /// The tile at column `x` and row `y` of `layer`, if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `layer` is out of bounds.
+    #[must_use]
+    pub fn tile(&self, layer: usize, x: u32, y: u32) -> Option<Tile> {
+        self.grid.tile(layer, x, y)
+    }
+    /// Set the color the tiles of `layer` are modulated with, such as a translucent white to
+    /// fade it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `layer` is out of bounds.
+    pub fn set_layer_color(&mut self, layer: usize, color: Color) {
+        self.grid.set_layer_color(layer, color);
+    }
+    /// Show or hide `layer`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `layer` is out of bounds.
+    pub fn set_layer_visible(&mut self, layer: usize, visible: bool) {
+        self.grid.set_layer_visible(layer, visible);
+    }
+    /// Animate the tile `id`: wherever it is used, it cycles through the given tiles, each
+    /// shown for its duration.
+    ///
+    /// Flips apply to all the frames. An empty list of frames removes the animation.
+    pub fn set_animation(&mut self, id: u32, frames: impl IntoIterator<Item = (u32, Time)>) {
+        self.grid.set_animation(id, frames);
+    }
+    /// The time of the tile animations.
+    #[must_use]
+    pub fn animation_time(&self) -> Time {
+        self.grid.time
+    }
+
+    /// Advance the tile animations by `delta`, and rebuild the vertex buffers of the chunks
+    /// that changed.
+    ///
+    /// Should be called every frame before drawing. Fails if a vertex buffer couldn't be
+    /// created or updated.
+    pub fn update(&mut self, delta: Time) -> SfResult<()> {
+        self.grid.update(delta)
+    }
+    /// The bounds of the map, before transformations.
+    #[must_use]
+    pub fn local_bounds(&self) -> FloatRect {
+        let size = self.grid.size.as_other::<f32>();
+        let cell_size = self.grid.cell_size;
+        FloatRect::new(
+            Vector2f::new(0., 0.),
+            Vector2f::new(size.x * cell_size.x, size.y * cell_size.y),
+        )
+    }
+    /// The bounds of the map, after transformations.
+    #[must_use]
+    pub fn global_bounds(&self) -> FloatRect {
+        self.transform().transform_rect(self.local_bounds())
+    }
+}
+
+impl TileGrid {
+    fn new(sheet: TileSheet, size: Vector2u) -> Self {
+        let mut grid = Self {
+            sheet,
+            size,
+            cell_size: sheet.tile_size.as_other(),
+            layers: Vec::new(),
+            animations: HashMap::new(),
+            time: Time::ZERO,
+            chunk_size: 16,
+            chunks: Vec::new(),
+        };
+        grid.reset_chunks();
+        grid
+    }
+    /// See [`TileMap::from_tiled`].
+    fn from_tiled(map: &TiledMap, tileset: usize) -> Self {
+        let tileset = &map.tilesets[tileset];
+        let mut layers = Vec::new();
+        let mut stack: Vec<_> = map.layers.iter().rev().map(|l| (l, true, 1.)).collect();
+        while let Some((layer, visible, opacity)) = stack.pop() {
+            let visible = visible && layer.visible;
+            let opacity = opacity * layer.opacity;
+            match &layer.kind {
+                LayerKind::Tiles(tiles) => layers.push((tiles, visible, opacity)),
+                LayerKind::Group(children) => {
+                    stack.extend(children.iter().rev().map(|l| (l, visible, opacity)));
+                }
+                LayerKind::Objects(_) | LayerKind::Image(_) => {}
+            }
+        }
+        // The tile data of the layers was checked against their size, unlike the header
+        let size = layers
+            .iter()
+            .map(|(tiles, ..)| tiles.size)
+            .max_by_key(|size| u64::from(size.x) * u64::from(size.y))
+            .unwrap_or_default();
+        let mut grid = Self::new(TileSheet::from(tileset), size);
+        grid.cell_size = map.tile_size.as_other();
+        for (tiles, visible, opacity) in layers {
+            let index = grid.add_layer();
+            #[expect(
+                clippy::cast_possible_truncation,
+                clippy::cast_sign_loss,
+                reason = "the opacity is clamped to 0..=1"
+            )]
+            let alpha = (opacity.clamp(0., 1.) * 255.).round() as u8;
+            grid.set_layer_color(index, Color::rgba(255, 255, 255, alpha));
+            grid.set_layer_visible(index, visible);
+            for y in 0..size.y.min(tiles.size.y) {
+                for x in 0..size.x.min(tiles.size.x) {
+                    let tile = Tile::from_gid(tiles.get(x, y), tileset.first_gid)
+                        .filter(|tile| tileset.tile_count == 0 || tile.id < tileset.tile_count);
+                    grid.set_tile(index, x, y, tile);
+                }
+            }
+        }
+        for tile in &tileset.tiles {
+            if !tile.animation.is_empty() {
+                grid.set_animation(
+                    tile.id,
+                    tile.animation.iter().map(|f| (f.tile_id, f.duration)),
+                );
+            }
+        }
+        grid
+    }
+    fn add_layer(&mut self) -> usize {
+        self.layers.push(MapLayer {
+            tiles: vec![None; area(self.size)],
+            color: Color::WHITE,
+            visible: true,
+        });
+        self.layers.len() - 1
+    }
+    fn set_chunk_size(&mut self, chunk_size: u32) {
+        assert!(chunk_size > 0, "The chunk size can't be 0");
+        self.chunk_size = chunk_size;
+        self.reset_chunks();
+    }
+    fn set_tile(&mut self, layer: usize, x: u32, y: u32, tile: Option<Tile>) {
+        assert!(x < self.size.x && y < self.size.y, "Tile out of bounds");
+        let index = self.tile_index(x, y);
+        let slot = &mut self.layers[layer].tiles[index];
+        if *slot != tile {
+            *slot = tile;
+            let chunk = self.chunk_of(x, y);
+            self.chunks[chunk].dirty = true;
+        }
+    }
+    fn tile(&self, layer: usize, x: u32, y: u32) -> Option<Tile> {
+        if x < self.size.x && y < self.size.y {
+            self.layers[layer].tiles[self.tile_index(x, y)]
+        } else {
+            None
+        }
+    }
+    fn set_layer_color(&mut self, layer: usize, color: Color) {
+        if self.layers[layer].color != color {
+            self.layers[layer].color = color;
+            self.mark_all_dirty();
+        }
+    }
+    fn set_layer_visible(&mut self, layer: usize, visible: bool) {
+        if self.layers[layer].visible != visible {
+            self.layers[layer].visible = visible;
+            self.mark_all_dirty();
+        }
+    }
+    fn set_animation(&mut self, id: u32, frames: impl IntoIterator<Item = (u32, Time)>) {
+        let frames: Vec<_> = frames.into_iter().collect();
+        let length = frames
+            .iter()
+            .fold(Time::ZERO, |length, &(_, duration)| length + duration);
+        if frames.is_empty() {
+            let _ = self.animations.remove(&id);
+        } else {
+            let _ = self.animations.insert(id, Animation { frames, length });
+        }
+        self.mark_all_dirty();
+    }
+    fn update(&mut self, delta: Time) -> SfResult<()> {
+        let previous = self.time;
+        self.time += delta;
+        let changed: Vec<u32> = self
+            .animations
+            .iter()
+            .filter(|(_, animation)| animation.frame_at(previous) != animation.frame_at(self.time))
+            .map(|(&id, _)| id)
+            .collect();
+        if !changed.is_empty() {
+            for chunk in &mut self.chunks {
+                if chunk.animated.iter().any(|id| changed.contains(id)) {
+                    chunk.dirty = true;
+                }
+            }
+        }
+        for index in 0..self.chunks.len() {
+            if !self.chunks[index].dirty {
+                continue;
+            }
+            let (vertices, animated) = self.chunk_vertices(index);
+            let chunk = &mut self.chunks[index];
+            chunk.dirty = false;
+            chunk.animated = animated;
+            chunk.vertex_count = vertices.len();
+            if vertices.is_empty() {
+                continue;
+            }
+            match &mut chunk.buffer {
+                Some(buffer) if buffer.vertex_count() >= vertices.len() => {}
+                Some(buffer) => buffer.recreate(vertices.len())?,
+                None => {
+                    chunk.buffer = Some(VertexBuffer::new(
+                        PrimitiveType::TRIANGLES,
+                        vertices.len(),
+                        VertexBufferUsage::DYNAMIC,
+                    )?);
+                }
+            }
+            if let Some(buffer) = &mut chunk.buffer {
+                buffer.update(&vertices, 0)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn tile_index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.size.x as usize + x as usize
+    }
+    /// The number of chunks in a row and in a column.
+    fn chunk_grid(&self) -> Vector2u {
+        Vector2u::new(
+            self.size.x.div_ceil(self.chunk_size),
+            self.size.y.div_ceil(self.chunk_size),
+        )
+    }
+    fn chunk_of(&self, x: u32, y: u32) -> usize {
+        let columns = self.chunk_grid().x as usize;
+        (y / self.chunk_size) as usize * columns + (x / self.chunk_size) as usize
+    }
+    fn reset_chunks(&mut self) {
+        let grid = self.chunk_grid();
+        self.chunks.clear();
+        self.chunks.resize_with(area(grid), || Chunk {
+            dirty: true,
+            ..Chunk::default()
+        });
+    }
+    fn mark_all_dirty(&mut self) {
+        for chunk in &mut self.chunks {
+            chunk.dirty = true;
+        }
+    }
+    /// The vertices of chunk `index`, and the animated tiles in it.
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "chunks are indexed by a grid of u32 columns and rows"
+    )]
+    fn chunk_vertices(&self, index: usize) -> (Vec<Vertex>, Vec<u32>) {
+        let columns = self.chunk_grid().x as usize;
+        let left = (index % columns) as u32 * self.chunk_size;
+        let top = (index / columns) as u32 * self.chunk_size;
+        let right = (left + self.chunk_size).min(self.size.x);
+        let bottom = (top + self.chunk_size).min(self.size.y);
+        let tile_size = self.sheet.tile_size.as_other::<f32>();
+        let mut vertices = Vec::new();
+        let mut animated = Vec::new();
+        for layer in self.layers.iter().filter(|layer| layer.visible) {
+            for y in top..bottom {
+                for x in left..right {
+                    let Some(tile) = layer.tiles[self.tile_index(x, y)] else {
+                        continue;
+                    };
+                    let id = match self.animations.get(&tile.id) {
+                        Some(animation) => {
+                            if !animated.contains(&tile.id) {
+                                animated.push(tile.id);
+                            }
+                            animation.frame_at(self.time)
+                        }
+                        None => tile.id,
+                    };
+                    let rect = self.sheet.tile_rect(id).as_other::<f32>();
+                    let (u0, v0) = (rect.position.x, rect.position.y);
+                    let (u1, v1) = (u0 + rect.size.x, v0 + rect.size.y);
+                    // Texture coordinates of the top left, top right, bottom left and bottom
+                    // right corners of the cell
+                    let mut tex = [(u0, v0), (u1, v0), (u0, v1), (u1, v1)];
+                    if tile.flip_diagonal {
+                        tex.swap(1, 2);
+                    }
+                    if tile.flip_horizontal {
+                        tex.swap(0, 1);
+                        tex.swap(2, 3);
+                    }
+                    if tile.flip_vertical {
+                        tex.swap(0, 2);
+                        tex.swap(1, 3);
+                    }
+                    let x0 = x as f32 * self.cell_size.x;
+                    let y1 = (y + 1) as f32 * self.cell_size.y;
+                    let (x1, y0) = (x0 + tile_size.x, y1 - tile_size.y);
+                    let corner = |x, y, (u, v)| Vertex {
+                        position: Vector2f::new(x, y),
+                        color: layer.color,
+                        tex_coords: Vector2f::new(u, v),
+                    };
+                    let top_left = corner(x0, y0, tex[0]);
+                    let top_right = corner(x1, y0, tex[1]);
+                    let bottom_left = corner(x0, y1, tex[2]);
+                    let bottom_right = corner(x1, y1, tex[3]);
+                    vertices.extend([
+                        top_left,
+                        top_right,
+                        bottom_left,
+                        bottom_left,
+                        top_right,
+                        bottom_right,
+                    ]);
+                }
+            }
+        }
+        (vertices, animated)
+    }
+    /// The columns and rows of chunks overlapping `area`, in local coordinates.
+    fn chunks_in(&self, area: FloatRect) -> (Range<u32>, Range<u32>) {
+        // Tiles taller or wider than the cells stick out of their chunk
+        let tile_size = self.sheet.tile_size.as_other::<f32>();
+        let chunk_width = self.chunk_size as f32 * self.cell_size.x;
+        let chunk_height = self.chunk_size as f32 * self.cell_size.y;
+        let grid = self.chunk_grid();
+        #[expect(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            reason = "the values are clamped to the chunk grid"
+        )]
+        let range = |start: f32, end: f32, chunk: f32, count: u32| {
+            if chunk <= 0. {
+                return 0..count;
+            }
+            let first = (start / chunk).floor().clamp(0., count as f32) as u32;
+            let last = (end / chunk).ceil().clamp(0., count as f32) as u32;
+            first..last
+        };
+        (
+            range(
+                area.position.x - tile_size.x,
+                area.position.x + area.size.x,
+                chunk_width,
+                grid.x,
+            ),
+            range(
+                area.position.y,
+                area.position.y + area.size.y + tile_size.y,
+                chunk_height,
+                grid.y,
+            ),
+        )
+    }
+}
+
+impl Drawable for TileMap<'_> {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn RenderTarget,
+        states: &RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        let mut states = *states;
+        states.transform.combine(self.transform());
+        states.texture = Some(self.texture);
+        let view = target.view();
+        let (center, size) = (view.center(), view.size());
+        // A rotated view sees at most the circle around its corners
+        let extent = if view.rotation() == 0. {
+            Vector2f::new(size.x.abs(), size.y.abs())
+        } else {
+            let diameter = size.x.hypot(size.y);
+            Vector2f::new(diameter, diameter)
+        };
+        let visible = FloatRect::new(center - extent / 2., extent);
+        let local = states.transform.inverse().transform_rect(visible);
+        let (columns, rows) = self.grid.chunks_in(local);
+        let grid_columns = self.grid.chunk_grid().x as usize;
+        for row in rows {
+            for column in columns.clone() {
+                let chunk = &self.grid.chunks[row as usize * grid_columns + column as usize];
+                if let Some(buffer) = &chunk.buffer {
+                    if chunk.vertex_count > 0 {
+                        target.draw_vertex_buffer_range(buffer, 0, chunk.vertex_count, &states);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl_transformable!(TileMap<'_>, transformation);
+
+/// The number of cells of a grid of `size`.
+fn area(size: Vector2u) -> usize {
+    usize::try_from(u64::from(size.x) * u64::from(size.y)).expect("The tile map is too large")
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{Tile, TileGrid, TileSheet},
+        crate::{
+            graphics::{
+                FloatRect, IntRect,
+                tiled::{Gid, TiledMap},
+            },
+            system::{Time, Vector2f, Vector2u},
+        },
+    };
+
+    fn sheet() -> TileSheet {
+        TileSheet {
+            tile_size: Vector2u::new(16, 16),
+            columns: 4,
+            margin: 1,
+            spacing: 2,
+        }
+    }
+
+    fn map(size: Vector2u) -> TileGrid {
+        TileGrid::new(sheet(), size)
+    }
+
+    #[test]
+    fn tiles_are_flipped() {
+        let mut map = map(Vector2u::new(2, 2));
+        let layer = map.add_layer();
+        let tile = Tile::from_gid(Gid(0xA000_0006), 1).expect("tile of the tileset");
+        assert!(tile.flip_horizontal && tile.flip_diagonal && !tile.flip_vertical);
+        assert_eq!(tile.id, 5);
+        map.set_tile(layer, 1, 1, Some(tile));
+        let (vertices, _) = map.chunk_vertices(0);
+        assert_eq!(vertices.len(), 6);
+        let [top_left, top_right, bottom_left, _, _, bottom_right] =
+            [0, 1, 2, 3, 4, 5].map(|i| (vertices[i].position, vertices[i].tex_coords));
+        assert_eq!(top_left.0, Vector2f::new(16., 16.));
+        assert_eq!(bottom_right.0, Vector2f::new(32., 32.));
+        // Tile 5 is at (19, 19) to (35, 35), rotated a quarter turn clockwise
+        assert_eq!(top_left.1, Vector2f::new(19., 35.));
+        assert_eq!(top_right.1, Vector2f::new(19., 19.));
+        assert_eq!(bottom_left.1, Vector2f::new(35., 35.));
+        assert_eq!(bottom_right.1, Vector2f::new(35., 19.));
+    }
+
+    #[test]
+    fn chunks_are_culled_and_rebuilt() {
+        let mut map = map(Vector2u::new(40, 20));
+        map.set_chunk_size(8);
+        assert_eq!(map.chunks.len(), 5 * 3);
+        let area = FloatRect::new(Vector2f::new(140., 20.), Vector2f::new(100., 50.));
+        // Columns 140 - 16 to 240, rows 20 to 70 + 16, in chunks of 128 pixels
+        assert_eq!(map.chunks_in(area), (0..2, 0..1));
+        let outside = FloatRect::new(Vector2f::new(-500., -500.), Vector2f::new(10., 10.));
+        assert_eq!(map.chunks_in(outside), (0..0, 0..0));
+
+        let layer = map.add_layer();
+        map.set_animation(
+            2,
+            [(2, Time::milliseconds(100)), (3, Time::milliseconds(100))],
+        );
+        for chunk in &mut map.chunks {
+            chunk.dirty = false;
+        }
+        map.set_tile(layer, 9, 17, Some(Tile::new(2)));
+        let dirty: Vec<_> = map.chunks.iter().map(|c| c.dirty).collect();
+        assert_eq!(dirty.iter().filter(|&&d| d).count(), 1);
+        assert!(dirty[2 * 5 + 1]);
+        let (vertices, animated) = map.chunk_vertices(2 * 5 + 1);
+        assert_eq!(animated, [2]);
+        assert_eq!(vertices[0].tex_coords, Vector2f::new(37., 1.));
+        map.time = Time::milliseconds(150);
+        let (vertices, _) = map.chunk_vertices(2 * 5 + 1);
+        assert_eq!(vertices[0].tex_coords, Vector2f::new(55., 1.));
+    }
+
+    #[test]
+    fn tile_rects_saturate() {
+        let sheet = sheet();
+        assert_eq!(
+            sheet.tile_rect(5),
+            IntRect::new((19, 19).into(), (16, 16).into())
+        );
+        let huge = TileSheet {
+            margin: u32::MAX,
+            ..sheet
+        };
+        assert_eq!(
+            huge.tile_rect(u32::MAX).position,
+            (i32::MAX, i32::MAX).into()
+        );
+    }
+
+    #[test]
+    fn tiled_maps_take_the_size_of_their_layers() {
+        let tmx = r#"<map orientation="orthogonal" width="100000" height="100000"
+                          tilewidth="16" tileheight="16">
+             <tileset firstgid="1" tilewidth="16" tileheight="16" tilecount="4" columns="2"/>
+             <layer width="2" height="1"><data encoding="csv">1,4</data></layer>
+           </map>"#;
+        let tiled = TiledMap::from_memory(tmx, "").expect("valid TMX");
+        let map = TileGrid::from_tiled(&tiled, 0);
+        assert_eq!(map.size, Vector2u::new(2, 1));
+        assert_eq!(map.layers.len(), 1);
+        assert_eq!(map.layers[0].tiles[1], Some(Tile::new(3)));
+    }
+}
@@ -0,0 +1,644 @@
+//! Loading of maps made with the [Tiled](https://www.mapeditor.org) map editor.
+//!
+//! A [`TiledMap`] is loaded from a `.tmx` (XML) or `.tmj` (JSON) file, along with its external
+//! `.tsx` or `.tsj` tilesets. It holds the tilesets, the tile, object, image and group layers,
+//! and the custom properties of the map, its layers, objects and tiles. Tile layers are
+//! usually drawn with a [`TileMap`].
+//!
+//! Tile layer data may be stored as CSV, XML or uncompressed base64. Compressed layer data and
+//! infinite maps aren't supported, and can be turned off in the map properties in Tiled.
+//!
+//! # Usage example
+//!
+//! ```no_run
+//! use sfml::graphics::{tiled::TiledMap, RenderTarget, RenderWindow, Texture, TileMap};
+//! # let mut window: RenderWindow = unimplemented!();
+//!
+//! let map = TiledMap::from_file("level.tmx").unwrap();
+//! let tileset = &map.tilesets[0];
+//! let texture = Texture::from_file(tileset.image.as_ref().unwrap().to_str().unwrap()).unwrap();
+//! let mut tile_map = TileMap::from_tiled(&map, 0, &texture);
+//! for object in map.objects() {
+//!     if object.class == "spawn" {
+//!         println!("Spawn point at {:?}", object.position);
+//!     }
+//! }
+//! tile_map.update(sfml::system::Time::ZERO).unwrap();
+//! window.draw(&tile_map);
+//! ```
+//!
+//! [`TileMap`]: crate::graphics::TileMap
+
+use {
+    crate::{
+        LoadError,
+        graphics::{Color, IntRect, TileSheet, json::Value},
+        system::{Time, Vector2f, Vector2u},
+    },
+    std::{
+        collections::HashMap,
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+mod tmj;
+mod tmx;
+mod xml;
+
+/// The custom properties of a map, tileset, tile, layer or object, by name.
+pub type Properties = HashMap<String, PropertyValue>;
+
+/// The value of a custom property.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    /// A `bool` property
+    Bool(bool),
+    /// An `int` property
+    Int(i64),
+    /// A `float` property
+    Float(f64),
+    /// A `string` property
+    String(String),
+    /// A `color` property
+    Color(Color),
+    /// A `file` property, relative to the current directory like the other paths of the map
+    File(PathBuf),
+    /// An `object` property, holding the id of an object, or 0 for none
+    Object(u32),
+    /// A `class` property, holding the values of its members
+    Class(Properties),
+}
+
+/// A global tile id, as found in tile layers: a tile of one of the tilesets of the map, with
+/// flip flags.
+///
+/// Tilesets own a range of ids starting at their [`first_gid`](Tileset::first_gid). The id 0
+/// means no tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Gid(pub u32);
+
+impl Gid {
+    const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+    const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+    const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+    const ROTATED_HEXAGONAL: u32 = 0x1000_0000;
+    const FLAGS: u32 = Self::FLIPPED_HORIZONTALLY
+        | Self::FLIPPED_VERTICALLY
+        | Self::FLIPPED_DIAGONALLY
+        | Self::ROTATED_HEXAGONAL;
+
+    /// The id without the flip flags.
+    #[must_use]
+    pub fn id(self) -> u32 {
+        self.0 & !Self::FLAGS
+    }
+    /// Whether there is no tile.
+    #[must_use]
+    pub fn is_empty(self) -> bool {
+        self.id() == 0
+    }
+    /// Whether the tile is mirrored left to right.
+    #[must_use]
+    pub fn flipped_horizontally(self) -> bool {
+        self.0 & Self::FLIPPED_HORIZONTALLY != 0
+    }
+    /// Whether the tile is mirrored top to bottom.
+    #[must_use]
+    pub fn flipped_vertically(self) -> bool {
+        self.0 & Self::FLIPPED_VERTICALLY != 0
+    }
+    /// Whether the x and y axes of the tile are swapped, which is done before the other flips.
+    ///
+    /// Combined with the other flips, this rotates tiles by 90 degrees.
+    #[must_use]
+    pub fn flipped_diagonally(self) -> bool {
+        self.0 & Self::FLIPPED_DIAGONALLY != 0
+    }
+}
+
+/// How the tiles of a map are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Orientation {
+    /// A grid of rectangles
+    #[default]
+    Orthogonal,
+    /// A grid of diamonds
+    Isometric,
+    /// Rows of diamonds, shifted every other row or column
+    Staggered,
+    /// Rows of hexagons, shifted every other row or column
+    Hexagonal,
+}
+
+/// A Tiled map.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TiledMap {
+    /// How the tiles are laid out
+    pub orientation: Orientation,
+    /// The size of the map, in tiles
+    pub size: Vector2u,
+    /// The size of a cell of the grid, in pixels
+    pub tile_size: Vector2u,
+    /// The background color, if any
+    pub background_color: Option<Color>,
+    /// The tilesets, by increasing first id
+    pub tilesets: Vec<Tileset>,
+    /// The layers, from bottom to top
+    pub layers: Vec<Layer>,
+    /// The custom properties
+    pub properties: Properties,
+}
+
+impl TiledMap {
+    /// Load a `.tmx` or `.tmj` map from the file at `path`.
+    ///
+    /// The paths in the map, such as tileset images and external tilesets, are relative to the
+    /// directory of the map.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        Self::from_memory(&text, path.parent().unwrap_or(Path::new("")))
+    }
+    /// Load a map from the text of a `.tmx` or `.tmj` file, detecting its format.
+    ///
+    /// The paths in the map are relative to `dir`.
+    pub fn from_memory(text: &str, dir: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let dir = dir.as_ref();
+        let mut map = if is_xml(text) {
+            tmx::map(&xml::Element::parse(text)?, dir)?
+        } else {
+            tmj::map(&Value::parse(text)?, dir)?
+        };
+        map.tilesets.sort_by_key(|tileset| tileset.first_gid);
+        Ok(map)
+    }
+    /// The tileset holding the tile `gid`, and the id of the tile in the tileset.
+    #[must_use]
+    pub fn tileset_of(&self, gid: Gid) -> Option<(&Tileset, u32)> {
+        if gid.is_empty() {
+            return None;
+        }
+        let tileset = self
+            .tilesets
+            .iter()
+            .rev()
+            .find(|tileset| tileset.first_gid <= gid.id())?;
+        Some((tileset, gid.id() - tileset.first_gid))
+    }
+    /// All the layers, with the layers of groups following their group, from bottom to top.
+    pub fn all_layers(&self) -> impl Iterator<Item = &Layer> {
+        let mut stack: Vec<&Layer> = self.layers.iter().rev().collect();
+        std::iter::from_fn(move || {
+            let layer = stack.pop()?;
+            if let LayerKind::Group(children) = &layer.kind {
+                stack.extend(children.iter().rev());
+            }
+            Some(layer)
+        })
+    }
+    /// The objects of all the object layers, from bottom to top.
+    pub fn objects(&self) -> impl Iterator<Item = &Object> {
+        self.all_layers().flat_map(|layer| match &layer.kind {
+            LayerKind::Objects(objects) => objects.objects.as_slice(),
+            _ => &[],
+        })
+    }
+}
+
+/// A set of tiles, cut from one image or made of one image per tile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tileset {
+    /// The global id of the first tile of the tileset
+    pub first_gid: u32,
+    /// The name of the tileset
+    pub name: String,
+    /// The size of the tiles, in pixels
+    pub tile_size: Vector2u,
+    /// The space between two tiles of the image, in pixels
+    pub spacing: u32,
+    /// The space around the tiles of the image, in pixels
+    pub margin: u32,
+    /// The number of tiles
+    pub tile_count: u32,
+    /// The number of tiles in a row of the image
+    pub columns: u32,
+    /// The image holding the tiles, or `None` for a collection of images
+    pub image: Option<PathBuf>,
+    /// The size of the image, in pixels
+    pub image_size: Vector2u,
+    /// The tiles with an animation, an image, a class or custom properties
+    pub tiles: Vec<TileData>,
+    /// The custom properties
+    pub properties: Properties,
+}
+
+impl Tileset {
+    /// The extra data of the tile `id`, if it has any.
+    #[must_use]
+    pub fn tile(&self, id: u32) -> Option<&TileData> {
+        self.tiles.iter().find(|tile| tile.id == id)
+    }
+    /// The area of the tileset image showing the tile `id`.
+    #[must_use]
+    pub fn tile_rect(&self, id: u32) -> IntRect {
+        TileSheet::from(self).tile_rect(id)
+    }
+}
+
+/// The extra data of a tile of a [`Tileset`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TileData {
+    /// The id of the tile in its tileset
+    pub id: u32,
+    /// The class of the tile, or an empty string
+    pub class: String,
+    /// The image of the tile, in tilesets made of images
+    pub image: Option<PathBuf>,
+    /// The frames of the animation of the tile, or nothing if it isn't animated
+    pub animation: Vec<AnimationFrame>,
+    /// The custom properties
+    pub properties: Properties,
+}
+
+/// A frame of a tile animation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimationFrame {
+    /// The id of the tile shown, in the same tileset
+    pub tile_id: u32,
+    /// How long the tile is shown
+    pub duration: Time,
+}
+
+/// A layer of a map.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layer {
+    /// The id of the layer
+    pub id: u32,
+    /// The name of the layer
+    pub name: String,
+    /// The class of the layer, or an empty string
+    pub class: String,
+    /// Whether the layer is shown
+    pub visible: bool,
+    /// The opacity of the layer, from 0 to 1
+    pub opacity: f32,
+    /// The offset of the layer, in pixels
+    pub offset: Vector2f,
+    /// The custom properties
+    pub properties: Properties,
+    /// The contents of the layer
+    pub kind: LayerKind,
+}
+
+/// The contents of a [`Layer`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayerKind {
+    /// A grid of tiles
+    Tiles(TileLayer),
+    /// Free-standing shapes, points and tiles
+    Objects(ObjectLayer),
+    /// A single image
+    Image(ImageLayer),
+    /// Other layers, from bottom to top
+    Group(Vec<Layer>),
+}
+
+/// A grid of tiles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TileLayer {
+    /// The size of the layer, in tiles
+    pub size: Vector2u,
+    /// The tiles, row by row
+    pub tiles: Vec<Gid>,
+}
+
+impl TileLayer {
+    /// The tile at column `x` and row `y`, or an empty tile outside of the layer.
+    #[must_use]
+    pub fn get(&self, x: u32, y: u32) -> Gid {
+        if x < self.size.x && y < self.size.y {
+            self.tiles[y as usize * self.size.x as usize + x as usize]
+        } else {
+            Gid(0)
+        }
+    }
+}
+
+/// The objects of an object layer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectLayer {
+    /// The color the objects are shown with in Tiled, if set
+    pub color: Option<Color>,
+    /// The objects, in drawing order
+    pub objects: Vec<Object>,
+}
+
+/// An object of an [`ObjectLayer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Object {
+    /// The id of the object, unique in the map
+    pub id: u32,
+    /// The name of the object
+    pub name: String,
+    /// The class of the object, or an empty string
+    pub class: String,
+    /// The position of the object, in pixels
+    ///
+    /// This is the top left corner of shapes, and the bottom left corner of tiles.
+    pub position: Vector2f,
+    /// The size of the object, in pixels
+    pub size: Vector2f,
+    /// The rotation of the object around its position, in degrees clockwise
+    pub rotation: f32,
+    /// The tile shown by the object, for tile objects
+    pub gid: Option<Gid>,
+    /// Whether the object is shown
+    pub visible: bool,
+    /// The shape of the object
+    pub shape: ObjectShape,
+    /// The custom properties
+    pub properties: Properties,
+}
+
+/// The shape of an [`Object`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjectShape {
+    /// A rectangle, or a tile for tile objects
+    Rectangle,
+    /// An ellipse filling the size of the object
+    Ellipse,
+    /// A point without size
+    Point,
+    /// A closed polygon, with points relative to the position of the object
+    Polygon(Vec<Vector2f>),
+    /// An open line, with points relative to the position of the object
+    Polyline(Vec<Vector2f>),
+    /// Text, wrapped in the size of the object
+    Text(String),
+}
+
+/// The image of an image layer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageLayer {
+    /// The image shown
+    pub image: PathBuf,
+    /// Whether the image is repeated horizontally
+    pub repeat_x: bool,
+    /// Whether the image is repeated vertically
+    pub repeat_y: bool,
+}
+
+fn is_xml(text: &str) -> bool {
+    text.trim_start().starts_with('<')
+}
+
+/// Load the external tileset at `path`, in either format.
+fn external_tileset(path: &Path, first_gid: u32) -> Result<Tileset, LoadError> {
+    let text = fs::read_to_string(path)?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    if is_xml(&text) {
+        tmx::tileset(&xml::Element::parse(&text)?, first_gid, dir)
+    } else {
+        tmj::tileset(&Value::parse(&text)?, first_gid, dir)
+    }
+}
+
+/// Decode tile layer data stored as comma separated ids.
+fn decode_csv(text: &str) -> Result<Vec<Gid>, LoadError> {
+    text.split(',')
+        .map(|id| id.trim().parse().map(Gid))
+        .collect::<Result<_, _>>()
+        .map_err(|_| LoadError::Invalid("invalid CSV tile data"))
+}
+
+/// Decode tile layer data stored as base64 little endian ids.
+fn decode_base64(text: &str, compression: Option<&str>) -> Result<Vec<Gid>, LoadError> {
+    if compression.is_some_and(|c| !c.is_empty()) {
+        return Err(LoadError::Unsupported("compressed tile layer data"));
+    }
+    let invalid = LoadError::Invalid("invalid base64 tile data");
+    let mut bytes = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            _ => return Err(invalid),
+        };
+        buffer = buffer << 6 | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits).to_le_bytes()[0]);
+        }
+    }
+    if bytes.len() % 4 != 0 {
+        return Err(invalid);
+    }
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|id| Gid(u32::from_le_bytes([id[0], id[1], id[2], id[3]])))
+        .collect())
+}
+
+/// The duration of an animation frame lasting `milliseconds`.
+fn frame_duration(milliseconds: u32) -> Result<Time, LoadError> {
+    i32::try_from(milliseconds)
+        .map(Time::milliseconds)
+        .map_err(|_| LoadError::Invalid("frame duration out of range"))
+}
+
+/// Check that tile data fills its layer.
+fn tile_layer(size: Vector2u, tiles: Vec<Gid>) -> Result<TileLayer, LoadError> {
+    if tiles.len() as u64 != u64::from(size.x) * u64::from(size.y) {
+        return Err(LoadError::Invalid("tile data doesn't match the layer size"));
+    }
+    Ok(TileLayer { size, tiles })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{Gid, LayerKind, ObjectShape, Orientation, PropertyValue, TiledMap, decode_base64},
+        crate::{
+            LoadError,
+            graphics::{Color, IntRect},
+            system::{Time, Vector2f, Vector2u},
+        },
+        std::path::Path,
+    };
+
+    const TMX: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" width="3" height="2" tilewidth="16" tileheight="8"
+     infinite="0" backgroundcolor="#80ff0000">
+ <properties>
+  <property name="music" value="forest.ogg" type="file"/>
+  <property name="gravity" type="float" value="9.5"/>
+  <property name="door" type="object" value="4"/>
+ </properties>
+ <tileset firstgid="1" name="tiles" tilewidth="16" tileheight="8" spacing="1" margin="2"
+          tilecount="6" columns="3">
+  <image source="tiles.png" width="53" height="20"/>
+  <tile id="1" type="water">
+   <properties><property name="deep" type="bool" value="true"/></properties>
+   <animation>
+    <frame tileid="1" duration="100"/>
+    <frame tileid="2" duration="150"/>
+   </animation>
+  </tile>
+ </tileset>
+ <group id="5" name="world" offsetx="4">
+  <layer id="1" name="ground" class="floor" width="3" height="2" opacity="0.5">
+   <data encoding="csv">
+1,2,3,
+0,2147483650,1073741825
+   </data>
+  </layer>
+ </group>
+ <objectgroup id="2" name="things" color="#00ff00">
+  <object id="3" name="start" type="spawn" x="8" y="4">
+   <properties><property name="lives" type="int" value="3"/></properties>
+   <point/>
+  </object>
+  <object id="4" x="0" y="16" width="16" height="8" gid="2" rotation="90"/>
+  <object id="6" x="1" y="2"><polygon points="0,0 4,0 4,4"/></object>
+  <object id="7" x="1" y="2" width="40" height="9"><text wrap="1">Hello</text></object>
+ </objectgroup>
+ <imagelayer id="8" name="sky" repeatx="1" visible="0">
+  <image source="sky.png"/>
+ </imagelayer>
+</map>"##;
+
+    const TMJ: &str = r##"{ "type": "map", "orientation": "orthogonal", "width": 3, "height": 2,
+  "tilewidth": 16, "tileheight": 8, "infinite": false, "backgroundcolor": "#80ff0000",
+  "properties": [
+    { "name": "music", "type": "file", "value": "forest.ogg" },
+    { "name": "gravity", "type": "float", "value": 9.5 },
+    { "name": "door", "type": "object", "value": 4 }
+  ],
+  "tilesets": [{ "firstgid": 1, "name": "tiles", "tilewidth": 16, "tileheight": 8,
+    "spacing": 1, "margin": 2, "tilecount": 6, "columns": 3,
+    "image": "tiles.png", "imagewidth": 53, "imageheight": 20,
+    "tiles": [{ "id": 1, "type": "water",
+      "properties": [{ "name": "deep", "type": "bool", "value": true }],
+      "animation": [{ "tileid": 1, "duration": 100 }, { "tileid": 2, "duration": 150 }] }] }],
+  "layers": [
+    { "type": "group", "id": 5, "name": "world", "offsetx": 4, "visible": true, "opacity": 1,
+      "layers": [{ "type": "tilelayer", "id": 1, "name": "ground", "class": "floor",
+        "width": 3, "height": 2,
+        "opacity": 0.5, "visible": true, "encoding": "base64",
+        "data": "AQAAAAIAAAADAAAAAAAAAAIAAIABAABA" }] },
+    { "type": "objectgroup", "id": 2, "name": "things", "color": "#00ff00",
+      "objects": [
+        { "id": 3, "name": "start", "type": "spawn", "x": 8, "y": 4, "point": true,
+          "properties": [{ "name": "lives", "type": "int", "value": 3 }] },
+        { "id": 4, "x": 0, "y": 16, "width": 16, "height": 8, "gid": 2, "rotation": 90 },
+        { "id": 6, "x": 1, "y": 2, "polygon": [{ "x": 0, "y": 0 }, { "x": 4, "y": 0 },
+          { "x": 4, "y": 4 }] },
+        { "id": 7, "x": 1, "y": 2, "width": 40, "height": 9,
+          "text": { "text": "Hello", "wrap": true } }] },
+    { "type": "imagelayer", "id": 8, "name": "sky", "image": "sky.png", "repeatx": true,
+      "visible": false }
+  ] }"##;
+
+    #[test]
+    fn loads_both_formats() {
+        let dir = Path::new("maps");
+        let tmx = TiledMap::from_memory(TMX, dir).expect("valid TMX");
+        let tmj = TiledMap::from_memory(TMJ, dir).expect("valid TMJ");
+        assert_eq!(tmx, tmj);
+        let map = tmx;
+        assert_eq!(map.orientation, Orientation::Orthogonal);
+        assert_eq!(map.size, Vector2u::new(3, 2));
+        assert_eq!(map.background_color, Some(Color::rgba(255, 0, 0, 128)));
+        assert_eq!(
+            map.properties["music"],
+            PropertyValue::File(dir.join("forest.ogg"))
+        );
+        assert_eq!(map.properties["gravity"], PropertyValue::Float(9.5));
+        assert_eq!(map.properties["door"], PropertyValue::Object(4));
+
+        let tileset = &map.tilesets[0];
+        assert_eq!(
+            tileset.image.as_deref(),
+            Some(dir.join("tiles.png").as_path())
+        );
+        assert_eq!(
+            tileset.tile_rect(4),
+            IntRect::new((19, 11).into(), (16, 8).into())
+        );
+        let water = tileset.tile(1).expect("tile with data");
+        assert_eq!(water.class, "water");
+        assert_eq!(water.properties["deep"], PropertyValue::Bool(true));
+        assert_eq!(water.animation[1].tile_id, 2);
+        assert_eq!(water.animation[1].duration, Time::milliseconds(150));
+
+        let layers: Vec<_> = map.all_layers().map(|l| l.name.as_str()).collect();
+        assert_eq!(layers, ["world", "ground", "things", "sky"]);
+        let ground = map.all_layers().nth(1).expect("ground layer");
+        assert_eq!(ground.class, "floor");
+        assert_eq!(ground.opacity, 0.5);
+        let LayerKind::Tiles(tiles) = &ground.kind else {
+            panic!("not a tile layer");
+        };
+        assert_eq!(tiles.get(2, 0), Gid(3));
+        assert_eq!(tiles.get(5, 0), Gid(0));
+        let flipped = tiles.get(1, 1);
+        assert!(flipped.flipped_horizontally() && !flipped.flipped_vertically());
+        assert_eq!(map.tileset_of(flipped).map(|(_, id)| id), Some(1));
+        assert!(tiles.get(2, 1).flipped_vertically());
+        assert_eq!(map.tileset_of(Gid(0)), None);
+
+        let objects: Vec<_> = map.objects().collect();
+        assert_eq!(objects[0].class, "spawn");
+        assert_eq!(objects[0].shape, ObjectShape::Point);
+        assert_eq!(objects[0].properties["lives"], PropertyValue::Int(3));
+        assert_eq!(objects[1].gid, Some(Gid(2)));
+        assert_eq!(objects[1].rotation, 90.);
+        let ObjectShape::Polygon(points) = &objects[2].shape else {
+            panic!("not a polygon");
+        };
+        assert_eq!(points[2], Vector2f::new(4., 4.));
+        assert_eq!(objects[3].shape, ObjectShape::Text("Hello".into()));
+        let sky = map.all_layers().last().expect("image layer");
+        assert!(!sky.visible);
+        let LayerKind::Image(image) = &sky.kind else {
+            panic!("not an image layer");
+        };
+        assert!(image.repeat_x && !image.repeat_y);
+    }
+
+    #[test]
+    fn rejects_unsupported_data() {
+        let compressed = TMX.replace(
+            "encoding=\"csv\"",
+            "encoding=\"base64\" compression=\"zlib\"",
+        );
+        assert_eq!(
+            TiledMap::from_memory(&compressed, "").map(drop),
+            Err(LoadError::Unsupported("compressed tile layer data"))
+        );
+        let short = TMX.replace("0,2147483650,1073741825", "0,2");
+        assert_eq!(
+            TiledMap::from_memory(&short, "").map(drop),
+            Err(LoadError::Invalid("tile data doesn't match the layer size"))
+        );
+        // Negative and overlong frame durations are rejected by both formats
+        for duration in ["-100", "4294967295"] {
+            let tmx = TMX.replace("duration=\"150\"", &format!("duration=\"{duration}\""));
+            assert!(TiledMap::from_memory(&tmx, "").is_err());
+            let tmj = TMJ.replace("\"duration\": 150", &format!("\"duration\": {duration}"));
+            assert!(TiledMap::from_memory(&tmj, "").is_err());
+        }
+        assert_eq!(
+            decode_base64("AQAAAAIAAAA=", None),
+            Ok(vec![Gid(1), Gid(2)])
+        );
+    }
+}
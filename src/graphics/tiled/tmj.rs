@@ -0,0 +1,341 @@
+//! Loading of the JSON `.tmj` and `.tsj` formats.
+
+use {
+    super::{
+        AnimationFrame, Gid, ImageLayer, Layer, LayerKind, Object, ObjectLayer, ObjectShape,
+        Orientation, Properties, PropertyValue, TileData, TileLayer, TiledMap, Tileset,
+        decode_base64, external_tileset, frame_duration, tile_layer,
+    },
+    crate::{
+        LoadError,
+        graphics::{
+            Color,
+            json::{INVALID_MEMBER, Value, parse_color},
+        },
+        system::{Vector2f, Vector2u},
+    },
+    std::path::Path,
+};
+
+const INVALID: LoadError = LoadError::Invalid(INVALID_MEMBER);
+
+fn u32_or(value: &Value, key: &str, default: u32) -> Result<u32, LoadError> {
+    value
+        .get(key)
+        .map_or(Ok(default), |v| v.as_u32().ok_or(INVALID))
+}
+
+#[expect(
+    clippy::cast_possible_truncation,
+    reason = "Tiled stores single precision values"
+)]
+fn f32_or(value: &Value, key: &str, default: f32) -> Result<f32, LoadError> {
+    value
+        .get(key)
+        .map_or(Ok(default), |v| v.as_f64().map(|v| v as f32).ok_or(INVALID))
+}
+
+fn bool_or(value: &Value, key: &str, default: bool) -> Result<bool, LoadError> {
+    value
+        .get(key)
+        .map_or(Ok(default), |v| v.as_bool().ok_or(INVALID))
+}
+
+fn string(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_owned()
+}
+
+/// The class of a tile or an object, called `type` before Tiled 1.9.
+fn class(value: &Value) -> String {
+    value
+        .get("class")
+        .or_else(|| value.get("type"))
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_owned()
+}
+
+pub(super) fn map(root: &Value, dir: &Path) -> Result<TiledMap, LoadError> {
+    if root.get("type").and_then(Value::as_str) != Some("map") {
+        return Err(LoadError::Invalid("the root object isn't a map"));
+    }
+    if bool_or(root, "infinite", false)? {
+        return Err(LoadError::Unsupported("infinite maps"));
+    }
+    let orientation = match root.get("orientation").and_then(Value::as_str) {
+        Some("orthogonal") | None => Orientation::Orthogonal,
+        Some("isometric") => Orientation::Isometric,
+        Some("staggered") => Orientation::Staggered,
+        Some("hexagonal") => Orientation::Hexagonal,
+        Some(_) => return Err(LoadError::Invalid("unknown orientation")),
+    };
+    let background_color = root
+        .get("backgroundcolor")
+        .map(|color| {
+            color
+                .as_str()
+                .and_then(|color| parse_color(color, true))
+                .ok_or(INVALID)
+        })
+        .transpose()?;
+    let tilesets = root
+        .array_or_empty("tilesets")
+        .ok_or(INVALID)?
+        .iter()
+        .map(|value| {
+            let first_gid = value.required_u32("firstgid").map_err(LoadError::Invalid)?;
+            match value.get("source").and_then(Value::as_str) {
+                Some(source) => external_tileset(&dir.join(source), first_gid),
+                None => tileset(value, first_gid, dir),
+            }
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(TiledMap {
+        orientation,
+        size: Vector2u::new(
+            root.required_u32("width").map_err(LoadError::Invalid)?,
+            root.required_u32("height").map_err(LoadError::Invalid)?,
+        ),
+        tile_size: Vector2u::new(
+            root.required_u32("tilewidth").map_err(LoadError::Invalid)?,
+            root.required_u32("tileheight")
+                .map_err(LoadError::Invalid)?,
+        ),
+        background_color,
+        tilesets,
+        layers: layers(root, dir)?,
+        properties: properties(root, dir)?,
+    })
+}
+
+pub(super) fn tileset(value: &Value, first_gid: u32, dir: &Path) -> Result<Tileset, LoadError> {
+    if value
+        .get("type")
+        .and_then(Value::as_str)
+        .is_some_and(|kind| kind != "tileset")
+    {
+        return Err(LoadError::Invalid("the root object isn't a tileset"));
+    }
+    let tiles = value
+        .array_or_empty("tiles")
+        .ok_or(INVALID)?
+        .iter()
+        .map(|tile| {
+            let animation = tile
+                .array_or_empty("animation")
+                .ok_or(INVALID)?
+                .iter()
+                .map(|frame| {
+                    let duration = frame.required_u32("duration").map_err(LoadError::Invalid)?;
+                    Ok(AnimationFrame {
+                        tile_id: frame.required_u32("tileid").map_err(LoadError::Invalid)?,
+                        duration: frame_duration(duration)?,
+                    })
+                })
+                .collect::<Result<_, LoadError>>()?;
+            Ok(TileData {
+                id: tile.required_u32("id").map_err(LoadError::Invalid)?,
+                class: class(tile),
+                image: tile
+                    .get("image")
+                    .and_then(Value::as_str)
+                    .map(|image| dir.join(image)),
+                animation,
+                properties: properties(tile, dir)?,
+            })
+        })
+        .collect::<Result<_, LoadError>>()?;
+    Ok(Tileset {
+        first_gid,
+        name: string(value, "name"),
+        tile_size: Vector2u::new(
+            value
+                .required_u32("tilewidth")
+                .map_err(LoadError::Invalid)?,
+            value
+                .required_u32("tileheight")
+                .map_err(LoadError::Invalid)?,
+        ),
+        spacing: u32_or(value, "spacing", 0)?,
+        margin: u32_or(value, "margin", 0)?,
+        tile_count: u32_or(value, "tilecount", 0)?,
+        columns: u32_or(value, "columns", 0)?,
+        image: value
+            .get("image")
+            .and_then(Value::as_str)
+            .map(|image| dir.join(image)),
+        image_size: Vector2u::new(
+            u32_or(value, "imagewidth", 0)?,
+            u32_or(value, "imageheight", 0)?,
+        ),
+        tiles,
+        properties: properties(value, dir)?,
+    })
+}
+
+/// The layers in the `layers` member of `parent`.
+fn layers(parent: &Value, dir: &Path) -> Result<Vec<Layer>, LoadError> {
+    let mut result = Vec::new();
+    for value in parent.array_or_empty("layers").ok_or(INVALID)? {
+        let kind = match value.get("type").and_then(Value::as_str) {
+            Some("tilelayer") => LayerKind::Tiles(tiles(value)?),
+            Some("objectgroup") => LayerKind::Objects(ObjectLayer {
+                color: value
+                    .get("color")
+                    .and_then(Value::as_str)
+                    .and_then(|color| parse_color(color, true)),
+                objects: value
+                    .array_or_empty("objects")
+                    .ok_or(INVALID)?
+                    .iter()
+                    .map(|object| self::object(object, dir))
+                    .collect::<Result<_, _>>()?,
+            }),
+            Some("imagelayer") => LayerKind::Image(ImageLayer {
+                image: dir.join(string(value, "image")),
+                repeat_x: bool_or(value, "repeatx", false)?,
+                repeat_y: bool_or(value, "repeaty", false)?,
+            }),
+            Some("group") => LayerKind::Group(layers(value, dir)?),
+            _ => continue,
+        };
+        result.push(Layer {
+            id: u32_or(value, "id", 0)?,
+            name: string(value, "name"),
+            class: string(value, "class"),
+            visible: bool_or(value, "visible", true)?,
+            opacity: f32_or(value, "opacity", 1.)?,
+            offset: Vector2f::new(f32_or(value, "offsetx", 0.)?, f32_or(value, "offsety", 0.)?),
+            properties: properties(value, dir)?,
+            kind,
+        });
+    }
+    Ok(result)
+}
+
+fn tiles(value: &Value) -> Result<TileLayer, LoadError> {
+    let size = Vector2u::new(
+        value.required_u32("width").map_err(LoadError::Invalid)?,
+        value.required_u32("height").map_err(LoadError::Invalid)?,
+    );
+    if value.get("chunks").is_some() {
+        return Err(LoadError::Unsupported("infinite maps"));
+    }
+    let data = value
+        .get("data")
+        .ok_or(LoadError::Invalid("tile layer without data"))?;
+    let tiles = match data {
+        Value::Array(ids) => ids
+            .iter()
+            .map(|id| id.as_u32().map(Gid).ok_or(INVALID))
+            .collect::<Result<_, _>>()?,
+        Value::String(text) => {
+            decode_base64(text, value.get("compression").and_then(Value::as_str))?
+        }
+        _ => return Err(INVALID),
+    };
+    tile_layer(size, tiles)
+}
+
+fn object(value: &Value, dir: &Path) -> Result<Object, LoadError> {
+    let shape = if bool_or(value, "ellipse", false)? {
+        ObjectShape::Ellipse
+    } else if bool_or(value, "point", false)? {
+        ObjectShape::Point
+    } else if value.get("polygon").is_some() {
+        ObjectShape::Polygon(points(value, "polygon")?)
+    } else if value.get("polyline").is_some() {
+        ObjectShape::Polyline(points(value, "polyline")?)
+    } else if let Some(text) = value.get("text") {
+        ObjectShape::Text(string(text, "text"))
+    } else {
+        ObjectShape::Rectangle
+    };
+    Ok(Object {
+        id: u32_or(value, "id", 0)?,
+        name: string(value, "name"),
+        class: class(value),
+        position: Vector2f::new(f32_or(value, "x", 0.)?, f32_or(value, "y", 0.)?),
+        size: Vector2f::new(f32_or(value, "width", 0.)?, f32_or(value, "height", 0.)?),
+        rotation: f32_or(value, "rotation", 0.)?,
+        gid: value
+            .get("gid")
+            .map(|gid| gid.as_u32().map(Gid).ok_or(INVALID))
+            .transpose()?,
+        visible: bool_or(value, "visible", true)?,
+        shape,
+        properties: properties(value, dir)?,
+    })
+}
+
+fn points(value: &Value, key: &str) -> Result<Vec<Vector2f>, LoadError> {
+    value
+        .array_or_empty(key)
+        .ok_or(INVALID)?
+        .iter()
+        .map(|point| {
+            Ok(Vector2f::new(
+                f32_or(point, "x", 0.)?,
+                f32_or(point, "y", 0.)?,
+            ))
+        })
+        .collect()
+}
+
+/// The custom properties in the `properties` member of `value`.
+fn properties(value: &Value, dir: &Path) -> Result<Properties, LoadError> {
+    value
+        .array_or_empty("properties")
+        .ok_or(INVALID)?
+        .iter()
+        .map(|property| {
+            let name = string(property, "name");
+            let value = property.get("value").unwrap_or(&Value::Null);
+            let value = match property.get("type").and_then(Value::as_str) {
+                Some("string") | None => {
+                    PropertyValue::String(value.as_str().ok_or(INVALID)?.into())
+                }
+                Some("int") => PropertyValue::Int(value.as_i64().ok_or(INVALID)?),
+                Some("float") => PropertyValue::Float(value.as_f64().ok_or(INVALID)?),
+                Some("bool") => PropertyValue::Bool(value.as_bool().ok_or(INVALID)?),
+                Some("color") => match value.as_str().ok_or(INVALID)? {
+                    "" => PropertyValue::Color(Color::TRANSPARENT),
+                    color => PropertyValue::Color(parse_color(color, true).ok_or(INVALID)?),
+                },
+                Some("file") => PropertyValue::File(dir.join(value.as_str().ok_or(INVALID)?)),
+                Some("object") => PropertyValue::Object(value.as_u32().ok_or(INVALID)?),
+                Some("class") => PropertyValue::Class(class_members(value)?),
+                Some(_) => return Err(LoadError::Invalid("unknown property type")),
+            };
+            Ok((name, value))
+        })
+        .collect()
+}
+
+/// The members of a class property, whose types aren't stored in the map.
+fn class_members(value: &Value) -> Result<Properties, LoadError> {
+    let members = match value {
+        Value::Null => &[],
+        value => value.as_object().ok_or(INVALID)?,
+    };
+    members
+        .iter()
+        .map(|(name, value)| {
+            let value = match value {
+                Value::Bool(value) => PropertyValue::Bool(*value),
+                Value::Number(number) => match value.as_i64() {
+                    Some(integer) => PropertyValue::Int(integer),
+                    None => PropertyValue::Float(*number),
+                },
+                Value::String(string) => PropertyValue::String(string.clone()),
+                Value::Object(_) => PropertyValue::Class(class_members(value)?),
+                Value::Null | Value::Array(_) => return Err(INVALID),
+            };
+            Ok((name.clone(), value))
+        })
+        .collect()
+}
@@ -0,0 +1,294 @@
+//! Loading of the XML `.tmx` and `.tsx` formats.
+
+use {
+    super::{
+        AnimationFrame, Gid, ImageLayer, Layer, LayerKind, Object, ObjectLayer, ObjectShape,
+        Orientation, Properties, PropertyValue, TileData, TileLayer, TiledMap, Tileset,
+        decode_base64, decode_csv, external_tileset, frame_duration, tile_layer, xml::Element,
+    },
+    crate::{
+        LoadError,
+        graphics::{Color, json::parse_color},
+        system::{Vector2f, Vector2u},
+    },
+    std::{path::Path, str::FromStr},
+};
+
+/// Parse the attribute `name`, if present.
+fn attribute<T: FromStr>(element: &Element, name: &str) -> Result<Option<T>, LoadError> {
+    element
+        .attribute(name)
+        .map(|value| value.trim().parse())
+        .transpose()
+        .map_err(|_| LoadError::Invalid("invalid attribute value"))
+}
+
+fn attribute_or<T: FromStr>(element: &Element, name: &str, default: T) -> Result<T, LoadError> {
+    Ok(attribute(element, name)?.unwrap_or(default))
+}
+
+fn required<T: FromStr>(element: &Element, name: &str) -> Result<T, LoadError> {
+    attribute(element, name)?.ok_or(LoadError::Invalid("missing attribute"))
+}
+
+/// Parse a `0` or `1` attribute.
+fn flag(element: &Element, name: &str, default: bool) -> Result<bool, LoadError> {
+    Ok(attribute_or(element, name, u8::from(default))? != 0)
+}
+
+fn string(element: &Element, name: &str) -> String {
+    element.attribute(name).unwrap_or_default().to_owned()
+}
+
+/// The class of a tile or an object, called `type` before Tiled 1.9.
+fn class(element: &Element) -> String {
+    element
+        .attribute("class")
+        .or_else(|| element.attribute("type"))
+        .unwrap_or_default()
+        .to_owned()
+}
+
+pub(super) fn map(root: &Element, dir: &Path) -> Result<TiledMap, LoadError> {
+    if root.name != "map" {
+        return Err(LoadError::Invalid("the root element isn't a map"));
+    }
+    if flag(root, "infinite", false)? {
+        return Err(LoadError::Unsupported("infinite maps"));
+    }
+    let orientation = match root.attribute("orientation").unwrap_or("orthogonal") {
+        "orthogonal" => Orientation::Orthogonal,
+        "isometric" => Orientation::Isometric,
+        "staggered" => Orientation::Staggered,
+        "hexagonal" => Orientation::Hexagonal,
+        _ => return Err(LoadError::Invalid("unknown orientation")),
+    };
+    let background_color = root
+        .attribute("backgroundcolor")
+        .map(|color| parse_color(color, true).ok_or(LoadError::Invalid("invalid color")))
+        .transpose()?;
+    let tilesets = root
+        .children("tileset")
+        .map(|element| {
+            let first_gid = required(element, "firstgid")?;
+            match element.attribute("source") {
+                Some(source) => external_tileset(&dir.join(source), first_gid),
+                None => tileset(element, first_gid, dir),
+            }
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(TiledMap {
+        orientation,
+        size: Vector2u::new(required(root, "width")?, required(root, "height")?),
+        tile_size: Vector2u::new(required(root, "tilewidth")?, required(root, "tileheight")?),
+        background_color,
+        tilesets,
+        layers: layers(root, dir)?,
+        properties: properties(root, dir)?,
+    })
+}
+
+pub(super) fn tileset(element: &Element, first_gid: u32, dir: &Path) -> Result<Tileset, LoadError> {
+    if element.name != "tileset" {
+        return Err(LoadError::Invalid("the root element isn't a tileset"));
+    }
+    let image = element.child("image");
+    let tiles = element
+        .children("tile")
+        .map(|tile| {
+            let animation = match tile.child("animation") {
+                Some(animation) => animation
+                    .children("frame")
+                    .map(|frame| {
+                        Ok(AnimationFrame {
+                            tile_id: required(frame, "tileid")?,
+                            duration: frame_duration(required(frame, "duration")?)?,
+                        })
+                    })
+                    .collect::<Result<_, LoadError>>()?,
+                None => Vec::new(),
+            };
+            Ok(TileData {
+                id: required(tile, "id")?,
+                class: class(tile),
+                image: tile
+                    .child("image")
+                    .and_then(|image| image.attribute("source"))
+                    .map(|source| dir.join(source)),
+                animation,
+                properties: properties(tile, dir)?,
+            })
+        })
+        .collect::<Result<_, LoadError>>()?;
+    Ok(Tileset {
+        first_gid,
+        name: string(element, "name"),
+        tile_size: Vector2u::new(
+            required(element, "tilewidth")?,
+            required(element, "tileheight")?,
+        ),
+        spacing: attribute_or(element, "spacing", 0)?,
+        margin: attribute_or(element, "margin", 0)?,
+        tile_count: attribute_or(element, "tilecount", 0)?,
+        columns: attribute_or(element, "columns", 0)?,
+        image: image
+            .and_then(|image| image.attribute("source"))
+            .map(|source| dir.join(source)),
+        image_size: match image {
+            Some(image) => Vector2u::new(
+                attribute_or(image, "width", 0)?,
+                attribute_or(image, "height", 0)?,
+            ),
+            None => Vector2u::new(0, 0),
+        },
+        tiles,
+        properties: properties(element, dir)?,
+    })
+}
+
+/// The layers among the children of `parent`.
+fn layers(parent: &Element, dir: &Path) -> Result<Vec<Layer>, LoadError> {
+    let mut result = Vec::new();
+    for element in &parent.children {
+        let kind = match element.name.as_str() {
+            "layer" => LayerKind::Tiles(tiles(element)?),
+            "objectgroup" => LayerKind::Objects(ObjectLayer {
+                color: element
+                    .attribute("color")
+                    .and_then(|color| parse_color(color, true)),
+                objects: element
+                    .children("object")
+                    .map(|object| self::object(object, dir))
+                    .collect::<Result<_, _>>()?,
+            }),
+            "imagelayer" => LayerKind::Image(ImageLayer {
+                image: dir.join(
+                    element
+                        .child("image")
+                        .and_then(|image| image.attribute("source"))
+                        .unwrap_or_default(),
+                ),
+                repeat_x: flag(element, "repeatx", false)?,
+                repeat_y: flag(element, "repeaty", false)?,
+            }),
+            "group" => LayerKind::Group(layers(element, dir)?),
+            _ => continue,
+        };
+        result.push(Layer {
+            id: attribute_or(element, "id", 0)?,
+            name: string(element, "name"),
+            class: string(element, "class"),
+            visible: flag(element, "visible", true)?,
+            opacity: attribute_or(element, "opacity", 1.)?,
+            offset: Vector2f::new(
+                attribute_or(element, "offsetx", 0.)?,
+                attribute_or(element, "offsety", 0.)?,
+            ),
+            properties: properties(element, dir)?,
+            kind,
+        });
+    }
+    Ok(result)
+}
+
+fn tiles(element: &Element) -> Result<TileLayer, LoadError> {
+    let size = Vector2u::new(required(element, "width")?, required(element, "height")?);
+    let data = element
+        .child("data")
+        .ok_or(LoadError::Invalid("tile layer without data"))?;
+    if data.child("chunk").is_some() {
+        return Err(LoadError::Unsupported("infinite maps"));
+    }
+    let tiles = match data.attribute("encoding") {
+        Some("csv") => decode_csv(&data.text)?,
+        Some("base64") => decode_base64(&data.text, data.attribute("compression"))?,
+        None => data
+            .children("tile")
+            .map(|tile| Ok(Gid(attribute_or(tile, "gid", 0)?)))
+            .collect::<Result<_, LoadError>>()?,
+        Some(_) => return Err(LoadError::Unsupported("tile layer encoding")),
+    };
+    tile_layer(size, tiles)
+}
+
+fn object(element: &Element, dir: &Path) -> Result<Object, LoadError> {
+    let shape = if element.child("ellipse").is_some() {
+        ObjectShape::Ellipse
+    } else if element.child("point").is_some() {
+        ObjectShape::Point
+    } else if let Some(polygon) = element.child("polygon") {
+        ObjectShape::Polygon(points(polygon)?)
+    } else if let Some(polyline) = element.child("polyline") {
+        ObjectShape::Polyline(points(polyline)?)
+    } else if let Some(text) = element.child("text") {
+        ObjectShape::Text(text.text.clone())
+    } else {
+        ObjectShape::Rectangle
+    };
+    Ok(Object {
+        id: attribute_or(element, "id", 0)?,
+        name: string(element, "name"),
+        class: class(element),
+        position: Vector2f::new(
+            attribute_or(element, "x", 0.)?,
+            attribute_or(element, "y", 0.)?,
+        ),
+        size: Vector2f::new(
+            attribute_or(element, "width", 0.)?,
+            attribute_or(element, "height", 0.)?,
+        ),
+        rotation: attribute_or(element, "rotation", 0.)?,
+        gid: attribute(element, "gid")?.map(Gid),
+        visible: flag(element, "visible", true)?,
+        shape,
+        properties: properties(element, dir)?,
+    })
+}
+
+/// Parse the `x,y x,y` points of a polygon or polyline.
+fn points(element: &Element) -> Result<Vec<Vector2f>, LoadError> {
+    let invalid = LoadError::Invalid("invalid points");
+    element
+        .attribute("points")
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(|point| {
+            let (x, y) = point.split_once(',').ok_or(invalid)?;
+            Ok(Vector2f::new(
+                x.parse().map_err(|_| invalid)?,
+                y.parse().map_err(|_| invalid)?,
+            ))
+        })
+        .collect()
+}
+
+/// The custom properties of `element`, from its `properties` child.
+fn properties(element: &Element, dir: &Path) -> Result<Properties, LoadError> {
+    let Some(properties) = element.child("properties") else {
+        return Ok(Properties::new());
+    };
+    properties
+        .children("property")
+        .map(|property| {
+            let name = string(property, "name");
+            // Multiline strings are stored as text rather than in `value`
+            let text = property
+                .attribute("value")
+                .unwrap_or(property.text.as_str());
+            let invalid = LoadError::Invalid("invalid property value");
+            let value = match property.attribute("type").unwrap_or("string") {
+                "string" => PropertyValue::String(text.to_owned()),
+                "int" => PropertyValue::Int(text.parse().map_err(|_| invalid)?),
+                "float" => PropertyValue::Float(text.parse().map_err(|_| invalid)?),
+                "bool" => PropertyValue::Bool(text == "true"),
+                "color" if text.is_empty() => PropertyValue::Color(Color::TRANSPARENT),
+                "color" => PropertyValue::Color(parse_color(text, true).ok_or(invalid)?),
+                "file" => PropertyValue::File(dir.join(text)),
+                "object" => PropertyValue::Object(text.parse().map_err(|_| invalid)?),
+                "class" => PropertyValue::Class(self::properties(property, dir)?),
+                _ => return Err(LoadError::Invalid("unknown property type")),
+            };
+            Ok((name, value))
+        })
+        .collect()
+}
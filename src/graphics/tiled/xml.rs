@@ -0,0 +1,239 @@
+//! A small XML reader, enough for the files written by Tiled.
+
+use crate::graphics::json::{MAX_DEPTH, SyntaxError};
+
+/// An XML element, with its attributes, child elements and text content.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub(super) struct Element {
+    pub(super) name: String,
+    pub(super) attributes: Vec<(String, String)>,
+    pub(super) children: Vec<Element>,
+    pub(super) text: String,
+}
+
+impl Element {
+    pub(super) fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+    pub(super) fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+    pub(super) fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    /// Parse a document, returning its root element.
+    pub(super) fn parse(text: &str) -> Result<Self, SyntaxError> {
+        let mut parser = Parser { text, pos: 0 };
+        parser.skip_misc()?;
+        let root = parser.element(0)?;
+        parser.skip_misc()?;
+        if parser.pos < text.len() {
+            return Err(parser.error("unexpected data after the root element"));
+        }
+        Ok(root)
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, reason: &'static str) -> SyntaxError {
+        SyntaxError::at(self.text.as_bytes(), self.pos, reason)
+    }
+    fn rest(&self) -> &str {
+        &self.text[self.pos..]
+    }
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+    /// Skip past `end`, which must come later in the document.
+    fn skip_past(&mut self, end: &str, reason: &'static str) -> Result<(), SyntaxError> {
+        match self.rest().find(end) {
+            Some(index) => {
+                self.pos += index + end.len();
+                Ok(())
+            }
+            None => Err(self.error(reason)),
+        }
+    }
+    /// Skip whitespace, comments, processing instructions and doctypes.
+    fn skip_misc(&mut self) -> Result<(), SyntaxError> {
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("<?") {
+                self.skip_past("?>", "unterminated processing instruction")?;
+            } else if self.rest().starts_with("<!--") {
+                self.skip_past("-->", "unterminated comment")?;
+            } else if self.rest().starts_with("<!DOCTYPE") {
+                self.skip_past(">", "unterminated doctype")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+    fn name(&mut self) -> Result<String, SyntaxError> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || "_-.:".contains(c)))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a name"));
+        }
+        let name = rest[..len].to_owned();
+        self.pos += len;
+        Ok(name)
+    }
+    /// Read an element, starting at its `<`.
+    fn element(&mut self, depth: usize) -> Result<Element, SyntaxError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nested too deeply"));
+        }
+        if !self.rest().starts_with('<') {
+            return Err(self.error("expected an element"));
+        }
+        self.pos += 1;
+        let mut element = Element {
+            name: self.name()?,
+            ..Element::default()
+        };
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.pos += 2;
+                return Ok(element);
+            }
+            if self.rest().starts_with('>') {
+                self.pos += 1;
+                break;
+            }
+            let name = self.name()?;
+            self.skip_whitespace();
+            if !self.rest().starts_with('=') {
+                return Err(self.error("expected '='"));
+            }
+            self.pos += 1;
+            self.skip_whitespace();
+            let Some(quote) = self
+                .rest()
+                .chars()
+                .next()
+                .filter(|c| matches!(c, '"' | '\''))
+            else {
+                return Err(self.error("expected a quoted value"));
+            };
+            self.pos += 1;
+            let Some(len) = self.rest().find(quote) else {
+                return Err(self.error("unterminated attribute value"));
+            };
+            let value =
+                unescape(&self.rest()[..len]).ok_or_else(|| self.error("invalid entity"))?;
+            self.pos += len + 1;
+            element.attributes.push((name, value));
+        }
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("</") {
+                self.pos += 2;
+                if self.name()? != element.name {
+                    return Err(self.error("mismatched closing tag"));
+                }
+                self.skip_whitespace();
+                if !self.rest().starts_with('>') {
+                    return Err(self.error("expected '>'"));
+                }
+                self.pos += 1;
+                return Ok(element);
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->", "unterminated comment")?;
+            } else if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+                let Some(len) = cdata.find("]]>") else {
+                    return Err(self.error("unterminated CDATA section"));
+                };
+                element.text.push_str(&cdata[..len]);
+                self.pos += "<![CDATA[".len() + len + "]]>".len();
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>", "unterminated processing instruction")?;
+            } else if rest.starts_with('<') {
+                element.children.push(self.element(depth + 1)?);
+            } else if rest.is_empty() {
+                return Err(self.error("unterminated element"));
+            } else {
+                let len = rest.find('<').unwrap_or(rest.len());
+                let text = unescape(&rest[..len]).ok_or_else(|| self.error("invalid entity"))?;
+                element.text.push_str(&text);
+                self.pos += len;
+            }
+        }
+    }
+}
+
+/// Replace the entity and character references of `text`.
+fn unescape(text: &str) -> Option<String> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        let end = rest[start..].find(';')? + start;
+        let entity = &rest[start + 1..end];
+        let c = match entity {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = if let Some(hex) = entity.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16).ok()?
+                } else {
+                    entity.strip_prefix('#')?.parse().ok()?
+                };
+                char::from_u32(code)?
+            }
+        };
+        result.push(c);
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::Element, crate::graphics::json::SyntaxError};
+
+    #[test]
+    fn parses_documents() {
+        let root = Element::parse(
+            "<?xml version=\"1.0\"?>\n<!-- map -->\n<map a='1' b=\"x &amp; &#x41;\">\
+             <layer name=\"ground\"><data>1,2,\n3</data></layer><layer/>\
+             <text><![CDATA[<hi>]]> &lt;3</text></map>\n",
+        )
+        .expect("valid XML");
+        assert_eq!(root.name, "map");
+        assert_eq!(root.attribute("b"), Some("x & A"));
+        assert_eq!(root.children("layer").count(), 2);
+        let data = root.child("layer").and_then(|l| l.child("data"));
+        assert_eq!(data.map(|d| d.text.as_str()), Some("1,2,\n3"));
+        assert_eq!(root.child("text").map(|t| t.text.as_str()), Some("<hi> <3"));
+    }
+
+    #[test]
+    fn reports_errors() {
+        let error = |text| match Element::parse(text) {
+            Err(SyntaxError { line, reason }) => (line, reason),
+            other => panic!("{other:?}"),
+        };
+        assert_eq!(error("<a>\n<b></a>"), (2, "mismatched closing tag"));
+        assert_eq!(error("<a x=1/>"), (1, "expected a quoted value"));
+        assert_eq!(error("<a>"), (1, "unterminated element"));
+        assert_eq!(error("<a x='&bad;'/>"), (1, "invalid entity"));
+    }
+}
@@ -0,0 +1,172 @@
+use crate::{
+    graphics::{Transform, Transformable},
+    system::{Angle, Vector2f},
+};
+
+/// A position, rotation, scale and origin, with their combined transform, for the drawables
+/// implemented in Rust.
+///
+/// Unlike the SFML transformables, the transforms are computed eagerly, so that
+/// [`Transformable::transform`] can hand out a reference.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Transformation {
+    position: Vector2f,
+    rotation: Angle,
+    scale: Vector2f,
+    origin: Vector2f,
+    transform: Transform,
+    inverse: Transform,
+}
+
+impl Default for Transformation {
+    fn default() -> Self {
+        Self {
+            position: Vector2f::new(0., 0.),
+            rotation: Angle::degrees(0.),
+            scale: Vector2f::new(1., 1.),
+            origin: Vector2f::new(0., 0.),
+            transform: Transform::IDENTITY,
+            inverse: Transform::IDENTITY,
+        }
+    }
+}
+
+impl Transformation {
+    /// Same as `sf::Transformable::getTransform`.
+    fn update(&mut self) {
+        let angle = -self.rotation.as_radians();
+        let (sine, cosine) = angle.sin_cos();
+        let (sxc, syc) = (self.scale.x * cosine, self.scale.y * cosine);
+        let (sxs, sys) = (self.scale.x * sine, self.scale.y * sine);
+        let tx = -self.origin.x * sxc - self.origin.y * sys + self.position.x;
+        let ty = self.origin.x * sxs - self.origin.y * syc + self.position.y;
+        self.transform = Transform::new(sxc, sys, tx, -sxs, syc, ty, 0., 0., 1.);
+        self.inverse = self.transform.inverse();
+    }
+}
+
+impl Transformable for Transformation {
+    fn set_position<P: Into<Vector2f>>(&mut self, position: P) {
+        self.position = position.into();
+        self.update();
+    }
+    fn set_rotation(&mut self, angle: Angle) {
+        self.rotation = angle.wrap_unsigned();
+        self.update();
+    }
+    fn set_scale<S: Into<Vector2f>>(&mut self, scale: S) {
+        self.scale = scale.into();
+        self.update();
+    }
+    fn set_origin<O: Into<Vector2f>>(&mut self, origin: O) {
+        self.origin = origin.into();
+        self.update();
+    }
+    fn position(&self) -> Vector2f {
+        self.position
+    }
+    fn rotation(&self) -> Angle {
+        self.rotation
+    }
+    fn get_scale(&self) -> Vector2f {
+        self.scale
+    }
+    fn origin(&self) -> Vector2f {
+        self.origin
+    }
+    fn move_<O: Into<Vector2f>>(&mut self, offset: O) {
+        self.set_position(self.position + offset.into());
+    }
+    fn rotate(&mut self, angle: Angle) {
+        self.set_rotation(self.rotation + angle);
+    }
+    fn scale<F: Into<Vector2f>>(&mut self, factors: F) {
+        let factors = factors.into();
+        self.set_scale(Vector2f::new(
+            self.scale.x * factors.x,
+            self.scale.y * factors.y,
+        ));
+    }
+    fn transform(&self) -> &Transform {
+        &self.transform
+    }
+    fn inverse_transform(&self) -> &Transform {
+        &self.inverse
+    }
+}
+
+/// Implement [`Transformable`] for a type by forwarding to its [`Transformation`] field.
+macro_rules! impl_transformable {
+    ($type:ty, $field:ident) => {
+        impl $crate::graphics::Transformable for $type {
+            fn set_position<P: Into<$crate::system::Vector2f>>(&mut self, position: P) {
+                $crate::graphics::Transformable::set_position(&mut self.$field, position);
+            }
+            fn set_rotation(&mut self, angle: $crate::system::Angle) {
+                $crate::graphics::Transformable::set_rotation(&mut self.$field, angle);
+            }
+            fn set_scale<S: Into<$crate::system::Vector2f>>(&mut self, scale: S) {
+                $crate::graphics::Transformable::set_scale(&mut self.$field, scale);
+            }
+            fn set_origin<O: Into<$crate::system::Vector2f>>(&mut self, origin: O) {
+                $crate::graphics::Transformable::set_origin(&mut self.$field, origin);
+            }
+            fn position(&self) -> $crate::system::Vector2f {
+                $crate::graphics::Transformable::position(&self.$field)
+            }
+            fn rotation(&self) -> $crate::system::Angle {
+                $crate::graphics::Transformable::rotation(&self.$field)
+            }
+            fn get_scale(&self) -> $crate::system::Vector2f {
+                $crate::graphics::Transformable::get_scale(&self.$field)
+            }
+            fn origin(&self) -> $crate::system::Vector2f {
+                $crate::graphics::Transformable::origin(&self.$field)
+            }
+            fn move_<O: Into<$crate::system::Vector2f>>(&mut self, offset: O) {
+                $crate::graphics::Transformable::move_(&mut self.$field, offset);
+            }
+            fn rotate(&mut self, angle: $crate::system::Angle) {
+                $crate::graphics::Transformable::rotate(&mut self.$field, angle);
+            }
+            fn scale<F: Into<$crate::system::Vector2f>>(&mut self, factors: F) {
+                $crate::graphics::Transformable::scale(&mut self.$field, factors);
+            }
+            fn transform(&self) -> &$crate::graphics::Transform {
+                $crate::graphics::Transformable::transform(&self.$field)
+            }
+            fn inverse_transform(&self) -> &$crate::graphics::Transform {
+                $crate::graphics::Transformable::inverse_transform(&self.$field)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_transformable;
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::Transformation,
+        crate::{graphics::Transformable, system::Angle},
+    };
+
+    #[test]
+    fn transform_matches_sfml() {
+        let mut transformation = Transformation::default();
+        transformation.set_position((10., 20.));
+        transformation.set_origin((1., 2.));
+        transformation.set_scale((2., 3.));
+        transformation.rotate(Angle::degrees(450.));
+        assert!((transformation.rotation().as_degrees() - 90.).abs() < 1e-3);
+        let m = transformation.transform().matrix();
+        // Rotating by 90 degrees turns x into y and y into -x
+        let expected = [0., 2., -3., 0., 16., 18.];
+        let actual = [m[0], m[1], m[4], m[5], m[12], m[13]];
+        for (actual, expected) in actual.into_iter().zip(expected) {
+            assert!((actual - expected).abs() < 1e-5, "{actual:?}");
+        }
+        let inverse = transformation.inverse_transform().matrix();
+        assert!((inverse[12] + 9.).abs() < 1e-4 && (inverse[13] - 16. / 3.).abs() < 1e-4);
+    }
+}
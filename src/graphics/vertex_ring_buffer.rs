@@ -0,0 +1,195 @@
+use crate::{
+    SfError, SfResult,
+    cpp::FBox,
+    graphics::{
+        PrimitiveType, RenderStates, RenderTarget, Vertex, VertexBuffer, VertexBufferUsage,
+    },
+};
+
+/// A range of vertices allocated in a [`VertexRingBuffer`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct VertexRange {
+    /// Index of the first vertex of the range in the vertex buffer
+    pub first: usize,
+    /// Number of vertices in the range
+    pub count: usize,
+}
+
+/// Where the next allocation of a ring buffer goes.
+///
+/// The vertices allocated since the start of the frame are the `frame_start..head` region,
+/// wrapping around the end of the buffer if `wrapped` is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RingCursor {
+    capacity: usize,
+    head: usize,
+    frame_start: usize,
+    wrapped: bool,
+}
+
+impl RingCursor {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            head: 0,
+            frame_start: 0,
+            wrapped: false,
+        }
+    }
+    fn frame_is_empty(&self) -> bool {
+        self.head == self.frame_start && !self.wrapped
+    }
+    /// Largest number of vertices that can be allocated at once.
+    fn remaining(&self) -> usize {
+        if self.frame_is_empty() {
+            self.capacity
+        } else if self.wrapped {
+            self.frame_start - self.head
+        } else {
+            (self.capacity - self.head).max(self.frame_start)
+        }
+    }
+    /// Allocate `count` contiguous vertices, returning the index of the first one.
+    fn alloc(&mut self, count: usize) -> Option<usize> {
+        if count > self.remaining() {
+            return None;
+        }
+        let first = if self.head + count <= self.capacity {
+            self.head
+        } else if self.frame_is_empty() {
+            // Nothing of this frame to protect, restart from the beginning of the buffer
+            self.frame_start = 0;
+            0
+        } else {
+            self.wrapped = true;
+            0
+        };
+        self.head = first + count;
+        Some(first)
+    }
+    fn begin_frame(&mut self) {
+        self.frame_start = self.head;
+        self.wrapped = false;
+    }
+}
+
+/// A [`VertexBuffer`] that dynamic geometry is streamed to every frame.
+///
+/// Vertices are sub-allocated one after the other with [`push`], wrapping around the end of the
+/// buffer, and each allocation is drawn on its own with [`draw`]. Allocations stay valid until
+/// [`begin_frame`] is called, which lets the next frame reuse the space.
+///
+/// All the vertices pushed between two calls to [`begin_frame`] must fit in the buffer.
+///
+/// [`push`]: Self::push
+/// [`draw`]: Self::draw
+/// [`begin_frame`]: Self::begin_frame
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::graphics::*;
+/// # let mut window: RenderWindow = unimplemented!();
+/// let mut ring = VertexRingBuffer::new(PrimitiveType::TRIANGLES, 4096).unwrap();
+/// while window.is_open() {
+///     ring.begin_frame();
+///     window.clear(Color::BLACK);
+///     let triangle = [
+///         Vertex::with_pos_color((10., 10.).into(), Color::RED),
+///         Vertex::with_pos_color((100., 10.).into(), Color::GREEN),
+///         Vertex::with_pos_color((10., 100.).into(), Color::BLUE),
+///     ];
+///     let range = ring.push(&triangle).unwrap();
+///     ring.draw(&mut window, range, &RenderStates::DEFAULT);
+///     window.display();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct VertexRingBuffer {
+    buffer: FBox<VertexBuffer>,
+    cursor: RingCursor,
+}
+
+impl VertexRingBuffer {
+    /// Create a ring buffer of primitives of type `primitive_type`, holding up to `capacity`
+    /// vertices per frame.
+    pub fn new(primitive_type: PrimitiveType, capacity: usize) -> SfResult<Self> {
+        Ok(Self {
+            buffer: VertexBuffer::new(primitive_type, capacity, VertexBufferUsage::STREAM)?,
+            cursor: RingCursor::new(capacity),
+        })
+    }
+    /// Start a new frame, making the space of all the previous allocations reusable.
+    pub fn begin_frame(&mut self) {
+        self.cursor.begin_frame();
+    }
+    /// Copy `vertices` to the buffer, and return the range they were allocated at.
+    ///
+    /// Fails if there isn't [`remaining`] space for them in this frame, or if the buffer couldn't
+    /// be updated.
+    ///
+    /// [`remaining`]: Self::remaining
+    pub fn push(&mut self, vertices: &[Vertex]) -> SfResult<VertexRange> {
+        let mut cursor = self.cursor;
+        let first = cursor.alloc(vertices.len()).ok_or(SfError::CallFailed)?;
+        if !vertices.is_empty() {
+            let offset = u32::try_from(first).map_err(|_| SfError::CallFailed)?;
+            self.buffer.update(vertices, offset)?;
+        }
+        self.cursor = cursor;
+        Ok(VertexRange {
+            first,
+            count: vertices.len(),
+        })
+    }
+    /// Draw a range returned by [`push`](Self::push) during this frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is outside of the buffer.
+    pub fn draw(&self, target: &mut dyn RenderTarget, range: VertexRange, rs: &RenderStates) {
+        target.draw_vertex_buffer_range(&self.buffer, range.first, range.count, rs);
+    }
+    /// The largest number of vertices that can still be pushed at once in this frame.
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        self.cursor.remaining()
+    }
+    /// The number of vertices the buffer can hold.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.cursor.capacity
+    }
+    /// The underlying vertex buffer.
+    #[must_use]
+    pub fn vertex_buffer(&self) -> &VertexBuffer {
+        &self.buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RingCursor;
+
+    #[test]
+    fn allocations_wrap_without_overwriting_the_frame() {
+        let mut cursor = RingCursor::new(10);
+        assert_eq!(cursor.alloc(4), Some(0));
+        assert_eq!(cursor.alloc(4), Some(4));
+        assert_eq!(cursor.remaining(), 2);
+        assert_eq!(cursor.alloc(3), None);
+        cursor.begin_frame();
+        // The tail is too short, the allocation wraps over the previous frame
+        assert_eq!(cursor.remaining(), 10);
+        assert_eq!(cursor.alloc(3), Some(0));
+        assert_eq!(cursor.alloc(2), Some(3));
+        cursor.begin_frame();
+        assert_eq!(cursor.alloc(4), Some(5));
+        assert_eq!(cursor.alloc(3), Some(0));
+        assert!(cursor.wrapped);
+        assert_eq!(cursor.remaining(), 2);
+        assert_eq!(cursor.alloc(2), Some(3));
+        assert_eq!(cursor.alloc(1), None);
+        assert_eq!(cursor.alloc(0), Some(5));
+    }
+}
@@ -35,7 +35,7 @@ pub mod graphics;
 pub mod system;
 #[cfg(feature = "window")]
 pub mod window;
-use std::{error::Error, fmt::Display};
+use std::{error::Error, fmt::Display, io};
 
 /// An SFML operation has failed
 #[derive(Clone, Copy, Debug)]
@@ -66,6 +66,44 @@ impl Error for SfError {}
 /// Result of a fallible SFML operation
 pub type SfResult<T> = Result<T, SfError>;
 
+/// Loading a file in one of the text formats read by this crate has failed, such as a sprite
+/// sheet, a Tiled map or key bindings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadError {
+    /// The text is malformed
+    Syntax {
+        /// The line number, starting at 1
+        line: usize,
+        /// What is wrong with the line
+        reason: &'static str,
+    },
+    /// The text is well formed, but its content isn't valid
+    Invalid(&'static str),
+    /// The content uses a feature that isn't supported
+    Unsupported(&'static str),
+    /// A file couldn't be read
+    Io(io::ErrorKind),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Syntax { line, reason } => write!(f, "Syntax error at line {line}: {reason}"),
+            Self::Invalid(what) => write!(f, "Invalid content: {what}"),
+            Self::Unsupported(what) => write!(f, "Unsupported feature: {what}"),
+            Self::Io(kind) => write!(f, "Failed to read file: {kind}"),
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error.kind())
+    }
+}
+
+impl Error for LoadError {}
+
 trait IntoSfResult<T> {
     fn into_sf_result(self) -> SfResult<T>;
 }
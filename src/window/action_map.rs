@@ -0,0 +1,868 @@
+use {
+    crate::{
+        LoadError,
+        window::{Input, InputState, Key, Scancode, joystick, mouse},
+    },
+    std::{fmt, fs, io, path::Path},
+};
+
+/// Find the variant of an enum whose `Debug` name is `name`.
+///
+/// The discriminants of the variants are contiguous, starting at -1 or 0.
+fn variant_named<T: TryFrom<i32> + fmt::Debug>(name: &str) -> Option<T> {
+    (-1..)
+        .map(T::try_from)
+        .skip_while(Result::is_err)
+        .map_while(Result::ok)
+        .find(|variant| format!("{variant:?}") == name)
+}
+
+/// How far an action has to be activated to count as pressed.
+const PRESS_THRESHOLD: f32 = 0.5;
+
+/// The modifier keys that have to be held for a key binding to activate.
+///
+/// Either the left or the right key of a modifier works. Modifiers that aren't required are
+/// ignored, so `Ctrl+S` activates a binding to `S`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Modifiers {
+    /// Alt has to be held
+    pub alt: bool,
+    /// Ctrl has to be held
+    pub ctrl: bool,
+    /// Shift has to be held
+    pub shift: bool,
+    /// System has to be held
+    pub system: bool,
+}
+
+impl Modifiers {
+    /// No modifier
+    pub const NONE: Self = Self {
+        alt: false,
+        ctrl: false,
+        shift: false,
+        system: false,
+    };
+
+    fn held(self, input: &InputState) -> bool {
+        let either = |left, right| input.pressed(left) || input.pressed(right);
+        (!self.alt || either(Key::LAlt, Key::RAlt))
+            && (!self.ctrl || either(Key::LControl, Key::RControl))
+            && (!self.shift || either(Key::LShift, Key::RShift))
+            && (!self.system || either(Key::LSystem, Key::RSystem))
+    }
+}
+
+/// A physical input an action is bound to.
+///
+/// Each binding has a value in range [0 .. 1], and the value of an action is the largest value
+/// of its bindings. Joystick bindings read the joystick assigned to the [`ActionMap`].
+///
+/// Bindings are saved as text by [`ActionMap::to_text`], in the format given by their
+/// [`Display`](fmt::Display) implementation:
+///
+/// | Binding                | Text                                              |
+/// |------------------------|---------------------------------------------------|
+/// | [`Key`](Self::Key)     | `key:Space`, `key:S+ctrl+shift`                   |
+/// | [`Scancode`](Self::Scancode) | `scan:W`, `scan:Z+alt`                      |
+/// | [`Mouse`](Self::Mouse) | `mouse:Left`                                      |
+/// | [`MouseWheel`](Self::MouseWheel) | `wheel:up`, `wheel:down`, `wheel:left`, `wheel:right` |
+/// | [`JoystickButton`](Self::JoystickButton) | `button:0`                      |
+/// | [`JoystickAxis`](Self::JoystickAxis) | `axis:X:20:100`                     |
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Binding {
+    /// A key, by its layout-dependent code, held with `modifiers`
+    Key {
+        /// The key
+        code: Key,
+        /// The modifiers to hold with the key
+        modifiers: Modifiers,
+    },
+    /// A key, by its physical position on the keyboard, held with `modifiers`
+    Scancode {
+        /// The scancode of the key
+        scan: Scancode,
+        /// The modifiers to hold with the key
+        modifiers: Modifiers,
+    },
+    /// A mouse button
+    Mouse(mouse::Button),
+    /// The mouse wheel, scrolled in one direction.
+    ///
+    /// The value is how much the wheel was scrolled during the frame, so it's only active for
+    /// the frames in which the wheel moves.
+    MouseWheel {
+        /// The wheel
+        wheel: mouse::Wheel,
+        /// Whether the binding is for positive deltas (up or left) or negative ones
+        positive: bool,
+    },
+    /// A button of the joystick
+    JoystickButton(u32),
+    /// A range of an axis of the joystick.
+    ///
+    /// The value is 0 at `from`, and rises linearly to 1 at `to`. Axis positions are in
+    /// range [-100 .. 100], so `from: 20., to: 100.` is the right half of an X axis with a
+    /// dead zone, and `from: -20., to: -100.` is the left half.
+    JoystickAxis {
+        /// The axis
+        axis: joystick::Axis,
+        /// The position where the value starts rising above 0
+        from: f32,
+        /// The position where the value reaches 1
+        to: f32,
+    },
+}
+
+impl Binding {
+    /// A binding to `code`, without modifiers.
+    #[must_use]
+    pub const fn key(code: Key) -> Self {
+        Self::Key {
+            code,
+            modifiers: Modifiers::NONE,
+        }
+    }
+    /// A binding to `scan`, without modifiers.
+    #[must_use]
+    pub const fn scancode(scan: Scancode) -> Self {
+        Self::Scancode {
+            scan,
+            modifiers: Modifiers::NONE,
+        }
+    }
+    /// Whether the binding is to a joystick, rather than to the keyboard or mouse.
+    #[must_use]
+    pub const fn is_joystick(&self) -> bool {
+        matches!(self, Self::JoystickButton(_) | Self::JoystickAxis { .. })
+    }
+    fn value(&self, input: &InputState, joystick: Option<u32>) -> f32 {
+        let active = |active| if active { 1. } else { 0. };
+        match *self {
+            Self::Key { code, modifiers } => active(input.pressed(code) && modifiers.held(input)),
+            Self::Scancode { scan, modifiers } => {
+                active(input.pressed(scan) && modifiers.held(input))
+            }
+            Self::Mouse(button) => active(input.pressed(button)),
+            Self::MouseWheel { wheel, positive } => {
+                let delta = input.wheel_delta(wheel);
+                (if positive { delta } else { -delta }).clamp(0., 1.)
+            }
+            Self::JoystickButton(button) => {
+                active(joystick.is_some_and(|joystick| {
+                    input.pressed(Input::JoystickButton { joystick, button })
+                }))
+            }
+            Self::JoystickAxis { axis, from, to } => joystick.map_or(0., |joystick| {
+                let position = input.joystick_axis(joystick, axis);
+                ((position - from) / (to - from)).clamp(0., 1.)
+            }),
+        }
+    }
+    fn parse(text: &str) -> Result<Self, &'static str> {
+        let (kind, rest) = text.split_once(':').ok_or("missing binding kind")?;
+        match kind {
+            "key" | "scan" => {
+                let mut parts = rest.split('+');
+                let name = parts.next().unwrap_or_default();
+                let mut modifiers = Modifiers::NONE;
+                for modifier in parts {
+                    let flag = match modifier {
+                        "alt" => &mut modifiers.alt,
+                        "ctrl" => &mut modifiers.ctrl,
+                        "shift" => &mut modifiers.shift,
+                        "system" => &mut modifiers.system,
+                        _ => return Err("unknown modifier"),
+                    };
+                    *flag = true;
+                }
+                if kind == "key" {
+                    let code = variant_named::<Key>(name).ok_or("unknown key")?;
+                    Ok(Self::Key { code, modifiers })
+                } else {
+                    let scan = variant_named::<Scancode>(name).ok_or("unknown scancode")?;
+                    Ok(Self::Scancode { scan, modifiers })
+                }
+            }
+            "mouse" => variant_named::<mouse::Button>(rest)
+                .map(Self::Mouse)
+                .ok_or("unknown mouse button"),
+            "wheel" => {
+                let (wheel, positive) = match rest {
+                    "up" => (mouse::Wheel::VerticalWheel, true),
+                    "down" => (mouse::Wheel::VerticalWheel, false),
+                    "left" => (mouse::Wheel::HorizontalWheel, true),
+                    "right" => (mouse::Wheel::HorizontalWheel, false),
+                    _ => return Err("unknown wheel direction"),
+                };
+                Ok(Self::MouseWheel { wheel, positive })
+            }
+            "button" => rest
+                .parse()
+                .map(Self::JoystickButton)
+                .map_err(|_| "invalid joystick button"),
+            "axis" => {
+                let mut parts = rest.split(':');
+                let axis = variant_named::<joystick::Axis>(parts.next().unwrap_or_default())
+                    .ok_or("unknown joystick axis")?;
+                let mut position = || -> Result<f32, &'static str> {
+                    parts
+                        .next()
+                        .and_then(|part| part.parse().ok())
+                        .filter(|position: &f32| position.is_finite())
+                        .ok_or("invalid joystick axis range")
+                };
+                let (from, to) = (position()?, position()?);
+                if from == to || parts.next().is_some() {
+                    return Err("invalid joystick axis range");
+                }
+                Ok(Self::JoystickAxis { axis, from, to })
+            }
+            _ => Err("unknown binding kind"),
+        }
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let modifiers = |f: &mut fmt::Formatter<'_>, modifiers: Modifiers| {
+            for (held, name) in [
+                (modifiers.ctrl, "ctrl"),
+                (modifiers.alt, "alt"),
+                (modifiers.shift, "shift"),
+                (modifiers.system, "system"),
+            ] {
+                if held {
+                    write!(f, "+{name}")?;
+                }
+            }
+            Ok(())
+        };
+        match *self {
+            Self::Key { code, modifiers: m } => {
+                write!(f, "key:{code:?}")?;
+                modifiers(f, m)
+            }
+            Self::Scancode { scan, modifiers: m } => {
+                write!(f, "scan:{scan:?}")?;
+                modifiers(f, m)
+            }
+            Self::Mouse(button) => write!(f, "mouse:{button:?}"),
+            Self::MouseWheel { wheel, positive } => f.write_str(match (wheel, positive) {
+                (mouse::Wheel::VerticalWheel, true) => "wheel:up",
+                (mouse::Wheel::VerticalWheel, false) => "wheel:down",
+                (mouse::Wheel::HorizontalWheel, true) => "wheel:left",
+                (mouse::Wheel::HorizontalWheel, false) => "wheel:right",
+            }),
+            Self::JoystickButton(button) => write!(f, "button:{button}"),
+            Self::JoystickAxis { axis, from, to } => write!(f, "axis:{axis:?}:{from}:{to}"),
+        }
+    }
+}
+
+/// Which side of an action a binding drives.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Polarity {
+    /// The binding pushes the action towards 1
+    #[default]
+    Positive,
+    /// The binding pushes the action towards -1, for analog axes like "move left/right"
+    Negative,
+}
+
+#[derive(Clone, Debug)]
+struct Action {
+    name: String,
+    positive: Vec<Binding>,
+    negative: Vec<Binding>,
+    value: f32,
+    previous: f32,
+}
+
+impl Action {
+    fn bindings_mut(&mut self, polarity: Polarity) -> &mut Vec<Binding> {
+        match polarity {
+            Polarity::Positive => &mut self.positive,
+            Polarity::Negative => &mut self.negative,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Rebinding {
+    action: usize,
+    polarity: Polarity,
+    axes: Option<[f32; 8]>,
+}
+
+/// Binds named actions and analog axes to keys, mouse buttons and joystick inputs.
+///
+/// Each action has a value resolved every frame by [`update`] from an [`InputState`]. Positive
+/// bindings push the value towards 1 and negative bindings towards -1, so an action is either a
+/// digital button like "jump" or an analog axis like "move right".
+///
+/// Each player has their own map, with the devices they use: the keyboard and mouse, a joystick,
+/// or both. Bindings can be changed at any time, including from the next input of the player with
+/// [`start_rebinding`], and saved and loaded as text, with one action per line:
+///
+/// ```text
+/// # Comments start with '#'
+/// jump = key:Space, button:0
+/// move_x = -key:A, key:D, -axis:X:-20:-100, axis:X:20:100
+/// ```
+///
+/// Negative bindings are prefixed with `-`. See [`Binding`] for the format of each binding.
+///
+/// [`update`]: Self::update
+/// [`start_rebinding`]: Self::start_rebinding
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::window::{ActionMap, Binding, InputState, Key, Polarity, Window};
+/// # let mut window: Window = unimplemented!();
+/// let mut input = InputState::new();
+/// let mut player = ActionMap::new();
+/// player.bind("jump", Binding::key(Key::Space));
+/// player.bind_axis("move_x", Binding::key(Key::A), Binding::key(Key::D));
+/// player.set_joystick(Some(0));
+/// player.bind("jump", Binding::JoystickButton(0));
+/// while window.is_open() {
+///     input.begin_frame();
+///     window.handle_events(&mut input);
+///     player.update(&input);
+///     if player.just_pressed("jump") {
+///         // Jump
+///     }
+///     let speed = player.value("move_x") * 200.;
+///     if input.just_pressed(Key::F1) {
+///         // Bind the next input to "jump"
+///         player.start_rebinding("jump", Polarity::Positive);
+///     }
+///     window.display();
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ActionMap {
+    actions: Vec<Action>,
+    keyboard_mouse: bool,
+    joystick: Option<u32>,
+    rebinding: Option<Rebinding>,
+}
+
+impl Default for ActionMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ActionMap {
+    /// Create a map without actions, using the keyboard and mouse.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            actions: Vec::new(),
+            keyboard_mouse: true,
+            joystick: None,
+            rebinding: None,
+        }
+    }
+    /// Set whether the bindings to the keyboard and mouse are active for this map.
+    pub fn set_keyboard_mouse(&mut self, enabled: bool) {
+        self.keyboard_mouse = enabled;
+    }
+    /// Whether the bindings to the keyboard and mouse are active for this map.
+    #[must_use]
+    pub fn keyboard_mouse(&self) -> bool {
+        self.keyboard_mouse
+    }
+    /// Set the joystick the joystick bindings read, or `None` to disable them.
+    pub fn set_joystick(&mut self, joystick: Option<u32>) {
+        self.joystick = joystick;
+    }
+    /// The joystick the joystick bindings read.
+    #[must_use]
+    pub fn joystick(&self) -> Option<u32> {
+        self.joystick
+    }
+
+    /// Bind `action` to `binding`, creating the action if needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name of the action is empty, or contains whitespace, `=`, `,` or `#`.
+    pub fn bind(&mut self, action: &str, binding: Binding) {
+        self.bind_with(action, Polarity::Positive, binding);
+    }
+    /// Bind `action` to `binding`, as a negative binding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name of the action is invalid, see [`bind`](Self::bind).
+    pub fn bind_negative(&mut self, action: &str, binding: Binding) {
+        self.bind_with(action, Polarity::Negative, binding);
+    }
+    /// Bind the analog axis `action` to `negative` and `positive`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name of the action is invalid, see [`bind`](Self::bind).
+    pub fn bind_axis(&mut self, action: &str, negative: Binding, positive: Binding) {
+        self.bind_negative(action, negative);
+        self.bind(action, positive);
+    }
+    /// Bind `action` to `binding`, on the side given by `polarity`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name of the action is invalid, see [`bind`](Self::bind).
+    pub fn bind_with(&mut self, action: &str, polarity: Polarity, binding: Binding) {
+        let index = self.action_or_insert(action);
+        let bindings = self.actions[index].bindings_mut(polarity);
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+    /// Remove `binding` from `action`, on both sides.
+    pub fn unbind(&mut self, action: &str, binding: &Binding) {
+        if let Some(action) = self.find_mut(action) {
+            action.positive.retain(|b| b != binding);
+            action.negative.retain(|b| b != binding);
+        }
+    }
+    /// Remove all the bindings of `action`. The action itself stays, with a value of 0.
+    pub fn clear(&mut self, action: &str) {
+        if let Some(action) = self.find_mut(action) {
+            action.positive.clear();
+            action.negative.clear();
+        }
+    }
+    /// The bindings of `action` on the side given by `polarity`.
+    #[must_use]
+    pub fn bindings(&self, action: &str, polarity: Polarity) -> &[Binding] {
+        self.find(action).map_or(&[], |action| match polarity {
+            Polarity::Positive => &action.positive,
+            Polarity::Negative => &action.negative,
+        })
+    }
+    /// The names of the actions, in the order they were created.
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.actions.iter().map(|action| action.name.as_str())
+    }
+
+    /// Resolve the value of all the actions for the current frame.
+    ///
+    /// This has to be called once per frame, after all the events of the frame were given to
+    /// `input`. If a rebinding is in progress, it also looks for the input to bind.
+    pub fn update(&mut self, input: &InputState) {
+        if self.rebinding.is_some() {
+            self.update_rebinding(input);
+        }
+        for action in &mut self.actions {
+            let strongest = |bindings: &[Binding]| {
+                bindings
+                    .iter()
+                    .filter(|binding| {
+                        if binding.is_joystick() {
+                            self.joystick.is_some()
+                        } else {
+                            self.keyboard_mouse
+                        }
+                    })
+                    .map(|binding| binding.value(input, self.joystick))
+                    .fold(0., f32::max)
+            };
+            action.previous = action.value;
+            action.value = strongest(&action.positive) - strongest(&action.negative);
+        }
+    }
+    /// The value of `action`, in range [-1 .. 1], or 0 if there is no such action.
+    #[must_use]
+    pub fn value(&self, action: &str) -> f32 {
+        self.find(action).map_or(0., |action| action.value)
+    }
+    /// Whether `action` is active, with a value of at least 0.5 either way.
+    #[must_use]
+    pub fn pressed(&self, action: &str) -> bool {
+        self.find(action)
+            .is_some_and(|action| action.value.abs() >= PRESS_THRESHOLD)
+    }
+    /// Whether `action` became active during this frame.
+    #[must_use]
+    pub fn just_pressed(&self, action: &str) -> bool {
+        self.find(action).is_some_and(|action| {
+            action.value.abs() >= PRESS_THRESHOLD && action.previous.abs() < PRESS_THRESHOLD
+        })
+    }
+    /// Whether `action` stopped being active during this frame.
+    #[must_use]
+    pub fn just_released(&self, action: &str) -> bool {
+        self.find(action).is_some_and(|action| {
+            action.value.abs() < PRESS_THRESHOLD && action.previous.abs() >= PRESS_THRESHOLD
+        })
+    }
+
+    /// Bind the next input of the player to `action`, creating the action if needed.
+    ///
+    /// The next key, mouse button, wheel scroll, joystick button or joystick axis pushed past
+    /// half of its range, from the devices of this map, replaces the bindings of `action` on the
+    /// side given by `polarity` from the same kind of device. So rebinding from the keyboard
+    /// keeps the joystick bindings, and the other way around. A key pressed with modifiers is
+    /// bound with them.
+    ///
+    /// Inputs are looked for from the next call to [`update`](Self::update), so this should be
+    /// called after it, to not bind the input that started the rebinding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name of the action is invalid, see [`bind`](Self::bind).
+    pub fn start_rebinding(&mut self, action: &str, polarity: Polarity) {
+        let action = self.action_or_insert(action);
+        self.rebinding = Some(Rebinding {
+            action,
+            polarity,
+            axes: None,
+        });
+    }
+    /// Stop waiting for an input to bind.
+    pub fn cancel_rebinding(&mut self) {
+        self.rebinding = None;
+    }
+    /// The action waiting for an input to bind, if any.
+    #[must_use]
+    pub fn rebinding(&self) -> Option<&str> {
+        let rebinding = self.rebinding.as_ref()?;
+        Some(&self.actions[rebinding.action].name)
+    }
+
+    /// Save the bindings as text, with one action per line.
+    #[must_use]
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for action in &self.actions {
+            text.push_str(&action.name);
+            text.push_str(" =");
+            let negative = action.negative.iter().map(|b| format!("-{b}"));
+            let positive = action.positive.iter().map(ToString::to_string);
+            for (i, binding) in negative.chain(positive).enumerate() {
+                text.push_str(if i == 0 { " " } else { ", " });
+                text.push_str(&binding);
+            }
+            text.push('\n');
+        }
+        text
+    }
+    /// Replace all the actions with the ones in `text`, as saved by [`to_text`].
+    ///
+    /// The devices of the map are kept. On error, the map is left unchanged.
+    ///
+    /// [`to_text`]: Self::to_text
+    pub fn load_text(&mut self, text: &str) -> Result<(), LoadError> {
+        let mut map = Self::new();
+        for (i, line) in text.lines().enumerate() {
+            let invalid = |reason| LoadError::Syntax {
+                line: i + 1,
+                reason,
+            };
+            let line = line.split_once('#').map_or(line, |(line, _)| line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (name, bindings) = line.split_once('=').ok_or(invalid("missing '='"))?;
+            let name = name.trim();
+            if !valid_name(name) {
+                return Err(invalid("invalid action name"));
+            }
+            let index = map.action_or_insert(name);
+            for binding in bindings.split(',').map(str::trim).filter(|b| !b.is_empty()) {
+                let (polarity, binding) = match binding.strip_prefix('-') {
+                    Some(binding) => (Polarity::Negative, binding),
+                    None => (Polarity::Positive, binding),
+                };
+                let binding = Binding::parse(binding).map_err(invalid)?;
+                map.actions[index].bindings_mut(polarity).push(binding);
+            }
+        }
+        self.actions = map.actions;
+        self.rebinding = None;
+        Ok(())
+    }
+    /// Save the bindings to the file at `path`.
+    pub fn save_to_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+    /// Replace all the actions with the ones in the file at `path`.
+    ///
+    /// See [`load_text`](Self::load_text).
+    pub fn load_from_file(&mut self, path: impl AsRef<Path>) -> Result<(), LoadError> {
+        self.load_text(&fs::read_to_string(path)?)
+    }
+
+    fn find(&self, name: &str) -> Option<&Action> {
+        self.actions.iter().find(|action| action.name == name)
+    }
+    fn find_mut(&mut self, name: &str) -> Option<&mut Action> {
+        self.actions.iter_mut().find(|action| action.name == name)
+    }
+    fn action_or_insert(&mut self, name: &str) -> usize {
+        if let Some(index) = self.actions.iter().position(|action| action.name == name) {
+            return index;
+        }
+        assert!(valid_name(name), "Invalid action name: {name:?}");
+        self.actions.push(Action {
+            name: name.to_owned(),
+            positive: Vec::new(),
+            negative: Vec::new(),
+            value: 0.,
+            previous: 0.,
+        });
+        self.actions.len() - 1
+    }
+    fn update_rebinding(&mut self, input: &InputState) {
+        let Some(rebinding) = &mut self.rebinding else {
+            return;
+        };
+        let axes = self.joystick.map(|joystick| {
+            let mut axes = [0.; 8];
+            for (i, position) in axes.iter_mut().enumerate() {
+                *position = input.joystick_axis(joystick, JOYSTICK_AXES[i]);
+            }
+            axes
+        });
+        // Axes already pushed when the rebinding starts don't count, only the ones pushed after
+        let previous_axes = std::mem::replace(&mut rebinding.axes, axes);
+        let mut captured = None;
+        if self.keyboard_mouse {
+            captured = input
+                .just_pressed_inputs()
+                .find_map(|pressed| match pressed {
+                    Input::Key(code) if code != Key::Unknown => Some(Binding::Key {
+                        code,
+                        modifiers: held_modifiers(input, code),
+                    }),
+                    Input::Mouse(button) => Some(Binding::Mouse(button)),
+                    _ => None,
+                });
+            captured = captured.or_else(|| {
+                [mouse::Wheel::VerticalWheel, mouse::Wheel::HorizontalWheel]
+                    .into_iter()
+                    .find(|&wheel| input.wheel_delta(wheel) != 0.)
+                    .map(|wheel| Binding::MouseWheel {
+                        wheel,
+                        positive: input.wheel_delta(wheel) > 0.,
+                    })
+            });
+        }
+        if let Some(joystick) = self.joystick {
+            captured = captured.or_else(|| {
+                input
+                    .just_pressed_inputs()
+                    .find_map(|pressed| match pressed {
+                        Input::JoystickButton {
+                            joystick: j,
+                            button,
+                        } if j == joystick => Some(Binding::JoystickButton(button)),
+                        _ => None,
+                    })
+            });
+            if let (Some(previous), Some(current)) = (previous_axes, axes) {
+                captured = captured.or_else(|| {
+                    (0..8)
+                        .find(|&i| previous[i].abs() < 50. && current[i].abs() >= 50.)
+                        .map(|i| {
+                            let sign = current[i].signum();
+                            Binding::JoystickAxis {
+                                axis: JOYSTICK_AXES[i],
+                                from: 20. * sign,
+                                to: 100. * sign,
+                            }
+                        })
+                });
+            }
+        }
+        if let Some(binding) = captured {
+            let Rebinding {
+                action, polarity, ..
+            } = *rebinding;
+            self.rebinding = None;
+            let bindings = self.actions[action].bindings_mut(polarity);
+            bindings.retain(|b| b.is_joystick() != binding.is_joystick());
+            bindings.push(binding);
+        }
+    }
+}
+
+const JOYSTICK_AXES: [joystick::Axis; 8] = [
+    joystick::Axis::X,
+    joystick::Axis::Y,
+    joystick::Axis::Z,
+    joystick::Axis::R,
+    joystick::Axis::U,
+    joystick::Axis::V,
+    joystick::Axis::PovX,
+    joystick::Axis::PovY,
+];
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '=' | ',' | '#'))
+}
+
+/// The modifiers held with `code`, not counting `code` itself if it's a modifier.
+fn held_modifiers(input: &InputState, code: Key) -> Modifiers {
+    let held = |left, right| {
+        code != left && code != right && (input.pressed(left) || input.pressed(right))
+    };
+    Modifiers {
+        alt: held(Key::LAlt, Key::RAlt),
+        ctrl: held(Key::LControl, Key::RControl),
+        shift: held(Key::LShift, Key::RShift),
+        system: held(Key::LSystem, Key::RSystem),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{ActionMap, Binding, Modifiers, Polarity},
+        crate::{
+            LoadError,
+            system::Time,
+            window::{Event, InputState, Key, Scancode, input_state::tests::key, joystick, mouse},
+        },
+    };
+
+    fn frame(input: &mut InputState, map: &mut ActionMap, events: impl IntoIterator<Item = Event>) {
+        input.begin_frame_at(Time::ZERO);
+        for event in events {
+            input.handle_event(event);
+        }
+        map.update(input);
+    }
+
+    #[test]
+    fn resolve_digital_and_analog_actions() {
+        let mut input = InputState::new();
+        let mut map = ActionMap::new();
+        map.bind(
+            "save",
+            Binding::Key {
+                code: Key::S,
+                modifiers: Modifiers {
+                    ctrl: true,
+                    ..Modifiers::NONE
+                },
+            },
+        );
+        map.bind_axis("move_x", Binding::key(Key::A), Binding::key(Key::D));
+        map.bind(
+            "move_x",
+            Binding::JoystickAxis {
+                axis: joystick::Axis::X,
+                from: 20.,
+                to: 100.,
+            },
+        );
+
+        frame(&mut input, &mut map, [key(Key::S, true), key(Key::A, true)]);
+        assert!(!map.pressed("save"));
+        assert_eq!(map.value("move_x"), -1.);
+        assert!(map.just_pressed("move_x"));
+
+        frame(
+            &mut input,
+            &mut map,
+            [key(Key::LControl, true), key(Key::A, false)],
+        );
+        assert!(map.just_pressed("save"));
+        assert!(map.just_released("move_x"));
+
+        let stick = Event::JoystickMoved {
+            joystickid: 1,
+            axis: joystick::Axis::X,
+            position: 60.,
+        };
+        frame(&mut input, &mut map, [stick]);
+        assert_eq!(map.value("move_x"), 0.);
+        map.set_joystick(Some(1));
+        frame(&mut input, &mut map, []);
+        assert_eq!(map.value("move_x"), 0.5);
+        assert!(map.just_pressed("move_x") && map.pressed("save"));
+        assert_eq!(map.value("missing"), 0.);
+    }
+
+    #[test]
+    fn bindings_round_trip_as_text() {
+        let text = "\
+# Player 1
+jump = key:Space, button:3
+move_x = -key:A, -axis:X:-20:-100, scan:D+ctrl+shift, axis:X:20:100
+zoom = wheel:up, -wheel:down, mouse:Middle
+idle =
+";
+        let mut map = ActionMap::new();
+        map.load_text(text).expect("Failed to load bindings");
+        assert_eq!(
+            map.actions().collect::<Vec<_>>(),
+            ["jump", "move_x", "zoom", "idle"]
+        );
+        assert_eq!(
+            map.bindings("move_x", Polarity::Positive)[0],
+            Binding::Scancode {
+                scan: Scancode::D,
+                modifiers: Modifiers {
+                    ctrl: true,
+                    shift: true,
+                    ..Modifiers::NONE
+                },
+            }
+        );
+        let saved = map.to_text();
+        let mut reloaded = ActionMap::new();
+        reloaded
+            .load_text(&saved)
+            .expect("Failed to reload bindings");
+        assert_eq!(reloaded.to_text(), saved);
+        assert!(saved.starts_with("jump = key:Space, button:3\n"));
+
+        assert_eq!(
+            map.load_text("ok = key:A\nbad = key:Nope\n"),
+            Err(LoadError::Syntax {
+                line: 2,
+                reason: "unknown key"
+            })
+        );
+        assert_eq!(map.to_text(), saved);
+    }
+
+    #[test]
+    fn rebinding_replaces_bindings_of_the_same_device() {
+        let mut input = InputState::new();
+        let mut map = ActionMap::new();
+        map.set_joystick(Some(0));
+        map.bind("jump", Binding::key(Key::Space));
+        map.bind("jump", Binding::JoystickButton(0));
+        frame(&mut input, &mut map, []);
+        map.start_rebinding("jump", Polarity::Positive);
+        assert_eq!(map.rebinding(), Some("jump"));
+
+        frame(&mut input, &mut map, [key(Key::LShift, true)]);
+        frame(
+            &mut input,
+            &mut map,
+            [Event::MouseButtonPressed {
+                button: mouse::Button::Right,
+                position: Default::default(),
+            }],
+        );
+        assert_eq!(map.rebinding(), None);
+        assert_eq!(
+            map.bindings("jump", Polarity::Positive),
+            [Binding::JoystickButton(0), Binding::key(Key::LShift)]
+        );
+        assert!(map.pressed("jump"));
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{
+    ffi::window::sfSensorType,
+    system::{Vector2i, Vector2u, Vector3f},
+    window::{Event, Key, Scancode, joystick, mouse},
+};
+
+/// The details of a [`KeyPressed`] or [`KeyReleased`] event.
+///
+/// [`KeyPressed`]: Event::KeyPressed
+/// [`KeyReleased`]: Event::KeyReleased
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KeyEvent {
+    /// The key
+    pub code: Key,
+    /// The scancode of the key
+    pub scan: Scancode,
+    /// Is alt pressed too?
+    pub alt: bool,
+    /// Is ctrl pressed too?
+    pub ctrl: bool,
+    /// Is shift pressed too?
+    pub shift: bool,
+    /// Is system pressed too?
+    pub system: bool,
+}
+
+/// Handles window events, with one method per kind of [`Event`].
+///
+/// All methods do nothing by default, so only the events of interest need to be handled.
+/// Events are dispatched to a handler with [`Event::dispatch`], or for all the pending events of
+/// a window with `handle_events` and `wait_and_handle_events`, on both
+/// [`Window`](crate::window::Window) and `RenderWindow`.
+///
+/// # Usage example
+///
+/// ```no_run
+/// use sfml::window::{EventHandler, Key, KeyEvent, Style, Window, window_enums::State};
+///
+/// #[derive(Default)]
+/// struct Game {
+///     paused: bool,
+///     quit: bool,
+/// }
+///
+/// impl EventHandler for Game {
+///     fn on_closed(&mut self) {
+///         self.quit = true;
+///     }
+///     fn on_key_pressed(&mut self, key: KeyEvent) {
+///         if key.code == Key::P {
+///             self.paused = !self.paused;
+///         }
+///     }
+/// }
+///
+/// let mut window =
+///     Window::new_open((800, 600), "Events", Style::DEFAULT, State::Windowed, &Default::default())
+///         .unwrap();
+/// let mut game = Game::default();
+/// while !game.quit {
+///     window.handle_events(&mut game);
+///     window.display();
+/// }
+/// ```
+#[expect(unused_variables, reason = "Default implementations ignore the event")]
+pub trait EventHandler {
+    /// The window requested to be closed
+    fn on_closed(&mut self) {}
+    /// The window was resized to `size` pixels
+    fn on_resized(&mut self, size: Vector2u) {}
+    /// The window lost the focus
+    fn on_focus_lost(&mut self) {}
+    /// The window gained the focus
+    fn on_focus_gained(&mut self) {}
+    /// A character was entered
+    fn on_text_entered(&mut self, unicode: char) {}
+    /// A key was pressed
+    fn on_key_pressed(&mut self, key: KeyEvent) {}
+    /// A key was released
+    fn on_key_released(&mut self, key: KeyEvent) {}
+    /// The mouse wheel was scrolled by `delta`, with the mouse pointer at `position`
+    fn on_mouse_wheel_scrolled(&mut self, wheel: mouse::Wheel, delta: f32, position: Vector2i) {}
+    /// A mouse button was pressed, with the mouse pointer at `position`
+    fn on_mouse_button_pressed(&mut self, button: mouse::Button, position: Vector2i) {}
+    /// A mouse button was released, with the mouse pointer at `position`
+    fn on_mouse_button_released(&mut self, button: mouse::Button, position: Vector2i) {}
+    /// The mouse cursor moved to `position`
+    fn on_mouse_moved(&mut self, position: Vector2i) {}
+    /// The mouse moved by `delta`, without any processing. See [`Event::MouseMovedRaw`].
+    fn on_mouse_moved_raw(&mut self, delta: Vector2i) {}
+    /// The mouse cursor entered the area of the window
+    fn on_mouse_entered(&mut self) {}
+    /// The mouse cursor left the area of the window
+    fn on_mouse_left(&mut self) {}
+    /// A joystick button was pressed
+    fn on_joystick_button_pressed(&mut self, joystickid: u32, button: u32) {}
+    /// A joystick button was released
+    fn on_joystick_button_released(&mut self, joystickid: u32, button: u32) {}
+    /// The joystick moved along an axis, to `position` (in range [-100 .. 100])
+    fn on_joystick_moved(&mut self, joystickid: u32, axis: joystick::Axis, position: f32) {}
+    /// A joystick was connected
+    fn on_joystick_connected(&mut self, joystickid: u32) {}
+    /// A joystick was disconnected
+    fn on_joystick_disconnected(&mut self, joystickid: u32) {}
+    /// A touch event began at `position`
+    fn on_touch_began(&mut self, finger: u32, position: Vector2i) {}
+    /// A touch moved to `position`
+    fn on_touch_moved(&mut self, finger: u32, position: Vector2i) {}
+    /// A touch event ended at `position`
+    fn on_touch_ended(&mut self, finger: u32, position: Vector2i) {}
+    /// A sensor value changed
+    fn on_sensor_changed(&mut self, type_: sfSensorType, value: Vector3f) {}
+}
+
+impl Event {
+    /// Call the method of `handler` matching this event.
+    pub fn dispatch<H: EventHandler + ?Sized>(self, handler: &mut H) {
+        match self {
+            Self::Closed => handler.on_closed(),
+            Self::Resized { size } => handler.on_resized(size),
+            Self::FocusLost => handler.on_focus_lost(),
+            Self::FocusGained => handler.on_focus_gained(),
+            Self::TextEntered { unicode } => handler.on_text_entered(unicode),
+            Self::KeyPressed {
+                code,
+                scan,
+                alt,
+                ctrl,
+                shift,
+                system,
+            } => handler.on_key_pressed(KeyEvent {
+                code,
+                scan,
+                alt,
+                ctrl,
+                shift,
+                system,
+            }),
+            Self::KeyReleased {
+                code,
+                scan,
+                alt,
+                ctrl,
+                shift,
+                system,
+            } => handler.on_key_released(KeyEvent {
+                code,
+                scan,
+                alt,
+                ctrl,
+                shift,
+                system,
+            }),
+            Self::MouseWheelScrolled {
+                wheel,
+                delta,
+                position,
+            } => handler.on_mouse_wheel_scrolled(wheel, delta, position),
+            Self::MouseButtonPressed { button, position } => {
+                handler.on_mouse_button_pressed(button, position);
+            }
+            Self::MouseButtonReleased { button, position } => {
+                handler.on_mouse_button_released(button, position);
+            }
+            Self::MouseMoved { position } => handler.on_mouse_moved(position),
+            Self::MouseMovedRaw { delta } => handler.on_mouse_moved_raw(delta),
+            Self::MouseEntered => handler.on_mouse_entered(),
+            Self::MouseLeft => handler.on_mouse_left(),
+            Self::JoystickButtonPressed { joystickid, button } => {
+                handler.on_joystick_button_pressed(joystickid, button);
+            }
+            Self::JoystickButtonReleased { joystickid, button } => {
+                handler.on_joystick_button_released(joystickid, button);
+            }
+            Self::JoystickMoved {
+                joystickid,
+                axis,
+                position,
+            } => handler.on_joystick_moved(joystickid, axis, position),
+            Self::JoystickConnected { joystickid } => handler.on_joystick_connected(joystickid),
+            Self::JoystickDisconnected { joystickid } => {
+                handler.on_joystick_disconnected(joystickid);
+            }
+            Self::TouchBegan { finger, position } => handler.on_touch_began(finger, position),
+            Self::TouchMoved { finger, position } => handler.on_touch_moved(finger, position),
+            Self::TouchEnded { finger, position } => handler.on_touch_ended(finger, position),
+            Self::SensorChanged { type_, value } => handler.on_sensor_changed(type_, value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{EventHandler, KeyEvent},
+        crate::{
+            system::Vector2u,
+            window::{Event, Key, Scancode},
+        },
+    };
+
+    #[derive(Default)]
+    struct Recorder {
+        log: Vec<String>,
+    }
+
+    impl EventHandler for Recorder {
+        fn on_closed(&mut self) {
+            self.log.push("closed".into());
+        }
+        fn on_resized(&mut self, size: Vector2u) {
+            self.log.push(format!("resized {}x{}", size.x, size.y));
+        }
+        fn on_key_released(&mut self, key: KeyEvent) {
+            self.log
+                .push(format!("released {:?} shift={}", key.code, key.shift));
+        }
+    }
+
+    #[test]
+    fn dispatch_to_matching_method() {
+        let mut recorder = Recorder::default();
+        let events = [
+            Event::Resized {
+                size: Vector2u::new(640, 480),
+            },
+            Event::FocusLost,
+            Event::KeyReleased {
+                code: Key::A,
+                scan: Scancode::A,
+                alt: false,
+                ctrl: false,
+                shift: true,
+                system: false,
+            },
+            Event::Closed,
+        ];
+        for event in events {
+            event.dispatch(&mut recorder);
+        }
+        assert_eq!(
+            recorder.log,
+            ["resized 640x480", "released A shift=true", "closed"]
+        );
+    }
+}
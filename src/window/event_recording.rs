@@ -0,0 +1,853 @@
+use {
+    crate::{
+        system::{Time, Vector2i, Vector2u, Vector3f},
+        window::{Event, Key, Scancode, Window},
+    },
+    std::{
+        cell::RefCell,
+        collections::VecDeque,
+        error::Error,
+        fmt, fs,
+        io::{self, Read, Write},
+        path::Path,
+        time::{Duration, Instant},
+    },
+};
+
+/// Something [`Event`]s can be polled from, like a window or an [`EventReplay`].
+///
+/// This lets the same event handling code run on live windows and on replayed recordings.
+pub trait EventSource {
+    /// Pop the event on top of the event queue, if any, and return it.
+    ///
+    /// See [`Window::poll_event`].
+    fn poll_event(&mut self) -> Option<Event>;
+    /// Wait for an event and return it, or `None` if `timeout` was reached.
+    ///
+    /// See [`Window::wait_event`].
+    fn wait_event(&mut self, timeout: Time) -> Option<Event>;
+}
+
+impl EventSource for Window {
+    fn poll_event(&mut self) -> Option<Event> {
+        Window::poll_event(self)
+    }
+    fn wait_event(&mut self, timeout: Time) -> Option<Event> {
+        Window::wait_event(self, timeout)
+    }
+}
+
+/// An [`Event`], with the time it happened at since the start of the recording
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TimedEvent {
+    /// Time since the start of the recording
+    pub time: Time,
+    /// The event
+    pub event: Event,
+}
+
+/// Error that can happen when loading an [`EventRecording`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordingError {
+    /// The data isn't a valid event recording
+    Invalid(&'static str),
+    /// The data couldn't be read
+    Io(io::ErrorKind),
+}
+
+impl Error for RecordingError {}
+
+impl fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(what) => write!(f, "Invalid event recording: {what}"),
+            Self::Io(kind) => write!(f, "Failed to read event recording: {kind}"),
+        }
+    }
+}
+
+impl From<io::Error> for RecordingError {
+    fn from(error: io::Error) -> Self {
+        if error.kind() == io::ErrorKind::UnexpectedEof {
+            Self::Invalid("truncated data")
+        } else {
+            Self::Io(error.kind())
+        }
+    }
+}
+
+const MAGIC: &[u8; 4] = b"SFEV";
+const VERSION: u8 = 1;
+
+/// A sequence of timestamped events, made by an [`EventRecorder`] and played by an
+/// [`EventReplay`].
+///
+/// Recordings are saved in a compact binary format, where each event takes a few bytes.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct EventRecording {
+    events: Vec<TimedEvent>,
+}
+
+impl EventRecording {
+    /// Create an empty recording.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add an event at `time`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `time` is before the time of the last event.
+    pub fn push(&mut self, time: Time, event: Event) {
+        assert!(
+            self.events.last().is_none_or(|last| last.time <= time),
+            "Events must be recorded in chronological order"
+        );
+        self.events.push(TimedEvent { time, event });
+    }
+    /// The recorded events, in chronological order.
+    #[must_use]
+    pub fn events(&self) -> &[TimedEvent] {
+        &self.events
+    }
+    /// The number of recorded events.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+    /// Whether there are no recorded events.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+    /// The time of the last event.
+    #[must_use]
+    pub fn duration(&self) -> Time {
+        self.events.last().map_or(Time::ZERO, |last| last.time)
+    }
+    /// Write the recording to `writer`.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.to_bytes())
+    }
+    /// Read a recording written by [`Self::write_to`].
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self, RecordingError> {
+        let mut data = Vec::new();
+        let _ = reader.read_to_end(&mut data)?;
+        Self::from_bytes(&data)
+    }
+    /// Save the recording to the file at `path`.
+    pub fn save_to_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+    /// Load a recording from the file at `path`.
+    pub fn load_from_file(path: impl AsRef<Path>) -> Result<Self, RecordingError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+    /// Encode the recording.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.push(VERSION);
+        write_varint(&mut out, self.events.len() as u64);
+        let mut last = 0;
+        for timed in &self.events {
+            let time = timed.time.as_microseconds();
+            write_varint(&mut out, time.abs_diff(last));
+            last = time;
+            encode_event(&mut out, &timed.event);
+        }
+        out
+    }
+    /// Decode a recording encoded by [`Self::to_bytes`].
+    pub fn from_bytes(data: &[u8]) -> Result<Self, RecordingError> {
+        let mut reader = data;
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(RecordingError::Invalid("not an event recording"));
+        }
+        if read_u8(&mut reader)? != VERSION {
+            return Err(RecordingError::Invalid("unsupported version"));
+        }
+        let count = read_varint(&mut reader)?;
+        let mut recording = Self::new();
+        let mut time = 0i64;
+        for _ in 0..count {
+            let delta = i64::try_from(read_varint(&mut reader)?)
+                .map_err(|_| RecordingError::Invalid("time out of range"))?;
+            time = time
+                .checked_add(delta)
+                .ok_or(RecordingError::Invalid("time out of range"))?;
+            let event = decode_event(&mut reader)?;
+            recording.push(Time::microseconds(time), event);
+        }
+        Ok(recording)
+    }
+}
+
+/// Records the events polled from an [`EventSource`], timestamped since the recorder was created.
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::window::{EventRecorder, Window};
+/// # let mut window: Window = unimplemented!();
+/// let mut recorder = EventRecorder::new();
+/// while window.is_open() {
+///     while let Some(event) = recorder.poll_event(&mut window) {
+///         // Handle the event
+///     }
+///     window.display();
+/// }
+/// recorder.finish().save_to_file("session.sfev").unwrap();
+/// ```
+#[derive(Debug)]
+pub struct EventRecorder {
+    start: Instant,
+    recording: EventRecording,
+}
+
+impl Default for EventRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventRecorder {
+    /// Start a new recording.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            recording: EventRecording::new(),
+        }
+    }
+    /// The time elapsed since the start of the recording.
+    #[must_use]
+    pub fn elapsed(&self) -> Time {
+        duration_to_time(self.start.elapsed())
+    }
+    /// Poll an event from `source`, and record it.
+    pub fn poll_event<S: EventSource + ?Sized>(&mut self, source: &mut S) -> Option<Event> {
+        let event = source.poll_event()?;
+        self.record(event);
+        Some(event)
+    }
+    /// Wait for an event from `source`, and record it.
+    pub fn wait_event<S: EventSource + ?Sized>(
+        &mut self,
+        source: &mut S,
+        timeout: Time,
+    ) -> Option<Event> {
+        let event = source.wait_event(timeout)?;
+        self.record(event);
+        Some(event)
+    }
+    /// Record an event that didn't come from an [`EventSource`], at the current time.
+    pub fn record(&mut self, event: Event) {
+        let time = self.elapsed();
+        self.recording.push(time, event);
+    }
+    /// The events recorded so far.
+    #[must_use]
+    pub fn recording(&self) -> &EventRecording {
+        &self.recording
+    }
+    /// Stop recording, and return the recording.
+    #[must_use]
+    pub fn finish(self) -> EventRecording {
+        self.recording
+    }
+}
+
+/// Plays an [`EventRecording`] back as an [`EventSource`], with the timing it was recorded with.
+///
+/// Events are returned once the time elapsed since the replay was created reaches their
+/// timestamp. For deterministic tests, [`poll_event_at`] instead takes the time from the caller,
+/// like the time of a fixed-step simulation.
+///
+/// [`poll_event_at`]: Self::poll_event_at
+#[derive(Debug)]
+pub struct EventReplay {
+    recording: EventRecording,
+    next: usize,
+    start: Instant,
+}
+
+impl EventReplay {
+    /// Start replaying `recording` from its beginning.
+    #[must_use]
+    pub fn new(recording: EventRecording) -> Self {
+        Self {
+            recording,
+            next: 0,
+            start: Instant::now(),
+        }
+    }
+    /// Restart the replay from the beginning of the recording.
+    pub fn restart(&mut self) {
+        self.next = 0;
+        self.start = Instant::now();
+    }
+    /// The time elapsed since the start of the replay.
+    #[must_use]
+    pub fn elapsed(&self) -> Time {
+        duration_to_time(self.start.elapsed())
+    }
+    /// Whether all the events have been returned.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.recording.len()
+    }
+    /// The recording being replayed.
+    #[must_use]
+    pub fn recording(&self) -> &EventRecording {
+        &self.recording
+    }
+    /// Return the next event if it happened at or before `time`.
+    pub fn poll_event_at(&mut self, time: Time) -> Option<Event> {
+        let next = self.recording.events.get(self.next)?;
+        if next.time > time {
+            return None;
+        }
+        self.next += 1;
+        Some(next.event)
+    }
+}
+
+impl EventSource for EventReplay {
+    fn poll_event(&mut self) -> Option<Event> {
+        self.poll_event_at(self.elapsed())
+    }
+    /// Wait until the time of the next event, or at most `timeout`, or indefinitely if `timeout`
+    /// is zero. Returns `None` right away when the replay is finished.
+    fn wait_event(&mut self, timeout: Time) -> Option<Event> {
+        let next = self.recording.events.get(self.next)?.time;
+        let until_next = next - self.elapsed();
+        let reached = timeout == Time::ZERO || until_next <= timeout;
+        let wait = if reached { until_next } else { timeout };
+        if wait > Time::ZERO {
+            let micros = u64::try_from(wait.as_microseconds()).unwrap_or_default();
+            std::thread::sleep(Duration::from_micros(micros));
+        }
+        // The sleep may be a bit shorter than asked for
+        if reached {
+            self.poll_event_at(next)
+        } else {
+            None
+        }
+    }
+}
+
+fn duration_to_time(duration: Duration) -> Time {
+    Time::microseconds(i64::try_from(duration.as_micros()).unwrap_or(i64::MAX))
+}
+
+// Events injected into the queue of windows, which are only used from the window thread
+
+thread_local! {
+    static INJECTED: RefCell<Vec<(usize, VecDeque<Event>)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Add a synthetic event to the queue of the window at `window`.
+pub(crate) fn inject_event(window: *const (), event: Event) {
+    let key = window as usize;
+    INJECTED.with_borrow_mut(|queues| {
+        if let Some((_, queue)) = queues.iter_mut().find(|(k, _)| *k == key) {
+            queue.push_back(event);
+        } else {
+            queues.push((key, VecDeque::from([event])));
+        }
+    });
+}
+
+/// Pop the next synthetic event of the window at `window`.
+pub(crate) fn take_injected_event(window: *const ()) -> Option<Event> {
+    let key = window as usize;
+    INJECTED.with_borrow_mut(|queues| {
+        let index = queues.iter().position(|(k, _)| *k == key)?;
+        let event = queues[index].1.pop_front();
+        if queues[index].1.is_empty() {
+            let _ = queues.swap_remove(index);
+        }
+        event
+    })
+}
+
+/// Drop the synthetic events of a window being destroyed.
+pub(crate) fn forget_injected_events(window: *const ()) {
+    let key = window as usize;
+    INJECTED.with_borrow_mut(|queues| queues.retain(|(k, _)| *k != key));
+}
+
+// Binary encoding
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+#[expect(clippy::cast_sign_loss)]
+fn write_signed(out: &mut Vec<u8>, value: i64) {
+    // Zigzag encoding, so small negative values stay small
+    write_varint(out, ((value << 1) ^ (value >> 63)) as u64);
+}
+
+fn write_f32(out: &mut Vec<u8>, value: f32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_vector2i(out: &mut Vec<u8>, value: Vector2i) {
+    write_signed(out, value.x.into());
+    write_signed(out, value.y.into());
+}
+
+fn read_u8(reader: &mut &[u8]) -> io::Result<u8> {
+    let mut byte = [0];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn read_varint(reader: &mut &[u8]) -> Result<u64, RecordingError> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = read_u8(reader)?;
+        value |= u64::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(RecordingError::Invalid("integer too long"))
+}
+
+fn read_u32(reader: &mut &[u8]) -> Result<u32, RecordingError> {
+    u32::try_from(read_varint(reader)?).map_err(|_| RecordingError::Invalid("integer too large"))
+}
+
+#[expect(clippy::cast_possible_wrap)]
+fn read_i32(reader: &mut &[u8]) -> Result<i32, RecordingError> {
+    let zigzag = read_varint(reader)?;
+    let value = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+    i32::try_from(value).map_err(|_| RecordingError::Invalid("integer too large"))
+}
+
+fn read_f32(reader: &mut &[u8]) -> io::Result<f32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(f32::from_le_bytes(bytes))
+}
+
+fn read_vector2i(reader: &mut &[u8]) -> Result<Vector2i, RecordingError> {
+    Ok(Vector2i::new(read_i32(reader)?, read_i32(reader)?))
+}
+
+/// Convert the discriminant of an enum back to the enum, failing with `what` if it's out of
+/// range.
+fn variant<T: TryFrom<i32>>(value: i32, what: &'static str) -> Result<T, RecordingError> {
+    T::try_from(value).map_err(|_| RecordingError::Invalid(what))
+}
+
+mod tag {
+    pub const CLOSED: u8 = 0;
+    pub const RESIZED: u8 = 1;
+    pub const FOCUS_LOST: u8 = 2;
+    pub const FOCUS_GAINED: u8 = 3;
+    pub const TEXT_ENTERED: u8 = 4;
+    pub const KEY_PRESSED: u8 = 5;
+    pub const KEY_RELEASED: u8 = 6;
+    pub const MOUSE_WHEEL_SCROLLED: u8 = 7;
+    pub const MOUSE_BUTTON_PRESSED: u8 = 8;
+    pub const MOUSE_BUTTON_RELEASED: u8 = 9;
+    pub const MOUSE_MOVED: u8 = 10;
+    pub const MOUSE_MOVED_RAW: u8 = 11;
+    pub const MOUSE_ENTERED: u8 = 12;
+    pub const MOUSE_LEFT: u8 = 13;
+    pub const JOYSTICK_BUTTON_PRESSED: u8 = 14;
+    pub const JOYSTICK_BUTTON_RELEASED: u8 = 15;
+    pub const JOYSTICK_MOVED: u8 = 16;
+    pub const JOYSTICK_CONNECTED: u8 = 17;
+    pub const JOYSTICK_DISCONNECTED: u8 = 18;
+    pub const TOUCH_BEGAN: u8 = 19;
+    pub const TOUCH_MOVED: u8 = 20;
+    pub const TOUCH_ENDED: u8 = 21;
+    pub const SENSOR_CHANGED: u8 = 22;
+}
+
+#[expect(clippy::too_many_lines)]
+fn encode_event(out: &mut Vec<u8>, event: &Event) {
+    let key = |out: &mut Vec<u8>, tag, code: Key, scan: Scancode, modifiers: [bool; 4]| {
+        out.push(tag);
+        write_signed(out, (code as i32).into());
+        write_signed(out, (scan as i32).into());
+        let flags = modifiers
+            .iter()
+            .enumerate()
+            .fold(0, |flags, (i, &set)| flags | (u8::from(set) << i));
+        out.push(flags);
+    };
+    match *event {
+        Event::Closed => out.push(tag::CLOSED),
+        Event::Resized { size } => {
+            out.push(tag::RESIZED);
+            write_varint(out, size.x.into());
+            write_varint(out, size.y.into());
+        }
+        Event::FocusLost => out.push(tag::FOCUS_LOST),
+        Event::FocusGained => out.push(tag::FOCUS_GAINED),
+        Event::TextEntered { unicode } => {
+            out.push(tag::TEXT_ENTERED);
+            write_varint(out, u32::from(unicode).into());
+        }
+        Event::KeyPressed {
+            code,
+            scan,
+            alt,
+            ctrl,
+            shift,
+            system,
+        } => key(
+            out,
+            tag::KEY_PRESSED,
+            code,
+            scan,
+            [alt, ctrl, shift, system],
+        ),
+        Event::KeyReleased {
+            code,
+            scan,
+            alt,
+            ctrl,
+            shift,
+            system,
+        } => key(
+            out,
+            tag::KEY_RELEASED,
+            code,
+            scan,
+            [alt, ctrl, shift, system],
+        ),
+        Event::MouseWheelScrolled {
+            wheel,
+            delta,
+            position,
+        } => {
+            out.push(tag::MOUSE_WHEEL_SCROLLED);
+            out.push(wheel as u8);
+            write_f32(out, delta);
+            write_vector2i(out, position);
+        }
+        Event::MouseButtonPressed { button, position } => {
+            out.extend_from_slice(&[tag::MOUSE_BUTTON_PRESSED, button as u8]);
+            write_vector2i(out, position);
+        }
+        Event::MouseButtonReleased { button, position } => {
+            out.extend_from_slice(&[tag::MOUSE_BUTTON_RELEASED, button as u8]);
+            write_vector2i(out, position);
+        }
+        Event::MouseMoved { position } => {
+            out.push(tag::MOUSE_MOVED);
+            write_vector2i(out, position);
+        }
+        Event::MouseMovedRaw { delta } => {
+            out.push(tag::MOUSE_MOVED_RAW);
+            write_vector2i(out, delta);
+        }
+        Event::MouseEntered => out.push(tag::MOUSE_ENTERED),
+        Event::MouseLeft => out.push(tag::MOUSE_LEFT),
+        Event::JoystickButtonPressed { joystickid, button } => {
+            out.push(tag::JOYSTICK_BUTTON_PRESSED);
+            write_varint(out, joystickid.into());
+            write_varint(out, button.into());
+        }
+        Event::JoystickButtonReleased { joystickid, button } => {
+            out.push(tag::JOYSTICK_BUTTON_RELEASED);
+            write_varint(out, joystickid.into());
+            write_varint(out, button.into());
+        }
+        Event::JoystickMoved {
+            joystickid,
+            axis,
+            position,
+        } => {
+            out.push(tag::JOYSTICK_MOVED);
+            write_varint(out, joystickid.into());
+            out.push(axis as u8);
+            write_f32(out, position);
+        }
+        Event::JoystickConnected { joystickid } => {
+            out.push(tag::JOYSTICK_CONNECTED);
+            write_varint(out, joystickid.into());
+        }
+        Event::JoystickDisconnected { joystickid } => {
+            out.push(tag::JOYSTICK_DISCONNECTED);
+            write_varint(out, joystickid.into());
+        }
+        Event::TouchBegan { finger, position } => {
+            out.push(tag::TOUCH_BEGAN);
+            write_varint(out, finger.into());
+            write_vector2i(out, position);
+        }
+        Event::TouchMoved { finger, position } => {
+            out.push(tag::TOUCH_MOVED);
+            write_varint(out, finger.into());
+            write_vector2i(out, position);
+        }
+        Event::TouchEnded { finger, position } => {
+            out.push(tag::TOUCH_ENDED);
+            write_varint(out, finger.into());
+            write_vector2i(out, position);
+        }
+        Event::SensorChanged { type_, value } => {
+            out.extend_from_slice(&[tag::SENSOR_CHANGED, type_ as u8]);
+            write_f32(out, value.x);
+            write_f32(out, value.y);
+            write_f32(out, value.z);
+        }
+    }
+}
+
+#[expect(clippy::too_many_lines)]
+fn decode_event(reader: &mut &[u8]) -> Result<Event, RecordingError> {
+    let key = |reader: &mut &[u8]| -> Result<_, RecordingError> {
+        let code = variant(read_i32(reader)?, "invalid Key value")?;
+        let scan = variant(read_i32(reader)?, "invalid Scancode value")?;
+        let flags = read_u8(reader)?;
+        let flag = |i: u8| flags & (1 << i) != 0;
+        Ok((code, scan, flag(0), flag(1), flag(2), flag(3)))
+    };
+    let button = |reader: &mut &[u8]| -> Result<_, RecordingError> {
+        variant(read_u8(reader)?.into(), "invalid mouse::Button value")
+    };
+    let event = match read_u8(reader)? {
+        tag::CLOSED => Event::Closed,
+        tag::RESIZED => Event::Resized {
+            size: Vector2u::new(read_u32(reader)?, read_u32(reader)?),
+        },
+        tag::FOCUS_LOST => Event::FocusLost,
+        tag::FOCUS_GAINED => Event::FocusGained,
+        tag::TEXT_ENTERED => Event::TextEntered {
+            unicode: char::from_u32(read_u32(reader)?)
+                .ok_or(RecordingError::Invalid("invalid character"))?,
+        },
+        tag::KEY_PRESSED => {
+            let (code, scan, alt, ctrl, shift, system) = key(reader)?;
+            Event::KeyPressed {
+                code,
+                scan,
+                alt,
+                ctrl,
+                shift,
+                system,
+            }
+        }
+        tag::KEY_RELEASED => {
+            let (code, scan, alt, ctrl, shift, system) = key(reader)?;
+            Event::KeyReleased {
+                code,
+                scan,
+                alt,
+                ctrl,
+                shift,
+                system,
+            }
+        }
+        tag::MOUSE_WHEEL_SCROLLED => Event::MouseWheelScrolled {
+            wheel: variant(read_u8(reader)?.into(), "invalid mouse::Wheel value")?,
+            delta: read_f32(reader)?,
+            position: read_vector2i(reader)?,
+        },
+        tag::MOUSE_BUTTON_PRESSED => Event::MouseButtonPressed {
+            button: button(reader)?,
+            position: read_vector2i(reader)?,
+        },
+        tag::MOUSE_BUTTON_RELEASED => Event::MouseButtonReleased {
+            button: button(reader)?,
+            position: read_vector2i(reader)?,
+        },
+        tag::MOUSE_MOVED => Event::MouseMoved {
+            position: read_vector2i(reader)?,
+        },
+        tag::MOUSE_MOVED_RAW => Event::MouseMovedRaw {
+            delta: read_vector2i(reader)?,
+        },
+        tag::MOUSE_ENTERED => Event::MouseEntered,
+        tag::MOUSE_LEFT => Event::MouseLeft,
+        tag::JOYSTICK_BUTTON_PRESSED => Event::JoystickButtonPressed {
+            joystickid: read_u32(reader)?,
+            button: read_u32(reader)?,
+        },
+        tag::JOYSTICK_BUTTON_RELEASED => Event::JoystickButtonReleased {
+            joystickid: read_u32(reader)?,
+            button: read_u32(reader)?,
+        },
+        tag::JOYSTICK_MOVED => Event::JoystickMoved {
+            joystickid: read_u32(reader)?,
+            axis: variant(read_u8(reader)?.into(), "invalid joystick::Axis value")?,
+            position: read_f32(reader)?,
+        },
+        tag::JOYSTICK_CONNECTED => Event::JoystickConnected {
+            joystickid: read_u32(reader)?,
+        },
+        tag::JOYSTICK_DISCONNECTED => Event::JoystickDisconnected {
+            joystickid: read_u32(reader)?,
+        },
+        tag::TOUCH_BEGAN => Event::TouchBegan {
+            finger: read_u32(reader)?,
+            position: read_vector2i(reader)?,
+        },
+        tag::TOUCH_MOVED => Event::TouchMoved {
+            finger: read_u32(reader)?,
+            position: read_vector2i(reader)?,
+        },
+        tag::TOUCH_ENDED => Event::TouchEnded {
+            finger: read_u32(reader)?,
+            position: read_vector2i(reader)?,
+        },
+        tag::SENSOR_CHANGED => Event::SensorChanged {
+            type_: variant(read_u8(reader)?.into(), "invalid sfSensorType value")?,
+            value: Vector3f::new(read_f32(reader)?, read_f32(reader)?, read_f32(reader)?),
+        },
+        _ => return Err(RecordingError::Invalid("unknown event type")),
+    };
+    Ok(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            ffi::window::sfSensorType,
+            window::{joystick, mouse},
+        },
+    };
+
+    fn sample_recording() -> EventRecording {
+        let mut recording = EventRecording::new();
+        let events = [
+            Event::Resized {
+                size: Vector2u::new(1280, 720),
+            },
+            Event::KeyPressed {
+                code: Key::Unknown,
+                scan: Scancode::LaunchMediaSelect,
+                alt: true,
+                ctrl: false,
+                shift: true,
+                system: false,
+            },
+            Event::TextEntered { unicode: 'é' },
+            Event::MouseWheelScrolled {
+                wheel: mouse::Wheel::HorizontalWheel,
+                delta: -1.5,
+                position: Vector2i::new(-3, 400),
+            },
+            Event::MouseButtonReleased {
+                button: mouse::Button::Extra2,
+                position: Vector2i::new(10, 20),
+            },
+            Event::JoystickMoved {
+                joystickid: 3,
+                axis: joystick::Axis::PovY,
+                position: 100.,
+            },
+            Event::SensorChanged {
+                type_: sfSensorType::Orientation,
+                value: Vector3f::new(0.5, -9.81, 3.),
+            },
+            Event::Closed,
+        ];
+        for (i, event) in (0..).zip(events) {
+            recording.push(Time::milliseconds(i * 16), event);
+        }
+        recording
+    }
+
+    #[test]
+    fn round_trip() {
+        let recording = sample_recording();
+        let bytes = recording.to_bytes();
+        assert_eq!(EventRecording::from_bytes(&bytes), Ok(recording));
+        assert!(bytes.len() < 100);
+        assert_eq!(
+            EventRecording::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(RecordingError::Invalid("truncated data"))
+        );
+        assert_eq!(
+            EventRecording::from_bytes(b"SFEV\x01\x01\x00\xFF"),
+            Err(RecordingError::Invalid("unknown event type"))
+        );
+    }
+
+    #[test]
+    fn replay_at_recorded_times() {
+        let mut replay = EventReplay::new(sample_recording());
+        assert_eq!(
+            replay.poll_event_at(Time::ZERO),
+            Some(Event::Resized {
+                size: Vector2u::new(1280, 720)
+            })
+        );
+        assert!(replay.poll_event_at(Time::milliseconds(15)).is_none());
+        assert!(replay.poll_event_at(Time::milliseconds(16)).is_some());
+        let mut rest = 0;
+        while replay.poll_event_at(Time::seconds(1.)).is_some() {
+            rest += 1;
+        }
+        assert_eq!(rest, 6);
+        assert!(replay.is_finished());
+        assert!(replay.wait_event(Time::ZERO).is_none());
+    }
+
+    #[test]
+    fn injected_events_are_per_window() {
+        let windows = [0u8; 2];
+        let (a, b) = (&raw const windows[0], &raw const windows[1]);
+        let (a, b) = (a.cast(), b.cast());
+        inject_event(a, Event::FocusLost);
+        inject_event(b, Event::Closed);
+        inject_event(a, Event::FocusGained);
+        assert_eq!(take_injected_event(a), Some(Event::FocusLost));
+        forget_injected_events(b);
+        assert_eq!(take_injected_event(b), None);
+        assert_eq!(take_injected_event(a), Some(Event::FocusGained));
+        assert_eq!(take_injected_event(a), None);
+    }
+
+    #[test]
+    fn discriminants_out_of_range_are_rejected() {
+        assert_eq!(Key::try_from(Key::Pause as i32), Ok(Key::Pause));
+        assert_eq!(Key::try_from(-1), Ok(Key::Unknown));
+        assert_eq!(Key::try_from(-2), Err(-2));
+        assert_eq!(
+            Key::try_from(Key::Pause as i32 + 1),
+            Err(Key::Pause as i32 + 1)
+        );
+        assert_eq!(
+            Scancode::try_from(Scancode::LaunchMediaSelect as i32 + 1),
+            Err(Scancode::LaunchMediaSelect as i32 + 1)
+        );
+        assert_eq!(mouse::Button::try_from(-1), Err(-1));
+        let mut bytes = Vec::new();
+        encode_event(
+            &mut bytes,
+            &Event::MouseButtonPressed {
+                button: mouse::Button::Left,
+                position: Vector2i::new(0, 0),
+            },
+        );
+        bytes[1] = 200;
+        assert!(matches!(
+            decode_event(&mut bytes.as_slice()),
+            Err(RecordingError::Invalid("invalid mouse::Button value"))
+        ));
+    }
+}
@@ -0,0 +1,998 @@
+//! Standard gamepad layout, using SDL's game controller mappings.
+//!
+//! [`joystick`] exposes raw button indices and axes, which are laid out differently by every
+//! controller. This module maps them to a standard layout, with [`Button`]s and [`Axis`] named
+//! after their position on an Xbox controller, using the mappings of SDL's
+//! [`gamecontrollerdb.txt`](https://github.com/mdqinc/SDL_GameControllerDB).
+//!
+//! A [`MappingDb`] has mappings for a few common controllers built in, and can be extended with
+//! more, like a complete `gamecontrollerdb.txt`. A mapping is found for a joystick from its
+//! vendor and product ids, or from its name. [`Gamepads`] tracks the connected joysticks that have
+//! a mapping, and the state of each [`Gamepad`], with a radial dead zone and a response curve for
+//! each stick.
+//!
+//! # Usage example
+//!
+//! ```no_run
+//! use sfml::window::gamepad::{Button, GamepadEvent, Gamepads, MappingDb, Stick};
+//!
+//! let mut db = MappingDb::with_defaults();
+//! // Add the latest mappings from the community database
+//! if let Err(e) = db.add_mappings_from_file("gamecontrollerdb.txt") {
+//!     eprintln!("Using the built-in mappings only: {e}");
+//! }
+//! let mut gamepads = Gamepads::new(db);
+//! loop {
+//!     // Window event handling updates the joysticks
+//!     for event in gamepads.update() {
+//!         if let GamepadEvent::Connected(id) = event {
+//!             println!("Player {id} joined with {}", gamepads.get(id).unwrap().name());
+//!         }
+//!     }
+//!     for (id, pad) in gamepads.iter() {
+//!         let movement = pad.stick(Stick::Left);
+//!         if pad.just_pressed(Button::A) {
+//!             // Player `id` jumps
+//!         }
+//!     }
+//! #   break;
+//! }
+//! ```
+
+use {
+    crate::{
+        LoadError,
+        system::Vector2f,
+        window::joystick::{self, Axis as RawAxis},
+    },
+    std::{fs, path::Path},
+};
+
+/// Mappings for a few common controllers, from SDL's `gamecontrollerdb.txt` (zlib license).
+///
+/// The complete database is too large to embed, and changes more often than this crate.
+const DEFAULT_MAPPINGS: &str = "\
+# Windows
+030000005e0400008e02000000000000,Xbox 360 Controller,a:b0,b:b1,back:b6,dpdown:h0.4,dpleft:h0.8,dpright:h0.2,dpup:h0.1,leftshoulder:b4,leftstick:b8,lefttrigger:+a2,leftx:a0,lefty:a1,rightshoulder:b5,rightstick:b9,righttrigger:-a2,rightx:a4,righty:a3,start:b7,x:b2,y:b3,platform:Windows,
+030000005e040000ea02000000000000,Xbox One Controller,a:b0,b:b1,back:b6,dpdown:h0.4,dpleft:h0.8,dpright:h0.2,dpup:h0.1,leftshoulder:b4,leftstick:b8,lefttrigger:+a2,leftx:a0,lefty:a1,rightshoulder:b5,rightstick:b9,righttrigger:-a2,rightx:a4,righty:a3,start:b7,x:b2,y:b3,platform:Windows,
+030000004c050000c405000000000000,PS4 Controller,a:b1,b:b2,back:b8,dpdown:h0.4,dpleft:h0.8,dpright:h0.2,dpup:h0.1,guide:b12,leftshoulder:b4,leftstick:b10,lefttrigger:a3,leftx:a0,lefty:a1,rightshoulder:b5,rightstick:b11,righttrigger:a4,rightx:a2,righty:a5,start:b9,x:b0,y:b3,touchpad:b13,platform:Windows,
+030000004c050000cc09000000000000,PS4 Controller,a:b1,b:b2,back:b8,dpdown:h0.4,dpleft:h0.8,dpright:h0.2,dpup:h0.1,guide:b12,leftshoulder:b4,leftstick:b10,lefttrigger:a3,leftx:a0,lefty:a1,rightshoulder:b5,rightstick:b11,righttrigger:a4,rightx:a2,righty:a5,start:b9,x:b0,y:b3,touchpad:b13,platform:Windows,
+030000004c050000e60c000000000000,PS5 Controller,a:b1,b:b2,back:b8,dpdown:h0.4,dpleft:h0.8,dpright:h0.2,dpup:h0.1,guide:b12,leftshoulder:b4,leftstick:b10,lefttrigger:a3,leftx:a0,lefty:a1,misc1:b14,rightshoulder:b5,rightstick:b11,righttrigger:a4,rightx:a2,righty:a5,start:b9,x:b0,y:b3,touchpad:b13,platform:Windows,
+# Mac OS X
+030000004c050000c405000000000000,PS4 Controller,a:b1,b:b2,back:b8,dpdown:h0.4,dpleft:h0.8,dpright:h0.2,dpup:h0.1,guide:b12,leftshoulder:b4,leftstick:b10,lefttrigger:a3,leftx:a0,lefty:a1,rightshoulder:b5,rightstick:b11,righttrigger:a4,rightx:a2,righty:a5,start:b9,x:b0,y:b3,touchpad:b13,platform:Mac OS X,
+030000004c050000cc09000000000000,PS4 Controller,a:b1,b:b2,back:b8,dpdown:h0.4,dpleft:h0.8,dpright:h0.2,dpup:h0.1,guide:b12,leftshoulder:b4,leftstick:b10,lefttrigger:a3,leftx:a0,lefty:a1,rightshoulder:b5,rightstick:b11,righttrigger:a4,rightx:a2,righty:a5,start:b9,x:b0,y:b3,touchpad:b13,platform:Mac OS X,
+030000005e040000e002000000000000,Xbox Wireless Controller,a:b0,b:b1,back:b6,dpdown:h0.4,dpleft:h0.8,dpright:h0.2,dpup:h0.1,leftshoulder:b4,leftstick:b8,lefttrigger:a2,leftx:a0,lefty:a1,rightshoulder:b5,rightstick:b9,righttrigger:a5,rightx:a3,righty:a4,start:b7,x:b2,y:b3,platform:Mac OS X,
+# Linux
+030000005e0400008e02000010010000,Xbox 360 Controller,a:b0,b:b1,back:b6,dpdown:h0.4,dpleft:h0.8,dpright:h0.2,dpup:h0.1,guide:b8,leftshoulder:b4,leftstick:b9,lefttrigger:a2,leftx:a0,lefty:a1,rightshoulder:b5,rightstick:b10,righttrigger:a5,rightx:a3,righty:a4,start:b7,x:b2,y:b3,platform:Linux,
+030000005e040000ea02000001030000,Xbox One Controller,a:b0,b:b1,back:b6,dpdown:h0.4,dpleft:h0.8,dpright:h0.2,dpup:h0.1,guide:b8,leftshoulder:b4,leftstick:b9,lefttrigger:a2,leftx:a0,lefty:a1,rightshoulder:b5,rightstick:b10,righttrigger:a5,rightx:a3,righty:a4,start:b7,x:b2,y:b3,platform:Linux,
+030000006d0400001dc2000014400000,Logitech F310 Gamepad (XInput),a:b0,b:b1,back:b6,dpdown:h0.4,dpleft:h0.8,dpright:h0.2,dpup:h0.1,guide:b8,leftshoulder:b4,leftstick:b9,lefttrigger:a2,leftx:a0,lefty:a1,rightshoulder:b5,rightstick:b10,righttrigger:a5,rightx:a3,righty:a4,start:b7,x:b2,y:b3,platform:Linux,
+030000004c050000c405000011810000,PS4 Controller,a:b0,b:b1,back:b8,dpdown:h0.4,dpleft:h0.8,dpright:h0.2,dpup:h0.1,guide:b10,leftshoulder:b4,leftstick:b11,lefttrigger:a2,leftx:a0,lefty:a1,rightshoulder:b5,rightstick:b12,righttrigger:a5,rightx:a3,righty:a4,start:b9,x:b3,y:b2,platform:Linux,
+030000004c050000cc09000011810000,PS4 Controller,a:b0,b:b1,back:b8,dpdown:h0.4,dpleft:h0.8,dpright:h0.2,dpup:h0.1,guide:b10,leftshoulder:b4,leftstick:b11,lefttrigger:a2,leftx:a0,lefty:a1,rightshoulder:b5,rightstick:b12,righttrigger:a5,rightx:a3,righty:a4,start:b9,x:b3,y:b2,platform:Linux,
+030000004c050000e60c000011810000,PS5 Controller,a:b0,b:b1,back:b8,dpdown:h0.4,dpleft:h0.8,dpright:h0.2,dpup:h0.1,guide:b10,leftshoulder:b4,leftstick:b11,lefttrigger:a2,leftx:a0,lefty:a1,misc1:b13,rightshoulder:b5,rightstick:b12,righttrigger:a5,rightx:a3,righty:a4,start:b9,x:b3,y:b2,touchpad:b14,platform:Linux,
+";
+
+/// The name SDL mappings use for the current platform.
+const PLATFORM: &str = if cfg!(target_os = "windows") {
+    "Windows"
+} else if cfg!(target_os = "macos") {
+    "Mac OS X"
+} else if cfg!(target_os = "ios") {
+    "iOS"
+} else if cfg!(target_os = "android") {
+    "Android"
+} else {
+    "Linux"
+};
+
+/// The order in which SDL numbers the axes that SFML reports, excluding the hat.
+#[cfg(target_os = "linux")]
+const RAW_AXIS_ORDER: [RawAxis; 6] = [
+    RawAxis::X,
+    RawAxis::Y,
+    RawAxis::Z,
+    RawAxis::U,
+    RawAxis::V,
+    RawAxis::R,
+];
+#[cfg(not(target_os = "linux"))]
+const RAW_AXIS_ORDER: [RawAxis; 6] = [
+    RawAxis::X,
+    RawAxis::Y,
+    RawAxis::Z,
+    RawAxis::R,
+    RawAxis::U,
+    RawAxis::V,
+];
+
+/// Whether SFML reports the up direction of the hat as a positive `PovY`.
+const HAT_UP_IS_POSITIVE: bool = cfg!(target_os = "windows");
+
+/// A button of the standard gamepad layout.
+///
+/// The face buttons are named after their position on an Xbox controller, so [`A`](Self::A) is
+/// the bottom one, which is Cross on Sony controllers and B on Nintendo ones.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Button {
+    /// The bottom face button
+    A,
+    /// The right face button
+    B,
+    /// The left face button
+    X,
+    /// The top face button
+    Y,
+    /// The back, select or share button
+    Back,
+    /// The guide, home or PS button
+    Guide,
+    /// The start or options button
+    Start,
+    /// Clicking the left stick
+    LeftStick,
+    /// Clicking the right stick
+    RightStick,
+    /// The left shoulder button
+    LeftShoulder,
+    /// The right shoulder button
+    RightShoulder,
+    /// Up on the d-pad
+    DPadUp,
+    /// Down on the d-pad
+    DPadDown,
+    /// Left on the d-pad
+    DPadLeft,
+    /// Right on the d-pad
+    DPadRight,
+    /// An extra button, like the share button of an Xbox Series controller
+    Misc1,
+    /// The first paddle
+    Paddle1,
+    /// The second paddle
+    Paddle2,
+    /// The third paddle
+    Paddle3,
+    /// The fourth paddle
+    Paddle4,
+    /// Clicking the touchpad
+    Touchpad,
+}
+
+impl Button {
+    /// The number of buttons.
+    pub const COUNT: usize = 21;
+
+    const NAMES: [(&'static str, Self); Self::COUNT] = [
+        ("a", Self::A),
+        ("b", Self::B),
+        ("x", Self::X),
+        ("y", Self::Y),
+        ("back", Self::Back),
+        ("guide", Self::Guide),
+        ("start", Self::Start),
+        ("leftstick", Self::LeftStick),
+        ("rightstick", Self::RightStick),
+        ("leftshoulder", Self::LeftShoulder),
+        ("rightshoulder", Self::RightShoulder),
+        ("dpup", Self::DPadUp),
+        ("dpdown", Self::DPadDown),
+        ("dpleft", Self::DPadLeft),
+        ("dpright", Self::DPadRight),
+        ("misc1", Self::Misc1),
+        ("paddle1", Self::Paddle1),
+        ("paddle2", Self::Paddle2),
+        ("paddle3", Self::Paddle3),
+        ("paddle4", Self::Paddle4),
+        ("touchpad", Self::Touchpad),
+    ];
+}
+
+/// An axis of the standard gamepad layout.
+///
+/// Stick axes are in range [-1 .. 1], with positive values to the right and down. Triggers are
+/// in range [0 .. 1].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Axis {
+    /// The horizontal axis of the left stick
+    LeftX,
+    /// The vertical axis of the left stick
+    LeftY,
+    /// The horizontal axis of the right stick
+    RightX,
+    /// The vertical axis of the right stick
+    RightY,
+    /// The left trigger
+    LeftTrigger,
+    /// The right trigger
+    RightTrigger,
+}
+
+impl Axis {
+    /// The number of axes.
+    pub const COUNT: usize = 6;
+
+    const NAMES: [(&'static str, Self); Self::COUNT] = [
+        ("leftx", Self::LeftX),
+        ("lefty", Self::LeftY),
+        ("rightx", Self::RightX),
+        ("righty", Self::RightY),
+        ("lefttrigger", Self::LeftTrigger),
+        ("righttrigger", Self::RightTrigger),
+    ];
+
+    fn is_trigger(self) -> bool {
+        matches!(self, Self::LeftTrigger | Self::RightTrigger)
+    }
+}
+
+/// A stick of the gamepad.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Stick {
+    /// The left stick
+    Left,
+    /// The right stick
+    Right,
+}
+
+/// How the distance of a stick from its center maps to its value, after the dead zone.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum ResponseCurve {
+    /// The value is proportional to the distance
+    #[default]
+    Linear,
+    /// The value is the distance raised to this power.
+    ///
+    /// Powers above 1 give more precision near the center, like 2 or 3 for aiming.
+    Power(f32),
+}
+
+impl ResponseCurve {
+    /// Apply the curve to `value`, in range [0 .. 1].
+    #[must_use]
+    pub fn apply(self, value: f32) -> f32 {
+        match self {
+            Self::Linear => value,
+            Self::Power(power) => value.powf(power),
+        }
+    }
+}
+
+/// How the position of a stick is processed.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct StickSettings {
+    /// The distance from the center under which the stick is at rest, in range [0 .. 1).
+    ///
+    /// The dead zone is radial, so it doesn't snap diagonal movements to the axes. The range
+    /// outside of it is scaled back to [0 .. 1], so the value rises smoothly from the edge of the
+    /// dead zone.
+    pub dead_zone: f32,
+    /// The response curve applied to the distance from the center
+    pub curve: ResponseCurve,
+}
+
+impl Default for StickSettings {
+    fn default() -> Self {
+        Self {
+            dead_zone: 0.15,
+            curve: ResponseCurve::Linear,
+        }
+    }
+}
+
+/// The raw state of a joystick, as read by [`JoystickState::read`].
+///
+/// Mappings are applied to this state, so it can also be built by hand, like in tests.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct JoystickState {
+    /// Whether each button is pressed
+    pub buttons: [bool; joystick::BUTTON_COUNT as usize],
+    /// The position of each axis, indexed by [`joystick::Axis`], or `None` if the joystick
+    /// doesn't have it
+    pub axes: [Option<f32>; joystick::AXIS_COUNT as usize],
+}
+
+impl JoystickState {
+    /// Read the current state of `joystick`.
+    #[must_use]
+    pub fn read(joystick: u32) -> Self {
+        let mut state = Self::default();
+        for (button, pressed) in (0..).zip(&mut state.buttons) {
+            *pressed = joystick::is_button_pressed(joystick, button);
+        }
+        for axis in RAW_AXIS_ORDER
+            .into_iter()
+            .chain([RawAxis::PovX, RawAxis::PovY])
+        {
+            if joystick::has_axis(joystick, axis) {
+                state.axes[axis as usize] = Some(joystick::axis_position(joystick, axis));
+            }
+        }
+        state
+    }
+    /// The axis that SDL numbers `index`, which is the index among the axes present.
+    fn raw_axis(&self, index: u32) -> Option<f32> {
+        RAW_AXIS_ORDER
+            .iter()
+            .filter_map(|&axis| self.axes[axis as usize])
+            .nth(index as usize)
+    }
+    fn hat(&self, hat: u32, mask: u8) -> bool {
+        if hat != 0 {
+            return false;
+        }
+        let x = self.axes[RawAxis::PovX as usize].unwrap_or(0.);
+        let mut y = self.axes[RawAxis::PovY as usize].unwrap_or(0.);
+        if HAT_UP_IS_POSITIVE {
+            y = -y;
+        }
+        (mask & 1 != 0 && y < -50.)
+            || (mask & 2 != 0 && x > 50.)
+            || (mask & 4 != 0 && y > 50.)
+            || (mask & 8 != 0 && x < -50.)
+    }
+}
+
+/// A half of an axis: `Some(true)` for the positive half, `Some(false)` for the negative one.
+type Half = Option<bool>;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Source {
+    Button(u32),
+    Axis {
+        index: u32,
+        half: Half,
+        inverted: bool,
+    },
+    Hat {
+        hat: u32,
+        mask: u8,
+    },
+}
+
+impl Source {
+    fn parse(text: &str) -> Result<Self, &'static str> {
+        let (half, text) = parse_half(text);
+        let (inverted, text) = match text.strip_suffix('~') {
+            Some(text) => (true, text),
+            None => (false, text),
+        };
+        let number = |text: &str| text.parse().map_err(|_| "invalid input index");
+        match text.split_at_checked(1) {
+            Some(("b", index)) if half.is_none() && !inverted => Ok(Self::Button(number(index)?)),
+            Some(("a", index)) => Ok(Self::Axis {
+                index: number(index)?,
+                half,
+                inverted,
+            }),
+            Some(("h", hat)) if half.is_none() && !inverted => {
+                let (hat, mask) = hat.split_once('.').ok_or("invalid hat")?;
+                Ok(Self::Hat {
+                    hat: number(hat)?,
+                    mask: mask.parse().map_err(|_| "invalid hat")?,
+                })
+            }
+            _ => Err("invalid input"),
+        }
+    }
+    /// How far the input is activated, in range [0 .. 1]
+    fn value(self, state: &JoystickState) -> f32 {
+        match self {
+            Self::Button(button) => {
+                let pressed = state.buttons.get(button as usize).copied();
+                if pressed.unwrap_or(false) { 1. } else { 0. }
+            }
+            Self::Axis {
+                index,
+                half,
+                inverted,
+            } => {
+                let mut value = state
+                    .raw_axis(index)
+                    .map_or(0., |v| (v / 100.).clamp(-1., 1.));
+                if inverted {
+                    value = -value;
+                }
+                match half {
+                    None => (value + 1.) / 2.,
+                    Some(true) => value.max(0.),
+                    Some(false) => (-value).max(0.),
+                }
+            }
+            Self::Hat { hat, mask } => {
+                if state.hat(hat, mask) {
+                    1.
+                } else {
+                    0.
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Target {
+    Button(Button),
+    Axis(Axis, Half),
+}
+
+fn parse_half(text: &str) -> (Half, &str) {
+    if let Some(text) = text.strip_prefix('+') {
+        (Some(true), text)
+    } else if let Some(text) = text.strip_prefix('-') {
+        (Some(false), text)
+    } else {
+        (None, text)
+    }
+}
+
+/// An SDL game controller mapping, from raw joystick inputs to the standard layout.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Mapping {
+    guid: String,
+    name: String,
+    platform: Option<String>,
+    bindings: Vec<(Target, Source)>,
+}
+
+impl Mapping {
+    /// Parse a mapping, in the `gamecontrollerdb.txt` format.
+    fn parse(line: &str) -> Result<Self, &'static str> {
+        let mut fields = line.split(',');
+        let guid = fields.next().unwrap_or_default().trim();
+        if guid.len() != 32 || !guid.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err("invalid GUID");
+        }
+        let name = fields.next().ok_or("missing name")?.trim();
+        let mut mapping = Self {
+            guid: guid.to_owned(),
+            name: name.to_owned(),
+            platform: None,
+            bindings: Vec::new(),
+        };
+        for field in fields.filter(|field| !field.is_empty()) {
+            let (key, value) = field.split_once(':').ok_or("missing ':'")?;
+            if key == "platform" {
+                mapping.platform = Some(value.to_owned());
+                continue;
+            }
+            let (half, key) = parse_half(key);
+            let target = if let Some(&(_, button)) = Button::NAMES.iter().find(|(n, _)| *n == key) {
+                Target::Button(button)
+            } else if let Some(&(_, axis)) = Axis::NAMES.iter().find(|(n, _)| *n == key) {
+                Target::Axis(axis, half)
+            } else {
+                // Other fields, like "crc" or "hint", don't affect the mapping
+                continue;
+            };
+            mapping.bindings.push((target, Source::parse(value)?));
+        }
+        Ok(mapping)
+    }
+    /// The SDL GUID of the controller, as 32 hexadecimal digits.
+    #[must_use]
+    pub fn guid(&self) -> &str {
+        &self.guid
+    }
+    /// The name of the controller.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// The platform the mapping is for, or `None` if it's for all platforms.
+    #[must_use]
+    pub fn platform(&self) -> Option<&str> {
+        self.platform.as_deref()
+    }
+    /// The USB vendor and product ids encoded in the GUID, if it has them.
+    #[must_use]
+    pub fn vendor_product(&self) -> Option<(u16, u16)> {
+        let guid = self.guid.as_bytes();
+        // The GUID is 8 little endian 16-bit words: bus, CRC, vendor, 0, product, 0, version and
+        // driver data
+        let word = |i: usize| {
+            let hex = std::str::from_utf8(&guid[i * 4..i * 4 + 4]).ok()?;
+            u16::from_str_radix(hex, 16).ok().map(u16::swap_bytes)
+        };
+        match (word(2)?, word(3)?, word(4)?, word(5)?) {
+            (vendor, 0, product, 0) if vendor != 0 => Some((vendor, product)),
+            _ => None,
+        }
+    }
+    fn applies_here(&self) -> bool {
+        self.platform.as_deref().is_none_or(|p| p == PLATFORM)
+    }
+}
+
+/// A database of gamepad mappings.
+///
+/// Mappings added later take precedence over the ones added before, so mappings added by the
+/// user override the built-in ones. Only the mappings for the current platform, or without a
+/// platform, are kept.
+#[derive(Clone, Debug, Default)]
+pub struct MappingDb {
+    mappings: Vec<Mapping>,
+}
+
+impl MappingDb {
+    /// Create an empty database.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create a database with mappings for common controllers, like Xbox and Sony ones.
+    ///
+    /// Only the Xbox 360, Xbox One, PS4, PS5 and Logitech F310 controllers are built in, not the
+    /// complete `gamecontrollerdb.txt`, which can be added with
+    /// [`add_mappings_from_file`](Self::add_mappings_from_file).
+    #[must_use]
+    #[expect(clippy::missing_panics_doc, reason = "The built-in mappings are valid")]
+    pub fn with_defaults() -> Self {
+        let mut db = Self::new();
+        let _ = db
+            .add_mappings(DEFAULT_MAPPINGS)
+            .expect("Built-in gamepad mappings are invalid");
+        db
+    }
+    /// Add the mappings in `text`, in the `gamecontrollerdb.txt` format, with one mapping per
+    /// line.
+    ///
+    /// Empty lines and comments starting with `#` are skipped, and each mapping has to start with
+    /// a GUID of 32 hexadecimal digits. Returns the number of mappings added for this platform. On
+    /// error, no mapping is added.
+    pub fn add_mappings(&mut self, text: &str) -> Result<usize, LoadError> {
+        let mut added = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mapping = Mapping::parse(line).map_err(|reason| LoadError::Syntax {
+                line: i + 1,
+                reason,
+            })?;
+            if mapping.applies_here() {
+                added.push(mapping);
+            }
+        }
+        let count = added.len();
+        self.mappings.append(&mut added);
+        Ok(count)
+    }
+    /// Add the mappings in the file at `path`, like a `gamecontrollerdb.txt`.
+    ///
+    /// See [`add_mappings`](Self::add_mappings).
+    pub fn add_mappings_from_file(&mut self, path: impl AsRef<Path>) -> Result<usize, LoadError> {
+        self.add_mappings(&fs::read_to_string(path)?)
+    }
+    /// Find the mapping for a joystick.
+    ///
+    /// Mappings are matched by vendor and product ids first, then by name.
+    #[must_use]
+    pub fn find(&self, name: &str, vendor_id: u32, product_id: u32) -> Option<&Mapping> {
+        let ids = (
+            u16::try_from(vendor_id).ok()?,
+            u16::try_from(product_id).ok()?,
+        );
+        self.mappings
+            .iter()
+            .rev()
+            .find(|mapping| ids.0 != 0 && mapping.vendor_product() == Some(ids))
+            .or_else(|| {
+                self.mappings
+                    .iter()
+                    .rev()
+                    .find(|mapping| mapping.name == name)
+            })
+    }
+    /// The number of mappings.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.mappings.len()
+    }
+    /// Whether the database has no mappings.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+    /// Iterate over the mappings, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = &Mapping> {
+        self.mappings.iter()
+    }
+}
+
+/// A joystick seen through a [`Mapping`], with the standard gamepad layout.
+#[derive(Clone, Debug)]
+pub struct Gamepad {
+    name: String,
+    mapping: Mapping,
+    buttons: [bool; Button::COUNT],
+    previous: [bool; Button::COUNT],
+    axes: [f32; Axis::COUNT],
+    sticks: [StickSettings; 2],
+}
+
+impl Gamepad {
+    /// Create a gamepad called `name`, reading its joystick through `mapping`.
+    ///
+    /// Nothing is pressed until the first [`update`](Self::update).
+    #[must_use]
+    pub fn new(name: impl Into<String>, mapping: Mapping) -> Self {
+        Self {
+            name: name.into(),
+            mapping,
+            buttons: [false; Button::COUNT],
+            previous: [false; Button::COUNT],
+            axes: [0.; Axis::COUNT],
+            sticks: [StickSettings::default(); 2],
+        }
+    }
+    /// Update the gamepad from the raw state of its joystick.
+    pub fn update(&mut self, state: &JoystickState) {
+        self.previous = self.buttons;
+        self.buttons = [false; Button::COUNT];
+        self.axes = [0.; Axis::COUNT];
+        for &(target, source) in &self.mapping.bindings {
+            let value = source.value(state);
+            match target {
+                Target::Button(button) => self.buttons[button as usize] |= value > 0.5,
+                Target::Axis(axis, half) => {
+                    let full_input = matches!(source, Source::Axis { half: None, .. });
+                    let value = match half {
+                        Some(true) => value,
+                        Some(false) => -value,
+                        // A full input axis covers the whole range of a stick axis
+                        None if full_input && !axis.is_trigger() => value * 2. - 1.,
+                        None => value,
+                    };
+                    let total = &mut self.axes[axis as usize];
+                    *total = (*total + value).clamp(-1., 1.);
+                }
+            }
+        }
+    }
+    /// The name of the joystick.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// The mapping used to read the joystick.
+    #[must_use]
+    pub fn mapping(&self) -> &Mapping {
+        &self.mapping
+    }
+    /// Whether `button` is pressed.
+    #[must_use]
+    pub fn pressed(&self, button: Button) -> bool {
+        self.buttons[button as usize]
+    }
+    /// Whether `button` was pressed since the previous update.
+    #[must_use]
+    pub fn just_pressed(&self, button: Button) -> bool {
+        self.buttons[button as usize] && !self.previous[button as usize]
+    }
+    /// Whether `button` was released since the previous update.
+    #[must_use]
+    pub fn just_released(&self, button: Button) -> bool {
+        !self.buttons[button as usize] && self.previous[button as usize]
+    }
+    /// The value of `axis`.
+    ///
+    /// Stick axes are components of [`stick`](Self::stick), so the dead zone and response curve
+    /// of the stick apply.
+    #[must_use]
+    pub fn axis(&self, axis: Axis) -> f32 {
+        match axis {
+            Axis::LeftX => self.stick(Stick::Left).x,
+            Axis::LeftY => self.stick(Stick::Left).y,
+            Axis::RightX => self.stick(Stick::Right).x,
+            Axis::RightY => self.stick(Stick::Right).y,
+            Axis::LeftTrigger | Axis::RightTrigger => self.axes[axis as usize],
+        }
+    }
+    /// The position of `stick`, after its dead zone and response curve, with a length of at
+    /// most 1.
+    #[must_use]
+    pub fn stick(&self, stick: Stick) -> Vector2f {
+        let (x, y) = match stick {
+            Stick::Left => (Axis::LeftX, Axis::LeftY),
+            Stick::Right => (Axis::RightX, Axis::RightY),
+        };
+        let raw = Vector2f::new(self.axes[x as usize], self.axes[y as usize]);
+        let settings = self.sticks[stick as usize];
+        let length = raw.length();
+        if length <= settings.dead_zone {
+            return Vector2f::default();
+        }
+        let scaled = ((length - settings.dead_zone) / (1. - settings.dead_zone)).min(1.);
+        raw * (settings.curve.apply(scaled) / length)
+    }
+    /// Set how the position of `stick` is processed.
+    pub fn set_stick_settings(&mut self, stick: Stick, settings: StickSettings) {
+        self.sticks[stick as usize] = settings;
+    }
+    /// How the position of `stick` is processed.
+    #[must_use]
+    pub fn stick_settings(&self, stick: Stick) -> StickSettings {
+        self.sticks[stick as usize]
+    }
+}
+
+/// A change in the connected gamepads, reported by [`Gamepads::update`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum GamepadEvent {
+    /// A joystick with a mapping was connected, with this index
+    Connected(u32),
+    /// A gamepad was disconnected
+    Disconnected(u32),
+}
+
+/// The connected gamepads: the joysticks that have a mapping in a [`MappingDb`].
+///
+/// Joysticks are identified by the same index as in [`joystick`].
+#[derive(Debug)]
+pub struct Gamepads {
+    db: MappingDb,
+    pads: [Option<Gamepad>; joystick::COUNT as usize],
+    unmapped: [bool; joystick::COUNT as usize],
+    stick_settings: [StickSettings; 2],
+}
+
+impl Gamepads {
+    /// Create a tracker using the mappings of `db`, without any gamepad until the first
+    /// [`update`](Self::update).
+    #[must_use]
+    pub fn new(db: MappingDb) -> Self {
+        Self {
+            db,
+            pads: Default::default(),
+            unmapped: [false; joystick::COUNT as usize],
+            stick_settings: [StickSettings::default(); 2],
+        }
+    }
+    /// The mapping database.
+    #[must_use]
+    pub fn db(&self) -> &MappingDb {
+        &self.db
+    }
+    /// The mapping database, to add mappings.
+    ///
+    /// Joysticks connected without a mapping are looked up again at the next update.
+    pub fn db_mut(&mut self) -> &mut MappingDb {
+        self.unmapped = [false; joystick::COUNT as usize];
+        &mut self.db
+    }
+    /// Set how the position of `stick` is processed, for the gamepads connected and to come.
+    pub fn set_stick_settings(&mut self, stick: Stick, settings: StickSettings) {
+        self.stick_settings[stick as usize] = settings;
+        for pad in self.pads.iter_mut().flatten() {
+            pad.set_stick_settings(stick, settings);
+        }
+    }
+    /// Track the connected joysticks and update the state of the gamepads.
+    ///
+    /// This reads the state of the joysticks, so it has to be called after [`joystick::update`],
+    /// which happens when handling the events of a window. Returns the gamepads connected and
+    /// disconnected since the previous update.
+    pub fn update(&mut self) -> Vec<GamepadEvent> {
+        self.update_with(
+            |id| joystick::is_connected(id).then(|| JoystickState::read(id)),
+            |id| {
+                let identification = joystick::identification(id);
+                (
+                    identification.name().to_string(),
+                    identification.vendor_id(),
+                    identification.product_id(),
+                )
+            },
+        )
+    }
+    fn update_with(
+        &mut self,
+        mut read: impl FnMut(u32) -> Option<JoystickState>,
+        mut identify: impl FnMut(u32) -> (String, u32, u32),
+    ) -> Vec<GamepadEvent> {
+        let mut events = Vec::new();
+        for (id, (pad, unmapped)) in (0..).zip(self.pads.iter_mut().zip(&mut self.unmapped)) {
+            let Some(state) = read(id) else {
+                if pad.take().is_some() {
+                    events.push(GamepadEvent::Disconnected(id));
+                }
+                *unmapped = false;
+                continue;
+            };
+            if pad.is_none() && !*unmapped {
+                let (name, vendor_id, product_id) = identify(id);
+                match self.db.find(&name, vendor_id, product_id) {
+                    Some(mapping) => {
+                        let mut gamepad = Gamepad::new(name, mapping.clone());
+                        gamepad.sticks = self.stick_settings;
+                        *pad = Some(gamepad);
+                        events.push(GamepadEvent::Connected(id));
+                    }
+                    None => *unmapped = true,
+                }
+            }
+            if let Some(pad) = pad {
+                pad.update(&state);
+            }
+        }
+        events
+    }
+    /// The gamepad with index `id`, if it's connected.
+    #[must_use]
+    pub fn get(&self, id: u32) -> Option<&Gamepad> {
+        self.pads.get(id as usize)?.as_ref()
+    }
+    /// The gamepad with index `id`, if it's connected.
+    pub fn get_mut(&mut self, id: u32) -> Option<&mut Gamepad> {
+        self.pads.get_mut(id as usize)?.as_mut()
+    }
+    /// Iterate over the connected gamepads, with their index.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &Gamepad)> {
+        (0..)
+            .zip(&self.pads)
+            .filter_map(|(id, pad)| Some((id, pad.as_ref()?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{
+            Axis, Button, Gamepad, GamepadEvent, Gamepads, JoystickState, MappingDb, ResponseCurve,
+            Stick, StickSettings,
+        },
+        crate::{LoadError, window::joystick::Axis as RawAxis},
+    };
+
+    const GUID: &str = "03000000d20400002e16000000000000";
+    const PAD: &str = "\
+# A made-up pad, for all platforms
+03000000d20400002e16000000000000,Test Pad,a:b1,b:b0,dpright:h0.2,leftx:a0,lefty:a1~,\
+lefttrigger:a2,righttrigger:b7,+rightx:b4,-rightx:b5,y:+a2,crc:1234,
+";
+
+    fn pad() -> Gamepad {
+        let mut db = MappingDb::new();
+        assert_eq!(db.add_mappings(PAD), Ok(1));
+        let mapping = db.find("", 0x04d2, 0x162e).expect("No mapping by ids");
+        assert_eq!(mapping.name(), "Test Pad");
+        assert_eq!(db.find("Test Pad", 1, 2), Some(mapping));
+        Gamepad::new("Test Pad", mapping.clone())
+    }
+
+    #[test]
+    fn default_mappings() {
+        let db = MappingDb::with_defaults();
+        assert!(!db.is_empty());
+        let xbox = db
+            .find("Some Xbox pad", 0x045e, 0x028e)
+            .expect("No Xbox 360 mapping");
+        assert_eq!(xbox.name(), "Xbox 360 Controller");
+        assert_eq!(xbox.vendor_product(), Some((0x045e, 0x028e)));
+        assert!(db.find("Unknown", 0x1234, 0x5678).is_none());
+    }
+
+    #[test]
+    fn map_buttons_and_axes() {
+        let mut pad = pad();
+        let mut state = JoystickState::default();
+        state.buttons[1] = true;
+        state.buttons[5] = true;
+        state.axes[RawAxis::X as usize] = Some(60.);
+        state.axes[RawAxis::Y as usize] = Some(50.);
+        state.axes[RawAxis::Z as usize] = Some(80.);
+        state.axes[RawAxis::PovX as usize] = Some(100.);
+        pad.set_stick_settings(
+            Stick::Left,
+            StickSettings {
+                dead_zone: 0.,
+                curve: ResponseCurve::Linear,
+            },
+        );
+        pad.update(&state);
+        assert!(pad.pressed(Button::A) && pad.just_pressed(Button::A));
+        assert!(!pad.pressed(Button::B));
+        assert!(pad.pressed(Button::DPadRight));
+        assert!(pad.pressed(Button::Y));
+        assert!((pad.axis(Axis::LeftX) - 0.6).abs() < 1e-6);
+        assert!((pad.axis(Axis::LeftY) + 0.5).abs() < 1e-6);
+        assert_eq!(pad.axis(Axis::LeftTrigger), 0.9);
+        assert_eq!(pad.axis(Axis::RightTrigger), 0.);
+        assert_eq!(pad.axis(Axis::RightX), -1.);
+
+        state.buttons[1] = false;
+        pad.update(&state);
+        assert!(pad.just_released(Button::A));
+    }
+
+    #[test]
+    fn radial_dead_zone_and_curve() {
+        let mut pad = pad();
+        let mut state = JoystickState::default();
+        state.axes[RawAxis::X as usize] = Some(30.);
+        state.axes[RawAxis::Y as usize] = Some(-40.);
+        pad.update(&state);
+        // 0.5 from the center, past the default dead zone of 0.15
+        let stick = pad.stick(Stick::Left);
+        assert!((stick.length() - 0.35 / 0.85).abs() < 1e-6);
+        assert!((stick.x / stick.y - 0.75).abs() < 1e-6);
+
+        pad.set_stick_settings(
+            Stick::Left,
+            StickSettings {
+                dead_zone: 0.5,
+                curve: ResponseCurve::Power(2.),
+            },
+        );
+        assert_eq!(pad.axis(Axis::LeftX), 0.);
+        state.axes[RawAxis::X as usize] = Some(45.);
+        state.axes[RawAxis::Y as usize] = Some(-60.);
+        pad.update(&state);
+        let stick = pad.stick(Stick::Left);
+        assert!((stick.length() - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn track_hotplug() {
+        let mut db = MappingDb::new();
+        assert_eq!(db.add_mappings(PAD), Ok(1));
+        let mut gamepads = Gamepads::new(db);
+        let mut connected = [true, false, true, false, false, false, false, false];
+        let identify = |id| match id {
+            0 => ("Test Pad".to_owned(), 0, 0),
+            _ => ("Flight Stick".to_owned(), 0x0001, 0x0002),
+        };
+        let read = |connected: [bool; 8]| {
+            move |id: u32| connected[id as usize].then(JoystickState::default)
+        };
+        assert_eq!(
+            gamepads.update_with(read(connected), identify),
+            [GamepadEvent::Connected(0)]
+        );
+        assert!(gamepads.update_with(read(connected), identify).is_empty());
+        connected[0] = false;
+        assert_eq!(
+            gamepads.update_with(read(connected), identify),
+            [GamepadEvent::Disconnected(0)]
+        );
+        assert_eq!(gamepads.iter().count(), 0);
+
+        let _ = gamepads
+            .db_mut()
+            .add_mappings("03000000010000000200000000000000,Flight Stick,a:b0,")
+            .expect("Invalid mapping");
+        assert_eq!(
+            gamepads.update_with(read(connected), identify),
+            [GamepadEvent::Connected(2)]
+        );
+        assert_eq!(gamepads.get(2).map(Gamepad::name), Some("Flight Stick"));
+    }
+
+    #[test]
+    fn invalid_mappings() {
+        let mut db = MappingDb::new();
+        assert_eq!(
+            db.add_mappings(&format!("# Comment\n\n{GUID},Pad,a:b0\n{GUID},Pad,a:q7\n")),
+            Err(LoadError::Syntax {
+                line: 4,
+                reason: "invalid input"
+            })
+        );
+        assert!(db.is_empty());
+        for guid in [
+            "guid",
+            &GUID[1..],
+            &format!("{GUID}0"),
+            &GUID.replace('d', "g"),
+        ] {
+            assert_eq!(
+                db.add_mappings(&format!("{guid},Pad,a:b0")),
+                Err(LoadError::Syntax {
+                    line: 1,
+                    reason: "invalid GUID"
+                })
+            );
+        }
+        assert!(db.is_empty());
+    }
+}
@@ -0,0 +1,585 @@
+use {
+    crate::{
+        system::{Angle, Time, Vector2f, Vector2i},
+        window::{Event, mouse},
+    },
+    std::{collections::VecDeque, time::Instant},
+};
+
+/// The finger that stands for the mouse, when mouse emulation is enabled.
+const MOUSE_FINGER: u32 = u32::MAX;
+
+/// The main direction of a swipe.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SwipeDirection {
+    /// Towards the left
+    Left,
+    /// Towards the right
+    Right,
+    /// Towards the top of the window
+    Up,
+    /// Towards the bottom of the window
+    Down,
+}
+
+/// A gesture recognized by a [`GestureRecognizer`].
+///
+/// Positions are in window coordinates, like in touch events.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Gesture {
+    /// A finger touched and left the screen quickly, without moving
+    Tap {
+        /// Where the finger touched
+        position: Vector2i,
+    },
+    /// A second tap, close in time and space to the previous one.
+    ///
+    /// The second tap is also reported as a [`Tap`](Self::Tap), before this.
+    DoubleTap {
+        /// Where the finger touched the second time
+        position: Vector2i,
+    },
+    /// A finger stayed on the screen without moving.
+    ///
+    /// The touch doesn't make a tap when the finger is lifted.
+    LongPress {
+        /// Where the finger touches
+        position: Vector2i,
+    },
+    /// A finger moved quickly across the screen and left it
+    Swipe {
+        /// Where the finger touched first
+        start: Vector2i,
+        /// Where the finger left the screen
+        end: Vector2i,
+        /// The main direction of the movement
+        direction: SwipeDirection,
+        /// The average speed of the finger, in pixels per second
+        speed: f32,
+    },
+    /// Two fingers moved closer or further apart
+    Pinch {
+        /// The point between the two fingers
+        center: Vector2f,
+        /// The distance between the fingers, relative to when they touched the screen
+        scale: f32,
+        /// The change of `scale` since the previous `Pinch`, as a factor
+        scale_delta: f32,
+    },
+    /// Two fingers turned around each other
+    Rotate {
+        /// The point between the two fingers
+        center: Vector2f,
+        /// The rotation since the fingers touched the screen, clockwise on the screen
+        angle: Angle,
+        /// The rotation since the previous `Rotate`
+        angle_delta: Angle,
+    },
+}
+
+/// The thresholds of a [`GestureRecognizer`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GestureSettings {
+    /// How far a finger can move, in pixels, and still make a tap or a long press
+    pub tap_max_distance: f32,
+    /// How long a finger can stay on the screen and still make a tap
+    pub tap_max_duration: Time,
+    /// The longest time between two taps that make a double tap
+    pub double_tap_interval: Time,
+    /// How far apart two taps can be, in pixels, and still make a double tap
+    pub double_tap_max_distance: f32,
+    /// How long a finger has to stay on the screen to make a long press
+    pub long_press_duration: Time,
+    /// How far a finger has to move, in pixels, to make a swipe
+    pub swipe_min_distance: f32,
+    /// How fast a finger has to move, in pixels per second, to make a swipe
+    pub swipe_min_speed: f32,
+    /// How much the distance between two fingers has to change, relative to the start, before
+    /// pinches are reported
+    pub pinch_threshold: f32,
+    /// How much two fingers have to turn before rotations are reported
+    pub rotation_threshold: Angle,
+}
+
+impl Default for GestureSettings {
+    fn default() -> Self {
+        Self {
+            tap_max_distance: 10.,
+            tap_max_duration: Time::milliseconds(300),
+            double_tap_interval: Time::milliseconds(300),
+            double_tap_max_distance: 30.,
+            long_press_duration: Time::milliseconds(500),
+            swipe_min_distance: 50.,
+            swipe_min_speed: 200.,
+            pinch_threshold: 0.05,
+            rotation_threshold: Angle::degrees(10.),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Finger {
+    id: u32,
+    start: Vector2i,
+    start_time: Time,
+    position: Vector2i,
+    moved: bool,
+    long_pressed: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TwoFingers {
+    start: Vector2f,
+    pinching: bool,
+    rotating: bool,
+    scale: f32,
+    angle: Angle,
+}
+
+/// Turns touch events into taps, swipes, pinches and other gestures.
+///
+/// Events are given to the recognizer with [`handle_event`], and the recognized gestures are
+/// taken with [`poll_gesture`]. Long presses happen without any event, so [`update`] has to be
+/// called every frame too. With mouse emulation, the left mouse button acts as a finger, to try
+/// single finger gestures on the desktop.
+///
+/// A touch that becomes a multi-touch, with a second finger, only makes pinches and rotations,
+/// until all fingers leave the screen. Pinches and rotations are reported for the first two
+/// fingers.
+///
+/// [`handle_event`]: Self::handle_event
+/// [`poll_gesture`]: Self::poll_gesture
+/// [`update`]: Self::update
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::window::{Gesture, GestureRecognizer, Window};
+/// # let mut window: Window = unimplemented!();
+/// let mut gestures = GestureRecognizer::new();
+/// gestures.set_mouse_emulation(true);
+/// let mut zoom = 1.0;
+/// while window.is_open() {
+///     while let Some(event) = window.poll_event() {
+///         gestures.handle_event(event);
+///     }
+///     gestures.update();
+///     while let Some(gesture) = gestures.poll_gesture() {
+///         match gesture {
+///             Gesture::DoubleTap { .. } => zoom = 1.0,
+///             Gesture::Pinch { scale_delta, .. } => zoom *= scale_delta,
+///             _ => {}
+///         }
+///     }
+///     window.display();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct GestureRecognizer {
+    settings: GestureSettings,
+    mouse_emulation: bool,
+    start: Instant,
+    fingers: Vec<Finger>,
+    multi_touch: bool,
+    two_fingers: Option<TwoFingers>,
+    last_tap: Option<(Time, Vector2i)>,
+    gestures: VecDeque<Gesture>,
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GestureRecognizer {
+    /// Create a recognizer with the default settings, without mouse emulation.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_settings(GestureSettings::default())
+    }
+    /// Create a recognizer with the thresholds in `settings`, without mouse emulation.
+    #[must_use]
+    pub fn with_settings(settings: GestureSettings) -> Self {
+        Self {
+            settings,
+            mouse_emulation: false,
+            start: Instant::now(),
+            fingers: Vec::new(),
+            multi_touch: false,
+            two_fingers: None,
+            last_tap: None,
+            gestures: VecDeque::new(),
+        }
+    }
+    /// The thresholds of the recognizer.
+    #[must_use]
+    pub fn settings(&self) -> &GestureSettings {
+        &self.settings
+    }
+    /// Change the thresholds of the recognizer.
+    pub fn set_settings(&mut self, settings: GestureSettings) {
+        self.settings = settings;
+    }
+    /// Set whether the left mouse button acts as a finger.
+    pub fn set_mouse_emulation(&mut self, enabled: bool) {
+        self.mouse_emulation = enabled;
+    }
+    /// Whether the left mouse button acts as a finger.
+    #[must_use]
+    pub fn mouse_emulation(&self) -> bool {
+        self.mouse_emulation
+    }
+
+    /// Handle an event, if it's a touch event, or a mouse event with mouse emulation.
+    ///
+    /// The time of the event is measured from the creation of the recognizer.
+    pub fn handle_event(&mut self, event: Event) {
+        let now = self.now();
+        self.handle_event_at(event, now);
+    }
+    /// Handle an event that happened at `time`, for a caller that keeps its own time, like a
+    /// replay or a test.
+    pub fn handle_event_at(&mut self, event: Event, time: Time) {
+        self.update_at(time);
+        match event {
+            Event::TouchBegan { finger, position } => self.touch_began(finger, position, time),
+            Event::TouchMoved { finger, position } => self.touch_moved(finger, position),
+            Event::TouchEnded { finger, position } => self.touch_ended(finger, position, time),
+            Event::MouseButtonPressed {
+                button: mouse::Button::Left,
+                position,
+            } if self.mouse_emulation => self.touch_began(MOUSE_FINGER, position, time),
+            Event::MouseMoved { position } if self.mouse_emulation => {
+                self.touch_moved(MOUSE_FINGER, position);
+            }
+            Event::MouseButtonReleased {
+                button: mouse::Button::Left,
+                position,
+            } if self.mouse_emulation => self.touch_ended(MOUSE_FINGER, position, time),
+            _ => {}
+        }
+    }
+    /// Recognize the gestures that happen without events, like long presses.
+    pub fn update(&mut self) {
+        let now = self.now();
+        self.update_at(now);
+    }
+    /// Recognize the gestures that happen without events, as of `time`.
+    pub fn update_at(&mut self, time: Time) {
+        if self.multi_touch {
+            return;
+        }
+        let [finger] = &mut self.fingers[..] else {
+            return;
+        };
+        if !finger.moved
+            && !finger.long_pressed
+            && time - finger.start_time >= self.settings.long_press_duration
+        {
+            finger.long_pressed = true;
+            self.gestures.push_back(Gesture::LongPress {
+                position: finger.position,
+            });
+        }
+    }
+    /// Take the oldest recognized gesture, if any.
+    pub fn poll_gesture(&mut self) -> Option<Gesture> {
+        self.gestures.pop_front()
+    }
+    /// Forget the fingers on the screen and the gestures in progress.
+    ///
+    /// Gestures already recognized can still be polled.
+    pub fn reset(&mut self) {
+        self.fingers.clear();
+        self.multi_touch = false;
+        self.two_fingers = None;
+        self.last_tap = None;
+    }
+
+    fn now(&self) -> Time {
+        let elapsed = self.start.elapsed().as_micros();
+        Time::microseconds(i64::try_from(elapsed).unwrap_or(i64::MAX))
+    }
+    fn touch_began(&mut self, id: u32, position: Vector2i, time: Time) {
+        if self.fingers.iter().any(|finger| finger.id == id) {
+            return;
+        }
+        if self.fingers.is_empty() {
+            self.multi_touch = false;
+        }
+        self.fingers.push(Finger {
+            id,
+            start: position,
+            start_time: time,
+            position,
+            moved: false,
+            long_pressed: false,
+        });
+        if self.fingers.len() == 2 {
+            self.multi_touch = true;
+            self.last_tap = None;
+            self.two_fingers = Some(TwoFingers {
+                start: self.span(),
+                pinching: false,
+                rotating: false,
+                scale: 1.,
+                angle: Angle::degrees(0.),
+            });
+        }
+    }
+    fn touch_moved(&mut self, id: u32, position: Vector2i) {
+        let Some(index) = self.fingers.iter().position(|finger| finger.id == id) else {
+            return;
+        };
+        let finger = &mut self.fingers[index];
+        finger.position = position;
+        if distance(finger.start, position) > self.settings.tap_max_distance {
+            finger.moved = true;
+        }
+        if index < 2 {
+            self.two_fingers_moved();
+        }
+    }
+    fn touch_ended(&mut self, id: u32, position: Vector2i, time: Time) {
+        let Some(index) = self.fingers.iter().position(|finger| finger.id == id) else {
+            return;
+        };
+        let mut finger = self.fingers.remove(index);
+        if index < 2 {
+            self.two_fingers = None;
+        }
+        if self.multi_touch || finger.long_pressed {
+            return;
+        }
+        finger.position = position;
+        let settings = &self.settings;
+        let duration = time - finger.start_time;
+        let moved = distance(finger.start, position);
+        if moved <= settings.tap_max_distance && duration <= settings.tap_max_duration {
+            self.gestures.push_back(Gesture::Tap { position });
+            let double = self.last_tap.is_some_and(|(last_time, last_position)| {
+                time - last_time <= settings.double_tap_interval
+                    && distance(last_position, position) <= settings.double_tap_max_distance
+            });
+            if double {
+                self.gestures.push_back(Gesture::DoubleTap { position });
+                self.last_tap = None;
+            } else {
+                self.last_tap = Some((time, position));
+            }
+        } else if moved >= settings.swipe_min_distance {
+            let speed = moved / duration.as_seconds().max(f32::EPSILON);
+            if speed >= settings.swipe_min_speed {
+                let delta = position - finger.start;
+                let direction = match (delta.x.abs() >= delta.y.abs(), delta.x < 0, delta.y < 0) {
+                    (true, true, _) => SwipeDirection::Left,
+                    (true, false, _) => SwipeDirection::Right,
+                    (false, _, true) => SwipeDirection::Up,
+                    (false, _, false) => SwipeDirection::Down,
+                };
+                self.gestures.push_back(Gesture::Swipe {
+                    start: finger.start,
+                    end: position,
+                    direction,
+                    speed,
+                });
+            }
+        }
+    }
+    /// The vector from the first finger to the second.
+    fn span(&self) -> Vector2f {
+        (self.fingers[1].position - self.fingers[0].position).as_other()
+    }
+    fn two_fingers_moved(&mut self) {
+        let Some(mut two) = self.two_fingers else {
+            return;
+        };
+        let span = self.span();
+        if span == Vector2f::default() || two.start == Vector2f::default() {
+            return;
+        }
+        let center = (self.fingers[0].position + self.fingers[1].position).as_other::<f32>() / 2.;
+        let scale = span.length() / two.start.length();
+        let angle = two.start.angle_to(span);
+        if !two.pinching && (scale - 1.).abs() >= self.settings.pinch_threshold {
+            two.pinching = true;
+        }
+        if two.pinching && scale != two.scale {
+            self.gestures.push_back(Gesture::Pinch {
+                center,
+                scale,
+                scale_delta: scale / two.scale,
+            });
+            two.scale = scale;
+        }
+        if !two.rotating
+            && angle.as_degrees().abs() >= self.settings.rotation_threshold.as_degrees()
+        {
+            two.rotating = true;
+        }
+        if two.rotating && angle != two.angle {
+            self.gestures.push_back(Gesture::Rotate {
+                center,
+                angle,
+                angle_delta: (angle - two.angle).wrap_signed(),
+            });
+            two.angle = angle;
+        }
+        self.two_fingers = Some(two);
+    }
+}
+
+fn distance(a: Vector2i, b: Vector2i) -> f32 {
+    (b - a).as_other::<f32>().length()
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{Gesture, GestureRecognizer, SwipeDirection},
+        crate::{
+            system::{Time, Vector2i},
+            window::{Event, mouse},
+        },
+    };
+
+    fn ms(milliseconds: i32) -> Time {
+        Time::milliseconds(milliseconds)
+    }
+
+    fn began(finger: u32, x: i32, y: i32) -> Event {
+        Event::TouchBegan {
+            finger,
+            position: Vector2i::new(x, y),
+        }
+    }
+
+    fn moved(finger: u32, x: i32, y: i32) -> Event {
+        Event::TouchMoved {
+            finger,
+            position: Vector2i::new(x, y),
+        }
+    }
+
+    fn ended(finger: u32, x: i32, y: i32) -> Event {
+        Event::TouchEnded {
+            finger,
+            position: Vector2i::new(x, y),
+        }
+    }
+
+    fn drain(gestures: &mut GestureRecognizer) -> Vec<Gesture> {
+        std::iter::from_fn(|| gestures.poll_gesture()).collect()
+    }
+
+    #[test]
+    fn taps_and_long_press() {
+        let mut gestures = GestureRecognizer::new();
+        gestures.handle_event_at(began(0, 100, 100), ms(0));
+        gestures.handle_event_at(ended(0, 103, 98), ms(100));
+        gestures.handle_event_at(began(0, 110, 105), ms(250));
+        gestures.handle_event_at(ended(0, 110, 105), ms(300));
+        let position = Vector2i::new(110, 105);
+        assert_eq!(
+            drain(&mut gestures),
+            [
+                Gesture::Tap {
+                    position: Vector2i::new(103, 98)
+                },
+                Gesture::Tap { position },
+                Gesture::DoubleTap { position },
+            ]
+        );
+
+        gestures.handle_event_at(began(1, 50, 50), ms(1000));
+        gestures.update_at(ms(1400));
+        assert_eq!(gestures.poll_gesture(), None);
+        gestures.update_at(ms(1500));
+        gestures.handle_event_at(ended(1, 50, 50), ms(1600));
+        assert_eq!(
+            drain(&mut gestures),
+            [Gesture::LongPress {
+                position: Vector2i::new(50, 50)
+            }]
+        );
+    }
+
+    #[test]
+    fn swipes_need_distance_and_speed() {
+        let mut gestures = GestureRecognizer::new();
+        gestures.handle_event_at(began(0, 200, 200), ms(0));
+        gestures.handle_event_at(moved(0, 150, 210), ms(50));
+        gestures.handle_event_at(ended(0, 100, 220), ms(100));
+        let Some(Gesture::Swipe {
+            direction, speed, ..
+        }) = gestures.poll_gesture()
+        else {
+            panic!("Expected a swipe");
+        };
+        assert_eq!(direction, SwipeDirection::Left);
+        assert!((speed - 1019.8).abs() < 0.1);
+
+        // Too slow
+        gestures.handle_event_at(began(0, 200, 200), ms(1000));
+        gestures.handle_event_at(moved(0, 200, 180), ms(1300));
+        gestures.handle_event_at(ended(0, 200, 100), ms(3000));
+        assert_eq!(gestures.poll_gesture(), None);
+    }
+
+    #[test]
+    fn pinch_and_rotate() {
+        let mut gestures = GestureRecognizer::new();
+        gestures.handle_event_at(began(0, 100, 100), ms(0));
+        gestures.handle_event_at(began(1, 200, 100), ms(10));
+        // Under the thresholds
+        gestures.handle_event_at(moved(1, 202, 100), ms(20));
+        assert_eq!(gestures.poll_gesture(), None);
+        gestures.handle_event_at(moved(1, 300, 100), ms(30));
+        // Same distance, a quarter turn
+        gestures.handle_event_at(moved(1, 100, 300), ms(40));
+        let gestures = drain(&mut gestures);
+        let [
+            Gesture::Pinch {
+                center,
+                scale,
+                scale_delta,
+            },
+            Gesture::Rotate {
+                angle, angle_delta, ..
+            },
+        ] = gestures[..]
+        else {
+            panic!("Unexpected gestures: {gestures:?}");
+        };
+        assert_eq!(center, (200., 100.).into());
+        assert_eq!((scale, scale_delta), (2., 2.));
+        assert!((angle.as_degrees() - 90.).abs() < 1e-4);
+        assert!((angle_delta.as_degrees() - 90.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn mouse_emulation() {
+        let mut gestures = GestureRecognizer::new();
+        let press = Event::MouseButtonPressed {
+            button: mouse::Button::Left,
+            position: Vector2i::new(10, 10),
+        };
+        let release = Event::MouseButtonReleased {
+            button: mouse::Button::Left,
+            position: Vector2i::new(10, 10),
+        };
+        gestures.handle_event_at(press, ms(0));
+        gestures.handle_event_at(release, ms(50));
+        assert_eq!(gestures.poll_gesture(), None);
+        gestures.set_mouse_emulation(true);
+        gestures.handle_event_at(press, ms(100));
+        gestures.handle_event_at(release, ms(150));
+        assert_eq!(
+            gestures.poll_gesture(),
+            Some(Gesture::Tap {
+                position: Vector2i::new(10, 10)
+            })
+        );
+    }
+}
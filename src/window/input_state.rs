@@ -0,0 +1,429 @@
+use {
+    crate::{
+        system::{Time, Vector2i, Vector2u},
+        window::{Event, EventHandler, Key, KeyEvent, Scancode, joystick, mouse},
+    },
+    std::{collections::HashMap, time::Instant},
+};
+
+/// A button tracked by [`InputState`]: a key, a mouse button or a joystick button.
+///
+/// Keys, scancodes and mouse buttons convert to `Input`, so they can be passed directly to the
+/// queries of [`InputState`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Input {
+    /// A key, by its layout-dependent code
+    Key(Key),
+    /// A key, by its physical position on the keyboard
+    Scancode(Scancode),
+    /// A mouse button
+    Mouse(mouse::Button),
+    /// A joystick button
+    JoystickButton {
+        /// Index of the joystick
+        joystick: u32,
+        /// Index of the button
+        button: u32,
+    },
+}
+
+impl From<Key> for Input {
+    fn from(key: Key) -> Self {
+        Self::Key(key)
+    }
+}
+
+impl From<Scancode> for Input {
+    fn from(scan: Scancode) -> Self {
+        Self::Scancode(scan)
+    }
+}
+
+impl From<mouse::Button> for Input {
+    fn from(button: mouse::Button) -> Self {
+        Self::Mouse(button)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ButtonState {
+    down: bool,
+    since: Time,
+    just_pressed: bool,
+    just_released: bool,
+}
+
+/// The state of the keyboard, mouse and joysticks, updated from events once per frame.
+///
+/// Unlike [`Key::is_pressed`] and the other real-time queries, which can miss a button pressed
+/// and released between two frames, `InputState` sees every event. A tap shorter than a frame is
+/// both [`just_pressed`] and [`just_released`] in the frame it happened.
+///
+/// Each frame, call [`begin_frame`], then give all the events of the frame to the state, with
+/// [`handle_event`] or by passing it to `handle_events` on a window, since it's an
+/// [`EventHandler`]. Losing the focus releases all the buttons, since their release events would
+/// go to another window.
+///
+/// [`just_pressed`]: Self::just_pressed
+/// [`just_released`]: Self::just_released
+/// [`begin_frame`]: Self::begin_frame
+/// [`handle_event`]: Self::handle_event
+///
+/// # Usage example
+///
+/// ```no_run
+/// # use sfml::{system::Time, window::{InputState, Key, Window, mouse}};
+/// # let mut window: Window = unimplemented!();
+/// let mut input = InputState::new();
+/// while window.is_open() {
+///     input.begin_frame();
+///     window.handle_events(&mut input);
+///     if input.just_pressed(Key::Space) {
+///         // Jump
+///     }
+///     if input.held_for(mouse::Button::Left, Time::seconds(0.5)) {
+///         // Charge the attack
+///     }
+///     window.display();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct InputState {
+    start: Instant,
+    now: Time,
+    buttons: HashMap<Input, ButtonState>,
+    mouse_position: Option<Vector2i>,
+    mouse_delta: Vector2i,
+    raw_mouse_delta: Vector2i,
+    wheel_delta: [f32; 2],
+    joystick_axes: HashMap<u32, [f32; 8]>,
+    text: String,
+    closed: bool,
+    resized: Option<Vector2u>,
+}
+
+impl Default for InputState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputState {
+    /// Create a state where nothing is pressed.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            now: Time::ZERO,
+            buttons: HashMap::new(),
+            mouse_position: None,
+            mouse_delta: Vector2i::default(),
+            raw_mouse_delta: Vector2i::default(),
+            wheel_delta: [0.; 2],
+            joystick_axes: HashMap::new(),
+            text: String::new(),
+            closed: false,
+            resized: None,
+        }
+    }
+    /// Start a new frame, forgetting what happened during the previous one.
+    ///
+    /// The current time is measured from the creation of the state.
+    pub fn begin_frame(&mut self) {
+        let elapsed = self.start.elapsed().as_micros();
+        self.begin_frame_at(Time::microseconds(
+            i64::try_from(elapsed).unwrap_or(i64::MAX),
+        ));
+    }
+    /// Start a new frame at `now`, for a caller that keeps its own time, like a replay.
+    pub fn begin_frame_at(&mut self, now: Time) {
+        self.now = now;
+        self.buttons.retain(|_, state| state.down);
+        for state in self.buttons.values_mut() {
+            state.just_pressed = false;
+            state.just_released = false;
+        }
+        self.mouse_delta = Vector2i::default();
+        self.raw_mouse_delta = Vector2i::default();
+        self.wheel_delta = [0.; 2];
+        self.text.clear();
+        self.closed = false;
+        self.resized = None;
+    }
+    /// Update the state from an event of the current frame.
+    pub fn handle_event(&mut self, event: Event) {
+        event.dispatch(self);
+    }
+
+    /// Whether `input` is down.
+    #[must_use]
+    pub fn pressed(&self, input: impl Into<Input>) -> bool {
+        self.buttons.get(&input.into()).is_some_and(|s| s.down)
+    }
+    /// Whether `input` was pressed during this frame.
+    #[must_use]
+    pub fn just_pressed(&self, input: impl Into<Input>) -> bool {
+        self.buttons
+            .get(&input.into())
+            .is_some_and(|s| s.just_pressed)
+    }
+    /// Whether `input` was released during this frame.
+    #[must_use]
+    pub fn just_released(&self, input: impl Into<Input>) -> bool {
+        self.buttons
+            .get(&input.into())
+            .is_some_and(|s| s.just_released)
+    }
+    /// The inputs pressed during this frame, in no particular order.
+    pub fn just_pressed_inputs(&self) -> impl Iterator<Item = Input> + '_ {
+        self.buttons
+            .iter()
+            .filter(|(_, state)| state.just_pressed)
+            .map(|(&input, _)| input)
+    }
+    /// How long `input` has been down, as of the start of the frame, or `None` if it's up.
+    #[must_use]
+    pub fn held_time(&self, input: impl Into<Input>) -> Option<Time> {
+        let state = self.buttons.get(&input.into()).filter(|s| s.down)?;
+        Some((self.now - state.since).max(Time::ZERO))
+    }
+    /// Whether `input` has been down for at least `duration`.
+    #[must_use]
+    pub fn held_for(&self, input: impl Into<Input>, duration: Time) -> bool {
+        self.held_time(input).is_some_and(|held| held >= duration)
+    }
+    /// The last known position of the mouse, relative to the window.
+    #[must_use]
+    pub fn mouse_position(&self) -> Option<Vector2i> {
+        self.mouse_position
+    }
+    /// How much the mouse cursor moved during this frame.
+    #[must_use]
+    pub fn mouse_delta(&self) -> Vector2i {
+        self.mouse_delta
+    }
+    /// How much the mouse moved during this frame, from the raw mouse movement events.
+    #[must_use]
+    pub fn raw_mouse_delta(&self) -> Vector2i {
+        self.raw_mouse_delta
+    }
+    /// How much `wheel` was scrolled during this frame.
+    #[must_use]
+    pub fn wheel_delta(&self, wheel: mouse::Wheel) -> f32 {
+        self.wheel_delta[wheel as usize]
+    }
+    /// The last known position of `axis` on `joystick`, in range [-100 .. 100].
+    #[must_use]
+    pub fn joystick_axis(&self, joystick: u32, axis: joystick::Axis) -> f32 {
+        self.joystick_axes
+            .get(&joystick)
+            .map_or(0., |axes| axes[axis as usize])
+    }
+    /// The text entered during this frame.
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    /// Whether the window requested to be closed during this frame.
+    #[must_use]
+    pub fn close_requested(&self) -> bool {
+        self.closed
+    }
+    /// The new size of the window, if it was resized during this frame.
+    #[must_use]
+    pub fn resized(&self) -> Option<Vector2u> {
+        self.resized
+    }
+
+    fn press(&mut self, input: Input) {
+        let now = self.now;
+        let state = self.buttons.entry(input).or_insert(ButtonState {
+            down: false,
+            since: now,
+            just_pressed: false,
+            just_released: false,
+        });
+        // Key repeats don't press again
+        if !state.down {
+            state.down = true;
+            state.since = now;
+            state.just_pressed = true;
+        }
+    }
+    fn release(&mut self, input: Input) {
+        if let Some(state) = self.buttons.get_mut(&input).filter(|s| s.down) {
+            state.down = false;
+            state.just_released = true;
+        }
+    }
+    fn release_all(&mut self, mut filter: impl FnMut(&Input) -> bool) {
+        for (_, state) in self
+            .buttons
+            .iter_mut()
+            .filter(|(input, state)| state.down && filter(input))
+        {
+            state.down = false;
+            state.just_released = true;
+        }
+    }
+    fn move_mouse(&mut self, position: Vector2i) {
+        if let Some(last) = self.mouse_position {
+            self.mouse_delta += position - last;
+        }
+        self.mouse_position = Some(position);
+    }
+}
+
+impl EventHandler for InputState {
+    fn on_closed(&mut self) {
+        self.closed = true;
+    }
+    fn on_resized(&mut self, size: Vector2u) {
+        self.resized = Some(size);
+    }
+    fn on_focus_lost(&mut self) {
+        self.release_all(|_| true);
+    }
+    fn on_text_entered(&mut self, unicode: char) {
+        self.text.push(unicode);
+    }
+    fn on_key_pressed(&mut self, key: KeyEvent) {
+        self.press(Input::Key(key.code));
+        self.press(Input::Scancode(key.scan));
+    }
+    fn on_key_released(&mut self, key: KeyEvent) {
+        self.release(Input::Key(key.code));
+        self.release(Input::Scancode(key.scan));
+    }
+    fn on_mouse_wheel_scrolled(&mut self, wheel: mouse::Wheel, delta: f32, position: Vector2i) {
+        self.wheel_delta[wheel as usize] += delta;
+        self.move_mouse(position);
+    }
+    fn on_mouse_button_pressed(&mut self, button: mouse::Button, position: Vector2i) {
+        self.press(Input::Mouse(button));
+        self.move_mouse(position);
+    }
+    fn on_mouse_button_released(&mut self, button: mouse::Button, position: Vector2i) {
+        self.release(Input::Mouse(button));
+        self.move_mouse(position);
+    }
+    fn on_mouse_moved(&mut self, position: Vector2i) {
+        self.move_mouse(position);
+    }
+    fn on_mouse_moved_raw(&mut self, delta: Vector2i) {
+        self.raw_mouse_delta += delta;
+    }
+    fn on_joystick_button_pressed(&mut self, joystick: u32, button: u32) {
+        self.press(Input::JoystickButton { joystick, button });
+    }
+    fn on_joystick_button_released(&mut self, joystick: u32, button: u32) {
+        self.release(Input::JoystickButton { joystick, button });
+    }
+    fn on_joystick_moved(&mut self, joystickid: u32, axis: joystick::Axis, position: f32) {
+        self.joystick_axes.entry(joystickid).or_default()[axis as usize] = position;
+    }
+    fn on_joystick_disconnected(&mut self, joystickid: u32) {
+        let _ = self.joystick_axes.remove(&joystickid);
+        self.release_all(|input| {
+            matches!(input, Input::JoystickButton { joystick, .. } if *joystick == joystickid)
+        });
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use {
+        super::InputState,
+        crate::{
+            system::{Time, Vector2i},
+            window::{Event, Key, Scancode, mouse},
+        },
+    };
+
+    /// A key event without scancode or modifiers.
+    pub(crate) fn key(code: Key, pressed: bool) -> Event {
+        let (scan, alt, ctrl, shift, system) = (Scancode::Unknown, false, false, false, false);
+        if pressed {
+            Event::KeyPressed {
+                code,
+                scan,
+                alt,
+                ctrl,
+                shift,
+                system,
+            }
+        } else {
+            Event::KeyReleased {
+                code,
+                scan,
+                alt,
+                ctrl,
+                shift,
+                system,
+            }
+        }
+    }
+
+    #[test]
+    fn taps_within_a_frame_are_seen() {
+        let mut input = InputState::new();
+        input.begin_frame_at(Time::ZERO);
+        input.handle_event(key(Key::Space, true));
+        input.handle_event(key(Key::Space, false));
+        input.handle_event(key(Key::A, true));
+        assert!(input.just_pressed(Key::Space) && input.just_released(Key::Space));
+        assert!(!input.pressed(Key::Space));
+        assert!(input.just_pressed(Key::A) && input.pressed(Key::A));
+
+        input.begin_frame_at(Time::milliseconds(500));
+        // Key repeat
+        input.handle_event(key(Key::A, true));
+        assert!(!input.just_pressed(Key::Space) && !input.just_released(Key::Space));
+        assert!(!input.just_pressed(Key::A) && input.pressed(Key::A));
+        assert_eq!(input.held_time(Key::A), Some(Time::milliseconds(500)));
+        assert!(input.held_for(Key::A, Time::milliseconds(500)));
+        assert!(!input.held_for(Key::A, Time::seconds(1.)));
+
+        input.begin_frame_at(Time::seconds(1.));
+        input.handle_event(Event::FocusLost);
+        assert!(input.just_released(Key::A) && !input.pressed(Key::A));
+        assert_eq!(input.held_time(Key::A), None);
+    }
+
+    #[test]
+    fn mouse_motion_and_text_accumulate() {
+        let mut input = InputState::new();
+        input.begin_frame_at(Time::ZERO);
+        input.handle_event(Event::MouseMoved {
+            position: Vector2i::new(10, 10),
+        });
+        input.begin_frame_at(Time::milliseconds(16));
+        for position in [Vector2i::new(15, 8), Vector2i::new(20, 4)] {
+            input.handle_event(Event::MouseMoved { position });
+        }
+        input.handle_event(Event::MouseButtonPressed {
+            button: mouse::Button::Right,
+            position: Vector2i::new(21, 4),
+        });
+        for delta in [1.0, 0.5] {
+            input.handle_event(Event::MouseWheelScrolled {
+                wheel: mouse::Wheel::VerticalWheel,
+                delta,
+                position: Vector2i::new(21, 4),
+            });
+        }
+        for unicode in "hé".chars() {
+            input.handle_event(Event::TextEntered { unicode });
+        }
+        assert_eq!(input.mouse_delta(), Vector2i::new(11, -6));
+        assert_eq!(input.mouse_position(), Some(Vector2i::new(21, 4)));
+        assert!(input.just_pressed(mouse::Button::Right));
+        assert_eq!(input.wheel_delta(mouse::Wheel::VerticalWheel), 1.5);
+        assert_eq!(input.wheel_delta(mouse::Wheel::HorizontalWheel), 0.);
+        assert_eq!(input.text(), "hé");
+        input.begin_frame_at(Time::milliseconds(32));
+        assert_eq!(input.mouse_delta(), Vector2i::new(0, 0));
+        assert_eq!(input.text(), "");
+        assert!(input.pressed(mouse::Button::Right));
+    }
+}
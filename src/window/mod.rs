@@ -2,10 +2,17 @@
 
 pub use {
     self::{
+        action_map::{ActionMap, Binding, Modifiers, Polarity},
         context::Context,
         context_settings::ContextSettings,
         cursor::{Cursor, Type as CursorType},
         event::Event,
+        event_handler::{EventHandler, KeyEvent},
+        event_recording::{
+            EventRecorder, EventRecording, EventReplay, EventSource, RecordingError, TimedEvent,
+        },
+        gesture::{Gesture, GestureRecognizer, GestureSettings, SwipeDirection},
+        input_state::{Input, InputState},
         keyboard::{Key, set_virtual_keyboard_visible},
         video_mode::VideoMode,
         window::{Handle, Window},
@@ -14,11 +21,17 @@ pub use {
     crate::ffi::window::Scancode,
 };
 
+mod action_map;
 pub mod clipboard;
 mod context;
 mod context_settings;
 mod cursor;
 mod event;
+mod event_handler;
+pub(crate) mod event_recording;
+pub mod gamepad;
+mod gesture;
+mod input_state;
 pub mod joystick;
 mod keyboard;
 pub mod mouse;
@@ -26,6 +39,7 @@ pub mod sensor;
 pub(crate) mod thread_safety;
 pub mod touch;
 mod video_mode;
+pub mod vulkan;
 #[expect(clippy::module_inception)]
 mod window;
 pub mod window_enums;
@@ -0,0 +1,85 @@
+//! Vulkan helper functions.
+//!
+//! SFML doesn't render with Vulkan, but its windows can be the target of a Vulkan renderer,
+//! through [`Window::create_vulkan_surface`].
+//! The handles here are the raw Vulkan handles, so they can be passed from and to bindings
+//! like `ash`.
+//!
+//! # Usage example
+//!
+//! ```no_run
+//! use sfml::window::vulkan;
+//!
+//! assert!(vulkan::is_available(true), "Vulkan isn't available");
+//! // The extensions to enable when creating the Vulkan instance
+//! let extensions: Vec<_> = vulkan::graphics_required_instance_extensions()
+//!     .iter()
+//!     .map(|name| name.as_ptr())
+//!     .collect();
+//! ```
+//!
+//! [`Window::create_vulkan_surface`]: crate::window::Window::create_vulkan_surface
+
+use {
+    crate::ffi::window as ffi,
+    std::ffi::{CStr, c_void},
+};
+
+/// A Vulkan instance handle (`VkInstance`)
+pub type VkInstance = *mut c_void;
+
+/// A Vulkan surface handle (`VkSurfaceKHR`)
+pub type VkSurfaceKHR = u64;
+
+/// Vulkan host memory allocation callbacks (`VkAllocationCallbacks`)
+pub type VkAllocationCallbacks = c_void;
+
+/// Pointer to a Vulkan function (`PFN_vkVoidFunction`)
+pub type VulkanFunctionPointer = unsafe extern "system" fn();
+
+/// Tell whether Vulkan is available on the system.
+///
+/// If `require_graphics` is true, this also checks that the extensions needed to create a
+/// surface for a window are supported.
+#[must_use]
+pub fn is_available(require_graphics: bool) -> bool {
+    unsafe { ffi::sfVulkan_isAvailable(require_graphics) }
+}
+
+/// Get the address of a global Vulkan function, like `vkCreateInstance`.
+///
+/// Returns `None` if Vulkan isn't available or if the function couldn't be found.
+#[must_use]
+pub fn get_function(name: &CStr) -> Option<VulkanFunctionPointer> {
+    unsafe { ffi::sfVulkan_getFunction(name.as_ptr()) }
+}
+
+/// Get the instance extensions required for graphics, like `VK_KHR_surface`.
+///
+/// These have to be enabled on the Vulkan instance passed to
+/// [`Window::create_vulkan_surface`](crate::window::Window::create_vulkan_surface).
+#[must_use]
+pub fn graphics_required_instance_extensions() -> Vec<&'static CStr> {
+    let mut count = 0;
+    unsafe {
+        let names = ffi::sfVulkan_getGraphicsRequiredInstanceExtensions(&mut count);
+        if names.is_null() {
+            return Vec::new();
+        }
+        // The names are static strings in a static vector, owned by SFML
+        std::slice::from_raw_parts(names, count)
+            .iter()
+            .map(|&name| CStr::from_ptr(name))
+            .collect()
+    }
+}
+
+#[cfg_attr(not(feature = "ci-headless"), test)]
+fn instance_helpers_test() {
+    if !is_available(true) {
+        return;
+    }
+    let extensions = graphics_required_instance_extensions();
+    assert!(extensions.contains(&c"VK_KHR_surface"));
+    assert!(get_function(c"vkCreateInstance").is_some());
+}
@@ -3,7 +3,11 @@ use crate::{
     cpp::FBox,
     ffi::window as ffi,
     system::{SfStrConv, Time, Vector2i, Vector2u},
-    window::{ContextSettings, Cursor, Event, Style, VideoMode, thread_safety},
+    window::{
+        ContextSettings, Cursor, Event, EventHandler, Style, VideoMode, event_recording,
+        thread_safety,
+        vulkan::{VkAllocationCallbacks, VkInstance, VkSurfaceKHR},
+    },
 };
 
 use super::window_enums::State;
@@ -130,6 +134,9 @@ impl Window {
     ///
     /// Returns `Some(event)` if an event was returned, or `None` if the event queue was empty
     pub fn poll_event(&mut self) -> Option<Event> {
+        if let Some(event) = event_recording::take_injected_event(self.as_injection_key()) {
+            return Some(event);
+        }
         let mut event = std::mem::MaybeUninit::uninit();
         let have_event = unsafe { ffi::sfWindow_pollEvent(self, event.as_mut_ptr()) };
         if have_event {
@@ -150,6 +157,9 @@ impl Window {
     ///
     /// Returns `Some(event)` or `None` if an error has occured
     pub fn wait_event(&mut self, timeout: Time) -> Option<Event> {
+        if let Some(event) = event_recording::take_injected_event(self.as_injection_key()) {
+            return Some(event);
+        }
         let mut event = std::mem::MaybeUninit::uninit();
         let have_event =
             unsafe { ffi::sfWindow_waitEvent(self, event.as_mut_ptr(), timeout.as_microseconds()) };
@@ -160,6 +170,52 @@ impl Window {
         }
     }
 
+    /// Call the method of `handler` matching each pending event, until the event queue is empty
+    ///
+    /// This replaces the usual `while let Some(event) = window.poll_event()` loop.
+    /// See [`EventHandler`] for an example.
+    ///
+    /// Returns the number of events handled
+    pub fn handle_events<H: EventHandler + ?Sized>(&mut self, handler: &mut H) -> usize {
+        let mut count = 0;
+        while let Some(event) = self.poll_event() {
+            event.dispatch(handler);
+            count += 1;
+        }
+        count
+    }
+
+    /// Wait for an event, then handle it and all the other pending events
+    ///
+    /// This is the blocking version of [`Self::handle_events`], waiting like
+    /// [`Self::wait_event`] with `timeout` if no event is pending.
+    ///
+    /// Returns the number of events handled, which is 0 if the timeout was reached
+    pub fn wait_and_handle_events<H: EventHandler + ?Sized>(
+        &mut self,
+        handler: &mut H,
+        timeout: Time,
+    ) -> usize {
+        let Some(event) = self.wait_event(timeout) else {
+            return 0;
+        };
+        event.dispatch(handler);
+        1 + self.handle_events(handler)
+    }
+
+    /// Add a synthetic event to the event queue of the window
+    ///
+    /// Synthetic events are returned by [`Self::poll_event`] and [`Self::wait_event`] before
+    /// the events of the system, in the order they were pushed.
+    /// This lets tests simulate user input.
+    pub fn push_event(&mut self, event: Event) {
+        event_recording::inject_event(self.as_injection_key(), event);
+    }
+
+    fn as_injection_key(&self) -> *const () {
+        std::ptr::from_ref(self).cast()
+    }
+
     /// Change a window's icon
     /// pixels must be an array of width x height pixels in 32-bits RGBA format.
     ///
@@ -434,10 +490,37 @@ impl Window {
     pub fn request_focus(&mut self) {
         unsafe { ffi::sfWindow_requestFocus(self) }
     }
+
+    /// Create a Vulkan rendering surface for the window.
+    ///
+    /// `instance` must have the [`vulkan::graphics_required_instance_extensions`] enabled.
+    /// `allocator` are the Vulkan allocation callbacks to use, or null for the default ones.
+    ///
+    /// Returns the new surface, which has to be destroyed with `vkDestroySurfaceKHR`
+    /// before the window.
+    ///
+    /// [`vulkan::graphics_required_instance_extensions`]:
+    ///     crate::window::vulkan::graphics_required_instance_extensions
+    ///
+    /// # Safety
+    ///
+    /// `instance` must be a valid Vulkan instance, and `allocator` either null or valid
+    /// allocation callbacks.
+    pub unsafe fn create_vulkan_surface(
+        &mut self,
+        instance: VkInstance,
+        allocator: *const VkAllocationCallbacks,
+    ) -> SfResult<VkSurfaceKHR> {
+        let mut surface = 0;
+        unsafe { ffi::sfWindow_createVulkanSurface(self, instance, &mut surface, allocator) }
+            .into_sf_result()?;
+        Ok(surface)
+    }
 }
 
 impl Drop for Window {
     fn drop(&mut self) {
+        event_recording::forget_injected_events(self.as_injection_key());
         unsafe {
             ffi::sfWindow_del(self);
         }